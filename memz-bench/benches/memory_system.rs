@@ -9,11 +9,14 @@
 //!   gossip_propagation ............... < 50μs
 //!   reputation_update ................ < 20μs
 //!   eviction_pass .................... < 100μs
+//!
+//! Retrieval at 2,000 and 20,000 memories is covered by `retrieval_scaling`.
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 use memz_core::behavior;
 use memz_core::config::{MemoryConfig, RetrievalConfig};
+use memz_core::determinism::IdGenerator;
 use memz_core::memory::MemoryBank;
 use memz_core::memory::episodic::EpisodicMemory;
 use memz_core::memory::social::SocialMemory;
@@ -21,6 +24,7 @@ use memz_core::observation::{self, EventKind, ObservedEvent};
 use memz_core::reputation::{NotableDeed, ReputationBoard};
 use memz_core::retrieval::RetrievalEngine;
 use memz_core::social;
+use memz_core::time::TimeModel;
 use memz_core::types::{
     Embedding, EntityId, GameTimestamp, Location, PersonalityTraits, SettlementId,
 };
@@ -121,6 +125,7 @@ fn bench_decay_pass(c: &mut Criterion) {
                 memz_core::decay::decay_episodic_memories(
                     black_box(&mut bank.episodic),
                     black_box(&current_time),
+                    black_box(&TimeModel::default()),
                     black_box(&config),
                 );
             }
@@ -156,16 +161,18 @@ fn bench_full_frame(c: &mut Criterion) {
         location: Location::default(),
         timestamp: current_time,
     };
+    let ids = IdGenerator::seeded(42);
 
     c.bench_function("full_frame_budget_20_active_npcs", |b| {
         b.iter(|| {
             for bank in &mut banks {
-                systems::observe_event(black_box(&event), black_box(bank));
+                systems::observe_event(black_box(&event), black_box(bank), &ids);
             }
             for bank in &mut banks {
                 systems::run_decay(
                     black_box(bank),
                     black_box(&current_time),
+                    black_box(&TimeModel::default()),
                     black_box(&config),
                 );
             }
@@ -199,6 +206,7 @@ fn bench_observation_pipeline(c: &mut Criterion) {
         pad_shift: None,
     };
     let known: Vec<EntityId> = (0..10).map(|_| EntityId::new()).collect();
+    let ids = IdGenerator::seeded(42);
 
     c.bench_function("observation_pipeline", |b| {
         b.iter(|| {
@@ -207,6 +215,7 @@ fn bench_observation_pipeline(c: &mut Criterion) {
                 black_box(observer),
                 black_box(&mut bank),
                 black_box(&known),
+                &ids,
             );
             black_box(result);
         });
@@ -249,6 +258,7 @@ fn bench_gossip_propagation(c: &mut Criterion) {
                 black_box(0.0),
                 black_box(0.7),
                 black_box(ts(2000)),
+                black_box(&TimeModel::default()),
             );
             black_box(result);
         });
@@ -294,7 +304,7 @@ fn bench_disposition_computation(c: &mut Criterion) {
     let mut bank = MemoryBank::new();
 
     // Build a realistic memory bank
-    for i in 0..100 {
+    for i in 0..100u32 {
         bank.episodic.push(EpisodicMemory::new(
             format!("Entity {player} did thing {i}"),
             vec![player],
@@ -0,0 +1,142 @@
+//! Persistence codec comparison (§12)
+//!
+//! Save / load time and stored size of 100- and 1000-memory banks for every
+//! row codec: JSON, MessagePack and bincode, each with and without zstd.
+//! Saves report the stored payload size as byte throughput, so criterion
+//! shows each codec's size next to its time.
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+
+use memz_core::config::PersistenceConfig;
+use memz_core::memory::MemoryBank;
+use memz_core::memory::episodic::EpisodicMemory;
+use memz_core::memory::semantic::SemanticMemory;
+use memz_core::memory::social::SocialMemory;
+use memz_core::persistence::PersistenceEngine;
+use memz_core::types::{EntityId, GameTimestamp, Location};
+
+const BANK_SIZES: [u32; 2] = [100, 1000];
+
+const CODECS: [(&str, &str); 6] = [
+    ("json", "none"),
+    ("msgpack", "none"),
+    ("bincode", "none"),
+    ("json", "zstd"),
+    ("msgpack", "zstd"),
+    ("bincode", "zstd"),
+];
+
+fn ts(tick: u64) -> GameTimestamp {
+    GameTimestamp::now(tick)
+}
+
+/// A bank of `size` memories: 70% episodic, 20% social, 10% semantic.
+fn make_bank(size: u32) -> MemoryBank {
+    let mut bank = MemoryBank::new();
+    for i in 0..size {
+        match i % 10 {
+            0 => bank.semantic.push(SemanticMemory::new(
+                format!("Fact {i}: the bridge to the north washes out in spring"),
+                0.8,
+                vec![],
+                "geography",
+                ts(u64::from(i) * 1000),
+            )),
+            1 | 2 => bank.social.push(SocialMemory::new(
+                EntityId::new(),
+                EntityId::new(),
+                format!("Rumour {i}: the miller has been shorting flour"),
+                0.6,
+                1,
+                ts(u64::from(i) * 1000),
+            )),
+            _ => bank.episodic.push(EpisodicMemory::new(
+                format!("Event number {i} happened in the town square"),
+                vec![EntityId::new(), EntityId::new()],
+                Location {
+                    x: i as f32,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                ts(u64::from(i) * 1000),
+                0.3,
+                0.5,
+            )),
+        }
+    }
+    bank
+}
+
+fn config(codec: &str, compression: &str) -> PersistenceConfig {
+    PersistenceConfig {
+        codec: codec.to_string(),
+        compression: compression.to_string(),
+        ..PersistenceConfig::default()
+    }
+}
+
+fn label(codec: &str, compression: &str) -> String {
+    if compression == "none" {
+        codec.to_string()
+    } else {
+        format!("{codec}+{compression}")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Bench: Full bank save
+// ---------------------------------------------------------------------------
+
+fn bench_save(c: &mut Criterion) {
+    let mut group = c.benchmark_group("persistence_save");
+    for size in BANK_SIZES {
+        let bank = make_bank(size);
+        for (codec, compression) in CODECS {
+            let engine =
+                PersistenceEngine::open_in_memory(&config(codec, compression)).unwrap();
+            let entity = EntityId::new();
+
+            engine.save_bank(&entity, &mut bank.clone()).unwrap();
+            group.throughput(Throughput::Bytes(engine.payload_bytes().unwrap()));
+
+            group.bench_with_input(
+                BenchmarkId::new(label(codec, compression), size),
+                &bank,
+                |b, bank| {
+                    let mut bank = bank.clone();
+                    b.iter(|| {
+                        // Force a full rewrite rather than a no-op diff.
+                        bank.mark_all_dirty();
+                        black_box(engine.save_bank(&entity, &mut bank).unwrap());
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// Bench: Full bank load
+// ---------------------------------------------------------------------------
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("persistence_load");
+    for size in BANK_SIZES {
+        let bank = make_bank(size);
+        for (codec, compression) in CODECS {
+            let engine =
+                PersistenceEngine::open_in_memory(&config(codec, compression)).unwrap();
+            let entity = EntityId::new();
+            engine.save_bank(&entity, &mut bank.clone()).unwrap();
+
+            group.bench_function(BenchmarkId::new(label(codec, compression), size), |b| {
+                b.iter(|| black_box(engine.load_bank(&entity).unwrap()));
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_save, bench_load);
+criterion_main!(benches);
@@ -0,0 +1,188 @@
+//! Retrieval scaling — HNSW pre-filter vs brute force (§12.4)
+//!
+//! Top-5 retrieval from banks of 200, 2,000 and 20,000 memories with
+//! 384-d embeddings, scoring either every memory (`brute_force`) or only the
+//! `hnsw_ef_search` nearest ones found by a per-bank `MemoryIndex`
+//! (`hnsw`).  `memory_index_build` and `memory_index_sync` time building
+//! and re-syncing that index, with the bank size as element throughput.
+//!
+//! `hnsw_insert` measures what memory creation pays (staging an embedding,
+//! target < 10µs) and `hnsw_link` the deferred, incremental graph insert.
+
+use criterion::{BenchmarkId, Criterion, Throughput, black_box, criterion_group, criterion_main};
+
+use memz_core::config::RetrievalConfig;
+use memz_core::hnsw::HnswIndex;
+use memz_core::memory::MemoryBank;
+use memz_core::memory::episodic::EpisodicMemory;
+use memz_core::retrieval::{MemoryIndex, RetrievalEngine};
+use memz_core::types::{Embedding, EntityId, GameTimestamp, Location, MemoryId};
+
+const BANK_SIZES: [u32; 3] = [200, 2_000, 20_000];
+const DIMENSIONS: usize = 384;
+const TOPICS: u32 = 50;
+
+fn ts(tick: u64) -> GameTimestamp {
+    GameTimestamp::now(tick)
+}
+
+/// Deterministic pseudo-random value in [-1, 1].
+fn noise(seed: u32, dim: usize) -> f32 {
+    ((seed as f32 * 12.9898 + dim as f32 * 78.233).sin() * 43_758.547).fract()
+}
+
+/// Unit embedding near topic `i % TOPICS`, so banks cluster like real
+/// sentence embeddings do.
+fn embedding(i: u32) -> Embedding {
+    let topic = i % TOPICS;
+    let values: Vec<f32> = (0..DIMENSIONS)
+        .map(|d| noise(topic + 1, d) + 0.3 * noise(i + 10_000, d))
+        .collect();
+    let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+    Embedding(values.into_iter().map(|v| v / norm).collect())
+}
+
+fn make_bank(size: u32) -> MemoryBank {
+    let mut bank = MemoryBank::new();
+    for i in 0..size {
+        let mut memory = EpisodicMemory::new(
+            format!("Event number {i} happened in the town square"),
+            vec![EntityId::new()],
+            Location {
+                x: i as f32,
+                y: 0.0,
+                z: 0.0,
+            },
+            ts(u64::from(i) * 100),
+            (i as f32 / size as f32 - 0.5).clamp(-1.0, 1.0),
+            0.1 + 0.8 * (i % 7) as f32 / 7.0,
+        );
+        memory.embedding = Some(embedding(i));
+        bank.episodic.push(memory);
+    }
+    bank
+}
+
+fn config(algorithm: &str) -> RetrievalConfig {
+    RetrievalConfig {
+        algorithm: algorithm.to_string(),
+        ..RetrievalConfig::default()
+    }
+}
+
+fn bench_retrieval_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memory_retrieval_top5");
+    let current_time = ts(3_000_000);
+    let query = embedding(7);
+
+    for size in BANK_SIZES {
+        let bank = make_bank(size);
+        let index = MemoryIndex::build(&config("hnsw"), &bank);
+
+        for algorithm in ["brute_force", "hnsw"] {
+            let engine = RetrievalEngine::new(config(algorithm));
+            group.bench_function(BenchmarkId::new(algorithm, size), |b| {
+                b.iter(|| {
+                    black_box(
+                        engine
+                            .retrieve_indexed(
+                                black_box(&query),
+                                &bank,
+                                &index,
+                                &current_time,
+                                None,
+                            )
+                            .unwrap(),
+                    )
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_index_sync(c: &mut Criterion) {
+    let mut group = c.benchmark_group("memory_index_build");
+    // A 20,000-memory build takes seconds; keep the sample count low.
+    group.sample_size(10);
+    for size in BANK_SIZES {
+        let bank = make_bank(size);
+        group.throughput(Throughput::Elements(u64::from(size)));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &bank, |b, bank| {
+            b.iter(|| black_box(MemoryIndex::build(&config("hnsw"), bank)));
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("memory_index_sync");
+    for size in BANK_SIZES {
+        let bank = make_bank(size);
+        let mut index = MemoryIndex::build(&config("hnsw"), &bank);
+        group.throughput(Throughput::Elements(u64::from(size)));
+        // Steady state: nothing changed since the last sync.
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| black_box(index.sync(black_box(&bank))));
+        });
+    }
+    group.finish();
+}
+
+/// Index over the embeddings of a `size`-memory bank, fully linked.
+fn make_index(size: u32) -> HnswIndex {
+    let mut index = HnswIndex::with_config(&RetrievalConfig::default());
+    for i in 0..size {
+        index.insert(MemoryId::new(), embedding(i));
+    }
+    index.build();
+    index
+}
+
+fn bench_hnsw_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hnsw_insert");
+    for size in BANK_SIZES {
+        let base = make_index(size);
+        let mut next = size;
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_batched_ref(
+                || {
+                    next += 1;
+                    (base.clone(), Some(embedding(next)))
+                },
+                |(index, fresh)| {
+                    if let Some(fresh) = fresh.take() {
+                        index.insert(MemoryId::new(), black_box(fresh));
+                    }
+                },
+                criterion::BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("hnsw_link");
+    for size in BANK_SIZES {
+        let base = make_index(size);
+        let mut next = size;
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_batched_ref(
+                || {
+                    next += 1;
+                    let mut index = base.clone();
+                    index.insert(MemoryId::new(), embedding(next));
+                    index
+                },
+                |index| black_box(index.link_pending(1)),
+                criterion::BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_retrieval_scaling,
+    bench_index_sync,
+    bench_hnsw_insert
+);
+criterion_main!(benches);
@@ -0,0 +1,448 @@
+//! Gist Archival — condensing forgotten memories (§12.2.1)
+//!
+//! Decay and the archive eviction ring remove episodic memories for good.
+//! Instead of forgetting them outright, archival condenses each batch of
+//! removed memories into one [`MemoryGist`] per participant — how many
+//! memories, how they felt on average, over which days — and keeps it as a
+//! [`SemanticMemory`] in the [`GIST_CATEGORY`] category.  Later batches
+//! about the same participant merge into that gist rather than adding
+//! another.  An NPC robbed by the player two months ago no longer remembers
+//! the details, but still knows "that one stole from me".
+//!
+//! Gists are rule-based by default.  Hosts with an LLM can render the
+//! `memory_summary` prompt from [`MemoryGist::prompt_vars`] and replace the
+//! fact with [`apply_summary`].
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::memory::MemoryBank;
+use crate::memory::episodic::EpisodicMemory;
+use crate::memory::semantic::SemanticMemory;
+use crate::time::TimeModel;
+use crate::types::{EntityId, GameTimestamp, MemoryId};
+
+/// Category of the semantic memories archival creates.
+pub const GIST_CATEGORY: &str = "gist";
+
+/// Most memories a gist keeps as examples (the most important ones).
+pub const MAX_GIST_EXAMPLES: usize = 5;
+
+/// Mean valence beyond which a gist reads as good or bad rather than mixed.
+const VALENCE_TONE_THRESHOLD: f32 = 0.3;
+
+/// Namespace of gist IDs (see [`MemoryGist::id_for`]).
+const GIST_NAMESPACE: Uuid = Uuid::from_u128(0x6d65_6d7a_656e_5000_8000_6769_7374_0001);
+
+/// A condensed summary of forgotten episodic memories about one entity.
+#[derive(Debug, Clone)]
+pub struct MemoryGist {
+    /// ID of the semantic memory this gist is stored as.
+    pub id: MemoryId,
+    /// The entity these memories were about (their first participant);
+    /// `None` for memories without participants.
+    pub about: Option<EntityId>,
+    /// Every participant of the condensed memories, in first-seen order.
+    pub participants: Vec<EntityId>,
+    /// IDs of the condensed memories, starting with the `earlier` ones.
+    pub sources: Vec<MemoryId>,
+    /// How many of `sources` the gist already held when this batch was
+    /// merged into it; the other fields describe the new batch only.
+    pub earlier: usize,
+    /// Mean emotional valence (-1.0 to 1.0).
+    pub mean_valence: f32,
+    /// Mean importance (0.0 to 1.0).
+    pub mean_importance: f32,
+    /// When the earliest condensed memory was formed.
+    pub first_seen: GameTimestamp,
+    /// When the latest condensed memory was formed.
+    pub last_seen: GameTimestamp,
+    /// The most important condensed memories, up to [`MAX_GIST_EXAMPLES`].
+    pub examples: Vec<EpisodicMemory>,
+}
+
+impl MemoryGist {
+    /// ID of the gist about `about`.  Derived from the entity, so each bank
+    /// holds one gist per entity that every archival pass merges into.
+    #[must_use]
+    pub fn id_for(about: Option<EntityId>) -> MemoryId {
+        let key = about.map_or(Uuid::nil(), |entity| entity.0);
+        MemoryId(Uuid::new_v5(&GIST_NAMESPACE, key.as_bytes()))
+    }
+
+    /// Number of memories condensed into this gist.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Rule-based one-sentence summary; a merged gist describes its newest
+    /// batch as the most recent of all its memories.
+    #[must_use]
+    pub fn fact(&self, time: &TimeModel) -> String {
+        let count = self.count();
+        let recent = count - self.earlier;
+        let plural = if recent == 1 { "" } else { "s" };
+        let (first, last) = (day(&self.first_seen, time), day(&self.last_seen, time));
+        let when = if first == last {
+            format!("on day {first}")
+        } else {
+            format!("between day {first} and day {last}")
+        };
+        let example = self
+            .examples
+            .first()
+            .map_or(String::new(), |m| format!(", such as: {}", m.event));
+
+        match self.about {
+            Some(entity) => {
+                let tone = if self.mean_valence <= -VALENCE_TONE_THRESHOLD {
+                    "bad experience"
+                } else if self.mean_valence >= VALENCE_TONE_THRESHOLD {
+                    "good experience"
+                } else {
+                    "dealing"
+                };
+                if self.earlier == 0 {
+                    format!("I remember {count} {tone}{plural} with {entity} {when}{example}.")
+                } else {
+                    format!(
+                        "I remember {count} dealings with {entity}, most recently \
+                         {recent} {tone}{plural} {when}{example}."
+                    )
+                }
+            }
+            None if self.earlier == 0 => {
+                format!("I remember {count} thing{plural} happening {when}{example}.")
+            }
+            None => format!("I remember {count} things happening, the latest {when}{example}."),
+        }
+    }
+
+    /// Confidence of the gist: more memories make a firmer impression, but
+    /// a gist is never as certain as a distilled fact.
+    #[must_use]
+    pub fn confidence(&self) -> f32 {
+        (0.3 + 0.1 * self.count() as f32).min(0.8)
+    }
+
+    /// The gist as a semantic memory formed at `now`.
+    #[must_use]
+    pub fn to_semantic(&self, now: GameTimestamp, time: &TimeModel) -> SemanticMemory {
+        SemanticMemory::new(
+            self.fact(time),
+            self.confidence(),
+            self.sources.clone(),
+            GIST_CATEGORY,
+            now,
+        )
+        .with_id(self.id)
+    }
+
+    /// Variables for the `memory_summary` prompt (`memories_formatted`,
+    /// `cluster_topic`, `memory_count`); the caller adds the NPC's name and
+    /// profession.
+    #[must_use]
+    pub fn prompt_vars(&self, time: &TimeModel) -> Vec<(&'static str, String)> {
+        let memories = self
+            .examples
+            .iter()
+            .map(|m| {
+                format!(
+                    "- Day {}: {} [valence: {:.1}, importance: {:.1}]",
+                    day(&m.timestamp, time),
+                    m.event,
+                    m.emotional_valence,
+                    m.importance
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let topic = self.about.map_or_else(
+            || "events without a particular person".to_string(),
+            |e| format!("dealings with {e}"),
+        );
+        vec![
+            ("memories_formatted", memories),
+            ("cluster_topic", topic),
+            ("memory_count", self.count().to_string()),
+        ]
+    }
+}
+
+/// Game day `timestamp` falls on.
+fn day(timestamp: &GameTimestamp, time: &TimeModel) -> u64 {
+    time.ticks_to_days(timestamp.tick) as u64
+}
+
+/// Group `memories` by their first participant (in first-seen order) and
+/// condense each group into a gist with ID [`MemoryGist::id_for`].
+#[must_use]
+pub fn condense(memories: &[EpisodicMemory]) -> Vec<MemoryGist> {
+    let mut order: Vec<Option<EntityId>> = Vec::new();
+    let mut groups: HashMap<Option<EntityId>, Vec<&EpisodicMemory>> = HashMap::new();
+    for memory in memories {
+        let about = memory.participants.first().copied();
+        groups
+            .entry(about)
+            .or_insert_with(|| {
+                order.push(about);
+                Vec::new()
+            })
+            .push(memory);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|about| {
+            let group = groups.remove(&about)?;
+            let first = group.first()?;
+            let n = group.len() as f32;
+
+            let mut participants: Vec<EntityId> = Vec::new();
+            for p in group.iter().flat_map(|m| &m.participants) {
+                if !participants.contains(p) {
+                    participants.push(*p);
+                }
+            }
+            let mut examples: Vec<EpisodicMemory> = group.iter().map(|m| (*m).clone()).collect();
+            examples.sort_by(|a, b| {
+                b.importance
+                    .partial_cmp(&a.importance)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            examples.truncate(MAX_GIST_EXAMPLES);
+
+            Some(MemoryGist {
+                id: MemoryGist::id_for(about),
+                about,
+                participants,
+                sources: group.iter().map(|m| m.id).collect(),
+                earlier: 0,
+                mean_valence: group.iter().map(|m| m.emotional_valence).sum::<f32>() / n,
+                mean_importance: group.iter().map(|m| m.importance).sum::<f32>() / n,
+                first_seen: group
+                    .iter()
+                    .map(|m| m.timestamp)
+                    .min_by_key(|t| t.tick)
+                    .unwrap_or(first.timestamp),
+                last_seen: group
+                    .iter()
+                    .map(|m| m.timestamp)
+                    .max_by_key(|t| t.tick)
+                    .unwrap_or(first.timestamp),
+                examples,
+            })
+        })
+        .collect()
+}
+
+/// Condense `forgotten` into gists and store them in `bank` as semantic
+/// memories formed at `now`.
+///
+/// A gist about an entity the bank already has a gist about is merged into
+/// it: the existing semantic memory gains the new sources, is reinforced
+/// at `now`, and gets the merged rule-based fact (replacing any earlier LLM
+/// summary).  Returns the new or merged gists, e.g. to request an LLM
+/// summary for them.
+pub fn archive(
+    bank: &mut MemoryBank,
+    forgotten: &[EpisodicMemory],
+    now: GameTimestamp,
+    time: &TimeModel,
+) -> Vec<MemoryGist> {
+    let mut gists = condense(forgotten);
+    for gist in &mut gists {
+        let existing = bank
+            .semantic
+            .iter_mut()
+            .find(|m| m.id == gist.id && m.category == GIST_CATEGORY);
+        match existing {
+            Some(memory) => {
+                gist.earlier = memory.derived_from.len();
+                gist.sources
+                    .splice(0..0, memory.derived_from.iter().copied());
+                memory.fact = gist.fact(time);
+                memory.confidence = gist.confidence();
+                memory.derived_from.clone_from(&gist.sources);
+                memory.last_reinforced = now;
+            }
+            None => bank.semantic.push(gist.to_semantic(now, time)),
+        }
+    }
+    gists
+}
+
+/// Replace the rule-based fact of gist `id` in `bank` with an LLM summary.
+///
+/// Returns `false` if the gist is no longer in the bank.
+pub fn apply_summary(
+    bank: &mut MemoryBank,
+    id: MemoryId,
+    fact: impl Into<String>,
+    confidence: f32,
+) -> bool {
+    match bank
+        .semantic
+        .iter_mut()
+        .find(|m| m.id == id && m.category == GIST_CATEGORY)
+    {
+        Some(memory) => {
+            memory.fact = fact.into();
+            memory.confidence = confidence.clamp(0.0, 1.0);
+            true
+        }
+        None => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Location;
+
+    fn memory(
+        event: &str,
+        participants: Vec<EntityId>,
+        day: u64,
+        valence: f32,
+        importance: f32,
+    ) -> EpisodicMemory {
+        let time = TimeModel::default();
+        EpisodicMemory::new(
+            event,
+            participants,
+            Location::default(),
+            GameTimestamp::now(day * time.ticks_per_day),
+            valence,
+            importance,
+        )
+    }
+
+    #[test]
+    fn gists_group_by_participant() {
+        let (player, guard) = (EntityId::new(), EntityId::new());
+        let forgotten = vec![
+            memory("The traveller stole bread", vec![player], 3, -0.6, 0.5),
+            memory("The guard waved hello", vec![guard], 4, 0.4, 0.2),
+            memory(
+                "The traveller broke my window",
+                vec![player, guard],
+                9,
+                -0.8,
+                0.7,
+            ),
+            memory("It rained", vec![], 5, 0.0, 0.1),
+        ];
+
+        let gists = condense(&forgotten);
+        assert_eq!(gists.len(), 3);
+        let about_player = &gists[0];
+        assert_eq!(about_player.about, Some(player));
+        assert_eq!(about_player.count(), 2);
+        assert_eq!(about_player.participants, vec![player, guard]);
+        assert!((about_player.mean_valence + 0.7).abs() < 1e-6);
+        assert_eq!(
+            about_player.examples[0].event,
+            "The traveller broke my window"
+        );
+
+        let time = TimeModel::default();
+        let fact = about_player.fact(&time);
+        assert!(
+            fact.starts_with("I remember 2 bad experiences with"),
+            "{fact}"
+        );
+        assert!(fact.contains("between day 3 and day 9"), "{fact}");
+        assert!(
+            gists[2]
+                .fact(&time)
+                .starts_with("I remember 1 thing happening on day 5")
+        );
+
+        let again = condense(&forgotten);
+        assert_eq!(again[0].id, about_player.id, "gist IDs follow the entity");
+        assert_ne!(gists[1].id, about_player.id);
+    }
+
+    #[test]
+    fn archived_gists_can_take_an_llm_summary() {
+        let player = EntityId::new();
+        let time = TimeModel::default();
+        let mut bank = MemoryBank::new();
+        let forgotten = vec![memory(
+            "The traveller stole bread",
+            vec![player],
+            1,
+            -0.6,
+            0.5,
+        )];
+
+        let gists = archive(&mut bank, &forgotten, GameTimestamp::now(0), &time);
+        assert_eq!(bank.semantic.len(), 1);
+        assert_eq!(bank.semantic[0].category, GIST_CATEGORY);
+        assert_eq!(bank.semantic[0].derived_from, gists[0].sources);
+
+        let vars = gists[0].prompt_vars(&time);
+        assert!(
+            vars.iter()
+                .any(|(k, v)| *k == "memories_formatted" && v.contains("stole bread"))
+        );
+
+        assert!(apply_summary(
+            &mut bank,
+            gists[0].id,
+            "That traveller is a thief.",
+            1.5
+        ));
+        assert_eq!(bank.semantic[0].fact, "That traveller is a thief.");
+        assert!((bank.semantic[0].confidence - 1.0).abs() < f32::EPSILON);
+        assert!(!apply_summary(&mut bank, MemoryId::new(), "unknown", 0.5));
+    }
+
+    #[test]
+    fn later_batches_merge_into_the_existing_gist() {
+        let (player, guard) = (EntityId::new(), EntityId::new());
+        let time = TimeModel::default();
+        let mut bank = MemoryBank::new();
+        let first = vec![
+            memory("The traveller stole bread", vec![player], 1, -0.6, 0.5),
+            memory("The guard waved hello", vec![guard], 2, 0.4, 0.2),
+        ];
+        archive(&mut bank, &first, GameTimestamp::now(0), &time);
+        assert_eq!(bank.semantic.len(), 2);
+
+        let second = vec![
+            memory("The traveller paid me back", vec![player], 40, 0.6, 0.5),
+            memory("The traveller bought a pie", vec![player], 41, 0.5, 0.3),
+        ];
+        let later = GameTimestamp::now(41 * time.ticks_per_day);
+        let gists = archive(&mut bank, &second, later, &time);
+        assert_eq!(bank.semantic.len(), 2, "no duplicate gist about the player");
+        assert_eq!(gists.len(), 1);
+        assert_eq!((gists[0].count(), gists[0].earlier), (3, 1));
+
+        let merged = bank
+            .semantic
+            .iter()
+            .find(|m| m.id == gists[0].id)
+            .expect("merged gist");
+        assert_eq!(merged.derived_from, gists[0].sources);
+        assert_eq!(merged.derived_from[0], first[0].id);
+        assert_eq!(merged.last_reinforced.tick, later.tick);
+        assert!((merged.confidence - 0.6).abs() < 1e-6);
+        assert!(
+            merged.fact.starts_with("I remember 3 dealings with")
+                && merged
+                    .fact
+                    .contains("most recently 2 good experiences between day 40 and day 41"),
+            "{}",
+            merged.fact
+        );
+    }
+}
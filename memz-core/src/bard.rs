@@ -14,6 +14,7 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::determinism::IdGenerator;
 use crate::types::{EntityId, GameTimestamp, MemoryId, SettlementId};
 
 /// A bard's musical composition based on real game events.
@@ -126,6 +127,14 @@ impl BardComposition {
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Record a performance of this song at a settlement.
     pub fn record_performance(&mut self, settlement: SettlementId) {
         self.performance_count += 1;
@@ -165,6 +174,7 @@ pub fn compose_rule_based(
     event_descriptions: &[String],
     avg_valence: f32,
     timestamp: GameTimestamp,
+    ids: &IdGenerator,
 ) -> BardComposition {
     let style = BardStyle::from_emotional_tone(avg_valence, avg_valence.abs());
 
@@ -246,6 +256,7 @@ pub fn compose_rule_based(
         timestamp,
         false,
     )
+    .with_id(ids.memory_id())
 }
 
 /// The bard's song repertoire — manages compositions per bard NPC.
@@ -338,7 +349,7 @@ mod tests {
             "the village was saved".to_string(),
         ];
 
-        let song = compose_rule_based(composer, None, &events, 0.8, GameTimestamp::now(36_000));
+        let song = compose_rule_based(composer, None, &events, 0.8, GameTimestamp::now(36_000), &IdGenerator::random());
         assert_eq!(song.style, BardStyle::Epic);
         assert!(!song.title.is_empty());
         assert!(song.verses.len() >= 4);
@@ -350,7 +361,7 @@ mod tests {
         let composer = EntityId::new();
         let events = vec!["a great warrior fell in battle".to_string()];
 
-        let song = compose_rule_based(composer, None, &events, -0.9, GameTimestamp::now(36_000));
+        let song = compose_rule_based(composer, None, &events, -0.9, GameTimestamp::now(36_000), &IdGenerator::random());
         assert_eq!(song.style, BardStyle::Tragic);
     }
 
@@ -362,6 +373,7 @@ mod tests {
             &["an event".to_string()],
             0.5,
             GameTimestamp::now(36_000),
+            &IdGenerator::random(),
         );
 
         let s1 = SettlementId::new();
@@ -385,12 +397,12 @@ mod tests {
         let mut rep = Repertoire::new(2);
         let ts = GameTimestamp::now(36_000);
 
-        rep.add(compose_rule_based(EntityId::new(), None, &["event1".to_string()], 0.5, ts));
-        rep.add(compose_rule_based(EntityId::new(), None, &["event2".to_string()], 0.5, ts));
+        rep.add(compose_rule_based(EntityId::new(), None, &["event1".to_string()], 0.5, ts, &IdGenerator::random()));
+        rep.add(compose_rule_based(EntityId::new(), None, &["event2".to_string()], 0.5, ts, &IdGenerator::random()));
         assert_eq!(rep.songs.len(), 2);
 
         // Adding a third should drop the least popular
-        rep.add(compose_rule_based(EntityId::new(), None, &["event3".to_string()], 0.5, ts));
+        rep.add(compose_rule_based(EntityId::new(), None, &["event3".to_string()], 0.5, ts, &IdGenerator::random()));
         assert_eq!(rep.songs.len(), 2);
     }
 
@@ -400,11 +412,11 @@ mod tests {
         let ts = GameTimestamp::now(36_000);
         let s1 = SettlementId::new();
 
-        let mut song1 = compose_rule_based(EntityId::new(), None, &["event1".to_string()], 0.5, ts);
+        let mut song1 = compose_rule_based(EntityId::new(), None, &["event1".to_string()], 0.5, ts, &IdGenerator::random());
         song1.record_performance(s1);
         rep.add(song1);
 
-        let song2 = compose_rule_based(EntityId::new(), None, &["event2".to_string()], 0.5, ts);
+        let song2 = compose_rule_based(EntityId::new(), None, &["event2".to_string()], 0.5, ts, &IdGenerator::random());
         rep.add(song2);
 
         let selected = rep.select_for_performance(s1);
@@ -11,12 +11,17 @@
 //!
 //! Performance target: < 0.2ms per behavior check (§12.6)
 
+use crate::error::Result;
 use crate::memory::MemoryBank;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::emotional::EmotionalMemory;
 use crate::memory::social::SocialMemory;
+use crate::persistence::PersistenceEngine;
 use crate::types::EntityId;
 
+/// Most cold memories [`compute_disposition_paged`] pages in per call.
+pub const DISPOSITION_PAGE_IN_LIMIT: usize = 16;
+
 /// Overall disposition of an NPC toward a specific entity.
 #[derive(Debug, Clone)]
 pub struct Disposition {
@@ -98,6 +103,22 @@ pub fn compute_disposition(
     }
 }
 
+/// [`compute_disposition`], first paging in up to
+/// [`DISPOSITION_PAGE_IN_LIMIT`] of `owner`'s cold memories about `target`
+/// from `store`, so long-past dealings still count.
+///
+/// Only touches `SQLite` when the bank's cold index has memories about
+/// `target`; paged-in memories stay in the bank.
+pub fn compute_disposition_paged(
+    bank: &mut MemoryBank,
+    owner: &EntityId,
+    store: &PersistenceEngine,
+    target: EntityId,
+) -> Result<Disposition> {
+    store.page_in(owner, bank, target, DISPOSITION_PAGE_IN_LIMIT)?;
+    Ok(compute_disposition(bank, target))
+}
+
 /// Compute direct sentiment from episodic and emotional memories.
 fn compute_direct_sentiment(
     bank: &MemoryBank,
@@ -454,7 +475,7 @@ mod tests {
         let bank = make_bank_with_positive_history(target);
         let disp = compute_disposition(&bank, target);
         let modifier = compute_price_modifier(&disp);
-        assert!(modifier < 1.0, "Expected discount, got {}", modifier);
+        assert!(modifier < 1.0, "Expected discount, got {modifier}");
     }
 
     #[test]
@@ -463,7 +484,7 @@ mod tests {
         let bank = make_bank_with_negative_history(target);
         let disp = compute_disposition(&bank, target);
         let modifier = compute_price_modifier(&disp);
-        assert!(modifier > 1.0, "Expected markup, got {}", modifier);
+        assert!(modifier > 1.0, "Expected markup, got {modifier}");
     }
 
     #[test]
@@ -472,8 +493,8 @@ mod tests {
         let bank = MemoryBank::new();
         let disp = compute_disposition(&bank, unknown);
 
-        assert_eq!(disp.sentiment, 0.0);
-        assert_eq!(disp.confidence, 0.0);
+        assert!(disp.sentiment.abs() < f32::EPSILON);
+        assert!(disp.confidence.abs() < f32::EPSILON);
         assert!(matches!(disp.basis, DispositionBasis::Unknown));
         assert_eq!(compute_greeting_style(&disp), GreetingStyle::Neutral);
         assert!((compute_price_modifier(&disp) - 1.0).abs() < 0.01);
@@ -2,8 +2,16 @@
 //!
 //! Maps directly to `memz.toml` — see §16 of the design doc.
 
+mod profile;
+mod validate;
+
 use serde::{Deserialize, Serialize};
 
+use crate::time::TimeModel;
+
+pub use profile::PROFILES;
+pub use validate::{ConfigIssue, Severity, ValidationReport};
+
 /// Top-level MEMZ configuration, loadable from TOML.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[derive(Default)]
@@ -11,6 +19,9 @@ pub struct MemzConfig {
     /// General settings.
     #[serde(default)]
     pub general: GeneralConfig,
+    /// Tick rate and game-day length, shared by every time conversion.
+    #[serde(default)]
+    pub time: TimeModel,
     /// Per-NPC memory limits and behavior.
     #[serde(default)]
     pub memory: MemoryConfig,
@@ -45,12 +56,43 @@ pub struct MemzConfig {
 
 
 impl MemzConfig {
-    /// Load configuration from a TOML string.
+    /// Load configuration from a TOML string, layering it over the preset
+    /// named by its `general.profile`.
     ///
     /// # Errors
     /// Returns `MemzError::Config` if the TOML is invalid.
     pub fn from_toml(toml_str: &str) -> crate::error::Result<Self> {
-        toml::from_str(toml_str).map_err(|e| crate::MemzError::Config(e.to_string()))
+        let table = parse_table(toml_str)?;
+        let profile = table
+            .get("general")
+            .and_then(|general| general.get("profile"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("auto")
+            .to_string();
+        Self::layered(table, &profile)
+    }
+
+    /// Load configuration from a TOML string over the preset for `profile`,
+    /// whatever the file's own `general.profile` says.  Used on hot reload,
+    /// where the profile chosen at startup stays in force.
+    ///
+    /// # Errors
+    /// Returns `MemzError::Config` if the TOML is invalid.
+    pub fn from_toml_with_profile(toml_str: &str, profile: &str) -> crate::error::Result<Self> {
+        Self::layered(parse_table(toml_str)?, profile)
+    }
+
+    /// The configuration a `memz.toml` containing only
+    /// `general.profile = "<profile>"` would produce.
+    ///
+    /// # Errors
+    /// Returns `MemzError::Config` if the preset fails to deserialize.
+    pub fn for_profile(profile: &str) -> crate::error::Result<Self> {
+        let mut general = toml::Table::new();
+        general.insert("profile".to_string(), toml::Value::String(profile.to_string()));
+        let mut table = toml::Table::new();
+        table.insert("general".to_string(), toml::Value::Table(general));
+        Self::layered(table, profile)
     }
 
     /// Load configuration from a TOML file.
@@ -61,6 +103,29 @@ impl MemzConfig {
         let content = std::fs::read_to_string(path)?;
         Self::from_toml(&content)
     }
+
+    /// Check every setting for values that parse but make no sense, such as
+    /// `retrieval.top_k = 0` or a hot ring longer than the warm ring.
+    ///
+    /// Use [`ValidationReport::into_result`] to reject configs with errors.
+    #[must_use]
+    pub fn validate(&self) -> ValidationReport {
+        validate::validate(self)
+    }
+
+    fn layered(table: toml::Table, profile: &str) -> crate::error::Result<Self> {
+        let table = match profile::preset(profile) {
+            Some(preset) => profile::layer(preset, table),
+            None => table,
+        };
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| crate::MemzError::Config(e.to_string()))
+    }
+}
+
+fn parse_table(toml_str: &str) -> crate::error::Result<toml::Table> {
+    toml_str.parse().map_err(|e: toml::de::Error| crate::MemzError::Config(e.to_string()))
 }
 
 // ---------------------------------------------------------------------------
@@ -97,7 +162,7 @@ pub struct MemoryConfig {
     /// Hard cap on episodic memories per NPC.
     #[serde(default = "default_200")]
     pub max_episodic_per_npc: usize,
-    /// Distilled knowledge cap per NPC.
+    /// Distilled knowledge cap per NPC (archival gists are exempt).
     #[serde(default = "default_50")]
     pub max_semantic_per_npc: usize,
     /// Gossip / hearsay cap per NPC.
@@ -157,6 +222,10 @@ pub struct EvictionConfig {
     /// Whether to protect first-meeting memories from eviction.
     #[serde(default = "default_true")]
     pub protect_first_meeting: bool,
+    /// Condense forgotten memories into gists instead of dropping them
+    /// (see [`crate::archival`]).
+    #[serde(default = "default_true")]
+    pub gist_archival: bool,
 }
 
 impl Default for EvictionConfig {
@@ -167,6 +236,7 @@ impl Default for EvictionConfig {
             cold_ring_days: 90,
             protect_emotional_threshold: 0.8,
             protect_first_meeting: true,
+            gist_archival: true,
         }
     }
 }
@@ -174,7 +244,7 @@ impl Default for EvictionConfig {
 /// Memory retrieval algorithm settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetrievalConfig {
-    /// Algorithm: "hnsw", "`brute_force`", "tfidf".
+    /// Algorithm: "hnsw", "`brute_force`", "tfidf", "hybrid".
     #[serde(default = "default_hnsw")]
     pub algorithm: String,
     /// Number of memories retrieved per interaction.
@@ -186,6 +256,33 @@ pub struct RetrievalConfig {
     /// Embedding vector dimensions.
     #[serde(default = "default_384")]
     pub embedding_dimensions: usize,
+    /// HNSW build quality (higher = better graph, slower build).
+    #[serde(default = "default_128")]
+    pub hnsw_ef_construction: usize,
+    /// HNSW search quality — also the number of semantic candidates
+    /// handed to the five-factor scorer.
+    #[serde(default = "default_64")]
+    pub hnsw_ef_search: usize,
+    /// HNSW connections per node.
+    #[serde(default = "default_16")]
+    pub hnsw_m: usize,
+    /// Banks with fewer memories than this are scored brute-force.
+    #[serde(default = "default_100")]
+    pub hnsw_min_memories: usize,
+    /// How `algorithm = "hybrid"` fuses vector and lexical relevance:
+    /// "rrf" (reciprocal-rank fusion) or "weighted".
+    #[serde(default = "default_rrf")]
+    pub hybrid_fusion: String,
+    /// Share of vector relevance in "weighted" fusion; lexical gets the rest.
+    #[serde(default = "default_0_5")]
+    pub hybrid_vector_weight: f32,
+    /// RRF rank constant `k` — higher values flatten the rank curve.
+    #[serde(default = "default_60")]
+    pub rrf_k: u32,
+    /// Distance (world units) within which a memory's location acts as a
+    /// place cue; also the spatial index cell size.
+    #[serde(default = "default_32_f32")]
+    pub place_cue_radius: f32,
     /// Retrieval weight tuning.
     #[serde(default)]
     pub weights: RetrievalWeights,
@@ -198,6 +295,14 @@ impl Default for RetrievalConfig {
             top_k: 5,
             embedding_model: "all-MiniLM-L6-v2".to_string(),
             embedding_dimensions: 384,
+            hnsw_ef_construction: 128,
+            hnsw_ef_search: 64,
+            hnsw_m: 16,
+            hnsw_min_memories: 100,
+            hybrid_fusion: "rrf".to_string(),
+            hybrid_vector_weight: 0.5,
+            rrf_k: 60,
+            place_cue_radius: 32.0,
             weights: RetrievalWeights::default(),
         }
     }
@@ -221,6 +326,12 @@ pub struct RetrievalWeights {
     /// Weight for social source trust.
     #[serde(default = "default_0_1")]
     pub social: f32,
+    /// Weight for the place cue (memories formed near the NPC's current
+    /// position); only applies when a position is given.  Opt-in: 0 by
+    /// default, so raising it means lowering the other five to keep the sum
+    /// at 1.0.
+    #[serde(default)]
+    pub place: f32,
 }
 
 impl Default for RetrievalWeights {
@@ -231,6 +342,7 @@ impl Default for RetrievalWeights {
             importance: 0.20,
             emotional: 0.20,
             social: 0.10,
+            place: 0.0,
         }
     }
 }
@@ -421,6 +533,17 @@ pub struct PersistenceConfig {
     /// Detect save corruption via checksums.
     #[serde(default = "default_true")]
     pub checksum_enabled: bool,
+    /// Row payload codec: "json" (default), "msgpack" or "bincode".
+    #[serde(default = "default_json")]
+    pub codec: String,
+    /// Row payload compression: "none" (default) or "zstd" (requires the
+    /// `zstd` feature).
+    #[serde(default = "default_none")]
+    pub compression: String,
+    /// On-disk embedding format: "f16" (default), "i8" or "none" (don't
+    /// persist embeddings).
+    #[serde(default = "default_f16")]
+    pub embedding_format: String,
 }
 
 impl Default for PersistenceConfig {
@@ -431,6 +554,9 @@ impl Default for PersistenceConfig {
             auto_save_interval_seconds: 300,
             backup_count: 3,
             checksum_enabled: true,
+            codec: "json".to_string(),
+            compression: "none".to_string(),
+            embedding_format: "f16".to_string(),
         }
     }
 }
@@ -544,6 +670,10 @@ fn default_tier1() -> String { "tier1".to_string() }
 fn default_templates() -> String { "templates".to_string() }
 fn default_silent() -> String { "silent".to_string() }
 fn default_sqlite() -> String { "sqlite".to_string() }
+fn default_json() -> String { "json".to_string() }
+fn default_none() -> String { "none".to_string() }
+fn default_f16() -> String { "f16".to_string() }
+fn default_rrf() -> String { "rrf".to_string() }
 fn default_moderate() -> String { "moderate".to_string() }
 fn default_prom_endpoint() -> String { "127.0.0.1:9090".to_string() }
 fn default_0_1() -> f32 { 0.1 }
@@ -554,6 +684,7 @@ fn default_0_8() -> f32 { 0.8 }
 fn default_1_0() -> f32 { 1.0 }
 fn default_2_0() -> f32 { 2.0 }
 fn default_5_0() -> f32 { 5.0 }
+fn default_32_f32() -> f32 { 32.0 }
 fn default_decay_rate() -> f32 { 0.05 }
 fn default_trust_decay() -> f32 { 0.01 }
 fn default_consolidation_budget() -> f32 { 0.1 }
@@ -566,12 +697,16 @@ fn default_5_usize() -> usize { 5 }
 fn default_7() -> u32 { 7 }
 fn default_10() -> u32 { 10 }
 fn default_20() -> u32 { 20 }
+fn default_16() -> usize { 16 }
 fn default_20_usize() -> usize { 20 }
 fn default_24() -> u32 { 24 }
 fn default_30() -> usize { 30 }
 fn default_50() -> usize { 50 }
+fn default_60() -> u32 { 60 }
+fn default_64() -> usize { 64 }
 fn default_90() -> u32 { 90 }
 fn default_100() -> usize { 100 }
+fn default_128() -> usize { 128 }
 fn default_200() -> usize { 200 }
 fn default_300() -> u32 { 300 }
 fn default_384() -> usize { 384 }
@@ -0,0 +1,166 @@
+//! Hardware profiles — `general.profile` presets layered under `memz.toml` (§16)
+//!
+//! Each profile is a partial `memz.toml` holding only the values it changes
+//! from the built-in defaults.  Loading merges the user's file on top of the
+//! preset, so anything the user writes explicitly always wins and anything
+//! they leave out comes from the profile rather than the generic default.
+
+use toml::Table;
+
+/// Profiles accepted by `general.profile`.  `auto` leaves the built-in
+/// defaults untouched; the host detects hardware and picks its own tuning.
+pub const PROFILES: &[&str] = &["auto", "minimal", "standard", "high", "server", "dev"];
+
+/// Low-end laptops: keyword retrieval, no LLM, small banks.
+const MINIMAL: &str = r#"
+[memory]
+max_episodic_per_npc = 50
+max_semantic_per_npc = 20
+max_social_per_npc = 30
+max_procedural_per_npc = 10
+max_reflective_per_npc = 5
+
+[retrieval]
+algorithm = "tfidf"
+top_k = 3
+
+[llm]
+provider = "none"
+
+[performance]
+frame_budget_ms = 1.0
+active_npc_radius_chunks = 2
+max_concurrent_llm_requests = 0
+
+[persistence]
+embedding_format = "none"
+"#;
+
+/// Mid-range desktops: the built-in defaults already target this tier.
+const STANDARD: &str = "";
+
+/// Gaming rigs with a GPU: bigger banks, hybrid retrieval, more Tier 2.
+const HIGH: &str = r#"
+[memory]
+max_episodic_per_npc = 500
+max_semantic_per_npc = 100
+max_social_per_npc = 200
+max_procedural_per_npc = 50
+max_reflective_per_npc = 40
+
+[retrieval]
+algorithm = "hybrid"
+top_k = 8
+hnsw_ef_search = 128
+
+[llm]
+max_tier2_calls_per_hour = 60
+
+[performance]
+frame_budget_ms = 3.0
+max_concurrent_llm_requests = 4
+"#;
+
+/// Dedicated servers: wide simulation radius, metrics, frequent saves.
+const SERVER: &str = r"
+[retrieval]
+hnsw_ef_search = 128
+
+[llm]
+max_tier2_calls_per_hour = 120
+
+[performance]
+active_npc_radius_chunks = 5
+max_concurrent_llm_requests = 8
+
+[persistence]
+auto_save_interval_seconds = 120
+backup_count = 5
+
+[telemetry]
+enabled = true
+";
+
+/// Local development: verbose logs, quick saves, slow-op tracing.
+const DEV: &str = r#"
+[general]
+log_level = "debug"
+
+[persistence]
+auto_save_interval_seconds = 30
+
+[telemetry]
+enabled = true
+log_slow_operations_ms = 1.0
+"#;
+
+/// The preset table for `profile`, or `None` for `auto` and unknown names
+/// (the latter are reported by validation).
+pub(super) fn preset(profile: &str) -> Option<Table> {
+    let source = match profile {
+        "minimal" => MINIMAL,
+        "standard" => STANDARD,
+        "high" => HIGH,
+        "server" => SERVER,
+        "dev" => DEV,
+        _ => return None,
+    };
+    // The presets are compile-time constants covered by tests.
+    source.parse().ok()
+}
+
+/// Merge `overrides` on top of `base`: nested tables merge key by key,
+/// every other value in `overrides` replaces the one in `base`.
+pub(super) fn layer(mut base: Table, overrides: Table) -> Table {
+    for (key, value) in overrides {
+        let merged = match (base.remove(&key), value) {
+            (Some(toml::Value::Table(under)), toml::Value::Table(over)) => {
+                toml::Value::Table(layer(under, over))
+            }
+            (_, value) => value,
+        };
+        base.insert(key, merged);
+    }
+    base
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MemzConfig;
+
+    #[test]
+    fn every_named_profile_has_a_valid_preset() {
+        for profile in PROFILES.iter().filter(|p| **p != "auto") {
+            assert!(preset(profile).is_some(), "{profile} preset does not parse");
+            let config = MemzConfig::for_profile(profile).expect("preset deserializes");
+            let report = config.validate();
+            assert!(report.issues.is_empty(), "{profile}:\n{report}");
+        }
+        assert!(preset("auto").is_none());
+        assert!(preset("turbo").is_none());
+    }
+
+    #[test]
+    fn user_values_win_over_the_preset() {
+        let config = MemzConfig::from_toml(
+            r#"
+            [general]
+            profile = "minimal"
+            [retrieval]
+            top_k = 4
+            "#,
+        )
+        .expect("valid config");
+
+        assert_eq!(config.general.profile, "minimal");
+        assert_eq!(config.retrieval.top_k, 4, "explicit value kept");
+        assert_eq!(config.retrieval.algorithm, "tfidf", "preset fills the rest");
+        assert_eq!(config.llm.provider, "none");
+        assert_eq!(config.retrieval.embedding_dimensions, 384, "untouched default");
+    }
+}
@@ -0,0 +1,394 @@
+//! Config validation — semantic checks beyond what TOML parsing catches (§16)
+//!
+//! `memz.toml` can be well-formed and still nonsensical: `top_k = 0`, a
+//! `decay_rate` above 1, a hot ring longer than the warm ring.  Every check
+//! here reports the dotted path of the offending field so operators can find
+//! it.  Errors make the config unusable; warnings flag settings that work
+//! but are probably not what was meant.
+
+use std::fmt;
+
+use crate::error::{MemzError, Result};
+use crate::persistence::EmbeddingFormat;
+use crate::persistence::codec::PayloadFormat;
+
+use super::{MemzConfig, profile};
+
+/// Log levels accepted by `general.log_level`.
+pub const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+/// Algorithms accepted by `retrieval.algorithm`.
+pub const RETRIEVAL_ALGORITHMS: &[&str] = &["hnsw", "brute_force", "tfidf", "hybrid"];
+/// Fusion strategies accepted by `retrieval.hybrid_fusion`.
+pub const FUSIONS: &[&str] = &["rrf", "weighted"];
+/// Providers accepted by `llm.provider`.
+pub const LLM_PROVIDERS: &[&str] = &["ollama", "openai", "llama_cpp", "none"];
+/// Backends accepted by `persistence.backend`.
+pub const PERSISTENCE_BACKENDS: &[&str] = &["sqlite", "json"];
+/// Levels accepted by `safety.profanity_filter`.
+pub const PROFANITY_FILTERS: &[&str] = &["off", "moderate", "strict"];
+
+/// How far the six retrieval weights (the five core weights plus the
+/// opt-in place cue) may sum away from 1.0 before a warning is raised.
+const WEIGHT_SUM_TOLERANCE: f32 = 0.05;
+
+/// Whether an issue makes the config unusable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The config must not be used.
+    Error,
+    /// The config works but is probably not what was meant.
+    Warning,
+}
+
+/// One problem with one setting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    /// Error or warning.
+    pub severity: Severity,
+    /// Dotted path of the setting, e.g. `retrieval.top_k`.
+    pub path: String,
+    /// What is wrong and what is expected instead.
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}: {}", self.path, self.message)
+    }
+}
+
+/// Every issue found in a config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Issues in the order the sections appear in `memz.toml`.
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ValidationReport {
+    /// Issues that make the config unusable.
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    /// Issues worth logging but not fatal.
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// Whether any error was found.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// The issue reported for `path`, if any.
+    #[must_use]
+    pub fn issue(&self, path: &str) -> Option<&ConfigIssue> {
+        self.issues.iter().find(|i| i.path == path)
+    }
+
+    /// `Ok(self)` (possibly with warnings) unless an error was found.
+    ///
+    /// # Errors
+    /// Returns `MemzError::Config` listing every error.
+    pub fn into_result(self) -> Result<Self> {
+        if !self.has_errors() {
+            return Ok(self);
+        }
+        let errors: Vec<String> = self.errors().map(ToString::to_string).collect();
+        Err(MemzError::Config(format!("invalid config:\n  {}", errors.join("\n  "))))
+    }
+
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.push(Severity::Error, path, message);
+    }
+
+    fn warning(&mut self, path: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, path, message);
+    }
+
+    fn push(&mut self, severity: Severity, path: &str, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            severity,
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn one_of(&mut self, path: &str, value: &str, allowed: &[&str]) {
+        if !allowed.contains(&value) {
+            self.error(path, format!("unknown value {value:?} (expected one of {allowed:?})"));
+        }
+    }
+
+    fn unit_interval(&mut self, path: &str, value: f32) {
+        if !(0.0..=1.0).contains(&value) {
+            self.error(path, format!("{value} is outside 0.0–1.0"));
+        }
+    }
+
+    fn positive(&mut self, path: &str, value: f64) {
+        if value.is_nan() || value <= 0.0 {
+            self.error(path, format!("{value} must be greater than 0"));
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Check every section of `config`.
+pub(super) fn validate(config: &MemzConfig) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    general(config, &mut report);
+    time(config, &mut report);
+    memory(config, &mut report);
+    retrieval(config, &mut report);
+    llm(config, &mut report);
+    rest(config, &mut report);
+    report
+}
+
+fn general(config: &MemzConfig, report: &mut ValidationReport) {
+    report.one_of("general.log_level", &config.general.log_level, LOG_LEVELS);
+    report.one_of("general.profile", &config.general.profile, profile::PROFILES);
+}
+
+fn time(config: &MemzConfig, report: &mut ValidationReport) {
+    report.positive("time.ticks_per_second", config.time.ticks_per_second);
+    if config.time.ticks_per_day == 0 {
+        report.error("time.ticks_per_day", "must be at least 1");
+    }
+}
+
+fn memory(config: &MemzConfig, report: &mut ValidationReport) {
+    let memory = &config.memory;
+    for (field, cap) in [
+        ("max_episodic_per_npc", memory.max_episodic_per_npc),
+        ("max_semantic_per_npc", memory.max_semantic_per_npc),
+        ("max_social_per_npc", memory.max_social_per_npc),
+        ("max_procedural_per_npc", memory.max_procedural_per_npc),
+        ("max_reflective_per_npc", memory.max_reflective_per_npc),
+    ] {
+        if cap == 0 {
+            report.warning(&format!("memory.{field}"), "0 means NPCs keep none of these memories");
+        }
+    }
+    if !(0.0..=1.0).contains(&memory.decay_rate) {
+        report.error(
+            "memory.decay_rate",
+            format!("{} is outside 0.0–1.0 (fraction forgotten per game-day)", memory.decay_rate),
+        );
+    }
+    if memory.consolidation_interval_days == 0 {
+        report.error("memory.consolidation_interval_days", "must be at least 1");
+    }
+
+    let eviction = &memory.eviction;
+    let warm_hours = u64::from(eviction.warm_ring_days) * 24;
+    if u64::from(eviction.hot_ring_hours) > warm_hours {
+        report.error(
+            "memory.eviction.hot_ring_hours",
+            format!(
+                "{} h is longer than the warm ring (memory.eviction.warm_ring_days = {} = {warm_hours} h)",
+                eviction.hot_ring_hours, eviction.warm_ring_days
+            ),
+        );
+    }
+    if eviction.warm_ring_days > eviction.cold_ring_days {
+        report.error(
+            "memory.eviction.warm_ring_days",
+            format!(
+                "{} days is longer than the cold ring (memory.eviction.cold_ring_days = {})",
+                eviction.warm_ring_days, eviction.cold_ring_days
+            ),
+        );
+    }
+    report.unit_interval(
+        "memory.eviction.protect_emotional_threshold",
+        eviction.protect_emotional_threshold,
+    );
+}
+
+fn retrieval(config: &MemzConfig, report: &mut ValidationReport) {
+    let retrieval = &config.retrieval;
+    report.one_of("retrieval.algorithm", &retrieval.algorithm, RETRIEVAL_ALGORITHMS);
+    if retrieval.top_k == 0 {
+        report.error("retrieval.top_k", "must be at least 1");
+    }
+    if retrieval.embedding_dimensions == 0 {
+        report.error("retrieval.embedding_dimensions", "must be at least 1");
+    }
+    if retrieval.hnsw_m == 0 {
+        report.error("retrieval.hnsw_m", "must be at least 1");
+    }
+    if retrieval.algorithm == "hnsw" && retrieval.hnsw_ef_search < retrieval.top_k {
+        report.warning(
+            "retrieval.hnsw_ef_search",
+            format!(
+                "{} candidates is fewer than retrieval.top_k = {}",
+                retrieval.hnsw_ef_search, retrieval.top_k
+            ),
+        );
+    }
+    report.one_of("retrieval.hybrid_fusion", &retrieval.hybrid_fusion, FUSIONS);
+    report.unit_interval("retrieval.hybrid_vector_weight", retrieval.hybrid_vector_weight);
+    report.positive("retrieval.place_cue_radius", f64::from(retrieval.place_cue_radius));
+
+    let w = &retrieval.weights;
+    let weights = [
+        ("recency", w.recency),
+        ("relevance", w.relevance),
+        ("importance", w.importance),
+        ("emotional", w.emotional),
+        ("social", w.social),
+        ("place", w.place),
+    ];
+    for (field, weight) in weights {
+        if weight.is_nan() || weight < 0.0 {
+            report.error(&format!("retrieval.weights.{field}"), format!("{weight} is negative"));
+        }
+    }
+    let sum: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    if sum <= 0.0 {
+        report.error("retrieval.weights", "every weight is 0");
+    } else if (sum - 1.0).abs() > WEIGHT_SUM_TOLERANCE {
+        report.warning(
+            "retrieval.weights",
+            format!("recency + relevance + importance + emotional + social + place = {sum:.2}, expected 1.0"),
+        );
+    }
+}
+
+fn llm(config: &MemzConfig, report: &mut ValidationReport) {
+    let llm = &config.llm;
+    report.one_of("llm.provider", &llm.provider, LLM_PROVIDERS);
+    if llm.provider == "none" {
+        return;
+    }
+    if llm.base_url.trim().is_empty() {
+        report.error("llm.base_url", format!("required for provider {:?}", llm.provider));
+    }
+    if llm.tier1_model.trim().is_empty() {
+        report.warning("llm.tier1_model", "empty: Tier 1 calls will fail and fall back");
+    }
+    if llm.tier2_model.trim().is_empty() {
+        report.warning("llm.tier2_model", "empty: Tier 2 calls will fail and fall back");
+    }
+    if llm.request_timeout_ms == 0 {
+        report.error("llm.request_timeout_ms", "must be at least 1");
+    }
+}
+
+fn rest(config: &MemzConfig, report: &mut ValidationReport) {
+    report.unit_interval("social.gossip_tendency_default", config.social.gossip_tendency_default);
+    if config.social.max_gossip_chain_depth == 0 {
+        report.warning("social.max_gossip_chain_depth", "0 disables gossip entirely");
+    }
+    report.positive("performance.frame_budget_ms", f64::from(config.performance.frame_budget_ms));
+
+    let persistence = &config.persistence;
+    report.one_of("persistence.backend", &persistence.backend, PERSISTENCE_BACKENDS);
+    if PayloadFormat::from_tag(&persistence.codec).is_none() {
+        report.error(
+            "persistence.codec",
+            format!("unknown value {:?} (expected \"json\", \"msgpack\" or \"bincode\")", persistence.codec),
+        );
+    }
+    match persistence.compression.as_str() {
+        "none" => {}
+        "zstd" if cfg!(feature = "zstd") => {}
+        "zstd" => report.error("persistence.compression", "\"zstd\" requires memz-core's `zstd` feature"),
+        other => report.error(
+            "persistence.compression",
+            format!("unknown value {other:?} (expected \"none\" or \"zstd\")"),
+        ),
+    }
+    if let Err(e) = EmbeddingFormat::from_config(&persistence.embedding_format) {
+        report.error("persistence.embedding_format", e.to_string());
+    }
+
+    report.one_of("safety.profanity_filter", &config.safety.profanity_filter, PROFANITY_FILTERS);
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_clean() {
+        let report = MemzConfig::default().validate();
+        assert!(report.issues.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn reports_every_error_with_its_path() {
+        let config = MemzConfig::from_toml(
+            r#"
+            [memory]
+            decay_rate = 1.5
+            [memory.eviction]
+            hot_ring_hours = 500
+            [retrieval]
+            top_k = 0
+            [llm]
+            provider = "gpt"
+            "#,
+        )
+        .expect("well-formed TOML");
+
+        let report = config.validate();
+        let paths: Vec<&str> = report.errors().map(|i| i.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["memory.decay_rate", "memory.eviction.hot_ring_hours", "retrieval.top_k", "llm.provider"]
+        );
+        let err = report.into_result().expect_err("errors are fatal");
+        assert!(err.to_string().contains("llm.provider: unknown value \"gpt\""));
+    }
+
+    #[test]
+    fn odd_weights_are_a_warning() {
+        let mut config = MemzConfig::default();
+        config.retrieval.weights.relevance = 0.6;
+
+        let report = config.validate();
+        assert!(!report.has_errors());
+        let issue = report.issue("retrieval.weights").expect("weight warning");
+        assert_eq!(issue.severity, Severity::Warning);
+        assert!(report.into_result().is_ok());
+    }
+
+    #[test]
+    fn place_counts_towards_the_weight_sum() {
+        let mut config = MemzConfig::default();
+        config.retrieval.weights.place = 0.4;
+        let report = config.validate();
+        let issue = report.issue("retrieval.weights").expect("weight warning");
+        assert!(issue.message.contains("+ place = 1.40"), "{}", issue.message);
+
+        config.retrieval.weights = crate::config::RetrievalWeights {
+            recency: 0.0,
+            relevance: 0.0,
+            importance: 0.0,
+            emotional: 0.0,
+            social: 0.0,
+            place: 1.0,
+        };
+        assert!(config.validate().issues.is_empty());
+    }
+}
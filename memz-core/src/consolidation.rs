@@ -7,13 +7,45 @@
 //!
 //! Grounded in sleep-mediated memory consolidation research:
 //!   - Stickgold, R. & Walker, M.P. (2013). "Sleep-Dependent Memory Consolidation."
-
+//!
+//! Accordingly, a [`ConsolidationScheduler`] runs each NPC's consolidation
+//! as a "sleep cycle": once every `consolidation_interval_days`, during the
+//! NPC's night, within `consolidation_budget_ms`.  A cycle that runs out of
+//! budget resumes on the next check.  Each NPC has its own
+//! [`bedtime`], so a town's cycles spread over the night instead of all
+//! landing on the first check after dark.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::config::{EvictionConfig, MemoryConfig};
+use crate::determinism::IdGenerator;
+use crate::memory::MemoryBank;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::procedural::ProceduralMemory;
 use crate::memory::reflective::ReflectiveMemory;
 use crate::memory::semantic::SemanticMemory;
+use crate::time::TimeModel;
 use crate::types::{EntityId, GameTimestamp, MemoryId};
 
+/// Game-hour at which NPCs go to sleep.
+pub const NIGHT_START_HOUR: f64 = 22.0;
+
+/// Game-hour at which NPCs wake up.
+pub const NIGHT_END_HOUR: f64 = 6.0;
+
+/// Hours after [`NIGHT_START_HOUR`] over which NPCs' bedtimes are spread;
+/// the rest of the night is left for cycles that resume after running out
+/// of budget.
+pub const BEDTIME_SPREAD_HOURS: f64 = 4.0;
+
+/// Merged sources below this importance are deleted; the rest are weakened.
+const KEEP_SOURCE_IMPORTANCE: f32 = 0.3;
+
+/// Factor applied to the importance of weakened sources, so they fade
+/// faster now that the knowledge they carried is held elsewhere.
+const SOURCE_WEAKENING: f32 = 0.5;
+
 /// A consolidation task describes a pending memory transformation.
 #[derive(Debug, Clone)]
 pub struct ConsolidationTask {
@@ -29,8 +61,8 @@ pub struct ConsolidationTask {
     pub created_at: GameTimestamp,
 }
 
-/// Types of memory (for consolidation routing).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Types of memory (for consolidation routing and persistence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MemoryType {
     /// Episodic memory type.
     Episodic,
@@ -71,10 +103,11 @@ pub enum ConsolidationResult {
 ///
 /// Heuristic: If 3+ episodic memories share similar participants/location/theme,
 /// they can be distilled into a general fact.
-#[must_use] 
+#[must_use]
 pub fn consolidate_episodic_to_semantic(
     memories: &[EpisodicMemory],
     current_time: GameTimestamp,
+    ids: &IdGenerator,
 ) -> ConsolidationResult {
     if memories.len() < 3 {
         return ConsolidationResult::NoConsolidation {
@@ -91,15 +124,19 @@ pub fn consolidate_episodic_to_semantic(
         .collect();
 
     // Compute average emotional valence.
-    let avg_valence: f32 = memories.iter().map(|m| m.emotional_valence).sum::<f32>()
-        / memories.len() as f32;
+    let avg_valence: f32 =
+        memories.iter().map(|m| m.emotional_valence).sum::<f32>() / memories.len() as f32;
 
     // Generate a summary fact.
     let fact = if common_participants.is_empty() {
         format!(
             "After {} recent experiences, the general pattern seems to be {}.",
             memories.len(),
-            if avg_valence > 0.0 { "positive" } else { "challenging" },
+            if avg_valence > 0.0 {
+                "positive"
+            } else {
+                "challenging"
+            },
         )
     } else {
         let sentiment = if avg_valence > 0.3 {
@@ -126,7 +163,8 @@ pub fn consolidate_episodic_to_semantic(
         derived_from,
         "person_knowledge",
         current_time,
-    );
+    )
+    .with_id(ids.memory_id());
 
     ConsolidationResult::NewSemantic(semantic)
 }
@@ -135,12 +173,13 @@ pub fn consolidate_episodic_to_semantic(
 ///
 /// If an NPC has performed the same type of action N times, they start
 /// developing a procedural memory for it.
-#[must_use] 
+#[must_use]
 pub fn consolidate_to_procedural(
     skill_name: &str,
     repetition_count: u32,
     current_time: GameTimestamp,
     learning_rate: f32,
+    ids: &IdGenerator,
 ) -> ConsolidationResult {
     if repetition_count < 3 {
         return ConsolidationResult::NoConsolidation {
@@ -148,7 +187,8 @@ pub fn consolidate_to_procedural(
         };
     }
 
-    let mut procedural = ProceduralMemory::new(skill_name, current_time, learning_rate);
+    let mut procedural =
+        ProceduralMemory::new(skill_name, current_time, learning_rate).with_id(ids.memory_id());
     // Apply all past repetitions at once.
     for _ in 0..repetition_count {
         procedural.practice(current_time);
@@ -159,29 +199,32 @@ pub fn consolidate_to_procedural(
 
 /// Identify consolidation opportunities from a set of episodic memories.
 ///
-/// Returns a list of consolidation tasks ordered by priority.
-#[must_use] 
+/// Returns a list of consolidation tasks ordered by priority; ties keep the
+/// order in which participants first appear, so the result is reproducible.
+#[must_use]
 pub fn identify_consolidation_tasks(
     episodic: &[EpisodicMemory],
     current_time: &GameTimestamp,
 ) -> Vec<ConsolidationTask> {
     let mut tasks = Vec::new();
 
-    // Group by common participants.
-    let mut participant_groups: std::collections::HashMap<EntityId, Vec<MemoryId>> =
+    // Group by common participants, in first-seen order.
+    let mut group_index: std::collections::HashMap<EntityId, usize> =
         std::collections::HashMap::new();
+    let mut participant_groups: Vec<Vec<MemoryId>> = Vec::new();
 
     for memory in episodic {
         for participant in &memory.participants {
-            participant_groups
-                .entry(*participant)
-                .or_default()
-                .push(memory.id);
+            let group = *group_index.entry(*participant).or_insert_with(|| {
+                participant_groups.push(Vec::new());
+                participant_groups.len() - 1
+            });
+            participant_groups[group].push(memory.id);
         }
     }
 
     // Create consolidation tasks for groups with 3+ memories.
-    for memory_ids in participant_groups.values() {
+    for memory_ids in &participant_groups {
         if memory_ids.len() >= 3 {
             tasks.push(ConsolidationTask {
                 source_type: MemoryType::Episodic,
@@ -203,6 +246,218 @@ pub fn identify_consolidation_tasks(
     tasks
 }
 
+// ---------------------------------------------------------------------------
+// Sleep Cycles
+// ---------------------------------------------------------------------------
+
+/// What applying consolidation results did to a bank.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsolidationReport {
+    /// Tasks processed.
+    pub tasks_run: usize,
+    /// New semantic, procedural or reflective memories added.
+    pub memories_created: usize,
+    /// Merged episodic sources deleted.
+    pub sources_removed: usize,
+    /// Merged episodic sources kept with lowered importance.
+    pub sources_weakened: usize,
+    /// Whether the budget ran out before every task was processed.
+    pub exhausted: bool,
+}
+
+/// Add `result` to `bank` and delete or weaken the episodic memories in
+/// `sources` it was built from.
+///
+/// Protected sources (first meetings, emotional flashbulbs per `protect`)
+/// are left untouched; unimportant ones are deleted, the rest weakened.
+pub fn apply_consolidation(
+    bank: &mut MemoryBank,
+    sources: &[MemoryId],
+    result: ConsolidationResult,
+    protect: &EvictionConfig,
+    report: &mut ConsolidationReport,
+) {
+    match result {
+        ConsolidationResult::NewSemantic(memory) => bank.semantic.push(memory),
+        ConsolidationResult::NewProcedural(memory) => bank.procedural.push(memory),
+        ConsolidationResult::NewReflective(memory) => bank.reflective.push(memory),
+        ConsolidationResult::NoConsolidation { .. } => return,
+    }
+    report.memories_created += 1;
+
+    let merged: HashSet<MemoryId> = sources.iter().copied().collect();
+    bank.episodic.retain_mut(|memory| {
+        let protected = (protect.protect_first_meeting && memory.is_first_meeting)
+            || memory.emotional_valence.abs() > protect.protect_emotional_threshold;
+        if !merged.contains(&memory.id) || protected {
+            return true;
+        }
+        if memory.importance < KEEP_SOURCE_IMPORTANCE {
+            report.sources_removed += 1;
+            return false;
+        }
+        memory.importance *= SOURCE_WEAKENING;
+        memory.strength *= SOURCE_WEAKENING;
+        report.sources_weakened += 1;
+        true
+    });
+}
+
+/// Identify and apply episodic → semantic consolidations in `bank`,
+/// stopping once `budget` is spent (at least one task always runs).
+///
+/// Episodic memories already distilled into a semantic memory are not
+/// consolidated again.
+pub fn consolidate_bank(
+    bank: &mut MemoryBank,
+    current_time: GameTimestamp,
+    protect: &EvictionConfig,
+    budget: Duration,
+    ids: &IdGenerator,
+) -> ConsolidationReport {
+    let start = Instant::now();
+    let mut report = ConsolidationReport::default();
+
+    let mut distilled: HashSet<MemoryId> = bank
+        .semantic
+        .iter()
+        .flat_map(|m| m.derived_from.iter().copied())
+        .collect();
+    let candidates: Vec<EpisodicMemory> = bank
+        .episodic
+        .iter()
+        .filter(|m| !distilled.contains(&m.id))
+        .cloned()
+        .collect();
+
+    for task in identify_consolidation_tasks(&candidates, &current_time) {
+        if report.tasks_run > 0 && start.elapsed() >= budget {
+            report.exhausted = true;
+            break;
+        }
+        report.tasks_run += 1;
+
+        // An earlier task may already have merged some of these memories.
+        let sources: Vec<EpisodicMemory> = bank
+            .episodic
+            .iter()
+            .filter(|m| task.source_ids.contains(&m.id) && !distilled.contains(&m.id))
+            .cloned()
+            .collect();
+        let merged: Vec<MemoryId> = sources.iter().map(|m| m.id).collect();
+        let result = consolidate_episodic_to_semantic(&sources, current_time, ids);
+        if matches!(result, ConsolidationResult::NewSemantic(_)) {
+            distilled.extend(merged.iter().copied());
+        }
+        apply_consolidation(bank, &merged, result, protect, &mut report);
+    }
+
+    report
+}
+
+/// Game-hour (0–24) of `tick` by MEMZ game time, for hosts without a
+/// time-of-day clock of their own.
+#[must_use]
+pub fn hour_of_day(tick: u64, time: &TimeModel) -> f64 {
+    time.ticks_to_hours(tick).rem_euclid(24.0)
+}
+
+/// Whether `hour` (0–24) falls in the night, when NPCs consolidate.
+#[must_use]
+pub fn is_night(hour: f64) -> bool {
+    !(NIGHT_END_HOUR..NIGHT_START_HOUR).contains(&hour)
+}
+
+/// Game-hour (0–24) at which `entity` goes to sleep: a fixed point within
+/// [`BEDTIME_SPREAD_HOURS`] of [`NIGHT_START_HOUR`], hashed from its ID.
+#[must_use]
+pub fn bedtime(entity: EntityId) -> f64 {
+    let (high, low) = entity.0.as_u64_pair();
+    let fraction = (high ^ low) as f64 / u64::MAX as f64;
+    (NIGHT_START_HOUR + fraction * BEDTIME_SPREAD_HOURS).rem_euclid(24.0)
+}
+
+/// Hours since [`NIGHT_START_HOUR`] at game-hour `hour`.
+fn hours_after_dark(hour: f64) -> f64 {
+    (hour - NIGHT_START_HOUR).rem_euclid(24.0)
+}
+
+/// Schedules per-NPC consolidation as nightly sleep cycles.
+#[derive(Debug, Clone)]
+pub struct ConsolidationScheduler {
+    /// Game-days between cycles.
+    interval_days: f64,
+    /// Time each NPC's cycle may take.
+    budget: Duration,
+    /// Tick at which each NPC last completed a cycle.
+    last_cycle: HashMap<EntityId, u64>,
+}
+
+impl ConsolidationScheduler {
+    /// A scheduler following `config`'s `consolidation_interval_days` and
+    /// `consolidation_budget_ms`.
+    #[must_use]
+    pub fn new(config: &MemoryConfig) -> Self {
+        let mut scheduler = Self {
+            interval_days: 1.0,
+            budget: Duration::ZERO,
+            last_cycle: HashMap::new(),
+        };
+        scheduler.reconfigure(config);
+        scheduler
+    }
+
+    /// Pick up a changed interval or budget, keeping each NPC's history.
+    pub fn reconfigure(&mut self, config: &MemoryConfig) {
+        self.interval_days = config.consolidation_interval_days.max(1) as f64;
+        self.budget = Duration::from_secs_f32(config.consolidation_budget_ms.max(0.0) / 1000.0);
+    }
+
+    /// Whether `entity` should consolidate at `tick`, given the hour of
+    /// its day (see [`hour_of_day`]).
+    ///
+    /// A cycle is due at night, after the entity's [`bedtime`], once
+    /// `interval_days` have passed since the last one finished — less half
+    /// a day, so that a cycle finished late one night does not push the
+    /// next one out by a whole night.
+    #[must_use]
+    pub fn is_due(&self, entity: EntityId, tick: u64, hour: f64, time: &TimeModel) -> bool {
+        is_night(hour)
+            && hours_after_dark(hour) >= hours_after_dark(bedtime(entity))
+            && self.last_cycle.get(&entity).is_none_or(|last| {
+                time.ticks_to_days(tick.saturating_sub(*last)) >= self.interval_days - 0.5
+            })
+    }
+
+    /// Run `entity`'s sleep cycle if it is due.  Returns `None` if it is
+    /// not; a report with `exhausted` set means the cycle resumes on the
+    /// next call.
+    pub fn run(
+        &mut self,
+        entity: EntityId,
+        bank: &mut MemoryBank,
+        now: GameTimestamp,
+        hour: f64,
+        time: &TimeModel,
+        protect: &EvictionConfig,
+        ids: &IdGenerator,
+    ) -> Option<ConsolidationReport> {
+        if !self.is_due(entity, now.tick, hour, time) {
+            return None;
+        }
+        let report = consolidate_bank(bank, now, protect, self.budget, ids);
+        if !report.exhausted {
+            self.last_cycle.insert(entity, now.tick);
+        }
+        Some(report)
+    }
+
+    /// Forget `entity`'s history (e.g. when its bank is unloaded).
+    pub fn forget(&mut self, entity: EntityId) {
+        self.last_cycle.remove(&entity);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -231,6 +486,7 @@ mod tests {
         let result = consolidate_episodic_to_semantic(
             &memories,
             GameTimestamp::now(0),
+            &IdGenerator::random(),
         );
 
         match result {
@@ -254,9 +510,13 @@ mod tests {
         let result = consolidate_episodic_to_semantic(
             &memories,
             GameTimestamp::now(0),
+            &IdGenerator::random(),
         );
 
-        assert!(matches!(result, ConsolidationResult::NoConsolidation { .. }));
+        assert!(matches!(
+            result,
+            ConsolidationResult::NoConsolidation { .. }
+        ));
     }
 
     #[test]
@@ -266,14 +526,19 @@ mod tests {
             2,
             GameTimestamp::now(0),
             1.0,
+            &IdGenerator::random(),
         );
-        assert!(matches!(result, ConsolidationResult::NoConsolidation { .. }));
+        assert!(matches!(
+            result,
+            ConsolidationResult::NoConsolidation { .. }
+        ));
 
         let result = consolidate_to_procedural(
             "sword_fighting",
             10,
             GameTimestamp::now(0),
             1.0,
+            &IdGenerator::random(),
         );
         match result {
             ConsolidationResult::NewProcedural(mem) => {
@@ -297,13 +562,172 @@ mod tests {
             make_episodic("event4", vec![e2], 0.2),
         ];
 
-        let tasks = identify_consolidation_tasks(
-            &memories,
-            &GameTimestamp::now(0),
-        );
+        let tasks = identify_consolidation_tasks(&memories, &GameTimestamp::now(0));
 
         // Entity 1 appears in 3 memories → should produce a task.
         assert!(!tasks.is_empty());
         assert!(tasks[0].source_ids.len() >= 3);
     }
+
+    #[test]
+    fn consolidation_merges_sources_once() {
+        let smith = EntityId::new();
+        let mut bank = MemoryBank::new();
+        for (event, importance) in [
+            ("bought nails", 0.1),
+            ("bought a hinge", 0.2),
+            ("haggled", 0.6),
+        ] {
+            let mut memory = make_episodic(event, vec![smith], 0.4);
+            memory.importance = importance;
+            bank.episodic.push(memory);
+        }
+        let mut first_meeting = make_episodic("met the smith", vec![smith], 0.4);
+        first_meeting.is_first_meeting = true;
+        bank.episodic.push(first_meeting);
+
+        let protect = EvictionConfig::default();
+        let ids = IdGenerator::seeded(3);
+        let report = consolidate_bank(
+            &mut bank,
+            GameTimestamp::now(0),
+            &protect,
+            Duration::from_secs(1),
+            &ids,
+        );
+        assert_eq!(report.tasks_run, 1);
+        assert_eq!(report.memories_created, 1);
+        assert_eq!(report.sources_removed, 2);
+        assert_eq!(report.sources_weakened, 1);
+        assert!(!report.exhausted);
+        assert_eq!(bank.semantic.len(), 1);
+        assert_eq!(bank.episodic.len(), 2);
+        assert!(
+            bank.episodic.iter().any(|m| m.is_first_meeting),
+            "protected source kept"
+        );
+        let haggled = bank
+            .episodic
+            .iter()
+            .find(|m| m.event == "haggled")
+            .expect("weakened source kept");
+        assert!((haggled.importance - 0.3).abs() < 1e-6);
+
+        // The same memories are not distilled a second time.
+        let again = consolidate_bank(
+            &mut bank,
+            GameTimestamp::now(0),
+            &protect,
+            Duration::from_secs(1),
+            &ids,
+        );
+        assert_eq!(again.tasks_run, 0);
+        assert_eq!(bank.semantic.len(), 1);
+    }
+
+    #[test]
+    fn scheduler_runs_nightly_within_budget() {
+        let time = TimeModel::default();
+        let hour = |h: f64| (h * time.ticks_per_hour()) as u64;
+        let config = MemoryConfig {
+            consolidation_budget_ms: 0.0,
+            ..MemoryConfig::default()
+        };
+        let mut scheduler = ConsolidationScheduler::new(&config);
+        let protect = EvictionConfig::default();
+        let ids = IdGenerator::seeded(5);
+
+        let npc = EntityId::new();
+        let mut bank = MemoryBank::new();
+        for i in 0..3 {
+            let (a, b) = (EntityId::new(), EntityId::new());
+            for j in 0..3 {
+                bank.episodic
+                    .push(make_episodic(&format!("event {i}.{j}"), vec![a, b], 0.1));
+            }
+        }
+
+        let run = |scheduler: &mut ConsolidationScheduler, bank: &mut MemoryBank, tick: u64| {
+            scheduler.run(
+                npc,
+                bank,
+                GameTimestamp::now(tick),
+                hour_of_day(tick, &time),
+                &time,
+                &protect,
+                &ids,
+            )
+        };
+
+        assert!(
+            run(&mut scheduler, &mut bank, hour(12.0)).is_none(),
+            "not during the day"
+        );
+
+        // By 02:00 every NPC is past its bedtime.  A zero budget still
+        // makes progress, one task per call.
+        let first = run(&mut scheduler, &mut bank, hour(26.0)).expect("due at night");
+        assert_eq!(first.tasks_run, 1);
+        assert!(first.exhausted);
+        let mut cycles = 1;
+        while run(&mut scheduler, &mut bank, hour(26.5)).is_some_and(|r| r.exhausted) {
+            cycles += 1;
+        }
+        assert_eq!(
+            bank.semantic.len(),
+            3,
+            "every group distilled across {cycles} calls"
+        );
+
+        assert!(
+            run(&mut scheduler, &mut bank, hour(27.0)).is_none(),
+            "one cycle per night"
+        );
+        assert!(
+            run(&mut scheduler, &mut bank, hour(50.0)).is_some(),
+            "due again the next night"
+        );
+
+        scheduler.reconfigure(&MemoryConfig {
+            consolidation_interval_days: 3,
+            ..config
+        });
+        assert!(!scheduler.is_due(npc, hour(70.0), 2.0, &time));
+        assert!(scheduler.is_due(npc, hour(50.0 + 60.0), 2.0, &time));
+    }
+
+    #[test]
+    fn bedtimes_spread_a_town_over_the_night() {
+        let time = TimeModel::default();
+        let scheduler = ConsolidationScheduler::new(&MemoryConfig::default());
+        let town: Vec<EntityId> = (0..200)
+            .map(|i| EntityId::derived(&format!("villager {i}")))
+            .collect();
+        let due = |hour: f64| {
+            town.iter()
+                .filter(|npc| scheduler.is_due(**npc, 0, hour, &time))
+                .count()
+        };
+
+        assert!(
+            due(22.0) < 20,
+            "only early sleepers at dusk, got {}",
+            due(22.0)
+        );
+        assert!(
+            (60..140).contains(&due(0.0)),
+            "about half by midnight, got {}",
+            due(0.0)
+        );
+        assert_eq!(due(2.0), town.len(), "everyone by 02:00");
+        assert_eq!(due(5.9), town.len());
+        assert_eq!(due(6.0), 0, "nobody after dawn");
+
+        for npc in &town[..10] {
+            assert!(hours_after_dark(bedtime(*npc)) < BEDTIME_SPREAD_HOURS);
+            let just_before = (bedtime(*npc) - 0.01).rem_euclid(24.0);
+            assert!(!scheduler.is_due(*npc, 0, just_before, &time));
+            assert!(scheduler.is_due(*npc, 0, bedtime(*npc), &time));
+        }
+    }
 }
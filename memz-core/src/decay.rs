@@ -19,15 +19,19 @@
 use crate::config::MemoryConfig;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::social::SocialMemory;
+use crate::time::TimeModel;
 use crate::types::GameTimestamp;
 
 /// Calculate the retention of an episodic memory using the Ebbinghaus forgetting curve.
 ///
 /// Returns a value in (0.0, 1.0] where 1.0 is perfect retention.
 #[must_use]
-pub fn episodic_retention(memory: &EpisodicMemory, current_time: &GameTimestamp) -> f64 {
-    let delta_ticks = current_time.tick.saturating_sub(memory.timestamp.tick);
-    let delta_days = delta_ticks as f64 / 72_000.0;
+pub fn episodic_retention(
+    memory: &EpisodicMemory,
+    current_time: &GameTimestamp,
+    time: &TimeModel,
+) -> f64 {
+    let delta_days = time.days_between(&memory.timestamp, current_time);
 
     let strength = memory_strength(
         memory.importance,
@@ -41,9 +45,12 @@ pub fn episodic_retention(memory: &EpisodicMemory, current_time: &GameTimestamp)
 
 /// Calculate the retention of a social memory (gossip decays faster).
 #[must_use]
-pub fn social_retention(memory: &SocialMemory, current_time: &GameTimestamp) -> f64 {
-    let delta_ticks = current_time.tick.saturating_sub(memory.received_at.tick);
-    let delta_days = delta_ticks as f64 / 72_000.0;
+pub fn social_retention(
+    memory: &SocialMemory,
+    current_time: &GameTimestamp,
+    time: &TimeModel,
+) -> f64 {
+    let delta_days = time.days_between(&memory.received_at, current_time);
 
     // Social memories decay faster (gossip is less durable than personal experience).
     let trust_factor = f64::from(memory.trust_in_source);
@@ -93,30 +100,45 @@ pub fn memory_strength(
 pub fn decay_episodic_memories(
     memories: &mut Vec<EpisodicMemory>,
     current_time: &GameTimestamp,
+    time: &TimeModel,
     config: &MemoryConfig,
 ) {
+    forget_episodic_memories(memories, current_time, time, config);
+}
+
+/// [`decay_episodic_memories`], returning the memories that were forgotten
+/// (e.g. to condense them into a gist, see [`crate::archival`]).
+pub fn forget_episodic_memories(
+    memories: &mut Vec<EpisodicMemory>,
+    current_time: &GameTimestamp,
+    time: &TimeModel,
+    config: &MemoryConfig,
+) -> Vec<EpisodicMemory> {
     let threshold = f64::from(config.decay_rate);
 
-    memories.retain(|memory| {
-        // Protected memories never decay.
-        if memory.is_first_meeting {
-            return true;
-        }
-        if memory.emotional_valence.abs() > 0.8 {
-            return true; // flashbulb memories
-        }
-        // Check retention against threshold.
-        episodic_retention(memory, current_time) > threshold
-    });
+    memories
+        .extract_if(.., |memory| {
+            // Protected memories never decay.
+            if memory.is_first_meeting {
+                return false;
+            }
+            if memory.emotional_valence.abs() > 0.8 {
+                return false; // flashbulb memories
+            }
+            // Check retention against threshold.
+            episodic_retention(memory, current_time, time) <= threshold
+        })
+        .collect()
 }
 
 /// Run a decay pass over social memories.
 pub fn decay_social_memories(
     memories: &mut Vec<SocialMemory>,
     current_time: &GameTimestamp,
+    time: &TimeModel,
     threshold: f64,
 ) {
-    memories.retain(|memory| social_retention(memory, current_time) > threshold);
+    memories.retain(|memory| social_retention(memory, current_time, time) > threshold);
 }
 
 #[cfg(test)]
@@ -178,4 +200,22 @@ mod tests {
             "Frequently accessed memories should be stronger"
         );
     }
+
+    #[test]
+    fn forgotten_memories_are_returned() {
+        use crate::types::{EntityId, Location};
+
+        let time = TimeModel::default();
+        let old = GameTimestamp::now(0);
+        let mut memories = vec![
+            EpisodicMemory::new("Bought bread", vec![EntityId::new()], Location::default(), old, 0.1, 0.1),
+            EpisodicMemory::new("Saw a dragon", vec![], Location::default(), old, -0.9, 0.9),
+        ];
+
+        let now = GameTimestamp::now(365 * time.ticks_per_day);
+        let forgotten = forget_episodic_memories(&mut memories, &now, &time, &MemoryConfig::default());
+        assert_eq!(forgotten.len(), 1);
+        assert_eq!(forgotten[0].event, "Bought bread");
+        assert_eq!(memories[0].event, "Saw a dragon", "flashbulb memory kept");
+    }
 }
@@ -0,0 +1,197 @@
+//! Determinism — injectable wall clock and ID generation.
+//!
+//! Memory IDs are random `UUIDv4`s and every [`GameTimestamp`] carries a
+//! wall-clock `real_time`, so by default two runs of the same event script
+//! produce different saves.  Systems that create memories therefore draw
+//! IDs from an [`IdGenerator`] and timestamps from a [`Clock`]:
+//!
+//! - In production, [`IdGenerator::random`] and [`SystemClock`] behave
+//!   exactly like `MemoryId::new()` and `GameTimestamp::now()`.
+//! - In tests, replays and benchmarks, [`IdGenerator::seeded`] and
+//!   [`TickClock`] make a whole simulation reproducible from one seed:
+//!   IDs come from a seeded RNG and `real_time` is derived from the tick.
+//!
+//! A seeded generator is only deterministic if IDs are requested in the
+//! same order, so callers iterate entities in a stable order.
+
+use std::fmt;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use uuid::Uuid;
+
+use crate::types::{EntityId, GameTimestamp, MemoryId, SettlementId};
+
+// ---------------------------------------------------------------------------
+// Clock
+// ---------------------------------------------------------------------------
+
+/// Source of the wall-clock half of a [`GameTimestamp`].
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Wall-clock time at game tick `tick`.
+    fn wall_time(&self, tick: u64) -> DateTime<Utc>;
+
+    /// Timestamp for game tick `tick`.
+    fn timestamp(&self, tick: u64) -> GameTimestamp {
+        GameTimestamp {
+            tick,
+            real_time: self.wall_time(tick),
+        }
+    }
+}
+
+/// The real wall clock — what [`GameTimestamp::now`] uses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn wall_time(&self, _tick: u64) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A simulated wall clock: `epoch` plus the real time the server would
+/// have taken to reach the tick at `ticks_per_second`.
+#[derive(Debug, Clone, Copy)]
+pub struct TickClock {
+    /// Wall-clock time at tick 0.
+    pub epoch: DateTime<Utc>,
+    /// Server ticks per real second.
+    pub ticks_per_second: f64,
+}
+
+impl TickClock {
+    /// A clock starting at `epoch` and advancing at `ticks_per_second`.
+    #[must_use]
+    pub fn new(epoch: DateTime<Utc>, ticks_per_second: f64) -> Self {
+        Self { epoch, ticks_per_second }
+    }
+}
+
+impl Default for TickClock {
+    /// Starts at the Unix epoch and runs at the default 60 TPS.
+    fn default() -> Self {
+        Self::new(DateTime::UNIX_EPOCH, crate::time::TimeModel::default().ticks_per_second)
+    }
+}
+
+impl Clock for TickClock {
+    fn wall_time(&self, tick: u64) -> DateTime<Utc> {
+        let millis = (tick as f64 / self.ticks_per_second.max(f64::MIN_POSITIVE) * 1000.0) as i64;
+        self.epoch + TimeDelta::milliseconds(millis)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// IDs
+// ---------------------------------------------------------------------------
+
+/// Source of memory, entity and settlement IDs.
+///
+/// Shared by reference (`&self`) so it can be threaded through systems that
+/// also hold a `&mut MemoryBank`.
+pub struct IdGenerator {
+    /// `None` draws `UUIDv4`s from the OS; `Some` from a seeded RNG.
+    rng: Option<Mutex<StdRng>>,
+    seed: Option<u64>,
+}
+
+impl IdGenerator {
+    /// Random `UUIDv4`s — the production default.
+    #[must_use]
+    pub fn random() -> Self {
+        Self { rng: None, seed: None }
+    }
+
+    /// Reproducible IDs: the same seed yields the same sequence.
+    #[must_use]
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: Some(Mutex::new(StdRng::seed_from_u64(seed))),
+            seed: Some(seed),
+        }
+    }
+
+    /// The seed, if this generator is deterministic.
+    #[must_use]
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The next UUID (version 4 layout either way).
+    #[must_use]
+    pub fn uuid(&self) -> Uuid {
+        match &self.rng {
+            None => Uuid::new_v4(),
+            Some(rng) => {
+                let mut bytes = [0u8; 16];
+                rng.lock().fill_bytes(&mut bytes);
+                uuid::Builder::from_random_bytes(bytes).into_uuid()
+            }
+        }
+    }
+
+    /// The next memory ID.
+    #[must_use]
+    pub fn memory_id(&self) -> MemoryId {
+        MemoryId(self.uuid())
+    }
+
+    /// The next entity ID.
+    #[must_use]
+    pub fn entity_id(&self) -> EntityId {
+        EntityId(self.uuid())
+    }
+
+    /// The next settlement ID.
+    #[must_use]
+    pub fn settlement_id(&self) -> SettlementId {
+        SettlementId(self.uuid())
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl fmt::Debug for IdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdGenerator").field("seed", &self.seed).finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_generators_repeat_their_sequence() {
+        let (a, b) = (IdGenerator::seeded(7), IdGenerator::seeded(7));
+        let first: Vec<MemoryId> = (0..5).map(|_| a.memory_id()).collect();
+        let second: Vec<MemoryId> = (0..5).map(|_| b.memory_id()).collect();
+        assert_eq!(first, second);
+        assert_ne!(first[0], first[1]);
+        assert_eq!(first[0].0.get_version_num(), 4);
+
+        assert_ne!(IdGenerator::seeded(8).memory_id(), first[0]);
+        assert_ne!(IdGenerator::random().memory_id(), IdGenerator::random().memory_id());
+    }
+
+    #[test]
+    fn tick_clock_advances_with_ticks() {
+        let clock = TickClock::new(DateTime::UNIX_EPOCH, 30.0);
+        assert_eq!(clock.timestamp(0).real_time, DateTime::UNIX_EPOCH);
+        let later = clock.timestamp(90);
+        assert_eq!(later.tick, 90);
+        assert_eq!((later.real_time - DateTime::UNIX_EPOCH).num_seconds(), 3);
+        assert_eq!(clock.timestamp(90), later, "same tick, same timestamp");
+    }
+}
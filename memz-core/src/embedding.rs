@@ -345,7 +345,7 @@ mod tests {
     fn cosine_mismatched_dimensions() {
         let a = Embedding(vec![1.0, 0.0]);
         let b = Embedding(vec![1.0, 0.0, 0.0]);
-        assert_eq!(cosine_similarity(&a, &b), 0.0);
+        assert!(cosine_similarity(&a, &b).abs() < 1e-6);
     }
 
     #[test]
@@ -54,6 +54,15 @@ pub enum MemzError {
         reason: String,
     },
 
+    /// Saved data was written by a newer MEMZ than this build understands.
+    #[error("Unsupported schema version {found} (this build supports up to {supported})")]
+    UnsupportedSchemaVersion {
+        /// Version found in the database or payload.
+        found: u32,
+        /// Newest version this build can read.
+        supported: u32,
+    },
+
     /// Generic I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
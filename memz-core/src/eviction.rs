@@ -6,6 +6,10 @@
 //! [`evict_memories`], and protected memories (emotional flashbulbs,
 //! first meetings, active-quest references) skip eviction.
 //!
+//! [`evict_to_cold_storage`] runs a pass over a whole [`MemoryBank`]:
+//! cold-ring memories are spilled to the [`PersistenceEngine`] and can be
+//! paged back in on demand (see [`crate::persistence::cold`]).
+//!
 //! ```text
 //! ┌──────────┐     ┌──────────┐     ┌──────────┐     ┌──────────┐
 //! │ Hot Ring │────▶│Warm Ring │────▶│Cold Ring │────▶│ Archive  │
@@ -14,9 +18,15 @@
 //! └──────────┘     └──────────┘     └──────────┘     └──────────┘
 //! ```
 
+use crate::archival;
 use crate::config::EvictionConfig;
+use crate::error::Result;
+use crate::memory::MemoryBank;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::social::SocialMemory;
+use crate::persistence::PersistenceEngine;
+use crate::time::TimeModel;
+use crate::types::{EntityId, GameTimestamp};
 
 // ---------------------------------------------------------------------------
 // Ring classification
@@ -48,24 +58,23 @@ impl std::fmt::Display for Ring {
 
 /// Classify a memory's ring based on its age in game ticks.
 ///
-/// `ticks_per_hour` converts tick deltas to game-hours so the ring
-/// thresholds (defined in hours/days in the config) can be compared.
+/// `time` converts tick deltas to game-hours so the ring thresholds
+/// (defined in hours/days in the config) can be compared.
 #[must_use]
 pub fn classify_ring(
     memory_tick: u64,
     current_tick: u64,
-    ticks_per_hour: u64,
+    time: &TimeModel,
     config: &EvictionConfig,
 ) -> Ring {
     if current_tick < memory_tick {
         return Ring::Hot; // clock skew guard
     }
-    let age_ticks = current_tick - memory_tick;
-    let age_hours = if ticks_per_hour == 0 { 0 } else { age_ticks / ticks_per_hour };
+    let age_hours = time.ticks_to_hours(current_tick - memory_tick);
 
-    let hot_limit = u64::from(config.hot_ring_hours);
-    let warm_limit = u64::from(config.warm_ring_days) * 24;
-    let cold_limit = u64::from(config.cold_ring_days) * 24;
+    let hot_limit = f64::from(config.hot_ring_hours);
+    let warm_limit = f64::from(config.warm_ring_days) * 24.0;
+    let cold_limit = f64::from(config.cold_ring_days) * 24.0;
 
     if age_hours < hot_limit {
         Ring::Hot
@@ -138,13 +147,12 @@ pub struct EvictionResult {
 /// separated out.  Within each ring the lowest-scored memories are
 /// dropped first if `max_in_memory` is exceeded.
 ///
-/// `ticks_per_hour` is the game-specific conversion factor (Veloren
-/// uses ~7200 ticks/hour at 30 TPS).
+/// `time` is the game-specific tick conversion (see [`TimeModel`]).
 #[must_use] 
 pub fn evict_episodic_memories(
     memories: Vec<EpisodicMemory>,
     current_tick: u64,
-    ticks_per_hour: u64,
+    time: &TimeModel,
     max_in_memory: usize,
     config: &EvictionConfig,
 ) -> EvictionResult {
@@ -154,7 +162,7 @@ pub fn evict_episodic_memories(
         let ring = classify_ring(
             mem.timestamp.tick,
             current_tick,
-            ticks_per_hour,
+            time,
             config,
         );
         match ring {
@@ -198,6 +206,129 @@ pub fn evict_episodic_memories(
     result
 }
 
+/// What an [`evict_to_cold_storage`] pass did to one bank.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpillReport {
+    /// Episodic memories still in the bank.
+    pub retained: usize,
+    /// Memories moved from the bank to cold storage.
+    pub spilled: usize,
+    /// Memories forgotten, from the bank or from cold storage.
+    pub archived: usize,
+    /// Gists the forgotten memories were condensed into.
+    pub gists: usize,
+}
+
+/// Run [`evict_episodic_memories`] on `bank` and carry out the result:
+/// cold-ring memories are spilled to `store` under `owner`, archive-ring
+/// memories are forgotten — including cold rows that have aged past
+/// `cold_ring_days` since they were spilled.  With `gist_archival`, the
+/// forgotten memories are condensed into gists first (see
+/// [`crate::archival`]).
+///
+/// If spilling fails the cold memories are put back into the bank.
+pub fn evict_to_cold_storage(
+    bank: &mut MemoryBank,
+    owner: &EntityId,
+    store: &PersistenceEngine,
+    now: &GameTimestamp,
+    time: &TimeModel,
+    max_in_memory: usize,
+    config: &EvictionConfig,
+) -> Result<SpillReport> {
+    let result = evict_episodic_memories(
+        std::mem::take(&mut bank.episodic),
+        now.tick,
+        time,
+        max_in_memory,
+        config,
+    );
+    bank.episodic = result.retained;
+
+    if let Err(e) = store.spill_cold(owner, bank, &result.to_cold_storage) {
+        bank.episodic.extend(result.to_cold_storage);
+        return Err(e);
+    }
+
+    let cold_ticks = time.ticks_per_game_hours(f64::from(config.cold_ring_days) * 24.0);
+    let mut forgotten = result.to_archive;
+    forgotten.extend(store.archive_cold(owner, bank, now.tick.saturating_sub(cold_ticks))?);
+
+    let gists = if config.gist_archival {
+        archival::archive(bank, &forgotten, *now, time).len()
+    } else {
+        0
+    };
+
+    Ok(SpillReport {
+        retained: bank.episodic.len(),
+        spilled: result.to_cold_storage.len(),
+        archived: forgotten.len(),
+        gists,
+    })
+}
+
+/// [`evict_to_cold_storage`] for a bank without a cold store: archive-ring
+/// memories are forgotten, and so are the lowest-scoring others once the
+/// bank holds more than `max_in_memory`.  Cold-ring memories otherwise
+/// stay in the bank, which keeps its order.
+pub fn evict_in_memory(
+    bank: &mut MemoryBank,
+    now: &GameTimestamp,
+    time: &TimeModel,
+    max_in_memory: usize,
+    config: &EvictionConfig,
+) -> SpillReport {
+    let (mut forgotten, mut live): (Vec<_>, Vec<_>) = std::mem::take(&mut bank.episodic)
+        .into_iter()
+        .partition(|m| classify_ring(m.timestamp.tick, now.tick, time, config) == Ring::Archive);
+
+    if live.len() > max_in_memory {
+        let mut scores: Vec<(f64, usize)> = live
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let ticks_since_access = now.tick.saturating_sub(m.last_accessed.tick);
+                let score = eviction_score(
+                    m.importance,
+                    m.emotional_valence,
+                    m.is_first_meeting,
+                    ticks_since_access,
+                    config,
+                );
+                (score, i)
+            })
+            .collect();
+        scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut keep = vec![false; live.len()];
+        for (_, i) in &scores[..max_in_memory] {
+            keep[*i] = true;
+        }
+        let mut kept = Vec::with_capacity(max_in_memory);
+        for (memory, keep) in live.into_iter().zip(keep) {
+            if keep {
+                kept.push(memory);
+            } else {
+                forgotten.push(memory);
+            }
+        }
+        live = kept;
+    }
+    bank.episodic = live;
+
+    let gists = if config.gist_archival && !forgotten.is_empty() {
+        archival::archive(bank, &forgotten, *now, time).len()
+    } else {
+        0
+    };
+    SpillReport {
+        retained: bank.episodic.len(),
+        spilled: 0,
+        archived: forgotten.len(),
+        gists,
+    }
+}
+
 /// Run a full eviction pass on social memories.
 ///
 /// Social memories don't have `is_first_meeting` or `last_accessed`
@@ -207,7 +338,7 @@ pub fn evict_episodic_memories(
 pub fn evict_social_memories(
     memories: Vec<SocialMemory>,
     current_tick: u64,
-    ticks_per_hour: u64,
+    time: &TimeModel,
     max_in_memory: usize,
     config: &EvictionConfig,
 ) -> (Vec<SocialMemory>, Vec<SocialMemory>) {
@@ -218,7 +349,7 @@ pub fn evict_social_memories(
         let ring = classify_ring(
             mem.received_at.tick,
             current_tick,
-            ticks_per_hour,
+            time,
             config,
         );
         match ring {
@@ -268,6 +399,14 @@ mod tests {
         EvictionConfig::default()
     }
 
+    /// 3,600 ticks per game-hour.
+    fn hourly() -> TimeModel {
+        TimeModel {
+            ticks_per_day: 24 * 3600,
+            ..TimeModel::default()
+        }
+    }
+
     fn make_episodic(tick: u64, importance: f32, valence: f32, first_meeting: bool) -> EpisodicMemory {
         let ts = GameTimestamp { tick, real_time: Utc::now() };
         EpisodicMemory {
@@ -291,7 +430,7 @@ mod tests {
     fn ring_classification_hot() {
         let config = default_config();
         // Memory 1 hour old, hot limit is 24h → Hot
-        let ring = classify_ring(100, 100 + 3600, 3600, &config);
+        let ring = classify_ring(100, 100 + 3600, &hourly(), &config);
         assert_eq!(ring, Ring::Hot);
     }
 
@@ -299,7 +438,7 @@ mod tests {
     fn ring_classification_warm() {
         let config = default_config();
         // 2 days old (48 hours) → Warm (24h < 48h < 7*24=168h)
-        let ring = classify_ring(0, 48 * 3600, 3600, &config);
+        let ring = classify_ring(0, 48 * 3600, &hourly(), &config);
         assert_eq!(ring, Ring::Warm);
     }
 
@@ -307,7 +446,7 @@ mod tests {
     fn ring_classification_cold() {
         let config = default_config();
         // 30 days → Cold (168h < 720h < 90*24=2160h)
-        let ring = classify_ring(0, 30 * 24 * 3600, 3600, &config);
+        let ring = classify_ring(0, 30 * 24 * 3600, &hourly(), &config);
         assert_eq!(ring, Ring::Cold);
     }
 
@@ -315,11 +454,12 @@ mod tests {
     fn ring_classification_archive() {
         let config = default_config();
         // 100 days → Archive (> 90*24 = 2160h)
-        let ring = classify_ring(0, 100 * 24 * 3600, 3600, &config);
+        let ring = classify_ring(0, 100 * 24 * 3600, &hourly(), &config);
         assert_eq!(ring, Ring::Archive);
     }
 
     #[test]
+    #[allow(clippy::float_cmp)] // `f64::MAX` is an exact sentinel
     fn protected_memories_not_evicted() {
         let config = default_config();
         // First meeting
@@ -334,14 +474,14 @@ mod tests {
     fn eviction_respects_capacity() {
         let config = default_config();
         let current_tick = 1000;
-        let ticks_per_hour = 3600;
+        let time = hourly();
 
         // Create 10 hot-ring memories
         let memories: Vec<_> = (0..10)
             .map(|i| make_episodic(current_tick - i * 10, 0.5, 0.3, false))
             .collect();
 
-        let result = evict_episodic_memories(memories, current_tick, ticks_per_hour, 5, &config);
+        let result = evict_episodic_memories(memories, current_tick, &time, 5, &config);
         assert_eq!(result.retained.len(), 5);
         assert_eq!(result.to_cold_storage.len(), 5);
     }
@@ -350,7 +490,7 @@ mod tests {
     fn eviction_keeps_protected() {
         let config = default_config();
         let current_tick = 1000;
-        let ticks_per_hour = 3600;
+        let time = hourly();
 
         let mut memories = Vec::new();
         // 3 normal + 2 protected (first meeting)
@@ -361,9 +501,73 @@ mod tests {
             memories.push(make_episodic(current_tick - i * 10, 0.1, 0.1, true));
         }
 
-        let result = evict_episodic_memories(memories, current_tick, ticks_per_hour, 3, &config);
+        let result = evict_episodic_memories(memories, current_tick, &time, 3, &config);
         // Both protected memories must be retained
         let protected_count = result.retained.iter().filter(|m| m.is_first_meeting).count();
         assert_eq!(protected_count, 2);
     }
+
+    #[test]
+    fn cold_ring_is_spilled_and_archive_forgotten() {
+        use crate::config::PersistenceConfig;
+
+        let config = default_config();
+        let time = hourly();
+        let day = 24 * 3600;
+        let current_tick = 200 * day;
+        let store = PersistenceEngine::open_in_memory(&PersistenceConfig::default())
+            .expect("open engine");
+        let (owner, player) = (EntityId::new(), EntityId::new());
+
+        let mut bank = MemoryBank::new();
+        for age_days in [0, 2, 30, 60, 120] {
+            let mut memory = make_episodic(current_tick - age_days * day, 0.5, 0.3, false);
+            memory.participants.push(player);
+            bank.episodic.push(memory);
+        }
+
+        let now = GameTimestamp { tick: current_tick, real_time: Utc::now() };
+        let report = evict_to_cold_storage(&mut bank, &owner, &store, &now, &time, 10, &config)
+            .expect("eviction pass");
+        assert_eq!(report, SpillReport { retained: 2, spilled: 2, archived: 1, gists: 1 });
+        assert_eq!(bank.semantic.len(), 1, "the forgotten memory left a gist");
+        assert_eq!(bank.episodic.len(), 2);
+        assert_eq!(bank.cold_count(player), 2);
+
+        // 40 days later the retained memories turn cold and the
+        // 60-day-old one has aged out of the cold ring.
+        let later = GameTimestamp { tick: current_tick + 40 * day, real_time: Utc::now() };
+        let config = EvictionConfig { gist_archival: false, ..config };
+        let report = evict_to_cold_storage(&mut bank, &owner, &store, &later, &time, 10, &config)
+            .expect("eviction pass");
+        assert_eq!(report, SpillReport { retained: 0, spilled: 2, archived: 1, gists: 0 });
+        assert_eq!(bank.semantic.len(), 1);
+        assert_eq!(bank.cold_count(player), 3);
+        // The archived row leaves cold storage with the next save.
+        assert_eq!(store.cold_memory_count(&owner).expect("count"), 4);
+        store.save_bank(&owner, &mut bank).expect("save");
+        assert_eq!(store.cold_memory_count(&owner).expect("count"), 3);
+    }
+
+    #[test]
+    fn without_a_store_the_weakest_are_forgotten() {
+        let config = default_config();
+        let time = hourly();
+        let day = 24 * 3600;
+        let current_tick = 200 * day;
+
+        let mut bank = MemoryBank::new();
+        let old = make_episodic(current_tick - 120 * day, 0.9, 0.3, false);
+        let weak = make_episodic(current_tick - 30 * day, 0.1, 0.1, false);
+        let strong = make_episodic(current_tick - 30 * day, 0.9, 0.1, false);
+        let fresh = make_episodic(current_tick, 0.5, 0.1, false);
+        let keep = vec![strong.id, fresh.id];
+        bank.episodic.extend([old, weak, strong, fresh]);
+
+        let now = GameTimestamp { tick: current_tick, real_time: Utc::now() };
+        let report = evict_in_memory(&mut bank, &now, &time, 2, &config);
+        assert_eq!(report, SpillReport { retained: 2, spilled: 0, archived: 2, gists: 1 });
+        // Bank order is kept, and the cold-ring survivor stays in memory.
+        assert_eq!(bank.episodic.iter().map(|m| m.id).collect::<Vec<_>>(), keep);
+    }
 }
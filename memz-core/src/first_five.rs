@@ -13,6 +13,7 @@
 //! 3. **First Meeting Protection**: The player's first interaction with any
 //!    NPC creates a "first meeting" memory that never decays
 
+use crate::determinism::IdGenerator;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::injected::{InjectedMemory, InjectedPriority};
 use crate::memory::semantic::SemanticMemory;
@@ -75,12 +76,14 @@ pub struct SeedGossip {
 /// Populate a memory bank for a seed NPC.
 ///
 /// This creates a rich set of pre-existing memories that make the NPC
-/// feel like they've been living in the world for a while.
+/// feel like they've been living in the world for a while.  Memory IDs and
+/// the placeholder gossip entities come from `ids`.
 #[must_use] 
 pub fn populate_seed_npc(
     template: &SeedNpcTemplate,
     npc_id: EntityId,
     timestamp: GameTimestamp,
+    ids: &IdGenerator,
 ) -> MemoryBank {
     let mut bank = MemoryBank::new();
 
@@ -100,6 +103,7 @@ pub fn populate_seed_npc(
             timestamp,
             priority,
         )
+        .with_id(ids.memory_id())
         .with_known_npcs(vec![npc_id]);
         memory.is_first_five_minutes = true;
         bank.injected.push(memory);
@@ -113,20 +117,22 @@ pub fn populate_seed_npc(
             vec![],
             "world_knowledge",
             timestamp,
-        );
+        )
+        .with_id(ids.memory_id());
         bank.semantic.push(semantic);
     }
 
     // --- 3. Gossip (social memories) ---
     for gossip in &template.gossip {
         let mut social = SocialMemory::new(
-            EntityId::new(), // placeholder entity
-            EntityId::new(), // placeholder source
+            ids.entity_id(), // placeholder entity
+            ids.entity_id(), // placeholder source
             gossip.claim.clone(),
             0.7, // moderate trust
             0,   // first-hand (they "observed" this)
             timestamp,
-        );
+        )
+        .with_id(ids.memory_id());
         social.sentiment = gossip.sentiment;
         bank.social.push(social);
     }
@@ -147,7 +153,8 @@ pub fn populate_seed_npc(
             timestamp,
             *valence,
             *importance,
-        );
+        )
+        .with_id(ids.memory_id());
         bank.episodic.push(episodic);
     }
 
@@ -160,18 +167,20 @@ pub fn inject_starter_gossip(
     banks: &mut [(EntityId, &mut MemoryBank)],
     gossip_items: &[SeedGossip],
     timestamp: GameTimestamp,
+    ids: &IdGenerator,
 ) {
     for (i, (_, bank)) in banks.iter_mut().enumerate() {
         // Each NPC gets a different subset of gossip
         for gossip in gossip_items.iter().skip(i % 2).take(3) {
             let mut social = SocialMemory::new(
-                EntityId::new(),
-                EntityId::new(),
+                ids.entity_id(),
+                ids.entity_id(),
                 gossip.claim.clone(),
                 0.6,
                 1, // heard from someone
                 timestamp,
-            );
+            )
+            .with_id(ids.memory_id());
             social.sentiment = gossip.sentiment;
             bank.social.push(social);
         }
@@ -260,7 +269,7 @@ mod tests {
     fn seed_npc_has_memories() {
         let template = default_blacksmith_template();
         let npc_id = EntityId::new();
-        let bank = populate_seed_npc(&template, npc_id, GameTimestamp::now(36_000));
+        let bank = populate_seed_npc(&template, npc_id, GameTimestamp::now(36_000), &IdGenerator::random());
 
         assert!(bank.injected.len() >= 4, "Should have backstory memories");
         assert!(bank.semantic.len() >= 3, "Should have knowledge");
@@ -271,7 +280,7 @@ mod tests {
     #[test]
     fn seed_npc_backstory_marked_first_five() {
         let template = default_blacksmith_template();
-        let bank = populate_seed_npc(&template, EntityId::new(), GameTimestamp::now(36_000));
+        let bank = populate_seed_npc(&template, EntityId::new(), GameTimestamp::now(36_000), &IdGenerator::random());
 
         for injected in &bank.injected {
             assert!(injected.is_first_five_minutes);
@@ -308,6 +317,7 @@ mod tests {
             &mut [(e1, &mut bank1), (e2, &mut bank2)],
             &gossip,
             ts,
+            &IdGenerator::random(),
         );
 
         assert!(!bank1.social.is_empty(), "NPC 1 should have gossip");
@@ -317,7 +327,7 @@ mod tests {
     #[test]
     fn tavern_keeper_template() {
         let template = default_tavern_keeper_template();
-        let bank = populate_seed_npc(&template, EntityId::new(), GameTimestamp::now(36_000));
+        let bank = populate_seed_npc(&template, EntityId::new(), GameTimestamp::now(36_000), &IdGenerator::random());
 
         assert!(bank.total_count() > 5, "Tavern keeper should have many memories");
     }
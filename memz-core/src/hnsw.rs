@@ -1,8 +1,20 @@
 //! HNSW Vector Index — Approximate Nearest-Neighbor Search (§12.4)
 //!
-//! Wraps `instant-distance` to provide fast cosine-similarity search over
-//! memory embeddings.  Used by the retrieval engine when the memory count
-//! exceeds the brute-force threshold (default: 100 memories).
+//! A small, dependency-free Hierarchical Navigable Small World graph
+//! (Malkov & Yashunin, 2018) over cosine similarity of memory embeddings.
+//! Used by the retrieval engine when the memory count exceeds the
+//! brute-force threshold (default: 100 memories).
+//!
+//! Unlike a build-once index, the graph is maintained incrementally:
+//!
+//! - **Insert** only normalises the vector and stages it — cheap enough for
+//!   the memory-creation budget (< 10µs).  Staged points are searched
+//!   exhaustively until [`HnswIndex::link_pending`] (or [`HnswIndex::build`])
+//!   links them into the graph one by one; no full rebuild is ever needed.
+//! - **Remove** tombstones the node.  Tombstoned nodes still route searches
+//!   but are never returned; once they exceed
+//!   [`HnswIndex::COMPACTION_THRESHOLD`] of the graph, the next
+//!   [`HnswIndex::build`] compacts them away.
 //!
 //! ## Usage
 //!
@@ -17,51 +29,41 @@
 //! assert!(!results.is_empty());
 //! ```
 
-use instant_distance::{Builder, HnswMap, Point, Search};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use crate::types::{Embedding, MemoryId};
-
-// ---------------------------------------------------------------------------
-// HnswPoint — adapter from Embedding to instant-distance Point trait
-// ---------------------------------------------------------------------------
+use ordered_float::OrderedFloat;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-/// A point in the HNSW index, wrapping an `Embedding` for cosine distance.
-#[derive(Clone, Debug)]
-struct HnswPoint {
-    /// The normalized embedding vector.
-    normalized: Vec<f32>,
-}
+use crate::config::RetrievalConfig;
+use crate::types::{Embedding, MemoryId};
 
-impl HnswPoint {
-    /// Create from a raw embedding. Normalizes to unit length for cosine distance.
-    fn from_embedding(embedding: &Embedding) -> Self {
-        let norm = embedding
-            .0
-            .iter()
-            .map(|x| x * x)
-            .sum::<f32>()
-            .sqrt()
-            .max(f32::EPSILON);
-        let normalized: Vec<f32> = embedding.0.iter().map(|x| x / norm).collect();
-        Self { normalized }
-    }
+/// Seed for level assignment — deterministic graphs for reproducibility.
+const LEVEL_SEED: u64 = 42;
+
+/// A distance paired with a node, ordered by distance.
+type Scored = (OrderedFloat<f32>, u32);
+
+/// Normalise `embedding` to unit length so cosine similarity is a dot product.
+fn normalize(embedding: &Embedding) -> Vec<f32> {
+    let norm = embedding
+        .0
+        .iter()
+        .map(|x| x * x)
+        .sum::<f32>()
+        .sqrt()
+        .max(f32::EPSILON);
+    embedding.0.iter().map(|x| x / norm).collect()
 }
 
-impl Point for HnswPoint {
-    /// Cosine distance = 1 - `cosine_similarity`.
-    /// Since vectors are pre-normalized, `cosine_similarity` = dot product.
-    fn distance(&self, other: &Self) -> f32 {
-        if self.normalized.len() != other.normalized.len() {
-            return 1.0; // Maximum distance for mismatched dimensions
-        }
-        let dot: f32 = self
-            .normalized
-            .iter()
-            .zip(other.normalized.iter())
-            .map(|(a, b)| a * b)
-            .sum();
-        (1.0 - dot).max(0.0) // Clamp to [0, 2] → typically [0, 1]
+/// Cosine distance between two unit vectors: `1 - dot`, clamped at 0.
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 1.0; // Maximum distance for mismatched dimensions
     }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    (1.0 - dot).max(0.0)
 }
 
 // ---------------------------------------------------------------------------
@@ -79,49 +81,81 @@ pub struct HnswResult {
     pub similarity: f32,
 }
 
+impl HnswResult {
+    fn new(memory_id: MemoryId, distance: f32) -> Self {
+        Self {
+            memory_id,
+            distance,
+            similarity: 1.0 - distance,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
-// HnswIndex — incremental insert + batch-build + search
+// HnswIndex — staged insert + incremental linking + tombstones
 // ---------------------------------------------------------------------------
 
+/// A graph node: one embedding and its neighbour lists, one per layer.
+#[derive(Debug, Clone)]
+struct Node {
+    memory_id: MemoryId,
+    vector: Vec<f32>,
+    /// `links[layer]` — neighbours on that layer (layer 0 is the densest).
+    links: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
 /// HNSW-based approximate nearest-neighbor index for memory embeddings.
 ///
 /// ## Lifecycle
 ///
-/// 1. **Insert** — Add embeddings with their memory IDs via [`insert`].
-/// 2. **Build** — Call [`build`] to construct the HNSW graph (O(N log N)).
-/// 3. **Search** — Query nearest neighbors via [`search`].
-///
-/// Insertions after build require re-building (incremental rebuild is
-/// amortized: only rebuild when dirty count exceeds threshold).
+/// 1. **Insert** — [`insert`](Self::insert) stages an embedding (O(d)).
+/// 2. **Link** — [`link_pending`](Self::link_pending) / [`build`](Self::build)
+///    wire staged points into the graph, O(log N) each.
+/// 3. **Search** — [`search`](Self::search) walks the graph and scans the
+///    (small) staging area.
+/// 4. **Remove** — [`remove`](Self::remove) tombstones; [`build`](Self::build)
+///    compacts once tombstones pass [`Self::COMPACTION_THRESHOLD`].
+#[derive(Debug, Clone)]
 pub struct HnswIndex {
-    /// Pending (not yet indexed) points.
-    pending_points: Vec<HnswPoint>,
-    /// Pending values (`MemoryId`).
-    pending_values: Vec<MemoryId>,
-    /// Built HNSW map (None until `build()` is called).
-    map: Option<HnswMap<HnswPoint, MemoryId>>,
-    /// Number of inserts since last build.
-    dirty_count: usize,
+    /// Graph nodes; a node's index is its id in `links`.
+    nodes: Vec<Node>,
+    /// Live graph node of each memory.
+    by_memory: HashMap<MemoryId, u32>,
+    /// Inserted but not yet linked points.
+    staged: Vec<(MemoryId, Vec<f32>)>,
+    /// Node every search starts from (on the top layer).
+    entry_point: Option<u32>,
+    /// Number of tombstoned nodes still in the graph.
+    tombstones: usize,
     /// `ef_construction` parameter (higher = more accurate build, slower).
     ef_construction: usize,
     /// `ef_search` parameter (higher = more accurate search, slower).
     ef_search: usize,
-    /// Threshold: auto-rebuild if `dirty_count` exceeds this fraction of total.
-    auto_rebuild_threshold: f32,
+    /// Max connections per node on layers ≥ 1 (`M`); layer 0 allows `2·M`.
+    m: usize,
+    /// Level generation RNG.
+    rng: StdRng,
 }
 
 impl HnswIndex {
+    /// Fraction of tombstoned nodes above which [`build`](Self::build)
+    /// compacts the graph.
+    pub const COMPACTION_THRESHOLD: f32 = 0.2;
+
     /// Create a new empty HNSW index with default parameters.
     #[must_use]
     pub fn new() -> Self {
         Self {
-            pending_points: Vec::new(),
-            pending_values: Vec::new(),
-            map: None,
-            dirty_count: 0,
+            nodes: Vec::new(),
+            by_memory: HashMap::new(),
+            staged: Vec::new(),
+            entry_point: None,
+            tombstones: 0,
             ef_construction: 100,
             ef_search: 50,
-            auto_rebuild_threshold: 0.2, // Rebuild when 20% new points
+            m: 16,
+            rng: StdRng::seed_from_u64(LEVEL_SEED),
         }
     }
 
@@ -135,148 +169,328 @@ impl HnswIndex {
         }
     }
 
+    /// Create with the `hnsw_*` parameters from `[retrieval]`.
+    #[must_use]
+    pub fn with_config(config: &RetrievalConfig) -> Self {
+        Self {
+            ef_construction: config.hnsw_ef_construction,
+            ef_search: config.hnsw_ef_search,
+            m: config.hnsw_m.max(2),
+            ..Self::new()
+        }
+    }
+
     /// Insert a memory embedding into the index.
     ///
-    /// The embedding is queued; call [`build`] to index it.
+    /// The embedding is staged — immediately searchable, linked into the
+    /// graph by the next [`link_pending`](Self::link_pending) or
+    /// [`build`](Self::build).  Re-inserting a memory replaces its embedding.
     pub fn insert(&mut self, memory_id: MemoryId, embedding: Embedding) {
-        self.pending_points
-            .push(HnswPoint::from_embedding(&embedding));
-        self.pending_values.push(memory_id);
-        self.dirty_count += 1;
+        self.remove(memory_id);
+        self.staged.push((memory_id, normalize(&embedding)));
     }
 
-    /// Number of points currently in the index (pending + built).
+    /// Number of live points in the index (linked + staged).
     #[must_use]
     pub fn len(&self) -> usize {
-        self.pending_points.len()
+        self.by_memory.len() + self.staged.len()
     }
 
     /// Whether the index is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.pending_points.is_empty()
+        self.len() == 0
     }
 
-    /// Whether the index needs rebuilding (dirty inserts exceed threshold).
+    /// Number of staged points waiting to be linked.
     #[must_use]
-    pub fn needs_rebuild(&self) -> bool {
-        if self.map.is_none() && !self.pending_points.is_empty() {
-            return true;
-        }
-        let total = self.pending_points.len();
-        if total == 0 {
-            return false;
+    pub fn pending(&self) -> usize {
+        self.staged.len()
+    }
+
+    /// Whether tombstones exceed [`Self::COMPACTION_THRESHOLD`] of the graph.
+    #[must_use]
+    pub fn needs_compaction(&self) -> bool {
+        !self.nodes.is_empty()
+            && (self.tombstones as f32 / self.nodes.len() as f32) > Self::COMPACTION_THRESHOLD
+    }
+
+    /// Link up to `max` staged points into the graph, oldest first.
+    /// Returns how many were linked.
+    pub fn link_pending(&mut self, max: usize) -> usize {
+        let count = max.min(self.staged.len());
+        let batch: Vec<_> = self.staged.drain(..count).collect();
+        for (memory_id, vector) in batch {
+            self.link(memory_id, vector);
         }
-        (self.dirty_count as f32 / total as f32) > self.auto_rebuild_threshold
+        count
     }
 
-    /// Build (or rebuild) the HNSW graph from all pending points.
+    /// Compact the graph if needed, then link every staged point.
     ///
-    /// This is O(N log N) and should be called during loading or
-    /// periodically between frames.
+    /// Linking is incremental (O(log N) per point); only compaction rebuilds,
+    /// and only after many removals.
     pub fn build(&mut self) {
-        if self.pending_points.is_empty() {
-            return;
+        if self.needs_compaction() {
+            self.compact();
         }
+        self.link_pending(usize::MAX);
+    }
 
-        let builder = Builder::default()
-            .ef_construction(self.ef_construction)
-            .ef_search(self.ef_search)
-            .seed(42); // Deterministic for reproducibility
-
-        let map = builder.build(
-            self.pending_points.clone(),
-            self.pending_values.clone(),
-        );
-
-        self.map = Some(map);
-        self.dirty_count = 0;
+    /// Rebuild the graph from its live nodes, dropping all tombstones.
+    pub fn compact(&mut self) {
+        let live: Vec<(MemoryId, Vec<f32>)> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .filter(|node| !node.deleted)
+            .map(|node| (node.memory_id, node.vector))
+            .collect();
+        self.by_memory.clear();
+        self.entry_point = None;
+        self.tombstones = 0;
+        self.rng = StdRng::seed_from_u64(LEVEL_SEED);
+        for (memory_id, vector) in live {
+            self.link(memory_id, vector);
+        }
     }
 
     /// Search for the `k` nearest neighbors to the query embedding.
     ///
     /// Returns results sorted by ascending distance (most similar first).
-    /// If the index hasn't been built yet, falls back to brute-force.
     #[must_use]
     pub fn search(&self, query: &Embedding, k: usize) -> Vec<HnswResult> {
-        let query_point = HnswPoint::from_embedding(query);
-
-        if let Some(map) = &self.map {
-            let mut search = Search::default();
-            let results: Vec<HnswResult> = map
-                .search(&query_point, &mut search)
-                .take(k)
-                .map(|item| HnswResult {
-                    memory_id: *item.value,
-                    distance: item.distance,
-                    similarity: 1.0 - item.distance,
-                })
-                .collect();
-            results
-        } else {
-            // Brute-force fallback when not built
-            self.brute_force_search(&query_point, k)
+        if k == 0 {
+            return Vec::new();
         }
-    }
-
-    /// Brute-force linear scan (used when index is not yet built).
-    fn brute_force_search(&self, query: &HnswPoint, k: usize) -> Vec<HnswResult> {
-        let mut scored: Vec<(f32, usize)> = self
-            .pending_points
-            .iter()
-            .enumerate()
-            .map(|(i, point)| (query.distance(point), i))
-            .collect();
-
-        scored.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
-        scored.truncate(k);
+        let query = normalize(query);
+        let mut results: Vec<HnswResult> = Vec::new();
+
+        if let Some(entry) = self.entry_point {
+            let entry = self.descend(&query, entry, 0);
+            let ef = self.ef_search.max(k);
+            results.extend(
+                self.search_layer(&query, &[entry], ef, 0, false)
+                    .into_iter()
+                    .map(|(d, node)| HnswResult::new(self.nodes[node as usize].memory_id, d.0)),
+            );
+        }
+        results.extend(
+            self.staged
+                .iter()
+                .map(|(memory_id, vector)| HnswResult::new(*memory_id, distance(&query, vector))),
+        );
 
-        scored
-            .into_iter()
-            .map(|(dist, idx)| HnswResult {
-                memory_id: self.pending_values[idx],
-                distance: dist,
-                similarity: 1.0 - dist,
-            })
-            .collect()
+        results.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        results.truncate(k);
+        results
     }
 
     /// Remove a memory from the index by ID.
     ///
-    /// Note: this marks the entry for removal on next rebuild.
-    /// The HNSW graph is not modified in-place (instant-distance is immutable).
+    /// Staged points are dropped outright; linked nodes are tombstoned and
+    /// reclaimed by the next compaction.
     pub fn remove(&mut self, memory_id: MemoryId) {
-        let mut i = 0;
-        while i < self.pending_values.len() {
-            if self.pending_values[i] == memory_id {
-                self.pending_values.swap_remove(i);
-                self.pending_points.swap_remove(i);
-                self.dirty_count += 1;
-                // Don't increment i — swapped element now at position i
-            } else {
-                i += 1;
-            }
+        if let Some(node) = self.by_memory.remove(&memory_id) {
+            self.nodes[node as usize].deleted = true;
+            self.tombstones += 1;
         }
+        self.staged.retain(|(id, _)| *id != memory_id);
     }
 
     /// Clear the entire index.
     pub fn clear(&mut self) {
-        self.pending_points.clear();
-        self.pending_values.clear();
-        self.map = None;
-        self.dirty_count = 0;
+        *self = Self {
+            ef_construction: self.ef_construction,
+            ef_search: self.ef_search,
+            m: self.m,
+            ..Self::new()
+        };
     }
 
     /// Get index statistics for debugging.
     #[must_use]
     pub fn stats(&self) -> HnswStats {
         HnswStats {
-            total_points: self.pending_points.len(),
-            dirty_count: self.dirty_count,
-            is_built: self.map.is_some(),
+            total_points: self.len(),
+            pending: self.staged.len(),
+            tombstones: self.tombstones,
+            layers: self
+                .entry_point
+                .map_or(0, |entry| self.nodes[entry as usize].links.len()),
             ef_construction: self.ef_construction,
             ef_search: self.ef_search,
+            m: self.m,
+        }
+    }
+
+    // -- graph internals ----------------------------------------------------
+
+    /// Max neighbours on `layer`.
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 { self.m * 2 } else { self.m }
+    }
+
+    /// Random top layer for a new node: `floor(-ln(U) · mL)`, `mL = 1/ln(M)`.
+    fn random_level(&mut self) -> usize {
+        let ml = 1.0 / (self.m as f64).ln();
+        let u: f64 = self.rng.r#gen();
+        (-(1.0 - u).ln() * ml).floor() as usize
+    }
+
+    fn dist_to(&self, query: &[f32], node: u32) -> OrderedFloat<f32> {
+        OrderedFloat(distance(query, &self.nodes[node as usize].vector))
+    }
+
+    /// Greedy walk from `entry` on the top layer down to `layer + 1`,
+    /// returning the closest node found.
+    fn descend(&self, query: &[f32], entry: u32, layer: usize) -> u32 {
+        let top = self.nodes[entry as usize].links.len() - 1;
+        let mut current = entry;
+        let mut current_dist = self.dist_to(query, current);
+        for l in (layer + 1..=top).rev() {
+            let mut improved = true;
+            while improved {
+                improved = false;
+                for &next in &self.nodes[current as usize].links[l] {
+                    let d = self.dist_to(query, next);
+                    if d < current_dist {
+                        current = next;
+                        current_dist = d;
+                        improved = true;
+                    }
+                }
+            }
+        }
+        current
+    }
+
+    /// Best-first search of one layer (Algorithm 2 of the HNSW paper).
+    /// Returns up to `ef` nodes sorted by ascending distance; tombstones are
+    /// traversed but only returned when `with_deleted` is set.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[u32],
+        ef: usize,
+        layer: usize,
+        with_deleted: bool,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entries.iter().copied().collect();
+        let mut candidates: BinaryHeap<Reverse<Scored>> = BinaryHeap::new();
+        let mut found: BinaryHeap<Scored> = BinaryHeap::new();
+
+        for &entry in entries {
+            let d = self.dist_to(query, entry);
+            candidates.push(Reverse((d, entry)));
+            if with_deleted || !self.nodes[entry as usize].deleted {
+                found.push((d, entry));
+            }
+        }
+
+        while let Some(Reverse((d, node))) = candidates.pop() {
+            if found.len() >= ef && found.peek().is_some_and(|&(worst, _)| d > worst) {
+                break;
+            }
+            for &next in &self.nodes[node as usize].links[layer] {
+                if !visited.insert(next) {
+                    continue;
+                }
+                let d = self.dist_to(query, next);
+                if found.len() < ef || found.peek().is_some_and(|&(worst, _)| d < worst) {
+                    candidates.push(Reverse((d, next)));
+                    if with_deleted || !self.nodes[next as usize].deleted {
+                        found.push((d, next));
+                        if found.len() > ef {
+                            found.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        found.into_sorted_vec()
+    }
+
+    /// Neighbour selection heuristic (Algorithm 4): prefer candidates closer
+    /// to the base than to any already-selected neighbour, which keeps links
+    /// spread across clusters; top up with the nearest leftovers.
+    fn select_neighbors(&self, sorted: &[Scored], max: usize) -> Vec<u32> {
+        let mut selected: Vec<u32> = Vec::with_capacity(max);
+        let mut skipped: Vec<u32> = Vec::new();
+        for &(d, candidate) in sorted {
+            if selected.len() >= max {
+                break;
+            }
+            let vector = &self.nodes[candidate as usize].vector;
+            if selected
+                .iter()
+                .all(|&s| OrderedFloat(distance(vector, &self.nodes[s as usize].vector)) > d)
+            {
+                selected.push(candidate);
+            } else {
+                skipped.push(candidate);
+            }
+        }
+        for candidate in skipped {
+            if selected.len() >= max {
+                break;
+            }
+            selected.push(candidate);
+        }
+        selected
+    }
+
+    /// Add a point to the graph (Algorithm 1).
+    fn link(&mut self, memory_id: MemoryId, vector: Vec<f32>) {
+        let level = self.random_level();
+        let id = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            memory_id,
+            vector,
+            links: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.by_memory.insert(memory_id, id);
+
+        let Some(entry) = self.entry_point else {
+            self.entry_point = Some(id);
+            return;
+        };
+
+        let query = self.nodes[id as usize].vector.clone();
+        let top = self.nodes[entry as usize].links.len() - 1;
+        let mut entries = vec![self.descend(&query, entry, level.min(top))];
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entries, self.ef_construction, layer, true);
+            let neighbors = self.select_neighbors(&found, self.m);
+            self.nodes[id as usize].links[layer].clone_from(&neighbors);
+            for &neighbor in &neighbors {
+                self.add_link(neighbor, id, layer);
+            }
+            entries = found.into_iter().map(|(_, node)| node).collect();
+        }
+
+        if level > top {
+            self.entry_point = Some(id);
+        }
+    }
+
+    /// Link `from → to` on `layer`, re-selecting `from`'s neighbours if it
+    /// now has too many.
+    fn add_link(&mut self, from: u32, to: u32, layer: usize) {
+        let max = self.max_links(layer);
+        self.nodes[from as usize].links[layer].push(to);
+        if self.nodes[from as usize].links[layer].len() <= max {
+            return;
         }
+        let base = &self.nodes[from as usize].vector;
+        let mut scored: Vec<Scored> = self.nodes[from as usize].links[layer]
+            .iter()
+            .map(|&n| (OrderedFloat(distance(base, &self.nodes[n as usize].vector)), n))
+            .collect();
+        scored.sort_unstable();
+        let kept = self.select_neighbors(&scored, max);
+        self.nodes[from as usize].links[layer] = kept;
     }
 }
 
@@ -289,16 +503,20 @@ impl Default for HnswIndex {
 /// Statistics about the HNSW index state.
 #[derive(Debug, Clone)]
 pub struct HnswStats {
-    /// Total number of indexed points.
+    /// Total number of live points (linked + staged).
     pub total_points: usize,
-    /// Number of insertions since last build.
-    pub dirty_count: usize,
-    /// Whether the HNSW graph has been built.
-    pub is_built: bool,
+    /// Points staged but not yet linked into the graph.
+    pub pending: usize,
+    /// Removed nodes awaiting compaction.
+    pub tombstones: usize,
+    /// Number of graph layers.
+    pub layers: usize,
     /// `ef_construction` parameter.
     pub ef_construction: usize,
     /// `ef_search` parameter.
     pub ef_search: usize,
+    /// Target connections per node.
+    pub m: usize,
 }
 
 // ---------------------------------------------------------------------------
@@ -313,6 +531,24 @@ mod tests {
         Embedding(values.to_vec())
     }
 
+    /// Pseudo-random unit vectors, deterministic per seed.
+    fn random_embeddings(count: usize, dims: usize, seed: u64) -> Vec<Embedding> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| Embedding((0..dims).map(|_| rng.gen_range(-1.0..1.0)).collect()))
+            .collect()
+    }
+
+    fn exact_top_k(points: &[(MemoryId, Embedding)], query: &Embedding, k: usize) -> Vec<MemoryId> {
+        let query = normalize(query);
+        let mut scored: Vec<(f32, MemoryId)> = points
+            .iter()
+            .map(|(id, e)| (distance(&query, &normalize(e)), *id))
+            .collect();
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored.into_iter().take(k).map(|(_, id)| id).collect()
+    }
+
     #[test]
     fn empty_index_returns_no_results() {
         let index = HnswIndex::new();
@@ -321,7 +557,7 @@ mod tests {
     }
 
     #[test]
-    fn insert_and_brute_force_search() {
+    fn insert_and_search_staged() {
         let mut index = HnswIndex::new();
 
         let id1 = MemoryId::new();
@@ -332,11 +568,12 @@ mod tests {
         index.insert(id2, make_embedding(&[0.0, 1.0, 0.0]));
         index.insert(id3, make_embedding(&[0.9, 0.1, 0.0]));
 
-        // Without building, should fall back to brute-force
+        // Staged points are searchable before they are linked.
+        assert_eq!(index.pending(), 3);
         let results = index.search(&make_embedding(&[1.0, 0.0, 0.0]), 2);
         assert_eq!(results.len(), 2);
-        // Most similar should be id1 (exact match) or id3 (close)
-        assert!(results[0].similarity > 0.9);
+        assert_eq!(results[0].memory_id, id1);
+        assert_eq!(results[1].memory_id, id3);
     }
 
     #[test]
@@ -350,44 +587,160 @@ mod tests {
         }
 
         index.build();
-        assert!(!index.needs_rebuild());
+        assert_eq!(index.pending(), 0);
 
         let results = index.search(&make_embedding(&[1.0, 0.0, 0.0]), 5);
         assert_eq!(results.len(), 5);
         // First result should be close to (1, 0, 0)
         assert!(results[0].similarity > 0.95, "Top result sim={}", results[0].similarity);
+        assert_eq!(results[0].memory_id, ids[0]);
     }
 
     #[test]
-    fn needs_rebuild_after_inserts() {
+    fn incremental_inserts_link_without_rebuild() {
         let mut index = HnswIndex::new();
-        assert!(!index.needs_rebuild()); // Empty
-
-        index.insert(MemoryId::new(), make_embedding(&[1.0, 0.0]));
-        assert!(index.needs_rebuild()); // Never built
+        let points: Vec<(MemoryId, Embedding)> = random_embeddings(300, 16, 1)
+            .into_iter()
+            .map(|e| (MemoryId::new(), e))
+            .collect();
 
+        for (id, e) in &points[..200] {
+            index.insert(*id, e.clone());
+        }
         index.build();
-        assert!(!index.needs_rebuild());
+        // Later points arrive one at a time, each linked on its own.
+        for (id, e) in &points[200..] {
+            index.insert(*id, e.clone());
+            assert_eq!(index.link_pending(1), 1);
+        }
+        assert_eq!(index.stats().total_points, 300);
+        assert_eq!(index.stats().pending, 0);
+
+        let (last_id, last) = &points[299];
+        assert_eq!(index.search(last, 1)[0].memory_id, *last_id);
+    }
 
-        // Insert 20% more → should trigger rebuild
-        // We have 1 point, so threshold = 0.2 * 2 = 0.4; 1 dirty > 0.4
-        index.insert(MemoryId::new(), make_embedding(&[0.0, 1.0]));
-        assert!(index.needs_rebuild());
+    #[test]
+    fn link_pending_respects_max() {
+        let mut index = HnswIndex::new();
+        for e in random_embeddings(10, 4, 2) {
+            index.insert(MemoryId::new(), e);
+        }
+        assert_eq!(index.link_pending(3), 3);
+        assert_eq!(index.pending(), 7);
+        assert_eq!(index.len(), 10);
     }
 
     #[test]
-    fn remove_works() {
+    fn remove_tombstones_linked_nodes() {
         let mut index = HnswIndex::new();
 
         let id1 = MemoryId::new();
         let id2 = MemoryId::new();
         index.insert(id1, make_embedding(&[1.0, 0.0]));
         index.insert(id2, make_embedding(&[0.0, 1.0]));
+        index.build();
 
         assert_eq!(index.len(), 2);
         index.remove(id1);
         assert_eq!(index.len(), 1);
-        assert_eq!(index.pending_values[0], id2);
+        assert_eq!(index.stats().tombstones, 1);
+
+        let results = index.search(&make_embedding(&[1.0, 0.0]), 2);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].memory_id, id2);
+    }
+
+    #[test]
+    fn remove_drops_staged_points() {
+        let mut index = HnswIndex::new();
+        let id = MemoryId::new();
+        index.insert(id, make_embedding(&[1.0, 0.0]));
+        index.remove(id);
+        assert!(index.is_empty());
+        assert_eq!(index.stats().tombstones, 0);
+    }
+
+    #[test]
+    fn reinsert_replaces_embedding() {
+        let mut index = HnswIndex::new();
+        let id = MemoryId::new();
+        index.insert(id, make_embedding(&[1.0, 0.0]));
+        index.build();
+        index.insert(id, make_embedding(&[0.0, 1.0]));
+        index.build();
+
+        assert_eq!(index.len(), 1);
+        let results = index.search(&make_embedding(&[0.0, 1.0]), 5);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].similarity > 0.99);
+    }
+
+    #[test]
+    fn compaction_after_many_removals() {
+        let mut index = HnswIndex::new();
+        let points: Vec<(MemoryId, Embedding)> = random_embeddings(200, 16, 3)
+            .into_iter()
+            .map(|e| (MemoryId::new(), e))
+            .collect();
+        for (id, e) in &points {
+            index.insert(*id, e.clone());
+        }
+        index.build();
+
+        for (id, _) in &points[..100] {
+            index.remove(*id);
+        }
+        assert!(index.needs_compaction());
+        index.build();
+        assert!(!index.needs_compaction());
+        assert_eq!(index.stats().tombstones, 0);
+        assert_eq!(index.len(), 100);
+
+        let survivors = &points[100..];
+        let query = &points[150].1;
+        let got: Vec<MemoryId> = index.search(query, 10).iter().map(|r| r.memory_id).collect();
+        assert_eq!(got[0], points[150].0);
+        assert!(got.iter().all(|id| survivors.iter().any(|(s, _)| s == id)));
+    }
+
+    #[test]
+    fn recall_matches_exact_search() {
+        let config = RetrievalConfig {
+            hnsw_m: 8,
+            ..RetrievalConfig::default()
+        };
+        let mut index = HnswIndex::with_config(&config);
+        let points: Vec<(MemoryId, Embedding)> = random_embeddings(1_000, 24, 4)
+            .into_iter()
+            .map(|e| (MemoryId::new(), e))
+            .collect();
+        for (id, e) in &points {
+            index.insert(*id, e.clone());
+        }
+        index.build();
+        // Churn: tombstones must not hurt recall of the survivors.
+        for (id, _) in points.iter().step_by(10) {
+            index.remove(*id);
+        }
+        let live: Vec<(MemoryId, Embedding)> = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % 10 != 0)
+            .map(|(_, p)| p.clone())
+            .collect();
+
+        let mut hits = 0;
+        for query in random_embeddings(50, 24, 5) {
+            let exact = exact_top_k(&live, &query, 10);
+            hits += index
+                .search(&query, 10)
+                .iter()
+                .filter(|r| exact.contains(&r.memory_id))
+                .count();
+        }
+        let recall = hits as f32 / 500.0;
+        assert!(recall > 0.95, "recall@10 = {recall}");
     }
 
     #[test]
@@ -397,25 +750,25 @@ mod tests {
             index.insert(MemoryId::new(), make_embedding(&[1.0, 0.0]));
         }
         index.build();
-        assert!(index.stats().is_built);
+        assert!(index.stats().layers > 0);
 
         index.clear();
         assert!(index.is_empty());
-        assert!(!index.stats().is_built);
+        assert_eq!(index.stats().layers, 0);
     }
 
     #[test]
     fn cosine_distance_identity() {
-        let a = HnswPoint::from_embedding(&make_embedding(&[1.0, 0.0, 0.0]));
-        let dist = a.distance(&a);
+        let a = normalize(&make_embedding(&[1.0, 0.0, 0.0]));
+        let dist = distance(&a, &a);
         assert!(dist < 0.001, "Self-distance should be ~0, got {dist}");
     }
 
     #[test]
     fn cosine_distance_orthogonal() {
-        let a = HnswPoint::from_embedding(&make_embedding(&[1.0, 0.0, 0.0]));
-        let b = HnswPoint::from_embedding(&make_embedding(&[0.0, 1.0, 0.0]));
-        let dist = a.distance(&b);
+        let a = normalize(&make_embedding(&[1.0, 0.0, 0.0]));
+        let b = normalize(&make_embedding(&[0.0, 1.0, 0.0]));
+        let dist = distance(&a, &b);
         assert!(
             (dist - 1.0).abs() < 0.01,
             "Orthogonal vectors should have distance ~1.0, got {dist}"
@@ -428,15 +781,29 @@ mod tests {
         assert_eq!(index.stats().ef_construction, 200);
         assert_eq!(index.stats().ef_search, 100);
         assert_eq!(index.stats().total_points, 0);
-        assert!(!index.stats().is_built);
+        assert_eq!(index.stats().layers, 0);
 
         index.insert(MemoryId::new(), make_embedding(&[1.0, 0.0]));
         assert_eq!(index.stats().total_points, 1);
-        assert_eq!(index.stats().dirty_count, 1);
+        assert_eq!(index.stats().pending, 1);
 
         index.build();
-        assert!(index.stats().is_built);
-        assert_eq!(index.stats().dirty_count, 0);
+        assert!(index.stats().layers > 0);
+        assert_eq!(index.stats().pending, 0);
+    }
+
+    #[test]
+    fn with_config_uses_retrieval_params() {
+        let config = RetrievalConfig {
+            hnsw_ef_construction: 64,
+            hnsw_ef_search: 32,
+            hnsw_m: 8,
+            ..RetrievalConfig::default()
+        };
+        let stats = HnswIndex::with_config(&config).stats();
+        assert_eq!(stats.ef_construction, 64);
+        assert_eq!(stats.ef_search, 32);
+        assert_eq!(stats.m, 8);
     }
 
     #[test]
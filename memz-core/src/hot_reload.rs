@@ -0,0 +1,396 @@
+//! Config hot-reload — watch `memz.toml` and swap the live config (§16)
+//!
+//! A [`LiveConfig`] is a shared handle to the current [`MemzConfig`].
+//! Consumers (`MemoryRule`, `RetrievalEngine`, the LLM client) hold a clone
+//! and pick up new settings by comparing [`LiveConfig::generation`], so a
+//! reload never tears a config half-way through a frame: readers always see
+//! either the old or the new `Arc<MemzConfig>`.
+//!
+//! [`ConfigWatcher`] re-parses the file whenever it changes on disk.  Edits
+//! that fail to parse or [validate](MemzConfig::validate) are rejected and
+//! logged together with the diff they would have applied; the live config
+//! stays as it was.  The hardware profile (`general.profile`) is read once
+//! at startup — changes to it are logged and take effect on restart.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use crate::config::MemzConfig;
+use crate::error::{MemzError, Result};
+
+/// Settings that only take effect on restart.
+pub const RESTART_ONLY: &[&str] = &["general.profile"];
+
+/// Quiet period after the last file event before the file is re-read, so a
+/// save that truncates and then writes is seen once, complete.
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(100);
+
+// ---------------------------------------------------------------------------
+// Diff
+// ---------------------------------------------------------------------------
+
+/// One changed setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    /// Dotted path, e.g. `retrieval.weights.recency`.
+    pub path: String,
+    /// Previous value (TOML syntax), `None` if newly set.
+    pub old: Option<String>,
+    /// New value (TOML syntax), `None` if removed.
+    pub new: Option<String>,
+}
+
+/// Setting-by-setting difference between two configs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    /// Changed settings, sorted by path.
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDiff {
+    /// Diff `old` against `new`.
+    ///
+    /// # Errors
+    /// Returns `MemzError::Serialization` if either config cannot be
+    /// represented as TOML.
+    pub fn between(old: &MemzConfig, new: &MemzConfig) -> Result<Self> {
+        let mut old_values = BTreeMap::new();
+        let mut new_values = BTreeMap::new();
+        flatten("", &to_toml(old)?, &mut old_values);
+        flatten("", &to_toml(new)?, &mut new_values);
+
+        let paths: BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+        let changes = paths
+            .into_iter()
+            .filter_map(|path| {
+                let (old, new) = (old_values.get(path), new_values.get(path));
+                (old != new).then(|| ConfigChange {
+                    path: path.clone(),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                })
+            })
+            .collect();
+        Ok(Self { changes })
+    }
+
+    /// Whether nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether the setting at `path` (or any setting below it) changed.
+    #[must_use]
+    pub fn touches(&self, path: &str) -> bool {
+        self.changes.iter().any(|c| is_under(&c.path, path))
+    }
+
+    /// Split into (hot-reloadable, restart-only) changes.
+    #[must_use]
+    pub fn partition_restart_only(self) -> (Self, Self) {
+        let (restart, hot): (Vec<_>, Vec<_>) = self
+            .changes
+            .into_iter()
+            .partition(|c| RESTART_ONLY.iter().any(|p| is_under(&c.path, p)));
+        (Self { changes: hot }, Self { changes: restart })
+    }
+}
+
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let old = change.old.as_deref().unwrap_or("(unset)");
+            let new = change.new.as_deref().unwrap_or("(unset)");
+            writeln!(f, "  {}: {old} -> {new}", change.path)?;
+        }
+        Ok(())
+    }
+}
+
+fn to_toml(config: &MemzConfig) -> Result<toml::Value> {
+    toml::Value::try_from(config).map_err(|e| MemzError::Serialization(e.to_string()))
+}
+
+/// Leaf values of `value` by dotted path, as TOML text.
+fn flatten(prefix: &str, value: &toml::Value, out: &mut BTreeMap<String, String>) {
+    if let toml::Value::Table(table) = value {
+        for (key, child) in table {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            flatten(&path, child, out);
+        }
+    } else {
+        out.insert(prefix.to_string(), value.to_string());
+    }
+}
+
+fn is_under(path: &str, prefix: &str) -> bool {
+    path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Copy the restart-only settings of `from` into `into`.
+fn keep_restart_only(from: &MemzConfig, into: &mut MemzConfig) {
+    into.general.profile.clone_from(&from.general.profile);
+}
+
+// ---------------------------------------------------------------------------
+// Live config
+// ---------------------------------------------------------------------------
+
+/// What a successful reload changed.
+#[derive(Debug, Clone, Default)]
+pub struct ReloadOutcome {
+    /// Settings now live.
+    pub applied: ConfigDiff,
+    /// Restart-only settings that changed on disk but were kept as they are.
+    pub needs_restart: ConfigDiff,
+}
+
+#[derive(Debug)]
+struct LiveInner {
+    current: RwLock<Arc<MemzConfig>>,
+    generation: AtomicU64,
+}
+
+/// Shared, atomically swappable handle to the running [`MemzConfig`].
+#[derive(Debug, Clone)]
+pub struct LiveConfig {
+    inner: Arc<LiveInner>,
+}
+
+impl LiveConfig {
+    /// Wrap an already-loaded config (generation 0).
+    #[must_use]
+    pub fn new(config: MemzConfig) -> Self {
+        Self {
+            inner: Arc::new(LiveInner {
+                current: RwLock::new(Arc::new(config)),
+                generation: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Load and validate `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, parsed or validated.
+    pub fn load(path: &Path) -> Result<Self> {
+        let config = MemzConfig::from_file(path)?;
+        let report = config.validate().into_result()?;
+        if !report.issues.is_empty() {
+            warn!("Config {} has warnings:\n{report}", path.display());
+        }
+        Ok(Self::new(config))
+    }
+
+    /// The config currently in effect.
+    #[must_use]
+    pub fn current(&self) -> Arc<MemzConfig> {
+        Arc::clone(&self.inner.current.read())
+    }
+
+    /// Incremented every time a new config is swapped in.
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.inner.generation.load(Ordering::Acquire)
+    }
+
+    /// The current config and its generation, if newer than `seen`.
+    #[must_use]
+    pub fn changed_since(&self, seen: u64) -> Option<(u64, Arc<MemzConfig>)> {
+        let current = self.inner.current.read();
+        let generation = self.generation();
+        (generation != seen).then(|| (generation, Arc::clone(&current)))
+    }
+
+    /// Validate `candidate` and swap it in.
+    ///
+    /// Restart-only settings keep their running values.  Nothing is swapped
+    /// (and the generation is unchanged) if no hot setting differs.
+    ///
+    /// # Errors
+    /// Returns `MemzError::Config` if `candidate` fails validation; the
+    /// rejected diff is logged and the live config is left untouched.
+    pub fn apply(&self, mut candidate: MemzConfig) -> Result<ReloadOutcome> {
+        let mut current = self.inner.current.write();
+        let diff = ConfigDiff::between(&current, &candidate)?;
+
+        let report = match candidate.validate().into_result() {
+            Ok(report) => report,
+            Err(e) => {
+                warn!("Rejected config edit: {e}\n{diff}");
+                return Err(e);
+            }
+        };
+        if !report.issues.is_empty() {
+            warn!("Config edit has warnings:\n{report}");
+        }
+
+        let (applied, needs_restart) = diff.partition_restart_only();
+        if !needs_restart.is_empty() {
+            warn!("Config changes that need a restart were not applied:\n{needs_restart}");
+            keep_restart_only(&current, &mut candidate);
+        }
+        if !applied.is_empty() {
+            *current = Arc::new(candidate);
+            self.inner.generation.fetch_add(1, Ordering::AcqRel);
+            info!("Config reloaded:\n{applied}");
+        }
+        Ok(ReloadOutcome { applied, needs_restart })
+    }
+
+    /// Re-read `path` and [`apply`](Self::apply) it.
+    ///
+    /// The file is layered over the preset of the running profile, since a
+    /// profile change only takes effect on restart.
+    ///
+    /// # Errors
+    /// Returns an error (and logs it) if the file cannot be read, parsed or
+    /// validated; the live config is left untouched.
+    pub fn reload_from(&self, path: &Path) -> Result<ReloadOutcome> {
+        let profile = self.current().general.profile.clone();
+        let candidate = std::fs::read_to_string(path)
+            .map_err(MemzError::from)
+            .and_then(|content| MemzConfig::from_toml_with_profile(&content, &profile))
+            .inspect_err(|e| warn!("Rejected config edit to {}: {e}", path.display()))?;
+        self.apply(candidate)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// File watcher
+// ---------------------------------------------------------------------------
+
+/// Watches a config file and reloads a [`LiveConfig`] when it changes.
+///
+/// The containing directory is watched so that editors which save by
+/// writing a temporary file and renaming it over the original are seen.
+/// Bursts of events are debounced by [`RELOAD_DEBOUNCE`] on a background
+/// thread. Dropping the watcher stops it.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Start watching `path`, applying every change to `live`.
+    ///
+    /// # Errors
+    /// Returns `MemzError::Config` if the file system watcher cannot start,
+    /// or `MemzError::Io` if the reload thread cannot be spawned.
+    pub fn spawn(path: &Path, live: LiveConfig) -> Result<Self> {
+        let path = path.to_path_buf();
+        let file_name = path.file_name().map(ToOwned::to_owned);
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let (changed, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else { return };
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event.paths.iter().any(|p| p.file_name() == file_name.as_deref())
+            {
+                let _ = changed.send(());
+            }
+        })
+        .map_err(watch_error)?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(watch_error)?;
+
+        // Ends when the watcher (and with it the sender) is dropped.
+        let target = path.clone();
+        std::thread::Builder::new()
+            .name("memz-config-watcher".to_string())
+            .spawn(move || {
+                while events.recv().is_ok() {
+                    while events.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+                    // Errors are logged by `reload_from`.
+                    let _ = live.reload_from(&target);
+                }
+            })?;
+
+        Ok(Self { path, _watcher: watcher })
+    }
+
+    /// The watched file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl fmt::Debug for ConfigWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigWatcher").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+fn watch_error(e: notify::Error) -> MemzError {
+    MemzError::Config(format!("cannot watch config file: {e}"))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_changed_paths() {
+        let old = MemzConfig::default();
+        let mut new = old.clone();
+        new.retrieval.top_k = 9;
+        new.llm.provider = "openai".to_string();
+
+        let diff = ConfigDiff::between(&old, &new).expect("diff");
+        let paths: Vec<&str> = diff.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["llm.provider", "retrieval.top_k"]);
+        assert!(diff.touches("llm"));
+        assert!(!diff.touches("memory"));
+        assert!(diff.to_string().contains("retrieval.top_k: 5 -> 9"));
+    }
+
+    #[test]
+    fn invalid_edit_keeps_live_config() {
+        let live = LiveConfig::new(MemzConfig::default());
+        let mut bad = MemzConfig::default();
+        bad.retrieval.hybrid_fusion = "median".to_string();
+
+        assert!(matches!(live.apply(bad), Err(MemzError::Config(_))));
+        assert_eq!(live.generation(), 0);
+        assert_eq!(live.current().retrieval.hybrid_fusion, "rrf");
+    }
+
+    #[test]
+    fn restart_only_settings_are_kept() {
+        let live = LiveConfig::new(MemzConfig::default());
+        let mut edit = MemzConfig::default();
+        edit.general.profile = "server".to_string();
+        edit.memory.decay_rate = 0.2;
+
+        let outcome = live.apply(edit).expect("valid edit");
+        assert!(outcome.needs_restart.touches("general.profile"));
+        assert!(outcome.applied.touches("memory.decay_rate"));
+        assert_eq!(live.generation(), 1);
+        assert_eq!(live.current().general.profile, "auto");
+        assert!((live.current().memory.decay_rate - 0.2).abs() < f32::EPSILON);
+        assert!(live.changed_since(0).is_some());
+        assert!(live.changed_since(1).is_none());
+    }
+}
@@ -37,17 +37,20 @@
 #![allow(clippy::trivially_copy_pass_by_ref)]
 #![allow(clippy::needless_pass_by_value)]
 
+pub mod archival;
 pub mod bard;
 pub mod behavior;
 pub mod config;
 pub mod conflict;
 pub mod consolidation;
 pub mod decay;
+pub mod determinism;
 pub mod embedding;
 pub mod error;
 pub mod eviction;
 pub mod first_five;
 pub mod hnsw;
+pub mod hot_reload;
 pub mod injection;
 pub mod memory;
 pub mod metrics;
@@ -59,6 +62,7 @@ pub mod reputation;
 pub mod retrieval;
 pub mod safety;
 pub mod social;
+pub mod time;
 pub mod types;
 
 pub use config::MemoryConfig;
@@ -62,6 +62,14 @@ impl EmotionalMemory {
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Update the emotion with a new event, shifting intensity and PAD state.
     pub fn update(
         &mut self,
@@ -38,6 +38,8 @@ pub struct EpisodicMemory {
     /// Whether this is a first-meeting memory (protected from eviction).
     pub is_first_meeting: bool,
     /// Vector embedding for semantic retrieval (lazily computed).
+    /// Stored in its own column by the persistence engine, not in the
+    /// serialised memory.
     #[serde(skip)]
     pub embedding: Option<Embedding>,
 }
@@ -77,6 +79,14 @@ impl EpisodicMemory {
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Mark this as a first-meeting memory (protected from eviction).
     #[must_use] 
     pub fn with_first_meeting(mut self) -> Self {
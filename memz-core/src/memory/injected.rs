@@ -67,12 +67,20 @@ impl InjectedMemory {
             priority,
             embedding: None,
             memory_timestamp: timestamp,
-            injected_at: Utc::now(),
+            injected_at: timestamp.real_time,
             tags: Vec::new(),
             is_first_five_minutes: false,
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Create a core identity memory that never decays.
     #[must_use]
     pub fn core_identity(
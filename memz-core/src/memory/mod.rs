@@ -19,8 +19,13 @@ pub use reflective::ReflectiveMemory;
 pub use semantic::SemanticMemory;
 pub use social::SocialMemory;
 
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 
+use crate::consolidation::MemoryType;
+use crate::types::{EntityId, MemoryId};
+
 /// A unified memory entry that can hold any of the 7 memory types.
 ///
 /// Used by the retrieval engine to score and rank memories of different types
@@ -43,6 +48,49 @@ pub enum MemoryEntry {
     Injected(InjectedMemory),
 }
 
+impl MemoryEntry {
+    /// ID of the wrapped memory.
+    #[must_use]
+    pub fn id(&self) -> MemoryId {
+        match self {
+            Self::Episodic(m) => m.id,
+            Self::Semantic(m) => m.id,
+            Self::Emotional(m) => m.id,
+            Self::Social(m) => m.id,
+            Self::Reflective(m) => m.id,
+            Self::Procedural(m) => m.id,
+            Self::Injected(m) => m.id,
+        }
+    }
+
+    /// Type of the wrapped memory.
+    #[must_use]
+    pub fn memory_type(&self) -> MemoryType {
+        match self {
+            Self::Episodic(_) => MemoryType::Episodic,
+            Self::Semantic(_) => MemoryType::Semantic,
+            Self::Emotional(_) => MemoryType::Emotional,
+            Self::Social(_) => MemoryType::Social,
+            Self::Reflective(_) => MemoryType::Reflective,
+            Self::Procedural(_) => MemoryType::Procedural,
+            Self::Injected(_) => MemoryType::Injected,
+        }
+    }
+
+    /// Natural-language text used for keyword retrieval: the episodic
+    /// event, semantic fact, social claim or injected content.
+    #[must_use]
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Episodic(m) => Some(&m.event),
+            Self::Semantic(m) => Some(&m.fact),
+            Self::Social(m) => Some(&m.claim),
+            Self::Injected(m) => Some(&m.content),
+            Self::Emotional(_) | Self::Reflective(_) | Self::Procedural(_) => None,
+        }
+    }
+}
+
 /// The memory bank — per-character aggregate of all memory types.
 ///
 /// Every NPC, player, and creature gets one `MemoryBank` that holds
@@ -63,6 +111,41 @@ pub struct MemoryBank {
     pub procedural: Vec<ProceduralMemory>,
     /// Injected memories — "My backstory."
     pub injected: Vec<InjectedMemory>,
+    /// Rows as last written to / read from storage (for content diffing).
+    #[serde(skip)]
+    pub(crate) persisted: PersistedRows,
+    /// Entity → number of episodic memories about it that were spilled to
+    /// cold storage and can be paged back in (see `persistence::cold`).
+    #[serde(skip)]
+    pub(crate) cold: HashMap<EntityId, usize>,
+    /// Cold memories paged in or archived since the last save; the next
+    /// save deletes their cold rows in the same transaction.
+    #[serde(skip)]
+    pub(crate) cold_taken: HashSet<MemoryId>,
+}
+
+/// Key of one persisted memory row: type, id and occurrence of that id
+/// within the bank (duplicated ids are stored as separate rows).
+pub(crate) type RowKey = (MemoryType, MemoryId, u32);
+
+/// Fingerprints of the rows a [`MemoryBank`] was last saved or loaded with.
+///
+/// `PersistenceEngine::save_bank` compares each memory's serialised bytes
+/// against this snapshot and only upserts rows whose content changed (or
+/// that must move to keep stored positions in bank order); memory types
+/// whose content hash matches the last save are skipped without encoding.
+/// Never serialised — a freshly deserialised bank has no snapshot and is
+/// rewritten in full.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PersistedRows {
+    /// Entity the snapshot belongs to.
+    pub(crate) owner: Option<EntityId>,
+    /// Row key → (content checksum, stored position). A `None`
+    /// checksum forces a rewrite (e.g. the row was stored in an old format).
+    pub(crate) rows: HashMap<RowKey, (Option<u32>, usize)>,
+    /// Memory type → content hash as of the last save. Empty after a load,
+    /// so the first save diffs every row.
+    pub(crate) types: HashMap<MemoryType, u64>,
 }
 
 impl MemoryBank {
@@ -97,4 +180,66 @@ impl MemoryBank {
         entries.extend(self.injected.iter().cloned().map(MemoryEntry::Injected));
         entries
     }
+
+    /// Clone the memory `id` of type `memory_type`, expected at `position`
+    /// in its `Vec`; falls back to a scan of that `Vec` if it has moved.
+    pub(crate) fn entry_at(
+        &self,
+        memory_type: MemoryType,
+        position: usize,
+        id: MemoryId,
+    ) -> Option<MemoryEntry> {
+        fn find<T: Clone>(
+            items: &[T],
+            position: usize,
+            id: MemoryId,
+            id_of: fn(&T) -> MemoryId,
+        ) -> Option<T> {
+            items
+                .get(position)
+                .filter(|m| id_of(m) == id)
+                .or_else(|| items.iter().find(|m| id_of(m) == id))
+                .cloned()
+        }
+        match memory_type {
+            MemoryType::Episodic => {
+                find(&self.episodic, position, id, |m| m.id).map(MemoryEntry::Episodic)
+            }
+            MemoryType::Semantic => {
+                find(&self.semantic, position, id, |m| m.id).map(MemoryEntry::Semantic)
+            }
+            MemoryType::Emotional => {
+                find(&self.emotional, position, id, |m| m.id).map(MemoryEntry::Emotional)
+            }
+            MemoryType::Social => find(&self.social, position, id, |m| m.id).map(MemoryEntry::Social),
+            MemoryType::Reflective => {
+                find(&self.reflective, position, id, |m| m.id).map(MemoryEntry::Reflective)
+            }
+            MemoryType::Procedural => {
+                find(&self.procedural, position, id, |m| m.id).map(MemoryEntry::Procedural)
+            }
+            MemoryType::Injected => {
+                find(&self.injected, position, id, |m| m.id).map(MemoryEntry::Injected)
+            }
+        }
+    }
+
+    /// Number of episodic memories about `entity` held in cold storage
+    /// rather than in this bank.
+    #[must_use]
+    pub fn cold_count(&self, entity: EntityId) -> usize {
+        self.cold.get(&entity).copied().unwrap_or(0)
+    }
+
+    /// Whether cold memories were paged in or archived since the last save,
+    /// so the next save has cold rows to delete.
+    #[must_use]
+    pub fn has_unsaved_cold_changes(&self) -> bool {
+        !self.cold_taken.is_empty()
+    }
+
+    /// Forget what was last persisted so the next save rewrites every row.
+    pub fn mark_all_dirty(&mut self) {
+        self.persisted = PersistedRows::default();
+    }
 }
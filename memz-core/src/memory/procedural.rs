@@ -92,10 +92,18 @@ impl ProceduralMemory {
             learning_rate: learning_rate.clamp(0.01, 2.0),
             related_skills: Vec::new(),
             routine_description: String::new(),
-            created_at: Utc::now(),
+            created_at: timestamp.real_time,
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Practice the skill once — proficiency grows with diminishing returns.
     ///
     /// Uses a logarithmic learning curve:
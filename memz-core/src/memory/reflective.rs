@@ -51,6 +51,14 @@ impl ReflectiveMemory {
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Add new beliefs discovered during reflection.
     #[must_use] 
     pub fn with_beliefs(mut self, beliefs: Vec<String>) -> Self {
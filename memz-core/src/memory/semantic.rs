@@ -27,6 +27,8 @@ pub struct SemanticMemory {
     /// When this fact was first formed.
     pub created_at: GameTimestamp,
     /// Vector embedding for semantic retrieval.
+    /// Stored in its own column by the persistence engine, not in the
+    /// serialised memory.
     #[serde(skip)]
     pub embedding: Option<Embedding>,
 }
@@ -53,6 +55,14 @@ impl SemanticMemory {
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Reinforce this fact with new evidence, boosting confidence.
     pub fn reinforce(&mut self, new_source: MemoryId, now: GameTimestamp) {
         self.derived_from.push(new_source);
@@ -61,6 +61,14 @@ impl SocialMemory {
         }
     }
 
+    /// Replace the random ID, e.g. with one from an
+    /// [`IdGenerator`](crate::determinism::IdGenerator).
+    #[must_use]
+    pub fn with_id(mut self, id: MemoryId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Mark this claim as believed after conflict resolution.
     pub fn accept(&mut self) {
         self.believed = true;
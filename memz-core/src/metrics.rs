@@ -478,7 +478,7 @@ mod tests {
     fn percentiles_with_data() {
         let monitor = FrameBudgetMonitor::new(2.0);
         for i in 0..100 {
-            monitor.record(i as f64 * 0.02); // 0.0 to 1.98ms
+            monitor.record(f64::from(i) * 0.02); // 0.0 to 1.98ms
         }
 
         let pct = monitor.percentiles();
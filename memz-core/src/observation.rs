@@ -10,6 +10,7 @@
 //!
 //! Performance target: < 0.1ms per event (§12.6)
 
+use crate::determinism::IdGenerator;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::emotional::EmotionalMemory;
 use crate::memory::social::SocialMemory;
@@ -86,7 +87,8 @@ pub struct ObservationResult {
 
 /// Process an observed event and create memories for the observer.
 ///
-/// This is the core observation pipeline entry point.
+/// This is the core observation pipeline entry point.  New memories take
+/// their IDs from `ids`.
 ///
 /// # Performance
 /// Target: < 0.1ms per call (§12.6)
@@ -95,6 +97,7 @@ pub fn observe(
     observer: EntityId,
     bank: &mut MemoryBank,
     known_entities: &[EntityId],
+    ids: &IdGenerator,
 ) -> ObservationResult {
     let mut result = ObservationResult {
         episodic_created: 0,
@@ -123,7 +126,8 @@ pub fn observe(
         } else {
             event.importance
         },
-    );
+    )
+    .with_id(ids.memory_id());
 
     if is_first_meeting {
         episodic = episodic.with_first_meeting();
@@ -143,7 +147,8 @@ pub fn observe(
                 event.pad_shift.unwrap_or_default(),
                 vec![], // basis memory IDs — populated during consolidation
                 event.timestamp,
-            );
+            )
+            .with_id(ids.memory_id());
             bank.emotional.push(emotional);
             result.emotional_created = 1;
         }
@@ -174,13 +179,14 @@ pub fn observe_as_witness(
     witness: EntityId,
     bank: &mut MemoryBank,
     known_entities: &[EntityId],
+    ids: &IdGenerator,
 ) -> ObservationResult {
     let mut witness_event = event.clone();
     witness_event.emotional_valence *= 0.6; // Reduced emotional impact
     witness_event.importance *= 0.7; // Less personally important
     witness_event.description = format!("Witnessed: {}", event.description);
 
-    observe(&witness_event, witness, bank, known_entities)
+    observe(&witness_event, witness, bank, known_entities, ids)
 }
 
 /// Detect if any participant is being met for the first time.
@@ -244,6 +250,7 @@ pub fn observe_gossip(
     propagation_depth: u32,
     timestamp: GameTimestamp,
     bank: &mut MemoryBank,
+    ids: &IdGenerator,
 ) {
     let social = SocialMemory::new(
         about,
@@ -252,7 +259,8 @@ pub fn observe_gossip(
         trust_in_source,
         propagation_depth,
         timestamp,
-    );
+    )
+    .with_id(ids.memory_id());
     bank.social.push(social);
 }
 
@@ -280,7 +288,7 @@ mod tests {
         let observer = event.participants[0];
         let mut bank = MemoryBank::new();
 
-        let result = observe(&event, observer, &mut bank, &[]);
+        let result = observe(&event, observer, &mut bank, &[], &IdGenerator::random());
         assert_eq!(result.episodic_created, 1);
         assert_eq!(bank.episodic.len(), 1);
     }
@@ -291,7 +299,7 @@ mod tests {
         let observer = event.participants[0];
         let mut bank = MemoryBank::new();
 
-        let result = observe(&event, observer, &mut bank, &[]);
+        let result = observe(&event, observer, &mut bank, &[], &IdGenerator::random());
         assert_eq!(result.emotional_created, 1);
         assert_eq!(bank.emotional.len(), 1);
         assert_eq!(bank.emotional[0].emotion, "gratitude");
@@ -304,7 +312,7 @@ mod tests {
         let known = vec![]; // observer hasn't met anyone
 
         let mut bank = MemoryBank::new();
-        let result = observe(&event, observer, &mut bank, &known);
+        let result = observe(&event, observer, &mut bank, &known, &IdGenerator::random());
         assert!(result.is_first_meeting);
         assert!(bank.episodic[0].is_first_meeting);
         // First meetings get boosted importance
@@ -319,7 +327,7 @@ mod tests {
         let known = vec![other]; // already met
 
         let mut bank = MemoryBank::new();
-        let result = observe(&event, observer, &mut bank, &known);
+        let result = observe(&event, observer, &mut bank, &known, &IdGenerator::random());
         assert!(!result.is_first_meeting);
     }
 
@@ -329,7 +337,7 @@ mod tests {
         let witness = EntityId::new();
         let mut bank = MemoryBank::new();
 
-        let result = observe_as_witness(&event, witness, &mut bank, &[]);
+        let result = observe_as_witness(&event, witness, &mut bank, &[], &IdGenerator::random());
         assert_eq!(result.episodic_created, 1);
         assert!(bank.episodic[0].event.starts_with("Witnessed:"));
         // Witness gets reduced importance
@@ -342,7 +350,7 @@ mod tests {
         let observer = event.participants[0];
         let mut bank = MemoryBank::new();
 
-        let result = observe(&event, observer, &mut bank, &[]);
+        let result = observe(&event, observer, &mut bank, &[], &IdGenerator::random());
         assert!(result.should_trigger_gossip);
     }
 
@@ -352,7 +360,7 @@ mod tests {
         let observer = event.participants[0];
         let mut bank = MemoryBank::new();
 
-        let result = observe(&event, observer, &mut bank, &[]);
+        let result = observe(&event, observer, &mut bank, &[], &IdGenerator::random());
         assert!(result.should_trigger_reflection);
     }
 
@@ -370,6 +378,7 @@ mod tests {
             1,
             GameTimestamp::now(36_000),
             &mut bank,
+            &IdGenerator::random(),
         );
 
         assert_eq!(bank.social.len(), 1);
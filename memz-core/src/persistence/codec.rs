@@ -0,0 +1,285 @@
+//! Payload codecs — how a memory row's envelope is turned into bytes.
+//!
+//! The codec is chosen by `[persistence] codec` / `compression` in
+//! `memz.toml` and written next to every row (`codec` column, e.g.
+//! `"msgpack+zstd"`), so a database can hold rows from several codecs at
+//! once: changing the config only affects rows written from then on.
+//!
+//! | Format    | Tag       | Notes                                          |
+//! |-----------|-----------|------------------------------------------------|
+//! | JSON      | `json`    | Default; human-readable, easiest to debug      |
+//! | `MessagePack` | `msgpack` | Binary, field names kept                   |
+//! | bincode   | `bincode` | Smallest and fastest, positional fields        |
+//!
+//! Any format can additionally be zstd-compressed (`+zstd` suffix), which
+//! requires the `zstd` cargo feature.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::config::PersistenceConfig;
+use crate::error::{MemzError, Result};
+
+/// zstd level used when compression is enabled (zstd's own default).
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// Serialisation format of a row payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PayloadFormat {
+    /// `serde_json`.
+    Json,
+    /// `rmp-serde`, with named struct fields.
+    MessagePack,
+    /// `bincode` 1.x.
+    Bincode,
+}
+
+impl PayloadFormat {
+    /// Tag used in config files and the `codec` column.
+    #[must_use]
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::MessagePack => "msgpack",
+            Self::Bincode => "bincode",
+        }
+    }
+
+    pub(crate) fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "json" => Some(Self::Json),
+            "msgpack" => Some(Self::MessagePack),
+            "bincode" => Some(Self::Bincode),
+            _ => None,
+        }
+    }
+}
+
+/// A payload format plus optional zstd compression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Codec {
+    /// Serialisation format.
+    pub format: PayloadFormat,
+    /// Whether the serialised bytes are zstd-compressed.
+    pub compressed: bool,
+}
+
+impl Default for Codec {
+    fn default() -> Self {
+        Self::JSON
+    }
+}
+
+impl Codec {
+    /// Uncompressed JSON — the format of every row written before codecs
+    /// were recorded.
+    pub const JSON: Self = Self {
+        format: PayloadFormat::Json,
+        compressed: false,
+    };
+
+    /// Codec selected by `config.codec` and `config.compression`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Config`] for an unknown codec or compression
+    /// name, or when zstd is requested but the `zstd` feature is disabled.
+    pub fn from_config(config: &PersistenceConfig) -> Result<Self> {
+        let format = PayloadFormat::from_tag(&config.codec).ok_or_else(|| {
+            MemzError::Config(format!(
+                "unknown persistence codec {:?} (expected \"json\", \"msgpack\" or \"bincode\")",
+                config.codec
+            ))
+        })?;
+        let compressed = match config.compression.as_str() {
+            "none" => false,
+            "zstd" if cfg!(feature = "zstd") => true,
+            "zstd" => {
+                return Err(MemzError::Config(
+                    "zstd compression requires memz-core's `zstd` feature".to_string(),
+                ));
+            }
+            other => {
+                return Err(MemzError::Config(format!(
+                    "unknown persistence compression {other:?} (expected \"none\" or \"zstd\")"
+                )));
+            }
+        };
+        Ok(Self { format, compressed })
+    }
+
+    /// Tag stored in the `codec` column, e.g. `"bincode+zstd"`.
+    #[must_use]
+    pub fn tag(self) -> &'static str {
+        match (self.format, self.compressed) {
+            (PayloadFormat::Json, false) => "json",
+            (PayloadFormat::Json, true) => "json+zstd",
+            (PayloadFormat::MessagePack, false) => "msgpack",
+            (PayloadFormat::MessagePack, true) => "msgpack+zstd",
+            (PayloadFormat::Bincode, false) => "bincode",
+            (PayloadFormat::Bincode, true) => "bincode+zstd",
+        }
+    }
+
+    /// Parse a `codec` column value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] for a tag this build does not know.
+    pub fn from_tag(tag: &str) -> Result<Self> {
+        let (format, compressed) = match tag.strip_suffix("+zstd") {
+            Some(format) => (format, true),
+            None => (tag, false),
+        };
+        PayloadFormat::from_tag(format)
+            .map(|format| Self { format, compressed })
+            .ok_or_else(|| MemzError::Serialization(format!("unknown row codec {tag:?}")))
+    }
+
+    /// Serialise `value`, compressing if configured.
+    pub(crate) fn encode<T: Serialize>(self, value: &T) -> Result<Vec<u8>> {
+        let bytes = match self.format {
+            PayloadFormat::Json => serde_json::to_vec(value).map_err(ser_err)?,
+            PayloadFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(ser_err)?,
+            PayloadFormat::Bincode => bincode::serialize(value).map_err(ser_err)?,
+        };
+        self.compress(bytes)
+    }
+
+    /// Decompress (if needed) and deserialise `bytes`.
+    pub(crate) fn decode<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        let bytes = self.decompress(bytes)?;
+        match self.format {
+            PayloadFormat::Json => serde_json::from_slice(&bytes).map_err(ser_err),
+            PayloadFormat::MessagePack => rmp_serde::from_slice(&bytes).map_err(ser_err),
+            PayloadFormat::Bincode => bincode::deserialize(&bytes).map_err(ser_err),
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    fn compress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        if self.compressed {
+            Ok(zstd::bulk::compress(&bytes, ZSTD_LEVEL)?)
+        } else {
+            Ok(bytes)
+        }
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    fn compress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        if self.compressed {
+            return Err(zstd_disabled());
+        }
+        Ok(bytes)
+    }
+
+    #[cfg(feature = "zstd")]
+    fn decompress(self, bytes: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>> {
+        if self.compressed {
+            Ok(zstd::stream::decode_all(bytes)?.into())
+        } else {
+            Ok(bytes.into())
+        }
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    fn decompress(self, bytes: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>> {
+        if self.compressed {
+            return Err(zstd_disabled());
+        }
+        Ok(bytes.into())
+    }
+}
+
+impl std::fmt::Display for Codec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+fn ser_err(e: impl std::fmt::Display) -> MemzError {
+    MemzError::Serialization(e.to_string())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_disabled() -> MemzError {
+    MemzError::Serialization(
+        "row is zstd-compressed but memz-core was built without the `zstd` feature".to_string(),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_FORMATS: [PayloadFormat; 3] = [
+        PayloadFormat::Json,
+        PayloadFormat::MessagePack,
+        PayloadFormat::Bincode,
+    ];
+
+    #[test]
+    fn tags_round_trip() {
+        for format in ALL_FORMATS {
+            for compressed in [false, true] {
+                let codec = Codec { format, compressed };
+                assert_eq!(Codec::from_tag(codec.tag()).expect("tag"), codec);
+            }
+        }
+        assert!(Codec::from_tag("yaml").is_err());
+    }
+
+    #[test]
+    fn every_format_round_trips() {
+        let value = (String::from("wolves at the gate"), 42_u64, vec![0.5_f32, -1.0]);
+        for format in ALL_FORMATS {
+            let codec = Codec {
+                format,
+                compressed: false,
+            };
+            let bytes = codec.encode(&value).expect("encode");
+            let back: (String, u64, Vec<f32>) = codec.decode(&bytes).expect("decode");
+            assert_eq!(back, value, "{codec}");
+        }
+    }
+
+    #[test]
+    fn config_selects_codec() {
+        let config = PersistenceConfig {
+            codec: "bincode".to_string(),
+            ..PersistenceConfig::default()
+        };
+        assert_eq!(
+            Codec::from_config(&config).expect("codec"),
+            Codec {
+                format: PayloadFormat::Bincode,
+                compressed: false,
+            }
+        );
+
+        let bad = PersistenceConfig {
+            codec: "xml".to_string(),
+            ..PersistenceConfig::default()
+        };
+        assert!(matches!(Codec::from_config(&bad), Err(MemzError::Config(_))));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn compression_round_trips_and_shrinks() {
+        let codec = Codec {
+            format: PayloadFormat::Json,
+            compressed: true,
+        };
+        let value = "the same gossip again ".repeat(50);
+        let bytes = codec.encode(&value).expect("encode");
+        assert!(bytes.len() < value.len() / 4);
+        let back: String = codec.decode(&bytes).expect("decode");
+        assert_eq!(back, value);
+    }
+}
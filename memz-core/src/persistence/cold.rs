@@ -0,0 +1,527 @@
+//! Cold ring — episodic memories spilled out of the hot bank (§12.2.1)
+//!
+//! Memories older than `warm_ring_days` (or pushed out by the in-memory
+//! cap) leave [`MemoryBank::episodic`] and are written to
+//! `cold_episodic_memories`, one row per memory in the same versioned
+//! payload format as the hot tables.  `cold_memory_entities` indexes each
+//! spilled memory by participant, so "what do I remember about this
+//! entity?" is a single indexed lookup.
+//!
+//! The bank keeps a per-entity count of its cold memories (loaded with the
+//! bank, updated on spill and page-in), which lets retrieval and
+//! disposition skip `SQLite` entirely for entities with no cold history.
+//! Paging in moves rows back into the bank — newest first; the next save
+//! of the bank deletes them from cold storage in the same transaction that
+//! writes them to the hot tables, so a crash in between loses nothing.
+//! The next eviction pass spills them again if they are still cold.
+//!
+//! [`MemoryBank::episodic`]: crate::memory::MemoryBank::episodic
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use rusqlite::{Connection, Transaction, params};
+use tracing::{debug, warn};
+
+use super::{PersistenceEngine, StoredMemory, StoredRow, crc32_hex, embeddings, migrations};
+use crate::consolidation::MemoryType;
+#[cfg(doc)]
+use crate::error::MemzError;
+use crate::error::Result;
+use crate::memory::{EpisodicMemory, MemoryBank};
+use crate::types::{EntityId, MemoryId};
+
+/// Create the cold-ring tables.
+pub(super) fn create_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS cold_episodic_memories (
+            owner      TEXT NOT NULL,
+            memory_id  TEXT NOT NULL,
+            tick       INTEGER NOT NULL,
+            data       BLOB NOT NULL,
+            spilled_at TEXT NOT NULL,
+            checksum   TEXT,
+            codec      TEXT NOT NULL DEFAULT 'json',
+            embedding       BLOB,
+            embedding_model TEXT,
+            embedding_dims  INTEGER,
+            PRIMARY KEY (owner, memory_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_cold_episodic_memories_tick
+            ON cold_episodic_memories (owner, tick);
+        CREATE TABLE IF NOT EXISTS cold_memory_entities (
+            owner      TEXT NOT NULL,
+            entity_id  TEXT NOT NULL,
+            memory_id  TEXT NOT NULL,
+            PRIMARY KEY (owner, entity_id, memory_id)
+        );",
+    )?;
+    Ok(())
+}
+
+/// Delete every cold memory of `owner`.
+pub(super) fn delete_owner(tx: &Transaction<'_>, owner: &str) -> Result<()> {
+    tx.execute(
+        "DELETE FROM cold_episodic_memories WHERE owner = ?1",
+        params![owner],
+    )?;
+    tx.execute(
+        "DELETE FROM cold_memory_entities WHERE owner = ?1",
+        params![owner],
+    )?;
+    Ok(())
+}
+
+/// Delete the cold rows of the memories `owner`'s bank took out of cold
+/// storage since its last save.
+pub(super) fn delete_taken(
+    tx: &Transaction<'_>,
+    owner: &str,
+    taken: &HashSet<MemoryId>,
+) -> Result<()> {
+    for id in taken {
+        let id = id.0.to_string();
+        tx.prepare_cached(
+            "DELETE FROM cold_episodic_memories WHERE owner = ?1 AND memory_id = ?2",
+        )?
+        .execute(params![owner, id])?;
+        tx.prepare_cached("DELETE FROM cold_memory_entities WHERE owner = ?1 AND memory_id = ?2")?
+            .execute(params![owner, id])?;
+    }
+    Ok(())
+}
+
+/// Ticks are stored as `INTEGER`; saturate rather than wrap.
+fn tick_column(tick: u64) -> i64 {
+    i64::try_from(tick).unwrap_or(i64::MAX)
+}
+
+/// Drop `memories`, which just left cold storage, from the bank's index.
+fn unindex(bank: &mut MemoryBank, memories: &[EpisodicMemory]) {
+    for participant in memories.iter().flat_map(|m| &m.participants) {
+        if let Some(count) = bank.cold.get_mut(participant) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                bank.cold.remove(participant);
+            }
+        }
+    }
+}
+
+impl PersistenceEngine {
+    /// Write `memories` (already removed from `bank`) to cold storage and
+    /// record them in the bank's cold index.
+    ///
+    /// Spilling a memory that is already cold replaces its row, and one
+    /// paged in since the last save keeps its row.  Returns the number of
+    /// memories written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] if encoding fails, or
+    /// [`MemzError::Database`] on `SQLite` failures; nothing is written then.
+    pub fn spill_cold(
+        &self,
+        owner: &EntityId,
+        bank: &mut MemoryBank,
+        memories: &[EpisodicMemory],
+    ) -> Result<usize> {
+        if memories.is_empty() {
+            return Ok(0);
+        }
+        let owner_str = owner.0.to_string();
+        let now = Utc::now().to_rfc3339();
+        let vectors = self.embedding_format.zip(self.embedding_model.as_ref());
+        let mut indexed: Vec<EntityId> = Vec::new();
+
+        let tx = self.conn.unchecked_transaction()?;
+        for memory in memories {
+            // Its rows are still there, waiting for the save to delete them.
+            let retaken = bank.cold_taken.remove(&memory.id);
+            let id = memory.id.0.to_string();
+            let data = migrations::encode(self.codec, MemoryType::Episodic, memory)?;
+            let checksum = self.config.checksum_enabled.then(|| crc32_hex(&data));
+            let (embedding, model) = vectors
+                .and_then(|(format, model)| {
+                    memory
+                        .embedding()
+                        .filter(|e| e.dimensions() == model.dimensions)
+                        .map(|e| (embeddings::encode(format, e), model))
+                })
+                .unzip();
+            tx.prepare_cached(
+                "INSERT INTO cold_episodic_memories
+                    (owner, memory_id, tick, data, spilled_at, checksum, codec,
+                     embedding, embedding_model, embedding_dims)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT(owner, memory_id) DO UPDATE SET
+                    tick = excluded.tick,
+                    data = excluded.data,
+                    spilled_at = excluded.spilled_at,
+                    checksum = excluded.checksum,
+                    codec = excluded.codec,
+                    embedding = excluded.embedding,
+                    embedding_model = excluded.embedding_model,
+                    embedding_dims = excluded.embedding_dims",
+            )?
+            .execute(params![
+                owner_str,
+                id,
+                tick_column(memory.timestamp.tick),
+                data,
+                now,
+                checksum,
+                self.codec.tag(),
+                embedding,
+                model.map(|m| m.name.as_str()),
+                model.map(|m| m.dimensions),
+            ])?;
+
+            for participant in &memory.participants {
+                let inserted = tx
+                    .prepare_cached(
+                        "INSERT OR IGNORE INTO cold_memory_entities (owner, entity_id, memory_id)
+                         VALUES (?1, ?2, ?3)",
+                    )?
+                    .execute(params![owner_str, participant.0.to_string(), id])?;
+                if inserted > 0 || retaken {
+                    indexed.push(*participant);
+                }
+            }
+        }
+        tx.commit()?;
+
+        for entity in indexed {
+            *bank.cold.entry(entity).or_insert(0) += 1;
+        }
+        debug!(entity = %owner, spilled = memories.len(), "Spilled memories to cold storage");
+        Ok(memories.len())
+    }
+
+    /// Move up to `limit` of `owner`'s cold memories about `about` back into
+    /// `bank`, newest first.
+    ///
+    /// Their cold rows stay until the bank is next saved with
+    /// [`save_bank`](Self::save_bank), which deletes them in the same
+    /// transaction.  Does not touch the database when the bank's cold index
+    /// has nothing about `about`.  Memories the bank already holds are
+    /// dropped from cold storage without being duplicated.  Returns the
+    /// number of memories added to the bank.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] if decoding fails, or
+    /// [`MemzError::Database`] on `SQLite` failures; the bank is unchanged
+    /// then.
+    pub fn page_in(
+        &self,
+        owner: &EntityId,
+        bank: &mut MemoryBank,
+        about: EntityId,
+        limit: usize,
+    ) -> Result<usize> {
+        self.page_in_matching(owner, bank, about, limit, None, |_| true)
+    }
+
+    /// [`page_in`](Self::page_in), taking only memories formed within the
+    /// inclusive tick window `ticks` (filtered in `SQLite`) that pass `keep`
+    /// (checked as rows are decoded, so rows past the `limit`-th match are
+    /// never read).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] if decoding fails, or
+    /// [`MemzError::Database`] on `SQLite` failures; the bank is unchanged
+    /// then.
+    pub fn page_in_matching(
+        &self,
+        owner: &EntityId,
+        bank: &mut MemoryBank,
+        about: EntityId,
+        limit: usize,
+        ticks: Option<(u64, u64)>,
+        keep: impl Fn(&EpisodicMemory) -> bool,
+    ) -> Result<usize> {
+        if limit == 0 || bank.cold_count(about) == 0 {
+            return Ok(0);
+        }
+        let owner_str = owner.0.to_string();
+        let (from, to) = ticks.unwrap_or((0, u64::MAX));
+        let paged = self.take_cold(
+            &owner_str,
+            bank,
+            limit,
+            keep,
+            "SELECT c.data, c.checksum, c.codec, c.embedding, c.embedding_model, c.embedding_dims
+             FROM cold_episodic_memories c
+             JOIN cold_memory_entities e
+               ON e.owner = c.owner AND e.memory_id = c.memory_id
+             WHERE c.owner = ?1 AND e.entity_id = ?2 AND c.tick BETWEEN ?3 AND ?4
+             ORDER BY c.tick DESC",
+            params![
+                owner_str,
+                about.0.to_string(),
+                tick_column(from),
+                tick_column(to)
+            ],
+        )?;
+
+        let held: HashSet<_> = bank.episodic.iter().map(|m| m.id).collect();
+        let before = bank.episodic.len();
+        bank.episodic
+            .extend(paged.into_iter().filter(|m| !held.contains(&m.id)));
+        let added = bank.episodic.len() - before;
+        debug!(entity = %owner, about = %about, added, "Paged in cold memories");
+        Ok(added)
+    }
+
+    /// Remove `owner`'s cold memories formed before `before_tick` (the
+    /// archive ring) from the bank's cold index; the next save deletes
+    /// them from cold storage.
+    ///
+    /// Returns the removed memories, e.g. to condense them into gists (see
+    /// [`crate::archival`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] if decoding fails, or
+    /// [`MemzError::Database`] on `SQLite` failures.
+    pub fn archive_cold(
+        &self,
+        owner: &EntityId,
+        bank: &mut MemoryBank,
+        before_tick: u64,
+    ) -> Result<Vec<EpisodicMemory>> {
+        let owner_str = owner.0.to_string();
+        let expired = self.take_cold(
+            &owner_str,
+            bank,
+            usize::MAX,
+            |_| true,
+            "SELECT data, checksum, codec, embedding, embedding_model, embedding_dims
+             FROM cold_episodic_memories
+             WHERE owner = ?1 AND tick < ?2
+             ORDER BY tick",
+            params![owner_str, tick_column(before_tick)],
+        )?;
+        Ok(expired)
+    }
+
+    /// Decode the rows selected by `query` (the six payload and embedding
+    /// columns, in `StoredRow` order) until `limit` pass `keep`, skipping
+    /// memories `bank` already took, and mark those taken: out of the cold
+    /// index, deleted on the next save.
+    fn take_cold(
+        &self,
+        owner: &str,
+        bank: &mut MemoryBank,
+        limit: usize,
+        keep: impl Fn(&EpisodicMemory) -> bool,
+        query: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<EpisodicMemory>> {
+        let mut stmt = self.conn.prepare_cached(query)?;
+        let mut rows = stmt.query(params)?;
+        let mut memories: Vec<EpisodicMemory> = Vec::new();
+        while memories.len() < limit
+            && let Some(row) = rows.next()?
+        {
+            let row = StoredRow {
+                seq: 0,
+                data: row.get(0)?,
+                checksum: row.get(1)?,
+                codec: row.get(2)?,
+                embedding: row.get(3)?,
+                embedding_model: row.get(4)?,
+                embedding_dims: row.get(5)?,
+            };
+            let (memory, _): (EpisodicMemory, _) =
+                self.decode_row(owner, "cold_episodic_memories", row)?;
+            if !bank.cold_taken.contains(&memory.id) && keep(&memory) {
+                memories.push(memory);
+            }
+        }
+        unindex(bank, &memories);
+        bank.cold_taken.extend(memories.iter().map(|m| m.id));
+        Ok(memories)
+    }
+
+    /// Number of `owner`'s memories in cold storage.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn cold_memory_count(&self, owner: &EntityId) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM cold_episodic_memories WHERE owner = ?1",
+            params![owner.0.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Entity → number of `owner`'s cold memories about it.
+    pub(super) fn cold_entities(&self, owner: &EntityId) -> Result<HashMap<EntityId, usize>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT entity_id, COUNT(*) FROM cold_memory_entities
+             WHERE owner = ?1 GROUP BY entity_id",
+        )?;
+        let rows = stmt.query_map(params![owner.0.to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+
+        let mut entities = HashMap::new();
+        for row in rows {
+            let (id_str, count) = row?;
+            if let Ok(uuid) = uuid::Uuid::parse_str(&id_str) {
+                entities.insert(EntityId(uuid), count as usize);
+            } else {
+                warn!(id = %id_str, "Skipping cold index row with invalid UUID");
+            }
+        }
+        Ok(entities)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PersistenceConfig;
+    use crate::types::{GameTimestamp, Location, MemoryId};
+
+    fn memory(tick: u64, participants: Vec<EntityId>) -> EpisodicMemory {
+        let ts = GameTimestamp {
+            tick,
+            real_time: Utc::now(),
+        };
+        EpisodicMemory {
+            id: MemoryId::new(),
+            event: format!("Event at tick {tick}"),
+            participants,
+            location: Location::default(),
+            timestamp: ts,
+            emotional_valence: 0.2,
+            importance: 0.5,
+            decay_rate: 0.02,
+            strength: 1.0,
+            access_count: 0,
+            last_accessed: ts,
+            is_first_meeting: false,
+            embedding: None,
+        }
+    }
+
+    #[test]
+    fn spilled_memories_page_back_in_newest_first() {
+        let engine =
+            PersistenceEngine::open_in_memory(&PersistenceConfig::default()).expect("open engine");
+        let (owner, player, other) = (EntityId::new(), EntityId::new(), EntityId::new());
+        let mut bank = MemoryBank::new();
+        let cold = vec![
+            memory(10, vec![player]),
+            memory(30, vec![player, other]),
+            memory(20, vec![player]),
+            memory(40, vec![other]),
+        ];
+
+        assert_eq!(
+            engine.spill_cold(&owner, &mut bank, &cold).expect("spill"),
+            4
+        );
+        assert_eq!(bank.cold_count(player), 3);
+        assert_eq!(bank.cold_count(other), 2);
+        assert_eq!(engine.cold_memory_count(&owner).expect("count"), 4);
+
+        assert_eq!(
+            engine
+                .page_in(&owner, &mut bank, player, 2)
+                .expect("page in"),
+            2
+        );
+        let ticks: Vec<u64> = bank.episodic.iter().map(|m| m.timestamp.tick).collect();
+        assert_eq!(ticks, vec![30, 20], "newest first");
+        assert_eq!(bank.cold_count(player), 1);
+        assert_eq!(bank.cold_count(other), 1, "shared memory left the index");
+        assert_eq!(
+            engine.cold_memory_count(&owner).expect("count"),
+            4,
+            "rows stay until saved"
+        );
+
+        // Paging in again does not hand out the same rows twice.
+        assert_eq!(
+            engine
+                .page_in(&owner, &mut bank, player, 1)
+                .expect("page in"),
+            1
+        );
+        assert_eq!(bank.episodic.last().map(|m| m.timestamp.tick), Some(10));
+        let oldest = bank.episodic.pop().expect("paged in");
+        engine
+            .spill_cold(&owner, &mut bank, &[oldest])
+            .expect("spill again");
+        assert_eq!(bank.cold_count(player), 1);
+
+        // The save deletes the paged-in rows; the index survives a
+        // save/load round trip.
+        engine.save_bank(&owner, &mut bank).expect("save");
+        assert_eq!(engine.cold_memory_count(&owner).expect("count"), 2);
+        let mut loaded = engine
+            .load_bank(&owner)
+            .expect("load")
+            .expect("bank exists");
+        assert_eq!(loaded.cold_count(player), 1);
+        assert_eq!(loaded.episodic.len(), 2);
+
+        let stranger = EntityId::new();
+        assert_eq!(
+            engine
+                .page_in(&owner, &mut loaded, stranger, 5)
+                .expect("no-op"),
+            0
+        );
+
+        let expired = engine
+            .archive_cold(&owner, &mut loaded, 15)
+            .expect("archive");
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].timestamp.tick, 10);
+        assert_eq!(loaded.cold_count(player), 0);
+        assert_eq!(loaded.cold_count(other), 1);
+
+        engine.save_bank(&owner, &mut loaded).expect("save");
+        assert_eq!(engine.cold_memory_count(&owner).expect("count"), 1);
+
+        assert!(engine.delete_bank(&owner).expect("delete"));
+        assert_eq!(engine.cold_memory_count(&owner).expect("count"), 0);
+    }
+
+    #[test]
+    fn paging_in_does_not_duplicate_held_memories() {
+        let engine =
+            PersistenceEngine::open_in_memory(&PersistenceConfig::default()).expect("open engine");
+        let (owner, player) = (EntityId::new(), EntityId::new());
+        let mut bank = MemoryBank::new();
+        let held = memory(5, vec![player]);
+        bank.episodic.push(held.clone());
+
+        // E.g. a spill committed but the bank was never saved afterwards.
+        engine
+            .spill_cold(&owner, &mut bank, &[held])
+            .expect("spill");
+        assert_eq!(
+            engine
+                .page_in(&owner, &mut bank, player, 5)
+                .expect("page in"),
+            0
+        );
+        assert_eq!(bank.episodic.len(), 1);
+        assert_eq!(bank.cold_count(player), 0);
+        engine.save_bank(&owner, &mut bank).expect("save");
+        assert_eq!(engine.cold_memory_count(&owner).expect("count"), 0);
+    }
+}
@@ -0,0 +1,264 @@
+//! Schema versions, payload envelopes and forward migrations.
+//!
+//! Every memory row is written inside a small envelope that records the
+//! format version and memory kind:
+//!
+//! ```json
+//! { "memz_schema": 3, "kind": "episodic", "memory": { ... } }
+//! ```
+//!
+//! On load, payloads older than [`CURRENT_SCHEMA_VERSION`] are run through
+//! [`MIGRATIONS`] in order before being deserialised, so renaming or
+//! restructuring a field never depends on serde defaults silently doing the
+//! right thing.  Rows are rewritten in the current format the next time
+//! their bank is saved.
+//!
+//! The envelope is serialised with the row's [`Codec`].  Payload migrations
+//! operate on JSON values, so they only see JSON rows; `MessagePack` and
+//! bincode rows (which exist from version 4 on) are decoded straight into
+//! the current types.  A migration that reshapes a payload must therefore
+//! also decide how binary rows of the old version are read.
+//!
+//! ## Version history
+//!
+//! | Version | Layout                                                        |
+//! |---------|---------------------------------------------------------------|
+//! | 1       | Whole `MemoryBank` as one JSON blob in `memory_banks`         |
+//! | 2       | One row per memory in per-type tables, bare JSON payload      |
+//! | 3       | Per-type tables, payload wrapped in a versioned envelope      |
+//! | 4       | Per-row `codec` column (JSON / `MessagePack` / bincode, zstd) |
+//! | 5       | Quantised embeddings stored next to rows, tagged with model   |
+//!
+//! Adding a version: bump [`CURRENT_SCHEMA_VERSION`], append a
+//! [`Migration`] whose `from` is the previous version, and add a golden
+//! fixture for the old format under `tests/golden/`.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::codec::{Codec, PayloadFormat};
+use crate::consolidation::MemoryType;
+use crate::error::{MemzError, Result};
+
+/// Newest database / payload version this build reads and writes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 5;
+
+/// One forward migration step, `from` → `from + 1`.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Version this step upgrades from.
+    pub from: u32,
+    /// Human-readable summary, logged when the step runs.
+    pub description: &'static str,
+    /// Rewrite one memory payload of the given kind.
+    pub upgrade_payload: fn(MemoryType, Value) -> Result<Value>,
+}
+
+/// Ordered migration registry — exactly one step per historical version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        description: "split whole-bank blobs into per-type rows",
+        // The split itself happens at the database level; memory payloads
+        // kept their shape.
+        upgrade_payload: unchanged,
+    },
+    Migration {
+        from: 2,
+        description: "wrap row payloads in a versioned envelope",
+        upgrade_payload: unchanged,
+    },
+    Migration {
+        from: 3,
+        description: "record the payload codec of every row",
+        // Existing rows are JSON; the column default says so.
+        upgrade_payload: unchanged,
+    },
+    Migration {
+        from: 4,
+        description: "store embeddings next to memory rows",
+        // Embeddings were never part of the payload.
+        upgrade_payload: unchanged,
+    },
+];
+
+#[allow(clippy::unnecessary_wraps)] // must match `Migration::upgrade_payload`
+fn unchanged(_: MemoryType, payload: Value) -> Result<Value> {
+    Ok(payload)
+}
+
+/// Stable name of a memory kind inside envelopes.
+#[must_use]
+pub fn kind_name(memory_type: MemoryType) -> &'static str {
+    match memory_type {
+        MemoryType::Episodic => "episodic",
+        MemoryType::Semantic => "semantic",
+        MemoryType::Emotional => "emotional",
+        MemoryType::Social => "social",
+        MemoryType::Reflective => "reflective",
+        MemoryType::Procedural => "procedural",
+        MemoryType::Injected => "injected",
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Envelope
+// ---------------------------------------------------------------------------
+
+/// Borrowing envelope used when writing.
+#[derive(Serialize)]
+struct EnvelopeRef<'a, T> {
+    memz_schema: u32,
+    kind: &'a str,
+    memory: &'a T,
+}
+
+/// Owned envelope used when reading; `M` is a JSON [`Value`] for JSON rows
+/// and the memory type itself for binary rows.
+#[derive(Deserialize)]
+struct Envelope<M> {
+    memz_schema: u32,
+    kind: String,
+    memory: M,
+}
+
+/// Serialise a memory in the current envelope format.
+pub(crate) fn encode<T: Serialize>(
+    codec: Codec,
+    memory_type: MemoryType,
+    memory: &T,
+) -> Result<Vec<u8>> {
+    codec.encode(&EnvelopeRef {
+        memz_schema: CURRENT_SCHEMA_VERSION,
+        kind: kind_name(memory_type),
+        memory,
+    })
+}
+
+/// Decode a stored payload of any supported version.
+///
+/// Returns the memory and the version it was stored with. JSON payloads
+/// without an envelope are version 2.
+pub(crate) fn decode<T: DeserializeOwned>(
+    codec: Codec,
+    memory_type: MemoryType,
+    bytes: &[u8],
+) -> Result<(T, u32)> {
+    if codec.format != PayloadFormat::Json {
+        let envelope: Envelope<T> = codec.decode(bytes)?;
+        check_kind(memory_type, &envelope.kind)?;
+        check_supported(envelope.memz_schema)?;
+        return Ok((envelope.memory, envelope.memz_schema));
+    }
+
+    let value: Value = codec.decode(bytes)?;
+    let (version, payload) = if value.get("memz_schema").is_some() {
+        let envelope: Envelope<Value> =
+            serde_json::from_value(value).map_err(|e| MemzError::Serialization(e.to_string()))?;
+        check_kind(memory_type, &envelope.kind)?;
+        (envelope.memz_schema, envelope.memory)
+    } else {
+        (2, value)
+    };
+
+    let payload = upgrade_payload(memory_type, version, payload)?;
+    let memory =
+        serde_json::from_value(payload).map_err(|e| MemzError::Serialization(e.to_string()))?;
+    Ok((memory, version))
+}
+
+/// Run `payload` through every migration from `version` to current.
+pub(crate) fn upgrade_payload(
+    memory_type: MemoryType,
+    version: u32,
+    mut payload: Value,
+) -> Result<Value> {
+    check_supported(version)?;
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        payload = (migration.upgrade_payload)(memory_type, payload)?;
+    }
+    Ok(payload)
+}
+
+fn check_kind(memory_type: MemoryType, kind: &str) -> Result<()> {
+    if kind != kind_name(memory_type) {
+        return Err(MemzError::Serialization(format!(
+            "expected {} payload, found {kind}",
+            kind_name(memory_type)
+        )));
+    }
+    Ok(())
+}
+
+/// Reject versions newer than this build.
+pub(crate) fn check_supported(version: u32) -> Result<()> {
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(MemzError::UnsupportedSchemaVersion {
+            found: version,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_contiguous() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.from as usize, i + 1, "{}", migration.description);
+        }
+        assert_eq!(
+            MIGRATIONS.last().map(|m| m.from + 1),
+            Some(CURRENT_SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    fn envelope_round_trip() {
+        let bytes = encode(Codec::JSON, MemoryType::Semantic, &serde_json::json!({"fact": "x"}))
+            .expect("encode");
+        let (value, version): (Value, u32) =
+            decode(Codec::JSON, MemoryType::Semantic, &bytes).expect("decode");
+        assert_eq!(version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(value["fact"], "x");
+    }
+
+    #[test]
+    fn binary_envelope_round_trip() {
+        let codec = Codec {
+            format: PayloadFormat::Bincode,
+            compressed: false,
+        };
+        let bytes = encode(codec, MemoryType::Procedural, &(7_u32, "smithing")).expect("encode");
+        let (memory, version): ((u32, String), u32) =
+            decode(codec, MemoryType::Procedural, &bytes).expect("decode");
+        assert_eq!(version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(memory, (7, "smithing".to_string()));
+        assert!(decode::<(u32, String)>(codec, MemoryType::Social, &bytes).is_err());
+    }
+
+    #[test]
+    fn kind_mismatch_is_rejected() {
+        let bytes =
+            encode(Codec::JSON, MemoryType::Semantic, &serde_json::json!({})).expect("encode");
+        assert!(decode::<Value>(Codec::JSON, MemoryType::Social, &bytes).is_err());
+    }
+
+    #[test]
+    fn newer_payloads_are_rejected() {
+        let bytes = br#"{"memz_schema": 99, "kind": "episodic", "memory": {}}"#;
+        let err = decode::<Value>(Codec::JSON, MemoryType::Episodic, bytes).expect_err("too new");
+        assert!(matches!(
+            err,
+            MemzError::UnsupportedSchemaVersion { found: 99, .. }
+        ));
+    }
+}
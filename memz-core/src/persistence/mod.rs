@@ -0,0 +1,1873 @@
+//! `SQLite` persistence layer for the MEMZ memory system.
+//!
+//! Memories are stored normalized: one table per memory type, one row per
+//! memory, keyed by owner [`EntityId`] and [`MemoryId`].  A small owner table
+//! records which entities have a bank at all (an empty bank is still a bank).
+//!
+//! ```sql
+//! CREATE TABLE IF NOT EXISTS memory_owners (
+//!     entity_id  TEXT PRIMARY KEY,
+//!     updated_at TEXT NOT NULL
+//! );
+//!
+//! -- one per type: episodic_memories, semantic_memories, emotional_memories,
+//! -- social_memories, reflective_memories, procedural_memories,
+//! -- injected_memories
+//! CREATE TABLE IF NOT EXISTS episodic_memories (
+//!     owner      TEXT NOT NULL,
+//!     memory_id  TEXT NOT NULL,
+//!     seq        INTEGER NOT NULL,
+//!     position   INTEGER NOT NULL,
+//!     data       BLOB NOT NULL,
+//!     updated_at TEXT NOT NULL,
+//!     checksum   TEXT,
+//!     codec      TEXT NOT NULL DEFAULT 'json',
+//!     embedding       BLOB,     -- f16 / i8 vector, see `embeddings`
+//!     embedding_model TEXT,
+//!     embedding_dims  INTEGER,
+//!     PRIMARY KEY (owner, memory_id, seq)
+//! );
+//!
+//! -- the cold eviction ring, see `cold`
+//! CREATE TABLE IF NOT EXISTS cold_episodic_memories (
+//!     owner      TEXT NOT NULL,
+//!     memory_id  TEXT NOT NULL,
+//!     tick       INTEGER NOT NULL,
+//!     data       BLOB NOT NULL,
+//!     spilled_at TEXT NOT NULL,
+//!     checksum   TEXT,
+//!     codec      TEXT NOT NULL DEFAULT 'json',
+//!     embedding       BLOB,
+//!     embedding_model TEXT,
+//!     embedding_dims  INTEGER,
+//!     PRIMARY KEY (owner, memory_id)
+//! );
+//! CREATE TABLE IF NOT EXISTS cold_memory_entities (
+//!     owner      TEXT NOT NULL,
+//!     entity_id  TEXT NOT NULL,
+//!     memory_id  TEXT NOT NULL,
+//!     PRIMARY KEY (owner, entity_id, memory_id)
+//! );
+//! ```
+//!
+//! Design rationale (from §12 of the design doc):
+//! - WAL mode for concurrent reads during gameplay
+//! - A serialised payload per row inside a BLOB column keeps the schema
+//!   stable across memory-type changes (forward-compatible), while owner /
+//!   id columns make memories queryable across NPCs.
+//! - The payload [`Codec`] (JSON, `MessagePack` or bincode, optionally
+//!   zstd-compressed) is configurable and recorded per row, so switching
+//!   codecs never strands rows written with the previous one.
+//! - Embeddings are stored quantised next to their row and tagged with the
+//!   model that produced them; switching models invalidates them (see
+//!   [`embeddings`]).
+//! - Content diffing against a snapshot on [`MemoryBank`] means a save only
+//!   upserts the rows that changed since the last save or load — adding one
+//!   episodic memory writes one row, not the whole bank — and memory types
+//!   whose content hash is unchanged are not even encoded.
+//! - Optional CRC-32 checksum per row detects save corruption.
+//! - Backup support via `SQLite`'s online-backup API.
+//! - A `schema_version` table and a versioned envelope around every row
+//!   payload let old saves be upgraded in order (see [`migrations`]).
+//!   Databases written by the old whole-bank layout (`memory_banks`) are
+//!   migrated to the normalized tables when opened.
+//! - Episodic memories that age into the cold ring leave the bank and are
+//!   spilled to their own tables, indexed by participant, so they can be
+//!   paged back in when a query needs older history (see [`cold`]).
+
+pub mod codec;
+pub mod cold;
+pub mod embeddings;
+pub mod migrations;
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::Utc;
+use rusqlite::{Connection, OpenFlags, Transaction, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::{debug, info, warn};
+
+use crate::config::PersistenceConfig;
+use crate::consolidation::MemoryType;
+use crate::error::{MemzError, Result};
+use crate::memory::{
+    EmotionalMemory, EpisodicMemory, InjectedMemory, MemoryBank, PersistedRows, ProceduralMemory,
+    ReflectiveMemory, RowKey, SemanticMemory, SocialMemory,
+};
+use crate::types::{Embedding, EntityId, MemoryId};
+
+pub use codec::{Codec, PayloadFormat};
+pub use embeddings::{EmbeddingFormat, EmbeddingModel};
+pub use migrations::CURRENT_SCHEMA_VERSION;
+
+// ---------------------------------------------------------------------------
+// CRC-32 checksum helper
+// ---------------------------------------------------------------------------
+
+/// Compute a CRC-32 checksum (Castagnoli / CRC-32C) of `data` and return it
+/// as a lowercase hex string.
+fn crc32_hex(data: &[u8]) -> String {
+    // Simple CRC-32 using the standard ISO polynomial via a lookup table.
+    // We avoid pulling in an extra crate by using a basic implementation.
+    let crc = crc32_compute(data);
+    format!("{crc:08x}")
+}
+
+/// Basic CRC-32 (ISO 3309 / ITU-T V.42) computation.
+fn crc32_compute(data: &[u8]) -> u32 {
+    !crc32_update(0xFFFF_FFFF, data)
+}
+
+/// Feed `data` into a running (non-finalised) CRC-32.
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    const POLY: u32 = 0xEDB8_8320;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            if crc & 1 == 1 {
+                crc = (crc >> 1) ^ POLY;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// Content-diffing fingerprint of a row's payload and embedding blob.
+fn row_fingerprint(data: &[u8], embedding: Option<&[u8]>) -> u32 {
+    let crc = crc32_update(0xFFFF_FFFF, data);
+    !embedding.map_or(crc, |blob| crc32_update(crc, blob))
+}
+
+// ---------------------------------------------------------------------------
+// Schema
+// ---------------------------------------------------------------------------
+
+/// Memory type → normalized table name.
+const MEMORY_TABLES: [(MemoryType, &str); 7] = [
+    (MemoryType::Episodic, "episodic_memories"),
+    (MemoryType::Semantic, "semantic_memories"),
+    (MemoryType::Emotional, "emotional_memories"),
+    (MemoryType::Social, "social_memories"),
+    (MemoryType::Reflective, "reflective_memories"),
+    (MemoryType::Procedural, "procedural_memories"),
+    (MemoryType::Injected, "injected_memories"),
+];
+
+/// Table holding memories of `memory_type`.
+fn table_for(memory_type: MemoryType) -> &'static str {
+    MEMORY_TABLES
+        .iter()
+        .find(|(t, _)| *t == memory_type)
+        .map_or("episodic_memories", |(_, table)| table)
+}
+
+/// Whether a table named `name` exists.
+fn table_exists(conn: &Connection, name: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![name],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Version of an existing database, inferring it for saves that predate the
+/// `schema_version` table.
+fn detect_schema_version(conn: &Connection) -> Result<u32> {
+    if table_exists(conn, "schema_version")? {
+        let version: u32 =
+            conn.query_row("SELECT version FROM schema_version", [], |row| row.get(0))?;
+        migrations::check_supported(version)?;
+        Ok(version)
+    } else if table_exists(conn, "memory_banks")? {
+        Ok(1)
+    } else if table_exists(conn, "episodic_memories")? {
+        Ok(2)
+    } else {
+        Ok(CURRENT_SCHEMA_VERSION)
+    }
+}
+
+/// Record `version` in the single-row `schema_version` table.
+fn set_schema_version(conn: &Connection, version: u32) -> Result<()> {
+    conn.execute(
+        "INSERT INTO schema_version (id, version, updated_at) VALUES (0, ?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET
+            version = excluded.version,
+            updated_at = excluded.updated_at",
+        params![version, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Create the version, owner, per-memory-type and cold-ring tables.
+fn create_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            id         INTEGER PRIMARY KEY CHECK (id = 0),
+            version    INTEGER NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS memory_owners (
+            entity_id  TEXT PRIMARY KEY,
+            updated_at TEXT NOT NULL
+        );",
+    )?;
+    for (_, table) in MEMORY_TABLES {
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                owner      TEXT NOT NULL,
+                memory_id  TEXT NOT NULL,
+                seq        INTEGER NOT NULL,
+                position   INTEGER NOT NULL,
+                data       BLOB NOT NULL,
+                updated_at TEXT NOT NULL,
+                checksum   TEXT,
+                codec      TEXT NOT NULL DEFAULT 'json',
+                embedding       BLOB,
+                embedding_model TEXT,
+                embedding_dims  INTEGER,
+                PRIMARY KEY (owner, memory_id, seq)
+            );
+            CREATE INDEX IF NOT EXISTS idx_{table}_owner ON {table} (owner, position);
+            CREATE INDEX IF NOT EXISTS idx_{table}_memory ON {table} (memory_id);"
+        ))?;
+    }
+    cold::create_tables(conn)
+}
+
+// ---------------------------------------------------------------------------
+// Stored memory types
+// ---------------------------------------------------------------------------
+
+/// A memory type persisted as one row per memory.
+trait StoredMemory: Serialize + DeserializeOwned {
+    /// Type (and therefore table) of the memory.
+    const TYPE: MemoryType;
+
+    /// Row identity.
+    fn memory_id(&self) -> MemoryId;
+
+    /// Embedding stored next to the row, for types that have one.
+    fn embedding(&self) -> Option<&Embedding> {
+        None
+    }
+
+    /// Restore an embedding loaded from the row.
+    fn set_embedding(&mut self, _embedding: Embedding) {}
+}
+
+impl StoredMemory for EpisodicMemory {
+    const TYPE: MemoryType = MemoryType::Episodic;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+    fn embedding(&self) -> Option<&Embedding> {
+        self.embedding.as_ref()
+    }
+    fn set_embedding(&mut self, embedding: Embedding) {
+        self.embedding = Some(embedding);
+    }
+}
+
+impl StoredMemory for SemanticMemory {
+    const TYPE: MemoryType = MemoryType::Semantic;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+    fn embedding(&self) -> Option<&Embedding> {
+        self.embedding.as_ref()
+    }
+    fn set_embedding(&mut self, embedding: Embedding) {
+        self.embedding = Some(embedding);
+    }
+}
+
+impl StoredMemory for EmotionalMemory {
+    const TYPE: MemoryType = MemoryType::Emotional;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+}
+
+impl StoredMemory for SocialMemory {
+    const TYPE: MemoryType = MemoryType::Social;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+}
+
+impl StoredMemory for ReflectiveMemory {
+    const TYPE: MemoryType = MemoryType::Reflective;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+}
+
+impl StoredMemory for ProceduralMemory {
+    const TYPE: MemoryType = MemoryType::Procedural;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+}
+
+impl StoredMemory for InjectedMemory {
+    const TYPE: MemoryType = MemoryType::Injected;
+    fn memory_id(&self) -> MemoryId {
+        self.id
+    }
+}
+
+/// Raw columns of one memory row.
+struct StoredRow {
+    seq: u32,
+    data: Vec<u8>,
+    checksum: Option<String>,
+    codec: String,
+    embedding: Option<Vec<u8>>,
+    embedding_model: Option<String>,
+    embedding_dims: Option<usize>,
+}
+
+// ---------------------------------------------------------------------------
+// Save reports
+// ---------------------------------------------------------------------------
+
+/// What a [`PersistenceEngine::save_bank`] call actually wrote.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SaveReport {
+    /// Rows inserted or updated.
+    pub upserted: usize,
+    /// Rows deleted because the memory left the bank.
+    pub deleted: usize,
+    /// Rows skipped because they were unchanged since the last save/load.
+    pub unchanged: usize,
+}
+
+/// `io::Write` adapter feeding everything written into a hasher.
+struct HashWriter(DefaultHasher);
+
+impl io::Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Hasher::write(&mut self.0, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes one bank's rows inside a transaction, diffing their content
+/// against the rows the bank was last persisted with.
+struct RowWriter<'a> {
+    tx: &'a Transaction<'a>,
+    owner: &'a str,
+    now: &'a str,
+    codec: Codec,
+    /// Set when embeddings are persisted; otherwise embedding columns are
+    /// left untouched.
+    embeddings: Option<(EmbeddingFormat, &'a EmbeddingModel)>,
+    checksum_enabled: bool,
+    previous: &'a HashMap<RowKey, (Option<u32>, usize)>,
+    next: HashMap<RowKey, (Option<u32>, usize)>,
+    /// Per-type content hashes of the last save, and of this one.
+    previous_types: &'a HashMap<MemoryType, u64>,
+    types: HashMap<MemoryType, u64>,
+    report: SaveReport,
+}
+
+impl RowWriter<'_> {
+    /// Upsert every memory of one type whose encoded row differs from the
+    /// snapshot.  When the type's [content hash](Self::type_fingerprint)
+    /// matches the last save, no row is encoded and the snapshot is kept.
+    fn diff_rows<T: StoredMemory>(&mut self, memories: &[T]) -> Result<()> {
+        let fingerprint = self.type_fingerprint(memories)?;
+        self.types.insert(T::TYPE, fingerprint);
+        if self.previous_types.get(&T::TYPE) == Some(&fingerprint) {
+            for (key, row) in self.previous.iter().filter(|(key, _)| key.0 == T::TYPE) {
+                self.next.insert(*key, *row);
+                self.report.unchanged += 1;
+            }
+            return Ok(());
+        }
+
+        let table = table_for(T::TYPE);
+        let mut occurrences: HashMap<MemoryId, u32> = HashMap::new();
+        // Stored positions only need to increase in bank order, so rows keep
+        // theirs while they still do: evicting from the front or appending
+        // rewrites nothing but the new rows.
+        let mut floor: Option<usize> = None;
+
+        for memory in memories {
+            let id = memory.memory_id();
+            let seq = occurrences.entry(id).or_insert(0);
+            let key = (T::TYPE, id, *seq);
+            *seq += 1;
+
+            let data = migrations::encode(self.codec, T::TYPE, memory)?;
+            let embedding = self.embeddings.and_then(|(format, model)| {
+                memory
+                    .embedding()
+                    .filter(|e| e.dimensions() == model.dimensions)
+                    .map(|e| embeddings::encode(format, e))
+            });
+            let crc = row_fingerprint(&data, embedding.as_deref());
+
+            if let Some(&(_, stored)) = self
+                .previous
+                .get(&key)
+                .filter(|&&(previous, stored)| previous == Some(crc) && floor.is_none_or(|f| stored > f))
+            {
+                floor = Some(stored);
+                self.next.insert(key, (Some(crc), stored));
+                self.report.unchanged += 1;
+                continue;
+            }
+            let position = floor.map_or(0, |f| f + 1);
+            floor = Some(position);
+            self.next.insert(key, (Some(crc), position));
+
+            let checksum = self.checksum_enabled.then(|| crc32_hex(&data));
+            let tagged = embedding.is_some();
+            let model = self.embeddings.map(|(_, model)| model);
+            let model_name = model.filter(|_| tagged).map(|m| m.name.as_str());
+            let dims = model.filter(|_| tagged).map(|m| m.dimensions);
+            // Without embedding persistence, leave whatever is stored alone.
+            let update_embedding = if self.embeddings.is_some() {
+                ",
+                        embedding = excluded.embedding,
+                        embedding_model = excluded.embedding_model,
+                        embedding_dims = excluded.embedding_dims"
+            } else {
+                ""
+            };
+            self.tx
+                .prepare_cached(&format!(
+                    "INSERT INTO {table} (owner, memory_id, seq, position, data, updated_at, checksum, codec,
+                                          embedding, embedding_model, embedding_dims)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                     ON CONFLICT(owner, memory_id, seq) DO UPDATE SET
+                        position = excluded.position,
+                        data = excluded.data,
+                        updated_at = excluded.updated_at,
+                        checksum = excluded.checksum,
+                        codec = excluded.codec{update_embedding}"
+                ))?
+                .execute(params![
+                    self.owner,
+                    id.0.to_string(),
+                    key.2,
+                    position,
+                    data,
+                    self.now,
+                    checksum,
+                    self.codec.tag(),
+                    embedding,
+                    model_name,
+                    dims
+                ])?;
+            self.report.upserted += 1;
+        }
+        Ok(())
+    }
+
+    /// Hash of `memories` and of the settings their rows are written with.
+    /// Serialising with bincode into a hasher skips the codec, compression
+    /// and per-row checksums, so an unchanged type costs one cheap pass.
+    fn type_fingerprint<T: StoredMemory>(&self, memories: &[T]) -> Result<u64> {
+        let mut writer = HashWriter(DefaultHasher::new());
+        let embeddings = self
+            .embeddings
+            .map(|(format, model)| (format, &model.name, model.dimensions));
+        (self.codec.tag(), self.checksum_enabled, embeddings, memories.len()).hash(&mut writer.0);
+        for memory in memories {
+            bincode::serialize_into(&mut writer, memory)
+                .map_err(|e| MemzError::Serialization(e.to_string()))?;
+            match memory.embedding() {
+                Some(embedding) => {
+                    embedding.0.len().hash(&mut writer.0);
+                    embedding.0.iter().for_each(|value| value.to_bits().hash(&mut writer.0));
+                }
+                None => usize::MAX.hash(&mut writer.0),
+            }
+        }
+        Ok(writer.0.finish())
+    }
+
+    /// Delete rows that were persisted before but are no longer in the bank.
+    fn delete_stale(&mut self) -> Result<()> {
+        for &(memory_type, id, seq) in self.previous.keys() {
+            if self.next.contains_key(&(memory_type, id, seq)) {
+                continue;
+            }
+            let table = table_for(memory_type);
+            self.report.deleted += self
+                .tx
+                .prepare_cached(&format!(
+                    "DELETE FROM {table} WHERE owner = ?1 AND memory_id = ?2 AND seq = ?3"
+                ))?
+                .execute(params![self.owner, id.0.to_string(), seq])?;
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PersistenceEngine
+// ---------------------------------------------------------------------------
+
+/// Handle to an open `SQLite` database that stores [`MemoryBank`]s.
+///
+/// # Usage
+///
+/// ```no_run
+/// # use memz_core::persistence::PersistenceEngine;
+/// # use memz_core::config::PersistenceConfig;
+/// # use memz_core::types::EntityId;
+/// # use memz_core::memory::MemoryBank;
+/// let engine = PersistenceEngine::open("world_save.db", &PersistenceConfig::default())?;
+/// let entity = EntityId::new();
+/// let mut bank = MemoryBank::new();
+/// engine.save_bank(&entity, &mut bank)?;
+/// let loaded = engine.load_bank(&entity)?;
+/// # Ok::<(), memz_core::error::MemzError>(())
+/// ```
+pub struct PersistenceEngine {
+    conn: Connection,
+    config: PersistenceConfig,
+    codec: Codec,
+    embedding_format: Option<EmbeddingFormat>,
+    embedding_model: Option<EmbeddingModel>,
+    db_path: PathBuf,
+}
+
+impl std::fmt::Debug for PersistenceEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PersistenceEngine")
+            .field("db_path", &self.db_path)
+            .field("config", &self.config)
+            .field("codec", &self.codec)
+            .field("embedding_model", &self.embedding_model)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PersistenceEngine {
+    /// Open (or create) an `SQLite` database at `path`.
+    ///
+    /// The schema is automatically created if it does not exist, and banks
+    /// saved in the legacy `memory_banks` layout are migrated.
+    /// WAL mode is enabled when `config.wal_mode` is `true`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Config`] if the configured codec is invalid, or
+    /// [`MemzError::Database`] on `SQLite` failures.
+    pub fn open<P: AsRef<Path>>(path: P, config: &PersistenceConfig) -> Result<Self> {
+        let codec = Codec::from_config(config)?;
+        let embedding_format = EmbeddingFormat::from_config(&config.embedding_format)?;
+        let db_path = path.as_ref().to_path_buf();
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE
+            | OpenFlags::SQLITE_OPEN_CREATE
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+
+        let conn = Connection::open_with_flags(&db_path, flags)?;
+
+        // Pragmas for performance and safety.
+        if config.wal_mode {
+            conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        }
+        conn.execute_batch("PRAGMA synchronous = NORMAL;")?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch("PRAGMA busy_timeout = 5000;")?;
+
+        let version = detect_schema_version(&conn)?;
+        create_schema(&conn)?;
+
+        let engine = Self {
+            conn,
+            config: config.clone(),
+            codec,
+            embedding_format,
+            embedding_model: None,
+            db_path,
+        };
+        engine.migrate_schema(version)?;
+
+        info!(
+            path = %engine.db_path.display(),
+            wal = config.wal_mode,
+            codec = %codec,
+            "MEMZ persistence engine opened"
+        );
+
+        Ok(engine)
+    }
+
+    /// Open an in-memory database (useful for tests).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Config`] if the configured codec is invalid, or
+    /// [`MemzError::Database`] on `SQLite` failures.
+    pub fn open_in_memory(config: &PersistenceConfig) -> Result<Self> {
+        let codec = Codec::from_config(config)?;
+        let embedding_format = EmbeddingFormat::from_config(&config.embedding_format)?;
+        let conn = Connection::open_in_memory()?;
+        create_schema(&conn)?;
+        set_schema_version(&conn, CURRENT_SCHEMA_VERSION)?;
+
+        Ok(Self {
+            conn,
+            config: config.clone(),
+            codec,
+            embedding_format,
+            embedding_model: None,
+            db_path: PathBuf::from(":memory:"),
+        })
+    }
+
+    /// Persist embeddings produced by `model` (usually
+    /// [`EmbeddingProvider::model_name`] and
+    /// [`EmbeddingProvider::dimensions`] of the active provider).
+    ///
+    /// Embeddings are saved in `config.embedding_format` and tagged with the
+    /// model.  On load, embeddings from any other model or dimensionality are
+    /// dropped (and cleared from the row on the next save) so the provider
+    /// recomputes them.  Without a model, embedding columns are neither read
+    /// nor written.
+    ///
+    /// [`EmbeddingProvider::model_name`]: crate::embedding::EmbeddingProvider::model_name
+    /// [`EmbeddingProvider::dimensions`]: crate::embedding::EmbeddingProvider::dimensions
+    #[must_use]
+    pub fn with_embedding_model(mut self, name: impl Into<String>, dimensions: usize) -> Self {
+        self.embedding_model = Some(EmbeddingModel {
+            name: name.into(),
+            dimensions,
+        });
+        self
+    }
+
+    // ------------------------------------------------------------------
+    // Core CRUD
+    // ------------------------------------------------------------------
+
+    /// Save an entity's [`MemoryBank`], writing only what changed.
+    ///
+    /// Each memory is serialised with the configured [`Codec`] and compared
+    /// with the snapshot the bank was last saved or loaded with; only new or
+    /// modified rows are upserted and memories that left the bank are
+    /// deleted.  Memory types unchanged since the last save are recognised
+    /// by a content hash and not encoded at all.  A bank that has
+    /// no snapshot for `entity_id` (new, deserialised elsewhere, or
+    /// [`MemoryBank::mark_all_dirty`]) replaces the entity's rows wholesale.
+    /// Cold memories paged in or archived since the last save leave cold
+    /// storage in the same transaction.  If `config.checksum_enabled` is
+    /// true, a CRC-32 of each row is stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] if encoding fails, or
+    /// [`MemzError::Database`] on `SQLite` failures.
+    pub fn save_bank(&self, entity_id: &EntityId, bank: &mut MemoryBank) -> Result<SaveReport> {
+        let start = Instant::now();
+
+        let tx = self.conn.unchecked_transaction()?;
+        let (report, rows) = self.write_bank(&tx, entity_id, bank)?;
+        tx.commit()?;
+
+        bank.persisted = rows;
+        bank.cold_taken.clear();
+
+        let elapsed = start.elapsed();
+        debug!(
+            entity = %entity_id,
+            memories = bank.total_count(),
+            upserted = report.upserted,
+            deleted = report.deleted,
+            elapsed_us = elapsed.as_micros(),
+            "Saved memory bank"
+        );
+
+        Ok(report)
+    }
+
+    /// Write `bank` inside `tx`, returning the report and the new snapshot.
+    fn write_bank(
+        &self,
+        tx: &Transaction<'_>,
+        entity_id: &EntityId,
+        bank: &MemoryBank,
+    ) -> Result<(SaveReport, PersistedRows)> {
+        let now = Utc::now().to_rfc3339();
+        let owner = entity_id.0.to_string();
+
+        tx.execute(
+            "INSERT INTO memory_owners (entity_id, updated_at) VALUES (?1, ?2)
+             ON CONFLICT(entity_id) DO UPDATE SET updated_at = excluded.updated_at",
+            params![owner, now],
+        )?;
+
+        // Without a snapshot for this entity we cannot know which rows exist.
+        let (empty, empty_types) = (HashMap::new(), HashMap::new());
+        let (previous, previous_types) = if bank.persisted.owner == Some(*entity_id) {
+            (&bank.persisted.rows, &bank.persisted.types)
+        } else {
+            for (_, table) in MEMORY_TABLES {
+                tx.execute(
+                    &format!("DELETE FROM {table} WHERE owner = ?1"),
+                    params![owner],
+                )?;
+            }
+            (&empty, &empty_types)
+        };
+
+        let mut writer = RowWriter {
+            tx,
+            owner: &owner,
+            now: &now,
+            codec: self.codec,
+            embeddings: self.embedding_format.zip(self.embedding_model.as_ref()),
+            checksum_enabled: self.config.checksum_enabled,
+            previous,
+            next: HashMap::with_capacity(bank.total_count()),
+            previous_types,
+            types: HashMap::new(),
+            report: SaveReport::default(),
+        };
+        writer.diff_rows(&bank.episodic)?;
+        writer.diff_rows(&bank.semantic)?;
+        writer.diff_rows(&bank.emotional)?;
+        writer.diff_rows(&bank.social)?;
+        writer.diff_rows(&bank.reflective)?;
+        writer.diff_rows(&bank.procedural)?;
+        writer.diff_rows(&bank.injected)?;
+        writer.delete_stale()?;
+        cold::delete_taken(tx, &owner, &bank.cold_taken)?;
+
+        let rows = PersistedRows {
+            owner: Some(*entity_id),
+            rows: writer.next,
+            types: writer.types,
+        };
+        Ok((writer.report, rows))
+    }
+
+    /// Load an entity's [`MemoryBank`].
+    ///
+    /// Returns `None` if the entity has never been saved.
+    /// If checksums are enabled and a stored row checksum doesn't match, a
+    /// warning is logged but the data is still returned.  The loaded bank
+    /// carries a snapshot of its rows, so saving it back unchanged writes
+    /// nothing (though the first save encodes every row to compare them).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Serialization`] if decoding fails, or
+    /// [`MemzError::Database`] on `SQLite` failures.
+    pub fn load_bank(&self, entity_id: &EntityId) -> Result<Option<MemoryBank>> {
+        let start = Instant::now();
+        let owner = entity_id.0.to_string();
+
+        let exists = self
+            .conn
+            .prepare_cached("SELECT 1 FROM memory_owners WHERE entity_id = ?1")?
+            .query_row(params![owner], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !exists {
+            return Ok(None);
+        }
+
+        let mut rows = HashMap::new();
+        let bank = MemoryBank {
+            episodic: self.load_rows(&owner, &mut rows)?,
+            semantic: self.load_rows(&owner, &mut rows)?,
+            emotional: self.load_rows(&owner, &mut rows)?,
+            social: self.load_rows(&owner, &mut rows)?,
+            reflective: self.load_rows(&owner, &mut rows)?,
+            procedural: self.load_rows(&owner, &mut rows)?,
+            injected: self.load_rows(&owner, &mut rows)?,
+            persisted: PersistedRows {
+                owner: Some(*entity_id),
+                rows,
+                types: HashMap::new(),
+            },
+            cold: self.cold_entities(entity_id)?,
+            cold_taken: HashSet::new(),
+        };
+
+        let elapsed = start.elapsed();
+        debug!(
+            entity = %entity_id,
+            memories = bank.total_count(),
+            elapsed_us = elapsed.as_micros(),
+            "Loaded memory bank"
+        );
+
+        Ok(Some(bank))
+    }
+
+    /// Load one memory type for `owner`, recording each row in `snapshot`.
+    ///
+    /// Rows stored in an older format are upgraded and recorded without a
+    /// fingerprint, so the next save rewrites them in the current format.
+    fn load_rows<T: StoredMemory>(
+        &self,
+        owner: &str,
+        snapshot: &mut HashMap<RowKey, (Option<u32>, usize)>,
+    ) -> Result<Vec<T>> {
+        let table = table_for(T::TYPE);
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT seq, data, checksum, codec, embedding, embedding_model, embedding_dims, position
+             FROM {table} WHERE owner = ?1 ORDER BY position"
+        ))?;
+        let rows = stmt.query_map(params![owner], |row| {
+            let stored = StoredRow {
+                seq: row.get(0)?,
+                data: row.get(1)?,
+                checksum: row.get(2)?,
+                codec: row.get(3)?,
+                embedding: row.get(4)?,
+                embedding_model: row.get(5)?,
+                embedding_dims: row.get(6)?,
+            };
+            Ok((stored, row.get::<_, usize>(7)?))
+        })?;
+
+        let mut memories = Vec::new();
+        for row in rows {
+            let (row, position) = row?;
+            let seq = row.seq;
+            let (memory, fingerprint): (T, _) = self.decode_row(owner, table, row)?;
+            snapshot.insert(
+                (T::TYPE, memory.memory_id(), seq),
+                (fingerprint, position),
+            );
+            memories.push(memory);
+        }
+        Ok(memories)
+    }
+
+    /// Decode one row of `table`, verifying its checksum and restoring its
+    /// embedding.  The fingerprint is `None` when the row is stored in an
+    /// older format or codec, or its embedding must be recomputed.
+    fn decode_row<T: StoredMemory>(
+        &self,
+        owner: &str,
+        table: &str,
+        row: StoredRow,
+    ) -> Result<(T, Option<u32>)> {
+        // Verify checksum if enabled.
+        if self.config.checksum_enabled
+            && let Some(ref expected) = row.checksum
+        {
+            let actual = crc32_hex(&row.data);
+            if *expected != actual {
+                warn!(
+                    owner = %owner,
+                    table = table,
+                    expected = %expected,
+                    actual = %actual,
+                    "Checksum mismatch — possible save corruption"
+                );
+            }
+        }
+
+        let codec = Codec::from_tag(&row.codec)?;
+        let (mut memory, version): (T, u32) = migrations::decode(codec, T::TYPE, &row.data)?;
+        // Rows in an old format or another codec are rewritten on save.
+        let mut fresh = version == CURRENT_SCHEMA_VERSION && codec == self.codec;
+
+        let mut embedding = None;
+        if self.embedding_format.is_some()
+            && let Some(model) = &self.embedding_model
+            && let Some(blob) = row.embedding
+        {
+            let same_model = row.embedding_model.as_deref() == Some(model.name.as_str())
+                && row.embedding_dims == Some(model.dimensions);
+            match same_model.then(|| embeddings::decode(&blob, model.dimensions)) {
+                Some(Ok(decoded)) => {
+                    memory.set_embedding(decoded);
+                    embedding = Some(blob);
+                }
+                Some(Err(e)) => {
+                    warn!(owner = %owner, table = table, error = %e, "Dropping unreadable embedding");
+                    fresh = false;
+                }
+                // Produced by another model — recompute, clear on save.
+                None => fresh = false,
+            }
+        }
+
+        let fingerprint = fresh.then(|| row_fingerprint(&row.data, embedding.as_deref()));
+        Ok((memory, fingerprint))
+    }
+
+    /// Delete an entity's [`MemoryBank`], including its cold-ring memories.
+    ///
+    /// Returns `true` if the entity had a saved bank.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn delete_bank(&self, entity_id: &EntityId) -> Result<bool> {
+        let id_str = entity_id.0.to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        for (_, table) in MEMORY_TABLES {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE owner = ?1"),
+                params![id_str],
+            )?;
+        }
+        cold::delete_owner(&tx, &id_str)?;
+        let deleted = tx.execute(
+            "DELETE FROM memory_owners WHERE entity_id = ?1",
+            params![id_str],
+        )?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// List all entity IDs that have a saved [`MemoryBank`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn list_entities(&self) -> Result<Vec<EntityId>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT entity_id FROM memory_owners")?;
+
+        let rows = stmt.query_map([], |row| {
+            let id_str: String = row.get(0)?;
+            Ok(id_str)
+        })?;
+
+        let mut entities = Vec::new();
+        for row in rows {
+            let id_str = row?;
+            if let Ok(uuid) = uuid::Uuid::parse_str(&id_str) {
+                entities.push(EntityId(uuid));
+            } else {
+                warn!(id = %id_str, "Skipping row with invalid UUID");
+            }
+        }
+
+        Ok(entities)
+    }
+
+    /// Return the total number of stored entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn entity_count(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM memory_owners", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    // ------------------------------------------------------------------
+    // Cross-entity queries
+    // ------------------------------------------------------------------
+
+    /// Number of stored memories of `memory_type`, across all entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn memory_count(&self, memory_type: MemoryType) -> Result<usize> {
+        let table = table_for(memory_type);
+        let count: i64 =
+            self.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })?;
+        Ok(count as usize)
+    }
+
+    /// Entities holding a memory with the given id (e.g. a shared gossip
+    /// chain or a memory copied between banks).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn owners_of(&self, memory_id: &MemoryId) -> Result<Vec<(EntityId, MemoryType)>> {
+        let id_str = memory_id.0.to_string();
+        let mut owners = Vec::new();
+        for (memory_type, table) in MEMORY_TABLES {
+            let mut stmt = self.conn.prepare_cached(&format!(
+                "SELECT DISTINCT owner FROM {table} WHERE memory_id = ?1"
+            ))?;
+            let rows = stmt.query_map(params![id_str], |row| row.get::<_, String>(0))?;
+            for row in rows {
+                if let Ok(uuid) = uuid::Uuid::parse_str(&row?) {
+                    owners.push((EntityId(uuid), memory_type));
+                }
+            }
+        }
+        Ok(owners)
+    }
+
+    // ------------------------------------------------------------------
+    // Schema migration
+    // ------------------------------------------------------------------
+
+    /// Schema version recorded in the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn schema_version(&self) -> Result<u32> {
+        Ok(self
+            .conn
+            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))?)
+    }
+
+    /// Bring a database at `version` up to [`CURRENT_SCHEMA_VERSION`].
+    ///
+    /// Database-level steps run here; row payloads are upgraded lazily by
+    /// [`migrations::decode`] as banks are loaded.
+    fn migrate_schema(&self, version: u32) -> Result<()> {
+        for migration in migrations::MIGRATIONS.iter().filter(|m| m.from >= version) {
+            info!(
+                from = migration.from,
+                to = migration.from + 1,
+                "Schema migration: {}",
+                migration.description
+            );
+            match migration.from {
+                1 => {
+                    self.migrate_legacy_banks()?;
+                }
+                3 => self.add_missing_columns(&[("codec", "TEXT NOT NULL DEFAULT 'json'")])?,
+                4 => self.add_missing_columns(&[
+                    ("embedding", "BLOB"),
+                    ("embedding_model", "TEXT"),
+                    ("embedding_dims", "INTEGER"),
+                ])?,
+                _ => {}
+            }
+        }
+        set_schema_version(&self.conn, CURRENT_SCHEMA_VERSION)
+    }
+
+    /// Add `columns` (name, declaration) to per-type tables created by an
+    /// older version.
+    fn add_missing_columns(&self, columns: &[(&str, &str)]) -> Result<()> {
+        for (_, table) in MEMORY_TABLES {
+            for (name, decl) in columns {
+                let exists = self
+                    .conn
+                    .prepare(&format!(
+                        "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+                    ))?
+                    .exists(params![name])?;
+                if !exists {
+                    self.conn
+                        .execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {name} {decl}"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------
+    // Legacy migration
+    // ------------------------------------------------------------------
+
+    /// Move banks stored in the old whole-bank `memory_banks` table into the
+    /// normalized tables.
+    ///
+    /// Rows that fail to decode are left in place (and logged) so no data is
+    /// lost; the legacy table is dropped once it is empty.  Returns the
+    /// number of banks migrated.
+    fn migrate_legacy_banks(&self) -> Result<usize> {
+        if !table_exists(&self.conn, "memory_banks")? {
+            return Ok(0);
+        }
+
+        let legacy: Vec<(String, Vec<u8>)> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT entity_id, data FROM memory_banks")?;
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<std::result::Result<_, _>>()?
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut migrated = 0;
+        for (id_str, data) in legacy {
+            let Ok(uuid) = uuid::Uuid::parse_str(&id_str) else {
+                warn!(id = %id_str, "Legacy bank has invalid UUID — left in place");
+                continue;
+            };
+            let bank: MemoryBank = match serde_json::from_slice(&data) {
+                Ok(bank) => bank,
+                Err(e) => {
+                    warn!(id = %id_str, error = %e, "Legacy bank failed to decode — left in place");
+                    continue;
+                }
+            };
+            self.write_bank(&tx, &EntityId(uuid), &bank)?;
+            tx.execute(
+                "DELETE FROM memory_banks WHERE entity_id = ?1",
+                params![id_str],
+            )?;
+            migrated += 1;
+        }
+
+        let remaining: i64 =
+            tx.query_row("SELECT COUNT(*) FROM memory_banks", [], |row| row.get(0))?;
+        if remaining == 0 {
+            tx.execute_batch("DROP TABLE memory_banks;")?;
+        }
+        tx.commit()?;
+
+        info!(migrated, remaining, "Migrated legacy memory banks");
+        Ok(migrated)
+    }
+
+    // ------------------------------------------------------------------
+    // Backup
+    // ------------------------------------------------------------------
+
+    /// Create a backup of the database to `dest_path` using `SQLite`'s
+    /// online-backup API.
+    ///
+    /// This is safe to call while the database is being read/written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures, or
+    /// [`MemzError::Io`] if the destination is not writable.
+    pub fn backup<P: AsRef<Path>>(&self, dest_path: P) -> Result<()> {
+        let start = Instant::now();
+        let mut dest = Connection::open(dest_path.as_ref())?;
+        let backup = rusqlite::backup::Backup::new(&self.conn, &mut dest)?;
+
+        // Step through 256 pages at a time, sleeping 50ms between steps.
+        backup.run_to_completion(256, std::time::Duration::from_millis(50), None)?;
+
+        info!(
+            dest = %dest_path.as_ref().display(),
+            elapsed_ms = start.elapsed().as_millis(),
+            "Database backup completed"
+        );
+        Ok(())
+    }
+
+    /// Create a numbered backup alongside the database file, rotating old
+    /// backups so that at most `config.backup_count` are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] or [`MemzError::Io`] on failure.
+    pub fn create_rotating_backup(&self) -> Result<()> {
+        if self.db_path.as_os_str() == ":memory:" {
+            return Ok(());
+        }
+
+        let max = self.config.backup_count;
+        if max == 0 {
+            return Ok(());
+        }
+
+        // Rotate existing backups (highest first so we don't overwrite).
+        for i in (1..max).rev() {
+            let src = self.backup_path(i);
+            let dst = self.backup_path(i + 1);
+            if src.exists() {
+                std::fs::rename(&src, &dst)?;
+            }
+        }
+
+        // Remove the oldest if it now exceeds the limit.
+        let oldest = self.backup_path(max + 1);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+
+        // Create the fresh backup as backup.1
+        let dest = self.backup_path(1);
+        self.backup(&dest)?;
+
+        info!(max_backups = max, "Rotating backup created");
+
+        Ok(())
+    }
+
+    /// Path to a numbered backup file (e.g. `world_save.db.bak.1`).
+    fn backup_path(&self, n: u32) -> PathBuf {
+        let mut p = self.db_path.clone();
+        let ext = format!(
+            "{}.bak.{n}",
+            p.extension()
+                .map_or(String::new(), |e| e.to_string_lossy().into_owned())
+        );
+        p.set_extension(ext);
+        p
+    }
+
+    // ------------------------------------------------------------------
+    // Utility
+    // ------------------------------------------------------------------
+
+    /// Return the path to the database file (or `:memory:` for in-memory DBs).
+    #[must_use]
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    /// Codec new rows are written with.
+    #[must_use]
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Total size of all stored memory payloads, in bytes (excluding
+    /// `SQLite` page and index overhead).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn payload_bytes(&self) -> Result<u64> {
+        let mut total = 0;
+        for (_, table) in MEMORY_TABLES {
+            let bytes: i64 = self.conn.query_row(
+                &format!("SELECT COALESCE(SUM(LENGTH(data)), 0) FROM {table}"),
+                [],
+                |row| row.get(0),
+            )?;
+            total += bytes as u64;
+        }
+        Ok(total)
+    }
+
+    /// Run an integrity check on the database.
+    ///
+    /// Returns `Ok(true)` if the database passes the check, `Ok(false)` if
+    /// corruption is detected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] if the integrity check query itself fails.
+    pub fn integrity_check(&self) -> Result<bool> {
+        let result: String = self
+            .conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+        Ok(result == "ok")
+    }
+
+    /// Reclaim unused space by running `VACUUM`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] on `SQLite` failures.
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn.execute_batch("VACUUM;")?;
+        Ok(())
+    }
+}
+
+/// Extension trait that adds an `.optional()` combinator to `rusqlite::Result`.
+///
+/// Converts `Err(QueryReturnedNoRows)` into `Ok(None)`.
+trait OptionalExt<T> {
+    /// Convert `QueryReturnedNoRows` into `Ok(None)`.
+    fn optional(self) -> std::result::Result<Option<T>, rusqlite::Error>;
+}
+
+impl<T> OptionalExt<T> for std::result::Result<T, rusqlite::Error> {
+    fn optional(self) -> std::result::Result<Option<T>, rusqlite::Error> {
+        match self {
+            Ok(val) => Ok(Some(val)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::episodic::EpisodicMemory;
+    use crate::memory::social::SocialMemory;
+    use crate::types::{Embedding, EntityId, GameTimestamp, Location, MemoryId};
+    use chrono::Utc;
+
+    fn test_config() -> PersistenceConfig {
+        PersistenceConfig {
+            checksum_enabled: true,
+            ..PersistenceConfig::default()
+        }
+    }
+
+    fn sample_bank() -> MemoryBank {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(EpisodicMemory {
+            id: MemoryId::new(),
+            event: "Met a wandering bard at the tavern".to_string(),
+            participants: vec![EntityId::new()],
+            location: Location {
+                x: 100.0,
+                y: 50.0,
+                z: 0.0,
+            },
+            timestamp: GameTimestamp {
+                tick: 1000,
+                real_time: Utc::now(),
+            },
+            emotional_valence: 0.6,
+            importance: 0.7,
+            decay_rate: 0.02,
+            strength: 1.0,
+            access_count: 0,
+            last_accessed: GameTimestamp {
+                tick: 1000,
+                real_time: Utc::now(),
+            },
+            is_first_meeting: true,
+            embedding: None,
+        });
+        bank.social.push(SocialMemory {
+            id: MemoryId::new(),
+            about: EntityId::new(),
+            source: EntityId::new(),
+            claim: "The blacksmith is secretly a mage".to_string(),
+            believed: true,
+            disbelief_reason: None,
+            trust_in_source: 0.8,
+            propagation_depth: 1,
+            received_at: GameTimestamp {
+                tick: 1001,
+                real_time: Utc::now(),
+            },
+            sentiment: 0.3,
+        });
+        bank
+    }
+
+    #[test]
+    fn round_trip_save_load() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+
+        engine.save_bank(&entity, &mut bank).expect("save");
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+
+        assert_eq!(loaded.episodic.len(), 1);
+        assert_eq!(loaded.social.len(), 1);
+        assert_eq!(loaded.episodic[0].event, bank.episodic[0].event);
+        assert_eq!(loaded.social[0].claim, bank.social[0].claim);
+    }
+
+    #[test]
+    fn load_nonexistent_returns_none() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let result = engine.load_bank(&entity).expect("load");
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn upsert_overwrites() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+
+        let mut bank1 = sample_bank();
+        engine.save_bank(&entity, &mut bank1).expect("save1");
+
+        let mut bank2 = sample_bank();
+        bank2.episodic.push(bank2.episodic[0].clone());
+        engine.save_bank(&entity, &mut bank2).expect("save2");
+
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert_eq!(loaded.episodic.len(), 2, "Should reflect the second save");
+    }
+
+    #[test]
+    fn delete_bank_works() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+
+        engine.save_bank(&entity, &mut bank).expect("save");
+        assert!(engine.delete_bank(&entity).expect("delete"));
+        assert!(!engine.delete_bank(&entity).expect("delete again"));
+        assert!(engine.load_bank(&entity).expect("load").is_none());
+    }
+
+    #[test]
+    fn list_entities_and_count() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+
+        let e1 = EntityId::new();
+        let e2 = EntityId::new();
+        let e3 = EntityId::new();
+        let mut bank = MemoryBank::new();
+
+        engine.save_bank(&e1, &mut bank).expect("save");
+        engine.save_bank(&e2, &mut bank).expect("save");
+        engine.save_bank(&e3, &mut bank).expect("save");
+
+        let entities = engine.list_entities().expect("list");
+        assert_eq!(entities.len(), 3);
+        assert_eq!(engine.entity_count().expect("count"), 3);
+    }
+
+    #[test]
+    fn integrity_check_passes() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        assert!(engine.integrity_check().expect("check"));
+    }
+
+    #[test]
+    fn checksum_detection() {
+        // Save with checksums, then manually corrupt and reload to verify
+        // the warning path. We can't easily assert on tracing output, so we
+        // just ensure the load still succeeds (warnings are logged).
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+        engine.save_bank(&entity, &mut bank).expect("save");
+
+        // Manually overwrite the checksum with a wrong value.
+        let id_str = entity.0.to_string();
+        engine
+            .conn
+            .execute(
+                "UPDATE episodic_memories SET checksum = 'deadbeef' WHERE owner = ?1",
+                params![id_str],
+            )
+            .expect("corrupt checksum");
+
+        // Load should still work but would have logged a warning.
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert_eq!(loaded.episodic.len(), 1);
+    }
+
+    #[test]
+    fn file_based_open_and_backup() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("test_memz.db");
+        let config = test_config();
+
+        let engine = PersistenceEngine::open(&db_path, &config).expect("open");
+        let entity = EntityId::new();
+        engine.save_bank(&entity, &mut sample_bank()).expect("save");
+
+        // Backup to a second file.
+        let backup_path = dir.path().join("test_memz_backup.db");
+        engine.backup(&backup_path).expect("backup");
+
+        // Open the backup and verify data.
+        let backup_engine = PersistenceEngine::open(&backup_path, &config).expect("open backup");
+        let loaded = backup_engine
+            .load_bank(&entity)
+            .expect("load from backup")
+            .expect("Some");
+        assert_eq!(loaded.episodic.len(), 1);
+    }
+
+    #[test]
+    fn rotating_backup() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("world.db");
+        let mut config = test_config();
+        config.backup_count = 2;
+
+        let engine = PersistenceEngine::open(&db_path, &config).expect("open");
+        engine
+            .save_bank(&EntityId::new(), &mut sample_bank())
+            .expect("save");
+
+        // Create 3 backups, should keep at most 2.
+        engine.create_rotating_backup().expect("backup 1");
+        engine.create_rotating_backup().expect("backup 2");
+        engine.create_rotating_backup().expect("backup 3");
+
+        assert!(dir.path().join("world.db.bak.1").exists());
+        assert!(dir.path().join("world.db.bak.2").exists());
+        // The 3rd oldest should have been removed.
+        assert!(!dir.path().join("world.db.bak.3").exists());
+    }
+
+    #[test]
+    fn unchanged_bank_writes_nothing() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+
+        let first = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(first.upserted, 2);
+
+        let second = engine.save_bank(&entity, &mut bank).expect("save again");
+        assert_eq!(
+            second,
+            SaveReport {
+                upserted: 0,
+                deleted: 0,
+                unchanged: 2
+            }
+        );
+
+        // A freshly loaded bank is clean too.
+        let mut loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        let third = engine.save_bank(&entity, &mut loaded).expect("save loaded");
+        assert_eq!(third.upserted, 0);
+    }
+
+    #[test]
+    fn only_changed_rows_are_upserted() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+        engine.save_bank(&entity, &mut bank).expect("save");
+
+        let mut extra = bank.episodic[0].clone();
+        extra.id = MemoryId::new();
+        extra.event = "Saw the bard leave at dawn".to_string();
+        bank.episodic.push(extra);
+        bank.social[0].believed = false;
+
+        let report = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(
+            report,
+            SaveReport {
+                upserted: 2,
+                deleted: 0,
+                unchanged: 1
+            }
+        );
+
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert_eq!(loaded.episodic[1].event, "Saw the bard leave at dawn");
+        assert!(!loaded.social[0].believed);
+    }
+
+    #[test]
+    fn unchanged_types_are_not_encoded() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+        engine.save_bank(&entity, &mut bank).expect("save");
+        let types = bank.persisted.types.clone();
+        assert_eq!(types.len(), 7);
+
+        let report = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(
+            report,
+            SaveReport {
+                upserted: 0,
+                deleted: 0,
+                unchanged: bank.total_count()
+            }
+        );
+        assert_eq!(bank.persisted.types, types);
+
+        // Only the type that changed gets a new hash.
+        bank.episodic[0].event = "Met the bard again".to_string();
+        let report = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(report.upserted, 1);
+        let changed: Vec<MemoryType> = MEMORY_TABLES
+            .iter()
+            .map(|&(memory_type, _)| memory_type)
+            .filter(|memory_type| bank.persisted.types[memory_type] != types[memory_type])
+            .collect();
+        assert_eq!(changed, [MemoryType::Episodic]);
+    }
+
+    #[test]
+    fn removed_memories_are_deleted() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+        engine.save_bank(&entity, &mut bank).expect("save");
+
+        bank.social.clear();
+        let report = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(report.deleted, 1);
+        assert_eq!(engine.memory_count(MemoryType::Social).expect("count"), 0);
+        assert_eq!(engine.memory_count(MemoryType::Episodic).expect("count"), 1);
+    }
+
+    #[test]
+    fn evicting_the_front_rewrites_no_remaining_rows() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        let mut bank = sample_bank();
+        for i in 0..4 {
+            let mut memory = bank.episodic[0].clone();
+            memory.id = MemoryId::new();
+            memory.event = format!("Event {i}");
+            bank.episodic.push(memory);
+        }
+        engine.save_bank(&entity, &mut bank).expect("save");
+
+        bank.episodic.remove(0);
+        let report = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(report, SaveReport { upserted: 0, deleted: 1, unchanged: 5 });
+
+        // New rows at either end still load in bank order.
+        let mut first = bank.episodic[0].clone();
+        first.id = MemoryId::new();
+        first.event = "Front".to_string();
+        bank.episodic.insert(0, first);
+        let mut last = bank.episodic[0].clone();
+        last.id = MemoryId::new();
+        last.event = "Back".to_string();
+        bank.episodic.push(last);
+        let report = engine.save_bank(&entity, &mut bank).expect("save");
+        assert_eq!(report.upserted, 2);
+
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        let events: Vec<&str> = loaded.episodic.iter().map(|m| m.event.as_str()).collect();
+        assert_eq!(events, ["Front", "Event 0", "Event 1", "Event 2", "Event 3", "Back"]);
+    }
+
+    #[test]
+    fn untracked_bank_replaces_existing_rows() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let entity = EntityId::new();
+        engine.save_bank(&entity, &mut sample_bank()).expect("save");
+
+        // A different bank object with no snapshot for this entity.
+        let mut replacement = MemoryBank::new();
+        engine.save_bank(&entity, &mut replacement).expect("save");
+
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert_eq!(loaded.total_count(), 0);
+    }
+
+    #[test]
+    fn memories_are_queryable_across_entities() {
+        let engine = PersistenceEngine::open_in_memory(&test_config()).expect("open");
+        let (a, b) = (EntityId::new(), EntityId::new());
+        let mut bank_a = sample_bank();
+        let mut bank_b = MemoryBank::new();
+        bank_b.social.push(bank_a.social[0].clone());
+
+        engine.save_bank(&a, &mut bank_a).expect("save a");
+        engine.save_bank(&b, &mut bank_b).expect("save b");
+
+        assert_eq!(engine.memory_count(MemoryType::Social).expect("count"), 2);
+        let owners = engine.owners_of(&bank_a.social[0].id).expect("owners");
+        assert_eq!(owners.len(), 2);
+        assert!(owners.contains(&(a, MemoryType::Social)));
+        assert!(owners.contains(&(b, MemoryType::Social)));
+    }
+
+    #[test]
+    fn legacy_memory_banks_are_migrated() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let db_path = dir.path().join("legacy.db");
+        let entity = EntityId::new();
+        let bank = sample_bank();
+
+        // Write a database in the old whole-bank layout.
+        {
+            let conn = Connection::open(&db_path).expect("open raw");
+            conn.execute_batch(
+                "CREATE TABLE memory_banks (
+                    entity_id  TEXT PRIMARY KEY,
+                    data       BLOB NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    checksum   TEXT
+                );",
+            )
+            .expect("legacy schema");
+            let json = serde_json::to_vec(&bank).expect("json");
+            conn.execute(
+                "INSERT INTO memory_banks VALUES (?1, ?2, ?3, NULL)",
+                params![entity.0.to_string(), json, Utc::now().to_rfc3339()],
+            )
+            .expect("legacy row");
+        }
+
+        let engine = PersistenceEngine::open(&db_path, &test_config()).expect("open");
+        let loaded = engine.load_bank(&entity).expect("load").expect("migrated");
+        assert_eq!(loaded.episodic[0].event, bank.episodic[0].event);
+        assert_eq!(loaded.social[0].claim, bank.social[0].claim);
+
+        let legacy_tables: i64 = engine
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'memory_banks'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("query");
+        assert_eq!(legacy_tables, 0, "legacy table should be dropped");
+    }
+
+    fn codec_config(codec: &str) -> PersistenceConfig {
+        PersistenceConfig {
+            codec: codec.to_string(),
+            ..test_config()
+        }
+    }
+
+    #[test]
+    fn every_codec_round_trips() {
+        for codec in ["json", "msgpack", "bincode"] {
+            let engine = PersistenceEngine::open_in_memory(&codec_config(codec)).expect("open");
+            let entity = EntityId::new();
+            let mut bank = sample_bank();
+
+            engine.save_bank(&entity, &mut bank).expect("save");
+            let mut loaded = engine.load_bank(&entity).expect("load").expect("Some");
+            assert_eq!(loaded.episodic[0].event, bank.episodic[0].event, "{codec}");
+            assert_eq!(loaded.social[0].claim, bank.social[0].claim, "{codec}");
+
+            let report = engine.save_bank(&entity, &mut loaded).expect("save loaded");
+            assert_eq!(report.upserted, 0, "{codec}");
+        }
+    }
+
+    #[test]
+    fn mixed_codec_database_loads() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("mixed.db");
+        let (json_npc, bincode_npc) = (EntityId::new(), EntityId::new());
+
+        let engine = PersistenceEngine::open(&path, &codec_config("json")).expect("open");
+        engine.save_bank(&json_npc, &mut sample_bank()).expect("save json");
+        drop(engine);
+
+        let engine = PersistenceEngine::open(&path, &codec_config("bincode")).expect("reopen");
+        engine.save_bank(&bincode_npc, &mut sample_bank()).expect("save bincode");
+
+        let codecs: Vec<String> = engine
+            .conn
+            .prepare("SELECT DISTINCT codec FROM episodic_memories ORDER BY codec")
+            .expect("prepare")
+            .query_map([], |row| row.get(0))
+            .expect("query")
+            .collect::<std::result::Result<_, _>>()
+            .expect("rows");
+        assert_eq!(codecs, ["bincode", "json"]);
+
+        // JSON rows still load, and are converted on the next save.
+        let mut old = engine.load_bank(&json_npc).expect("load").expect("Some");
+        assert_eq!(old.episodic.len(), 1);
+        let report = engine.save_bank(&json_npc, &mut old).expect("convert");
+        assert_eq!(report.upserted, 2);
+        assert!(engine.load_bank(&bincode_npc).expect("load").is_some());
+    }
+
+    #[test]
+    fn unknown_codec_is_a_config_error() {
+        let err = PersistenceEngine::open_in_memory(&codec_config("yaml")).expect_err("bad codec");
+        assert!(matches!(err, crate::error::MemzError::Config(_)));
+    }
+
+    fn embedded_bank() -> MemoryBank {
+        let mut bank = sample_bank();
+        bank.episodic[0].embedding = Some(Embedding(vec![0.5, -0.25, 0.125, 1.0]));
+        bank
+    }
+
+    #[test]
+    fn embeddings_survive_reload() {
+        for format in ["f16", "i8"] {
+            let config = PersistenceConfig {
+                embedding_format: format.to_string(),
+                ..test_config()
+            };
+            let engine = PersistenceEngine::open_in_memory(&config)
+                .expect("open")
+                .with_embedding_model("test-model", 4);
+            let entity = EntityId::new();
+            let mut bank = embedded_bank();
+            engine.save_bank(&entity, &mut bank).expect("save");
+
+            let mut loaded = engine.load_bank(&entity).expect("load").expect("Some");
+            let original = bank.episodic[0].embedding.as_ref().expect("embedding");
+            let restored = loaded.episodic[0].embedding.as_ref().expect("restored");
+            assert!(original.cosine_similarity(restored) > 0.99, "{format}");
+
+            let report = engine.save_bank(&entity, &mut loaded).expect("save loaded");
+            assert_eq!(report.upserted, 0, "{format}");
+        }
+    }
+
+    #[test]
+    fn embeddings_from_another_model_are_dropped() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("models.db");
+        let entity = EntityId::new();
+
+        let engine = PersistenceEngine::open(&path, &test_config())
+            .expect("open")
+            .with_embedding_model("old-model", 4);
+        engine.save_bank(&entity, &mut embedded_bank()).expect("save");
+        drop(engine);
+
+        let engine = PersistenceEngine::open(&path, &test_config())
+            .expect("reopen")
+            .with_embedding_model("new-model", 4);
+        let mut loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert!(loaded.episodic[0].embedding.is_none());
+
+        // The stale vector is cleared by the next save.
+        let report = engine.save_bank(&entity, &mut loaded).expect("save");
+        assert_eq!(report.upserted, 1);
+        let stale: Option<Vec<u8>> = engine
+            .conn
+            .query_row("SELECT embedding FROM episodic_memories", [], |row| row.get(0))
+            .expect("row");
+        assert!(stale.is_none());
+    }
+
+    #[test]
+    fn engine_without_model_leaves_embeddings_alone() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("no_model.db");
+        let entity = EntityId::new();
+
+        let engine = PersistenceEngine::open(&path, &test_config())
+            .expect("open")
+            .with_embedding_model("test-model", 4);
+        engine.save_bank(&entity, &mut embedded_bank()).expect("save");
+        drop(engine);
+
+        let engine = PersistenceEngine::open(&path, &test_config()).expect("reopen");
+        let mut loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert!(loaded.episodic[0].embedding.is_none());
+        loaded.episodic[0].strength = 0.5;
+        engine.save_bank(&entity, &mut loaded).expect("save");
+        drop(engine);
+
+        let engine = PersistenceEngine::open(&path, &test_config())
+            .expect("reopen")
+            .with_embedding_model("test-model", 4);
+        let loaded = engine.load_bank(&entity).expect("load").expect("Some");
+        assert!((loaded.episodic[0].strength - 0.5).abs() < f32::EPSILON);
+        assert!(loaded.episodic[0].embedding.is_some());
+    }
+
+    #[test]
+    fn crc32_basic() {
+        // Known test vector: CRC-32 of "123456789" = 0xCBF43926
+        let crc = crc32_compute(b"123456789");
+        assert_eq!(crc, 0xCBF4_3926);
+    }
+}
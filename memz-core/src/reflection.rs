@@ -9,13 +9,28 @@
 //!   - Produces `ReflectiveMemory` outputs with beliefs, questions, mood shifts
 //!   - Falls back to rule-based summarisation if LLM is unavailable
 //!
+//! The LLM path: [`ReflectionInput::from_bank`] selects the memories,
+//! [`ReflectionInput::prompt_vars`] fills the `reflection` prompt, and
+//! [`reflect_from_llm`] turns the parsed answer into a `ReflectiveMemory`.
+//! The host runs the call in between; on timeout or a bad answer it calls
+//! [`reflect_rule_based`] with the same input instead.
+//!
 //! Grounded in Flavell's metacognition theory (1979).
 
+use crate::determinism::IdGenerator;
 use crate::error::MemzError;
+use crate::memory::MemoryBank;
 use crate::memory::episodic::EpisodicMemory;
 use crate::memory::reflective::ReflectiveMemory;
 use crate::memory::semantic::SemanticMemory;
-use crate::types::{GameTimestamp, MemoryId};
+use crate::time::TimeModel;
+use crate::types::{GameTimestamp, MemoryId, PADState};
+
+/// Confidence of an LLM-generated reflection (rule-based ones get 0.5).
+const LLM_REFLECTION_CONFIDENCE: f32 = 0.7;
+
+/// Most beliefs and most questions kept from one LLM reflection.
+pub const MAX_REFLECTION_ITEMS: usize = 5;
 
 /// Configuration for the reflection engine.
 #[derive(Debug, Clone)]
@@ -58,6 +73,84 @@ pub struct ReflectionInput {
     pub personality_summary: String,
 }
 
+impl ReflectionInput {
+    /// Gather the input for reflecting on `bank`: the most recent episodic
+    /// memories at or above the importance threshold and the most
+    /// confident semantic knowledge, each capped per `config`.
+    #[must_use]
+    pub fn from_bank(
+        bank: &MemoryBank,
+        npc_name: impl Into<String>,
+        npc_role: impl Into<String>,
+        personality_summary: impl Into<String>,
+        current_time: GameTimestamp,
+        config: &ReflectionConfig,
+    ) -> Self {
+        let mut recent_episodic: Vec<EpisodicMemory> = bank
+            .episodic
+            .iter()
+            .filter(|m| m.importance >= config.importance_threshold)
+            .cloned()
+            .collect();
+        recent_episodic.sort_by_key(|m| std::cmp::Reverse(m.timestamp.tick));
+        recent_episodic.truncate(config.max_input_memories);
+
+        let mut existing_semantic = bank.semantic.clone();
+        existing_semantic.sort_by(|a, b| {
+            b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        existing_semantic.truncate(config.max_context_memories);
+
+        Self {
+            npc_name: npc_name.into(),
+            npc_role: npc_role.into(),
+            recent_episodic,
+            existing_semantic,
+            current_time,
+            personality_summary: personality_summary.into(),
+        }
+    }
+
+    /// Variables for the `reflection` prompt.
+    #[must_use]
+    pub fn prompt_vars(&self, time: &TimeModel) -> Vec<(&'static str, String)> {
+        let episodic = self
+            .recent_episodic
+            .iter()
+            .map(|m| {
+                let hours_ago = time.hours_between(&m.timestamp, &self.current_time);
+                format!("- {hours_ago:.0} hours ago: {} [valence: {:.1}]", m.event, m.emotional_valence)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let semantic = if self.existing_semantic.is_empty() {
+            "- (nothing yet)".to_string()
+        } else {
+            self.existing_semantic
+                .iter()
+                .map(|m| format!("- {} (confidence: {:.1})", m.fact, m.confidence))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let window_hours = self
+            .recent_episodic
+            .iter()
+            .map(|m| time.hours_between(&m.timestamp, &self.current_time))
+            .fold(0.0_f64, f64::max)
+            .ceil()
+            .max(1.0);
+
+        vec![
+            ("npc_name", self.npc_name.clone()),
+            ("npc_profession", self.npc_role.clone()),
+            ("time_window", format!("{window_hours:.0} game-hours")),
+            ("recent_episodic_formatted", episodic),
+            ("semantic_formatted", semantic),
+            ("personality_summary", self.personality_summary.clone()),
+        ]
+    }
+}
+
 /// Output of a reflection — either LLM-generated or rule-based fallback.
 #[derive(Debug)]
 pub struct ReflectionOutput {
@@ -71,7 +164,10 @@ pub struct ReflectionOutput {
 ///
 /// This produces a simpler but always-available reflection by
 /// identifying patterns in recent episodic memories.
-pub fn reflect_rule_based(input: &ReflectionInput) -> Result<ReflectionOutput, MemzError> {
+pub fn reflect_rule_based(
+    input: &ReflectionInput,
+    ids: &IdGenerator,
+) -> Result<ReflectionOutput, MemzError> {
     // Identify the most emotionally significant recent memory.
     let most_significant = input
         .recent_episodic
@@ -107,8 +203,8 @@ pub fn reflect_rule_based(input: &ReflectionInput) -> Result<ReflectionOutput, M
 
     let basis: Vec<MemoryId> = input.recent_episodic.iter().map(|m| m.id).collect();
 
-    let mut memory =
-        ReflectiveMemory::new(reflection_text, basis, 0.5, input.current_time);
+    let mut memory = ReflectiveMemory::new(reflection_text, basis, 0.5, input.current_time)
+        .with_id(ids.memory_id());
 
     // Generate simple questions based on participants in recent memories.
     let questions: Vec<String> = input
@@ -129,6 +225,52 @@ pub fn reflect_rule_based(input: &ReflectionInput) -> Result<ReflectionOutput, M
     })
 }
 
+/// Build a reflection from an LLM's answer to the `reflection` prompt.
+///
+/// Beliefs and questions are trimmed, emptied ones dropped and each list
+/// capped at [`MAX_REFLECTION_ITEMS`]; the mood shift is clamped to the
+/// PAD range.
+///
+/// # Errors
+///
+/// Returns [`MemzError::ContentRejected`] if the reflection text is empty,
+/// so the caller can fall back to [`reflect_rule_based`].
+pub fn reflect_from_llm(
+    input: &ReflectionInput,
+    reflection: &str,
+    new_beliefs: Vec<String>,
+    questions: Vec<String>,
+    mood_shift: PADState,
+    ids: &IdGenerator,
+) -> Result<ReflectionOutput, MemzError> {
+    let reflection = reflection.trim();
+    if reflection.is_empty() {
+        return Err(MemzError::ContentRejected {
+            reason: "LLM returned an empty reflection".to_string(),
+        });
+    }
+    let tidy = |items: Vec<String>| -> Vec<String> {
+        items
+            .into_iter()
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .take(MAX_REFLECTION_ITEMS)
+            .collect()
+    };
+
+    let basis: Vec<MemoryId> = input.recent_episodic.iter().map(|m| m.id).collect();
+    let memory = ReflectiveMemory::new(reflection, basis, LLM_REFLECTION_CONFIDENCE, input.current_time)
+        .with_id(ids.memory_id())
+        .with_beliefs(tidy(new_beliefs))
+        .with_questions(tidy(questions))
+        .with_mood_shift(PADState::new(mood_shift.pleasure, mood_shift.arousal, mood_shift.dominance));
+
+    Ok(ReflectionOutput {
+        memory,
+        llm_generated: true,
+    })
+}
+
 /// Determine whether an NPC should reflect right now.
 ///
 /// Based on:
@@ -141,11 +283,10 @@ pub fn should_reflect(
     current_tick: u64,
     unprocessed_episodic_count: usize,
     recent_max_emotional_intensity: f32,
+    time: &TimeModel,
     config: &ReflectionConfig,
 ) -> bool {
-    // Convert interval from game-minutes to ticks.
-    // Assume 20 ticks/sec, 60 sec/min → 1200 ticks/game-minute.
-    let interval_ticks = (config.interval_minutes * 1200.0) as u64;
+    let interval_ticks = time.ticks_per_game_hours(f64::from(config.interval_minutes) / 60.0);
     let ticks_since_last = current_tick.saturating_sub(last_reflection_tick);
 
     // Normal interval-based trigger.
@@ -173,26 +314,103 @@ mod tests {
     #[test]
     fn should_reflect_after_interval() {
         let config = ReflectionConfig::default();
-        assert!(should_reflect(0, 10_000, 5, 0.3, &config));
+        assert!(should_reflect(0, 10_000, 5, 0.3, &TimeModel::default(), &config));
     }
 
     #[test]
     fn should_not_reflect_too_soon() {
         let config = ReflectionConfig::default();
-        assert!(!should_reflect(0, 100, 5, 0.3, &config));
+        assert!(!should_reflect(0, 100, 5, 0.3, &TimeModel::default(), &config));
     }
 
     #[test]
     fn emotional_events_trigger_early_reflection() {
         let config = ReflectionConfig::default();
         // Half the normal interval, but high emotional intensity.
-        let half_interval = (config.interval_minutes * 1200.0 / 2.0) as u64;
-        assert!(should_reflect(0, half_interval, 3, 0.9, &config));
+        let time = TimeModel::default();
+        let half_interval = time.ticks_per_game_hours(f64::from(config.interval_minutes) / 120.0);
+        assert!(should_reflect(0, half_interval, 3, 0.9, &time, &config));
     }
 
     #[test]
     fn overflow_triggers_reflection() {
         let config = ReflectionConfig::default();
-        assert!(should_reflect(0, 1, 15, 0.1, &config));
+        assert!(should_reflect(0, 1, 15, 0.1, &TimeModel::default(), &config));
+    }
+
+    fn bank_with_history() -> MemoryBank {
+        use crate::types::{EntityId, Location};
+
+        let mut bank = MemoryBank::new();
+        let player = EntityId::new();
+        for (i, (event, importance)) in [("sold bread", 0.2), ("was robbed", 0.9), ("a stranger paid double", 0.6)]
+            .into_iter()
+            .enumerate()
+        {
+            bank.episodic.push(EpisodicMemory::new(
+                event,
+                vec![player],
+                Location::default(),
+                GameTimestamp::now(i as u64 * 3_000),
+                if importance > 0.8 { -0.8 } else { 0.3 },
+                importance,
+            ));
+        }
+        bank.semantic.push(SemanticMemory::new("Bread sells best at dawn", 0.8, vec![], "world_knowledge", GameTimestamp::now(0)));
+        bank
+    }
+
+    #[test]
+    fn reflection_input_comes_from_the_bank() {
+        let bank = bank_with_history();
+        let config = ReflectionConfig::default();
+        let now = GameTimestamp::now(9_000);
+        let input = ReflectionInput::from_bank(&bank, "Greta", "baker", "warm, cautious", now, &config);
+
+        let events: Vec<&str> = input.recent_episodic.iter().map(|m| m.event.as_str()).collect();
+        assert_eq!(events, ["a stranger paid double", "was robbed"], "newest first, unimportant skipped");
+        assert_eq!(input.existing_semantic.len(), 1);
+
+        let vars = input.prompt_vars(&TimeModel::default());
+        let var = |key: &str| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.clone()).expect(key);
+        assert_eq!(var("npc_profession"), "baker");
+        assert_eq!(var("time_window"), "2 game-hours");
+        assert!(var("recent_episodic_formatted").contains("2 hours ago: was robbed"));
+        assert!(var("semantic_formatted").contains("Bread sells best at dawn"));
+    }
+
+    #[test]
+    fn llm_reflections_become_reflective_memories() {
+        let bank = bank_with_history();
+        let input = ReflectionInput::from_bank(
+            &bank,
+            "Greta",
+            "baker",
+            "warm",
+            GameTimestamp::now(9_000),
+            &ReflectionConfig::default(),
+        );
+        let ids = IdGenerator::seeded(9);
+
+        let output = reflect_from_llm(
+            &input,
+            "  Strangers can be generous or cruel.  ",
+            vec!["Not every traveller is a thief".into(), "   ".into()],
+            vec!["Who robbed me?".into()],
+            PADState { pleasure: -1.5, arousal: 0.4, dominance: 0.0 },
+            &ids,
+        )
+        .expect("valid reflection");
+        assert!(output.llm_generated);
+        let memory = output.memory;
+        assert_eq!(memory.reflection, "Strangers can be generous or cruel.");
+        assert_eq!(memory.new_beliefs, ["Not every traveller is a thief"]);
+        assert_eq!(memory.questions, ["Who robbed me?"]);
+        assert_eq!(memory.basis.len(), 2);
+        let mood = memory.mood_shift.expect("mood shift kept");
+        assert!((mood.pleasure + 1.0).abs() < f32::EPSILON, "clamped");
+
+        let empty = reflect_from_llm(&input, " ", vec![], vec![], PADState::NEUTRAL, &ids);
+        assert!(matches!(empty, Err(MemzError::ContentRejected { .. })));
     }
 }
@@ -9,6 +9,7 @@
 
 use crate::memory::MemoryBank;
 use crate::memory::episodic::EpisodicMemory;
+use crate::time::TimeModel;
 use crate::types::{EntityId, GameTimestamp};
 
 /// A memory selected for replay in dialogue.
@@ -38,6 +39,7 @@ pub fn select_replay(
     bank: &MemoryBank,
     target: EntityId,
     current_time: &GameTimestamp,
+    time: &TimeModel,
     min_strength: f32,
 ) -> Option<MemoryReplay> {
     let candidates: Vec<&EpisodicMemory> = bank
@@ -57,8 +59,8 @@ pub fn select_replay(
     let best = candidates
         .iter()
         .max_by(|a, b| {
-            let score_a = replay_score(a, current_time);
-            let score_b = replay_score(b, current_time);
+            let score_a = replay_score(a, current_time, time);
+            let score_b = replay_score(b, current_time, time);
             score_a
                 .partial_cmp(&score_b)
                 .unwrap_or(std::cmp::Ordering::Equal)
@@ -75,14 +77,14 @@ pub fn select_replay(
 }
 
 /// Compute replay priority score for a memory.
-fn replay_score(memory: &EpisodicMemory, current_time: &GameTimestamp) -> f32 {
+fn replay_score(memory: &EpisodicMemory, current_time: &GameTimestamp, time: &TimeModel) -> f32 {
     let emotional_weight = memory.emotional_valence.abs();
     let importance_weight = memory.importance;
     let strength_weight = memory.strength;
 
     // Novelty: prefer memories not recently accessed
-    let ticks_since_access = current_time.tick.saturating_sub(memory.last_accessed.tick);
-    let novelty = (ticks_since_access as f32 / 72_000.0).min(10.0) / 10.0; // 0–1 over 10 days
+    let days_since_access = current_time.days_since(&memory.last_accessed, time);
+    let novelty = days_since_access.min(10.0) / 10.0; // 0–1 over 10 days
 
     // First meetings get a bonus
     let first_meeting_bonus = if memory.is_first_meeting { 0.3 } else { 0.0 };
@@ -131,9 +133,10 @@ pub fn should_replay(
     last_replay_tick: u64,
     current_tick: u64,
     emotional_intensity: f32,
+    time: &TimeModel,
 ) -> bool {
     let ticks_since_last = current_tick.saturating_sub(last_replay_tick);
-    let min_interval_ticks = 36_000; // ~30 game-minutes
+    let min_interval_ticks = time.ticks_per_game_hours(0.5); // 30 game-minutes
 
     if ticks_since_last < min_interval_ticks {
         return false;
@@ -172,7 +175,7 @@ mod tests {
         bank.episodic.push(make_memory(target, "fought wolves together", 0.7, 0.8));
         bank.episodic.push(make_memory(other, "unrelated event", 0.5, 0.9));
 
-        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), 0.3);
+        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), &TimeModel::default(), 0.3);
         assert!(replay.is_some());
         let replay = replay.expect("should select");
         assert!(replay.memory.participants.contains(&target));
@@ -183,7 +186,7 @@ mod tests {
         let unknown = EntityId::new();
         let bank = MemoryBank::new();
 
-        let replay = select_replay(&bank, unknown, &GameTimestamp::now(72_000), 0.3);
+        let replay = select_replay(&bank, unknown, &GameTimestamp::now(72_000), &TimeModel::default(), 0.3);
         assert!(replay.is_none());
     }
 
@@ -194,7 +197,7 @@ mod tests {
 
         bank.episodic.push(make_memory(target, "old event", 0.5, 0.1)); // low strength
 
-        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), 0.3);
+        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), &TimeModel::default(), 0.3);
         assert!(replay.is_none());
     }
 
@@ -204,7 +207,7 @@ mod tests {
         let mut bank = MemoryBank::new();
         bank.episodic.push(make_memory(target, "saved my shop", 0.8, 0.9));
 
-        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), 0.3)
+        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), &TimeModel::default(), 0.3)
             .expect("should have replay");
         assert!(replay.dialogue_hint.contains("fondly"));
     }
@@ -215,14 +218,14 @@ mod tests {
         let mut bank = MemoryBank::new();
         bank.episodic.push(make_memory(target, "stole from me", -0.8, 0.9));
 
-        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), 0.3)
+        let replay = select_replay(&bank, target, &GameTimestamp::now(72_000), &TimeModel::default(), 0.3)
             .expect("should have replay");
         assert!(replay.dialogue_hint.contains("forgotten"));
     }
 
     #[test]
     fn should_replay_respects_interval() {
-        assert!(!should_replay(36_000, 36_100, 0.9)); // too soon
-        assert!(should_replay(0, 72_000, 0.9)); // enough time + high emotion
+        assert!(!should_replay(36_000, 36_100, 0.9, &TimeModel::default())); // too soon
+        assert!(should_replay(0, 72_000, 0.9, &TimeModel::default())); // enough time + high emotion
     }
 }
@@ -12,6 +12,7 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::time::TimeModel;
 use crate::types::{EntityId, GameTimestamp, SettlementId};
 
 /// A settlement's reputation board.
@@ -200,12 +201,14 @@ impl ReputationBoard {
     ///
     /// This allows redemption — a villain can eventually become neutral
     /// if they stop committing crimes.
-    pub fn decay_reputations(&mut self, decay_rate: f32, timestamp: GameTimestamp) {
+    pub fn decay_reputations(
+        &mut self,
+        decay_rate: f32,
+        timestamp: GameTimestamp,
+        time: &TimeModel,
+    ) {
         for entry in &mut self.entries {
-            let days_since_update = {
-                let delta = timestamp.tick.saturating_sub(entry.last_updated.tick);
-                delta as f32 / 72_000.0 // ticks per game-day
-            };
+            let days_since_update = timestamp.days_since(&entry.last_updated, time);
             let decay = (-decay_rate * days_since_update).exp();
             entry.score *= decay;
             entry.tier = ReputationTier::from_score(entry.score);
@@ -301,7 +304,7 @@ mod tests {
         board.report_sentiment(player, 0.9, GameTimestamp::now(0));
 
         // Simulate time passing (72000 ticks = 1 game-day)
-        board.decay_reputations(0.1, GameTimestamp::now(720_000)); // 10 days
+        board.decay_reputations(0.1, GameTimestamp::now(720_000), &TimeModel::default()); // 10 days
 
         let rep = board.get_reputation(player);
         // After decay, score should be lower
@@ -0,0 +1,183 @@
+//! Hybrid relevance — fusing vector and lexical signals (§12.4)
+//!
+//! Embedding similarity is good at paraphrase ("beasts" ≈ "wolves") but blind
+//! to rare names a small sentence model has never seen; BM25 is the reverse.
+//! `algorithm = "hybrid"` scores each candidate with both and fuses them into
+//! the single relevance factor of the five-factor score:
+//!
+//! - **RRF** (reciprocal-rank fusion, Cormack et al. 2009) — each source
+//!   ranks the candidates it matches and scores them `1 / (k + rank)`,
+//!   normalised per source so its rank 1 scores 1.0; a memory's relevance
+//!   is the mean over the sources it has a signal from.  Insensitive to the
+//!   two sources' very different score scales.
+//! - **Weighted** — `w · vector + (1 - w) · lexical` on the raw `[0, 1]`
+//!   scores.
+//!
+//! A memory with only one signal (no embedding, or no text) is scored by
+//! that signal alone; one with neither keeps the neutral 0.5.
+
+use crate::config::RetrievalConfig;
+use crate::error::{MemzError, Result};
+
+/// Relevance of memories with neither an embedding nor text.
+const NEUTRAL_RELEVANCE: f64 = 0.5;
+
+/// How vector and lexical relevance are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fusion {
+    /// Reciprocal-rank fusion with rank constant `k`.
+    Rrf {
+        /// Rank constant (60 in the original paper).
+        k: f64,
+    },
+    /// Weighted sum of the raw scores.
+    Weighted {
+        /// Share of the vector score, in `[0, 1]`.
+        vector_weight: f64,
+    },
+}
+
+impl Fusion {
+    /// Fusion selected by `hybrid_fusion`, `rrf_k` and `hybrid_vector_weight`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Config`] for an unknown `hybrid_fusion`.
+    pub fn from_config(config: &RetrievalConfig) -> Result<Self> {
+        match config.hybrid_fusion.as_str() {
+            "rrf" => Ok(Self::Rrf {
+                k: f64::from(config.rrf_k),
+            }),
+            "weighted" => Ok(Self::Weighted {
+                vector_weight: f64::from(config.hybrid_vector_weight.clamp(0.0, 1.0)),
+            }),
+            other => Err(MemzError::Config(format!(
+                "unknown hybrid fusion {other:?} (expected \"rrf\" or \"weighted\")"
+            ))),
+        }
+    }
+
+    /// Fuse per-candidate scores (`None` = signal unavailable) into one
+    /// relevance in `[0, 1]` per candidate.
+    #[must_use]
+    pub fn fuse(self, vector: &[Option<f64>], lexical: &[Option<f64>]) -> Vec<f64> {
+        debug_assert_eq!(vector.len(), lexical.len());
+        match self {
+            Self::Rrf { k } => {
+                let vector_rrf = reciprocal_ranks(vector, k);
+                let lexical_rrf = reciprocal_ranks(lexical, k);
+                // Per source, so a memory with one signal can reach 1.0.
+                let best = 1.0 / (k + 1.0);
+                (0..vector.len())
+                    .map(|i| {
+                        let signals =
+                            usize::from(vector[i].is_some()) + usize::from(lexical[i].is_some());
+                        if signals == 0 {
+                            NEUTRAL_RELEVANCE
+                        } else {
+                            (vector_rrf[i] + lexical_rrf[i]) / (best * signals as f64)
+                        }
+                    })
+                    .collect()
+            }
+            Self::Weighted { vector_weight } => vector
+                .iter()
+                .zip(lexical)
+                .map(|(v, l)| match (v, l) {
+                    (Some(v), Some(l)) => vector_weight * v + (1.0 - vector_weight) * l,
+                    (Some(only), None) | (None, Some(only)) => *only,
+                    (None, None) => NEUTRAL_RELEVANCE,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `1 / (k + rank)` of each candidate among those with a positive score
+/// (rank 1 = best); 0 for candidates the source did not match.
+fn reciprocal_ranks(scores: &[Option<f64>], k: f64) -> Vec<f64> {
+    let mut matched: Vec<(usize, f64)> = scores
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.filter(|s| *s > 0.0).map(|s| (i, s)))
+        .collect();
+    matched.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut rrf = vec![0.0; scores.len()];
+    for (rank, (i, _)) in matched.into_iter().enumerate() {
+        rrf[i] = 1.0 / (k + rank as f64 + 1.0);
+    }
+    rrf
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rrf_rewards_agreement() {
+        let fusion = Fusion::Rrf { k: 60.0 };
+        let vector = [Some(0.9), Some(0.8), Some(0.1)];
+        let lexical = [Some(0.7), Some(0.0), Some(0.9)];
+        let fused = fusion.fuse(&vector, &lexical);
+
+        // Top of both lists → 1/61 + 1/62 ≈ the maximum.
+        assert!(fused[0] > 0.99);
+        // Matched by one source only.
+        assert!(fused[1] < 0.51);
+        assert!(fused[0] > fused[2] && fused[2] > fused[1]);
+    }
+
+    #[test]
+    fn rrf_normalises_per_signal() {
+        let fusion = Fusion::Rrf { k: 60.0 };
+        // No embedding, but the best lexical match: above neutral.
+        let fused = fusion.fuse(&[None, None, Some(0.4)], &[Some(0.9), Some(0.2), Some(0.3)]);
+        assert!((fused[0] - 1.0).abs() < 1e-9);
+        assert!(fused[0] > NEUTRAL_RELEVANCE && fused[1] < fused[0]);
+        // Both signals available: only the full pair reaches 1.0.
+        assert!(fused[2] < 1.0);
+    }
+
+    #[test]
+    fn missing_signals() {
+        for fusion in [Fusion::Rrf { k: 60.0 }, Fusion::Weighted { vector_weight: 0.5 }] {
+            let fused = fusion.fuse(&[None, Some(0.8)], &[None, None]);
+            assert!((fused[0] - NEUTRAL_RELEVANCE).abs() < f64::EPSILON, "{fusion:?}");
+            assert!(fused[1] > 0.0, "{fusion:?}");
+        }
+    }
+
+    #[test]
+    fn weighted_mixes_raw_scores() {
+        let fusion = Fusion::Weighted { vector_weight: 0.25 };
+        let fused = fusion.fuse(&[Some(0.8)], &[Some(0.4)]);
+        assert!((fused[0] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn config_selects_fusion() {
+        let config = RetrievalConfig {
+            hybrid_fusion: "weighted".to_string(),
+            hybrid_vector_weight: 0.7,
+            ..RetrievalConfig::default()
+        };
+        assert!(matches!(
+            Fusion::from_config(&config),
+            Ok(Fusion::Weighted { vector_weight }) if (vector_weight - 0.7).abs() < 1e-6
+        ));
+        assert_eq!(
+            Fusion::from_config(&RetrievalConfig::default()).expect("default"),
+            Fusion::Rrf { k: 60.0 }
+        );
+        let bad = RetrievalConfig {
+            hybrid_fusion: "max".to_string(),
+            ..RetrievalConfig::default()
+        };
+        assert!(matches!(Fusion::from_config(&bad), Err(MemzError::Config(_))));
+    }
+}
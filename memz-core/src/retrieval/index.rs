@@ -0,0 +1,379 @@
+//! Per-bank retrieval index — HNSW candidate pre-filter (§12.4)
+//!
+//! A [`MemoryIndex`] shadows one [`MemoryBank`]: every memory with an
+//! embedding is held in an [`HnswIndex`], and every memory's type and
+//! position in the bank is remembered.  [`RetrievalEngine::retrieve_indexed`]
+//! uses it to score only the `hnsw_ef_search` nearest memories (plus the
+//! memories that have no embedding and so cannot be pre-filtered) instead of
+//! every memory in the bank.
+//!
+//! Memories are pushed straight into the bank's `Vec`s all over the crate
+//! and embeddings are computed lazily, so the index is kept current by
+//! [`MemoryIndex::sync`]: call it after inserting, embedding or evicting
+//! memories.  A sync only hashes ids and embeddings and applies the
+//! differences as deltas — [`MemoryIndex::insert`] and [`MemoryIndex::remove`], which
+//! callers that know what changed can use directly.  The HNSW graph links
+//! new embeddings in place and compacts after enough evictions.
+//!
+//! [`RetrievalEngine::retrieve_indexed`]: super::RetrievalEngine::retrieve_indexed
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use crate::config::RetrievalConfig;
+use crate::consolidation::MemoryType;
+use crate::hnsw::HnswIndex;
+use crate::memory::{MemoryBank, MemoryEntry};
+use crate::types::{Embedding, MemoryId};
+
+/// Where a memory lives in its bank, as of the last sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Slot {
+    memory_type: MemoryType,
+    position: usize,
+    /// [`fingerprint`] of the indexed embedding, if it has one.
+    embedding: Option<u64>,
+}
+
+impl Slot {
+    fn embedded(self) -> bool {
+        self.embedding.is_some()
+    }
+}
+
+/// Hash of an embedding's exact values, so a sync notices re-embeddings.
+fn fingerprint(embedding: &Embedding) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for value in &embedding.0 {
+        value.to_bits().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// What a [`MemoryIndex::sync`] changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexSync {
+    /// Embeddings added to the HNSW graph (new, newly embedded or
+    /// re-embedded memories).
+    pub inserted: usize,
+    /// Embeddings removed (evicted or re-embedded memories).
+    pub removed: usize,
+}
+
+/// HNSW index over one [`MemoryBank`]'s embeddings.
+pub struct MemoryIndex {
+    hnsw: HnswIndex,
+    /// Every memory seen by the last sync.
+    slots: HashMap<MemoryId, Slot>,
+    /// Memories without an embedding — always scored in full.
+    unembedded: Vec<MemoryId>,
+}
+
+impl MemoryIndex {
+    /// Create an empty index using the `hnsw_*` parameters of `config`.
+    #[must_use]
+    pub fn new(config: &RetrievalConfig) -> Self {
+        Self {
+            hnsw: HnswIndex::with_config(config),
+            slots: HashMap::new(),
+            unembedded: Vec::new(),
+        }
+    }
+
+    /// Create an index and sync it with `bank`.
+    #[must_use]
+    pub fn build(config: &RetrievalConfig, bank: &MemoryBank) -> Self {
+        let mut index = Self::new(config);
+        index.sync(bank);
+        index
+    }
+
+    /// Number of memories known to the index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Whether the index knows no memories.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Number of memories in the HNSW graph.
+    #[must_use]
+    pub fn embedded_count(&self) -> usize {
+        self.hnsw.len()
+    }
+
+    /// Record memory `id`, added to (or re-embedded in) the bank at
+    /// `position` in its `Vec`, replacing any previous entry.  A new
+    /// embedding is staged in the HNSW graph: searchable at once, linked by
+    /// the next [`sync`](Self::sync).
+    pub fn insert(
+        &mut self,
+        memory_type: MemoryType,
+        position: usize,
+        id: MemoryId,
+        embedding: Option<&Embedding>,
+    ) {
+        self.remove(id);
+        let slot = Slot {
+            memory_type,
+            position,
+            embedding: embedding.map(fingerprint),
+        };
+        match embedding {
+            Some(embedding) => self.hnsw.insert(id, embedding.clone()),
+            None => self.unembedded.push(id),
+        }
+        self.slots.insert(id, slot);
+    }
+
+    /// Record that memory `id` left the bank.
+    pub fn remove(&mut self, id: MemoryId) {
+        let Some(slot) = self.slots.remove(&id) else {
+            return;
+        };
+        if slot.embedded() {
+            self.hnsw.remove(id);
+        } else {
+            self.unembedded.retain(|other| *other != id);
+        }
+    }
+
+    /// Bring the index in line with `bank`: insert memories that are new or
+    /// whose embedding was added or changed, remove memories that were
+    /// evicted, and update where the rest now sit.  Only those deltas touch
+    /// the HNSW graph — new embeddings are linked in place and tombstones
+    /// are compacted once they pile up.
+    pub fn sync(&mut self, bank: &MemoryBank) -> IndexSync {
+        let mut report = IndexSync::default();
+        let mut live = HashSet::with_capacity(bank.total_count());
+
+        let mut visit = |memory_type, position, id, embedding: Option<&Embedding>| {
+            live.insert(id);
+            match self.slots.get_mut(&id) {
+                // Unchanged, though evictions may have shifted it.
+                Some(slot) if slot.embedding == embedding.map(fingerprint) => {
+                    slot.memory_type = memory_type;
+                    slot.position = position;
+                }
+                old => {
+                    report.removed += usize::from(old.is_some_and(|old| old.embedded()));
+                    report.inserted += usize::from(embedding.is_some());
+                    self.insert(memory_type, position, id, embedding);
+                }
+            }
+        };
+
+        for (i, m) in bank.episodic.iter().enumerate() {
+            visit(MemoryType::Episodic, i, m.id, m.embedding.as_ref());
+        }
+        for (i, m) in bank.semantic.iter().enumerate() {
+            visit(MemoryType::Semantic, i, m.id, m.embedding.as_ref());
+        }
+        for (i, m) in bank.injected.iter().enumerate() {
+            visit(MemoryType::Injected, i, m.id, m.embedding.as_ref());
+        }
+        for (i, m) in bank.emotional.iter().enumerate() {
+            visit(MemoryType::Emotional, i, m.id, None);
+        }
+        for (i, m) in bank.social.iter().enumerate() {
+            visit(MemoryType::Social, i, m.id, None);
+        }
+        for (i, m) in bank.reflective.iter().enumerate() {
+            visit(MemoryType::Reflective, i, m.id, None);
+        }
+        for (i, m) in bank.procedural.iter().enumerate() {
+            visit(MemoryType::Procedural, i, m.id, None);
+        }
+
+        // Every live memory now has a slot, so any extra slot was evicted.
+        if self.slots.len() > live.len() {
+            let evicted: Vec<(MemoryId, bool)> = self
+                .slots
+                .iter()
+                .filter(|(id, _)| !live.contains(id))
+                .map(|(id, slot)| (*id, slot.embedded()))
+                .collect();
+            for (id, embedded) in evicted {
+                self.remove(id);
+                report.removed += usize::from(embedded);
+            }
+        }
+
+        if self.hnsw.needs_compaction() {
+            self.hnsw.compact();
+        }
+        if self.hnsw.pending() > 0 {
+            self.hnsw.link_pending(usize::MAX);
+        }
+        report
+    }
+
+    /// Memories worth scoring for `query`: the `k` nearest embedded memories
+    /// plus every memory without an embedding.
+    ///
+    /// Memories added to the bank since the last [`sync`](Self::sync) are
+    /// not returned; evicted ones are skipped.
+    #[must_use]
+    pub fn candidates(&self, bank: &MemoryBank, query: &Embedding, k: usize) -> Vec<MemoryEntry> {
+        let nearest = self.hnsw.search(query, k);
+        let mut entries = Vec::with_capacity(nearest.len() + self.unembedded.len());
+        for hit in nearest {
+            if let Some(slot) = self.slots.get(&hit.memory_id)
+                && let Some(entry) = bank.entry_at(slot.memory_type, slot.position, hit.memory_id)
+            {
+                entries.push(entry);
+            }
+        }
+        for id in &self.unembedded {
+            if let Some(slot) = self.slots.get(id)
+                && let Some(entry) = bank.entry_at(slot.memory_type, slot.position, *id)
+            {
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::episodic::EpisodicMemory;
+    use crate::memory::social::SocialMemory;
+    use crate::types::{EntityId, GameTimestamp, Location};
+
+    fn episodic(x: f32, y: f32) -> EpisodicMemory {
+        let mut memory = EpisodicMemory::new(
+            "event",
+            vec![],
+            Location::default(),
+            GameTimestamp::now(0),
+            0.0,
+            0.5,
+        );
+        memory.embedding = Some(Embedding(vec![x, y]));
+        memory
+    }
+
+    fn entry_id(entry: &MemoryEntry) -> MemoryId {
+        match entry {
+            MemoryEntry::Episodic(m) => m.id,
+            MemoryEntry::Social(m) => m.id,
+            _ => unreachable!("test banks hold episodic and social memories"),
+        }
+    }
+
+    #[test]
+    fn sync_tracks_inserts_and_evictions() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(episodic(1.0, 0.0));
+        bank.episodic.push(episodic(0.0, 1.0));
+        let mut index = MemoryIndex::build(&RetrievalConfig::default(), &bank);
+        assert_eq!(index.embedded_count(), 2);
+
+        bank.episodic.remove(0);
+        bank.episodic.push(episodic(0.7, 0.7));
+        let report = index.sync(&bank);
+        assert_eq!(report, IndexSync { inserted: 1, removed: 1 });
+        assert_eq!(index.embedded_count(), 2);
+        assert_eq!(index.sync(&bank), IndexSync::default());
+    }
+
+    #[test]
+    fn deltas_apply_without_a_sync() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(episodic(0.0, 1.0));
+        let mut index = MemoryIndex::build(&RetrievalConfig::default(), &bank);
+
+        bank.episodic.push(episodic(1.0, 0.0));
+        let added = &bank.episodic[1];
+        index.insert(MemoryType::Episodic, 1, added.id, added.embedding.as_ref());
+        let candidates = index.candidates(&bank, &Embedding(vec![1.0, 0.0]), 1);
+        assert_eq!(entry_id(&candidates[0]), added.id);
+
+        index.remove(bank.episodic.remove(0).id);
+        assert_eq!(index.embedded_count(), 1);
+        // The sync finds no deltas left; it only links the staged insert.
+        assert_eq!(index.hnsw.pending(), 1);
+        assert_eq!(index.sync(&bank), IndexSync::default());
+        assert_eq!(index.hnsw.pending(), 0);
+    }
+
+    #[test]
+    fn embedding_a_memory_moves_it_into_the_graph() {
+        let mut bank = MemoryBank::new();
+        let mut memory = episodic(1.0, 0.0);
+        memory.embedding = None;
+        bank.episodic.push(memory);
+        let mut index = MemoryIndex::build(&RetrievalConfig::default(), &bank);
+        assert_eq!(index.embedded_count(), 0);
+        assert_eq!(index.len(), 1);
+
+        bank.episodic[0].embedding = Some(Embedding(vec![1.0, 0.0]));
+        assert_eq!(index.sync(&bank).inserted, 1);
+        assert_eq!(index.embedded_count(), 1);
+    }
+
+    #[test]
+    fn re_embedding_a_memory_replaces_it_in_the_graph() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(episodic(0.0, 1.0));
+        bank.episodic.push(episodic(1.0, 0.0));
+        let mut index = MemoryIndex::build(&RetrievalConfig::default(), &bank);
+
+        // A new embedding model: the first memory now points the other way.
+        bank.episodic[0].embedding = Some(Embedding(vec![1.0, 0.1]));
+        assert_eq!(index.sync(&bank), IndexSync { inserted: 1, removed: 1 });
+        assert_eq!(index.embedded_count(), 2);
+        assert_eq!(index.sync(&bank), IndexSync::default());
+
+        let candidates = index.candidates(&bank, &Embedding(vec![1.0, 0.1]), 1);
+        assert_eq!(entry_id(&candidates[0]), bank.episodic[0].id);
+    }
+
+    #[test]
+    fn candidates_include_unembedded_memories() {
+        let mut bank = MemoryBank::new();
+        for i in 0..20 {
+            let angle = i as f32 / 20.0 * std::f32::consts::PI;
+            bank.episodic.push(episodic(angle.cos(), angle.sin()));
+        }
+        bank.social.push(SocialMemory::new(
+            EntityId::new(),
+            EntityId::new(),
+            "rumour",
+            0.5,
+            1,
+            GameTimestamp::now(0),
+        ));
+        let index = MemoryIndex::build(&RetrievalConfig::default(), &bank);
+
+        let candidates = index.candidates(&bank, &Embedding(vec![1.0, 0.0]), 3);
+        assert_eq!(candidates.len(), 4);
+        let ids: Vec<MemoryId> = candidates.iter().map(entry_id).collect();
+        assert!(ids.contains(&bank.episodic[0].id));
+        assert!(ids.contains(&bank.social[0].id));
+    }
+
+    #[test]
+    fn stale_positions_still_resolve() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(episodic(0.0, 1.0));
+        bank.episodic.push(episodic(1.0, 0.0));
+        let index = MemoryIndex::build(&RetrievalConfig::default(), &bank);
+
+        // Evicted without a sync: the survivor shifted down one slot.
+        bank.episodic.remove(0);
+        let candidates = index.candidates(&bank, &Embedding(vec![1.0, 0.0]), 2);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(entry_id(&candidates[0]), bank.episodic[0].id);
+    }
+}
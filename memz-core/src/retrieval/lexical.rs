@@ -0,0 +1,368 @@
+//! Lexical retrieval — BM25 keyword matching (§12.4, `algorithm = "tfidf"`)
+//!
+//! The fallback for machines without an embedding model (the Ultra-Low
+//! hardware profile, or [`StubEmbeddingProvider`]).  A [`LexicalIndex`]
+//! holds term statistics for the free text of every episodic event,
+//! semantic fact, social claim and injected memory, and scores memories
+//! against a query with Okapi BM25 (`k1 = 1.2`, `b = 0.75`).
+//!
+//! Scores are normalised to `[0, 1]` so they can stand in for cosine
+//! similarity in [`scoring::compute_breakdown_with_relevance`]: a memory of
+//! average length containing every query term once scores ~1.0, one sharing
+//! no term scores 0.0.  Memory types without text keep the neutral 0.5.
+//!
+//! [`StubEmbeddingProvider`]: crate::embedding::StubEmbeddingProvider
+//! [`scoring::compute_breakdown_with_relevance`]: super::scoring::compute_breakdown_with_relevance
+
+use std::collections::{HashMap, HashSet};
+
+use crate::memory::{MemoryBank, MemoryEntry};
+use crate::types::MemoryId;
+
+use super::index::IndexSync;
+
+/// BM25 term-frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 length normalisation.
+const B: f64 = 0.75;
+
+/// Relevance of memories that have no text to match.
+const NEUTRAL_RELEVANCE: f64 = 0.5;
+
+/// Words too common to carry meaning.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "been", "but", "by", "for", "from", "had", "has",
+    "have", "he", "her", "him", "his", "i", "in", "into", "is", "it", "its", "me", "my", "of",
+    "on", "or", "our", "she", "so", "that", "the", "their", "them", "they", "this", "to", "was",
+    "we", "were", "what", "when", "which", "who", "will", "with", "you", "your",
+];
+
+/// Split `text` into lowercase, lightly stemmed terms, dropping stopwords.
+#[must_use]
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+        .filter(|word| word.chars().count() > 1)
+        .collect()
+}
+
+/// Strip common English inflections so "wolves"/"wolf" stay distinct but
+/// "bandits"/"bandit" and "attacked"/"attack" match.
+fn stem(word: &str) -> String {
+    let len = word.chars().count();
+    for (suffix, min_len) in [("ing", 6), ("ed", 5), ("es", 5), ("s", 4)] {
+        if len >= min_len && word.ends_with(suffix) && !word.ends_with("ss") {
+            return word[..word.len() - suffix.len()].to_string();
+        }
+    }
+    word.to_string()
+}
+
+/// Term counts of one document.
+#[derive(Debug, Clone)]
+struct Document {
+    terms: HashMap<String, u32>,
+    len: u32,
+}
+
+impl Document {
+    fn new(text: &str) -> Self {
+        let tokens = tokenize(text);
+        let len = tokens.len() as u32;
+        let mut terms = HashMap::new();
+        for token in tokens {
+            *terms.entry(token).or_insert(0) += 1;
+        }
+        Self { terms, len }
+    }
+}
+
+/// A tokenised query with per-term IDF weights, from [`LexicalIndex::query`].
+#[derive(Debug, Clone)]
+pub struct LexicalQuery {
+    terms: Vec<(String, f64)>,
+    /// Sum of IDFs — the score of a perfect, average-length match.
+    norm: f64,
+}
+
+impl LexicalQuery {
+    /// Whether the query has no searchable terms.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// BM25 index over the text of one [`MemoryBank`].
+#[derive(Debug, Clone, Default)]
+pub struct LexicalIndex {
+    docs: HashMap<MemoryId, Document>,
+    /// Number of documents containing each term.
+    doc_freq: HashMap<String, u32>,
+    /// Sum of all document lengths.
+    total_len: u64,
+}
+
+impl LexicalIndex {
+    /// Create an empty index.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an index over the text of every memory in `bank`.
+    #[must_use]
+    pub fn build(bank: &MemoryBank) -> Self {
+        let mut index = Self::new();
+        index.sync(bank);
+        index
+    }
+
+    /// Number of indexed memories.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Whether no memory is indexed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Index `text` under `id`, replacing any previous text.
+    pub fn insert(&mut self, id: MemoryId, text: &str) {
+        self.remove(id);
+        let doc = Document::new(text);
+        for term in doc.terms.keys() {
+            *self.doc_freq.entry(term.clone()).or_insert(0) += 1;
+        }
+        self.total_len += u64::from(doc.len);
+        self.docs.insert(id, doc);
+    }
+
+    /// Drop memory `id` from the index.
+    pub fn remove(&mut self, id: MemoryId) {
+        let Some(doc) = self.docs.remove(&id) else {
+            return;
+        };
+        for term in doc.terms.keys() {
+            if let Some(df) = self.doc_freq.get_mut(term) {
+                *df -= 1;
+                if *df == 0 {
+                    self.doc_freq.remove(term);
+                }
+            }
+        }
+        self.total_len -= u64::from(doc.len);
+    }
+
+    /// Bring the index in line with `bank`: index new memories and drop
+    /// evicted ones.  Text is assumed not to change under a given id.
+    pub fn sync(&mut self, bank: &MemoryBank) -> IndexSync {
+        let mut report = IndexSync::default();
+        let mut live = HashSet::with_capacity(bank.total_count());
+        let texts = bank
+            .episodic
+            .iter()
+            .map(|m| (m.id, m.event.as_str()))
+            .chain(bank.semantic.iter().map(|m| (m.id, m.fact.as_str())))
+            .chain(bank.social.iter().map(|m| (m.id, m.claim.as_str())))
+            .chain(bank.injected.iter().map(|m| (m.id, m.content.as_str())));
+        for (id, text) in texts {
+            live.insert(id);
+            if !self.docs.contains_key(&id) {
+                self.insert(id, text);
+                report.inserted += 1;
+            }
+        }
+        let evicted: Vec<MemoryId> = self
+            .docs
+            .keys()
+            .filter(|id| !live.contains(id))
+            .copied()
+            .collect();
+        for id in evicted {
+            self.remove(id);
+            report.removed += 1;
+        }
+        report
+    }
+
+    /// Prepare `text` for scoring against this index.
+    #[must_use]
+    pub fn query(&self, text: &str) -> LexicalQuery {
+        let mut seen = HashSet::new();
+        let terms: Vec<(String, f64)> = tokenize(text)
+            .into_iter()
+            .filter(|term| seen.insert(term.clone()))
+            .map(|term| {
+                let idf = self.idf(&term);
+                (term, idf)
+            })
+            .collect();
+        let norm = terms.iter().map(|(_, idf)| idf).sum();
+        LexicalQuery { terms, norm }
+    }
+
+    /// Relevance of `memory` to `query` in `[0, 1]`.
+    ///
+    /// Memories added since the last sync are tokenised on the fly; memory
+    /// types without text get a neutral 0.5.
+    #[must_use]
+    pub fn relevance(&self, query: &LexicalQuery, memory: &MemoryEntry) -> f64 {
+        self.text_relevance(query, memory).unwrap_or(NEUTRAL_RELEVANCE)
+    }
+
+    /// Like [`relevance`](Self::relevance), but `None` for memory types
+    /// without text.
+    #[must_use]
+    pub fn text_relevance(&self, query: &LexicalQuery, memory: &MemoryEntry) -> Option<f64> {
+        let text = memory.text()?;
+        if query.norm <= 0.0 {
+            return Some(0.0);
+        }
+        let score = match self.docs.get(&memory.id()) {
+            Some(doc) => self.bm25(query, doc),
+            None => self.bm25(query, &Document::new(text)),
+        };
+        Some((score / query.norm).min(1.0))
+    }
+
+    fn avg_len(&self) -> f64 {
+        if self.docs.is_empty() {
+            1.0
+        } else {
+            (self.total_len as f64 / self.docs.len() as f64).max(1.0)
+        }
+    }
+
+    /// Robertson–Spärck Jones IDF, floored at a small positive value so
+    /// terms present in every memory still count a little.
+    fn idf(&self, term: &str) -> f64 {
+        let n = self.docs.len() as f64;
+        let df = f64::from(self.doc_freq.get(term).copied().unwrap_or(0));
+        (1.0 + (n - df + 0.5) / (df + 0.5)).ln().max(0.01)
+    }
+
+    fn bm25(&self, query: &LexicalQuery, doc: &Document) -> f64 {
+        let len_norm = 1.0 - B + B * f64::from(doc.len) / self.avg_len();
+        query
+            .terms
+            .iter()
+            .filter_map(|(term, idf)| {
+                let tf = f64::from(*doc.terms.get(term)?);
+                Some(idf * tf * (K1 + 1.0) / (tf + K1 * len_norm))
+            })
+            .sum()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::episodic::EpisodicMemory;
+    use crate::memory::semantic::SemanticMemory;
+    use crate::types::{GameTimestamp, Location};
+
+    fn episodic(text: &str) -> EpisodicMemory {
+        EpisodicMemory::new(text, vec![], Location::default(), GameTimestamp::now(0), 0.0, 0.5)
+    }
+
+    fn bank(texts: &[&str]) -> MemoryBank {
+        let mut bank = MemoryBank::new();
+        bank.episodic.extend(texts.iter().map(|t| episodic(t)));
+        bank
+    }
+
+    #[test]
+    fn tokenize_drops_stopwords_and_stems() {
+        assert_eq!(
+            tokenize("The bandits attacked the Mill at dawn!"),
+            vec!["bandit", "attack", "mill", "dawn"]
+        );
+        assert_eq!(tokenize("Grass, moss & glass"), vec!["grass", "moss", "glass"]);
+        assert!(tokenize("it is what it is").is_empty());
+    }
+
+    #[test]
+    fn matching_memory_outranks_others() {
+        let bank = bank(&[
+            "Bandits attacked the mill at dawn",
+            "Bought bread from the baker",
+            "The blacksmith repaired my sword",
+        ]);
+        let index = LexicalIndex::build(&bank);
+        let query = index.query("who attacked the mill?");
+        let scores: Vec<f64> = bank
+            .all_entries()
+            .iter()
+            .map(|m| index.relevance(&query, m))
+            .collect();
+        assert!(scores[0] > 0.8, "{scores:?}");
+        assert!(scores[1].abs() < f64::EPSILON);
+        assert!(scores[2].abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn rare_terms_weigh_more() {
+        let bank = bank(&[
+            "The dragon burned the village",
+            "The village held a festival",
+            "The village well ran dry",
+        ]);
+        let index = LexicalIndex::build(&bank);
+        let query = index.query("dragon village");
+        let entries = bank.all_entries();
+        let dragon = index.relevance(&query, &entries[0]);
+        let festival = index.relevance(&query, &entries[1]);
+        assert!(dragon > 3.0 * festival, "{dragon} vs {festival}");
+    }
+
+    #[test]
+    fn sync_follows_the_bank() {
+        let mut bank = bank(&["Wolves howled near the farm"]);
+        bank.semantic.push(SemanticMemory::new(
+            "Wolves avoid fire",
+            0.9,
+            vec![],
+            "nature",
+            GameTimestamp::now(0),
+        ));
+        let mut index = LexicalIndex::build(&bank);
+        assert_eq!(index.len(), 2);
+
+        bank.episodic.clear();
+        bank.episodic.push(episodic("A merchant arrived"));
+        let report = index.sync(&bank);
+        assert_eq!(report, IndexSync { inserted: 1, removed: 1 });
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.doc_freq.get("farm"), None);
+    }
+
+    #[test]
+    fn unindexed_and_textless_memories() {
+        let index = LexicalIndex::build(&bank(&["Bandits attacked the mill"]));
+        let query = index.query("mill");
+        let fresh = MemoryEntry::Episodic(episodic("Rebuilt the mill"));
+        assert!(index.relevance(&query, &fresh) > 0.5);
+
+        let emotional = MemoryEntry::Emotional(crate::memory::emotional::EmotionalMemory::new(
+            crate::types::EntityId::new(),
+            "fear",
+            0.8,
+            crate::types::PADState::default(),
+            vec![],
+            GameTimestamp::now(0),
+        ));
+        assert!((index.relevance(&query, &emotional) - NEUTRAL_RELEVANCE).abs() < f64::EPSILON);
+        assert!(index.relevance(&index.query("the"), &fresh).abs() < f64::EPSILON);
+    }
+}
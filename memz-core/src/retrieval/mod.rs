@@ -3,16 +3,54 @@
 //! The retrieval algorithm combines five weighted factors:
 //!   Score = w₁·Recency + w₂·Relevance + w₃·Importance + w₄·Emotional + w₅·Social
 //!
+//! plus w₆·Place when the NPC's position is known (see [`spatial`]).
+//!
 //! Based on the Stanford Generative Agents retrieval function, enhanced with
 //! Ebbinghaus-curve decay, emotional flashbulb effect, trust-weighted hearsay,
-//! and personality-modulated weights.
+//! and personality-modulated weights ([`PersonalityWeightOverrides`]).
+//!
+//! With `algorithm = "hnsw"`, [`RetrievalEngine::retrieve_indexed`] first
+//! narrows a bank down to its `hnsw_ef_search` semantically nearest memories
+//! using a per-bank [`MemoryIndex`], then applies the five-factor score to
+//! those candidates only.
+//!
+//! With `algorithm = "tfidf"` (no embedding model), [`RetrievalEngine::retrieve_lexical`]
+//! takes the query as text and derives relevance from a BM25 [`LexicalIndex`].
+//! With `algorithm = "hybrid"`, [`RetrievalEngine::retrieve_hybrid`] fuses
+//! both relevance sources (see [`Fusion`]).
+//!
+//! [`RetrievalEngine::retrieve_query`] answers a structured [`RetrievalQuery`]:
+//! metadata filters (type, entity, time window, place, strength, valence,
+//! tags) narrow the bank before scoring.
+//! [`RetrievalEngine::retrieve_query_paged`] additionally pages older
+//! history about the query's entity back in from cold storage when the
+//! bank alone cannot fill the top-K, within `retrieval_budget_us`.
 
+pub mod fusion;
+pub mod index;
+pub mod lexical;
+pub mod query;
 pub mod scoring;
+pub mod spatial;
+
+pub use fusion::Fusion;
+pub use index::{IndexSync, MemoryIndex};
+pub use lexical::LexicalIndex;
+pub use query::RetrievalQuery;
+pub use spatial::SpatialIndex;
 
-use crate::config::RetrievalConfig;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use crate::config::{MemzConfig, PerformanceConfig, RetrievalConfig, RetrievalWeights};
+use crate::consolidation::MemoryType;
 use crate::error::MemzError;
-use crate::memory::MemoryEntry;
-use crate::types::{Embedding, GameTimestamp};
+use crate::memory::{MemoryBank, MemoryEntry};
+use crate::persistence::PersistenceEngine;
+use crate::time::TimeModel;
+use crate::types::{Embedding, EntityId, GameTimestamp, Location, MemoryId, PersonalityTraits};
 
 /// A scored retrieval result.
 #[derive(Debug, Clone)]
@@ -38,70 +76,391 @@ pub struct ScoreBreakdown {
     pub emotional: f64,
     /// Social trust contribution.
     pub social: f64,
+    /// Place-cue contribution (0 unless the query has a position).
+    pub place: f64,
+    /// Unweighted embedding similarity, when the memory has an embedding
+    /// and the query was scored against vectors.
+    pub vector_relevance: Option<f64>,
+    /// Unweighted BM25 relevance, when the memory has text and the query
+    /// was scored lexically.
+    pub lexical_relevance: Option<f64>,
 }
 
 /// The retrieval engine that finds relevant memories for a given context.
 pub struct RetrievalEngine {
     config: RetrievalConfig,
+    time: TimeModel,
+    budget: Duration,
+}
+
+/// `retrieval_budget_us` as a [`Duration`].
+fn retrieval_budget(config: &PerformanceConfig) -> Duration {
+    Duration::from_micros(u64::from(config.retrieval_budget_us))
 }
 
 impl RetrievalEngine {
     /// Create a new retrieval engine with the given configuration.
     #[must_use]
     pub fn new(config: RetrievalConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            time: TimeModel::default(),
+            budget: retrieval_budget(&PerformanceConfig::default()),
+        }
+    }
+
+    /// Use `time` to convert memory ages to game-days for recency.
+    #[must_use]
+    pub fn with_time_model(mut self, time: TimeModel) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Stop paging in cold memories once a query has taken `budget`.
+    #[must_use]
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Create an engine from the `[retrieval]` and `[time]` sections and
+    /// `performance.retrieval_budget_us`.
+    #[must_use]
+    pub fn from_memz_config(config: &MemzConfig) -> Self {
+        Self::new(config.retrieval.clone())
+            .with_time_model(config.time)
+            .with_budget(retrieval_budget(&config.performance))
+    }
+
+    /// The `[retrieval]` section this engine scores with.
+    #[must_use]
+    pub fn config(&self) -> &RetrievalConfig {
+        &self.config
+    }
+
+    /// Adopt the `[retrieval]` and `[time]` sections and retrieval budget of
+    /// a reloaded config.
+    pub fn reconfigure(&mut self, config: &MemzConfig) {
+        self.config.clone_from(&config.retrieval);
+        self.time = config.time;
+        self.budget = retrieval_budget(&config.performance);
     }
 
     /// Retrieve the top-K most relevant memories given a context embedding.
     ///
+    /// `personality_weights` rescales the configured factor weights for this
+    /// query (see [`PersonalityWeightOverrides::from_traits`]).
+    ///
     /// Performance target: < 0.5ms P50 for 200 memories.
     pub fn retrieve(
         &self,
         context_embedding: &Embedding,
         memories: &[MemoryEntry],
         current_time: &GameTimestamp,
-        _personality_weights: Option<&PersonalityWeightOverrides>,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Result<Vec<RetrievalResult>, MemzError> {
+        Ok(self.rank(memories, personality_weights, |_, memory, w| ScoreBreakdown {
+            vector_relevance: scoring::embedding_similarity(memory, context_embedding),
+            ..scoring::compute_breakdown(
+                memory,
+                context_embedding,
+                current_time,
+                &self.time,
+                w.recency,
+                w.relevance,
+                w.importance,
+                w.emotional,
+                w.social,
+            )
+        }))
+    }
+
+    /// Retrieve the top-K memories for a text query using BM25 keyword
+    /// matching instead of embeddings (`algorithm = "tfidf"`).
+    ///
+    /// `index` supplies corpus statistics; memories it has not seen yet are
+    /// still scored.  See [`lexical`] for how relevance is normalised.
+    pub fn retrieve_lexical(
+        &self,
+        query: &str,
+        memories: &[MemoryEntry],
+        index: &LexicalIndex,
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Result<Vec<RetrievalResult>, MemzError> {
+        let query = index.query(query);
+        Ok(self.rank(memories, personality_weights, |_, memory, w| {
+            let lexical = index.text_relevance(&query, memory);
+            ScoreBreakdown {
+                lexical_relevance: lexical,
+                ..scoring::compute_breakdown_with_relevance(
+                    memory,
+                    lexical.unwrap_or(0.5),
+                    current_time,
+                    &self.time,
+                    w.recency,
+                    w.relevance,
+                    w.importance,
+                    w.emotional,
+                    w.social,
+                )
+            }
+        }))
+    }
+
+    /// Retrieve the top-K memories using both the context embedding and the
+    /// query text (`algorithm = "hybrid"`), fusing the two relevance signals
+    /// as configured by `hybrid_fusion`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Config`] for an unknown `hybrid_fusion`.
+    pub fn retrieve_hybrid(
+        &self,
+        context_embedding: &Embedding,
+        query: &str,
+        memories: &[MemoryEntry],
+        index: &LexicalIndex,
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Result<Vec<RetrievalResult>, MemzError> {
+        let fusion = Fusion::from_config(&self.config)?;
+        let query = index.query(query);
+        let vector: Vec<Option<f64>> = memories
+            .iter()
+            .map(|m| scoring::embedding_similarity(m, context_embedding))
+            .collect();
+        let lexical: Vec<Option<f64>> = memories
+            .iter()
+            .map(|m| index.text_relevance(&query, m))
+            .collect();
+        let fused = fusion.fuse(&vector, &lexical);
+
+        Ok(self.rank(memories, personality_weights, |i, memory, w| ScoreBreakdown {
+            vector_relevance: vector[i],
+            lexical_relevance: lexical[i],
+            ..scoring::compute_breakdown_with_relevance(
+                memory,
+                fused[i],
+                current_time,
+                &self.time,
+                w.recency,
+                w.relevance,
+                w.importance,
+                w.emotional,
+                w.social,
+            )
+        }))
+    }
+
+    /// Retrieve the top-K memories of `bank` that pass the filters of
+    /// `query`, scoring relevance against its embedding (neutral 0.5 for
+    /// every candidate when it has none) and, if it has a place cue, adding
+    /// the place factor.
+    pub fn retrieve_query(
+        &self,
+        query: &RetrievalQuery,
+        bank: &MemoryBank,
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Result<Vec<RetrievalResult>, MemzError> {
+        let candidates = query.candidates(bank);
+        Ok(self.rank_query(query, &candidates, current_time, personality_weights))
+    }
+
+    /// [`retrieve_query`](Self::retrieve_query), paging in cold history.
+    ///
+    /// When `query` is about an entity, wants episodic memories, and `bank`
+    /// has fewer than `top_k` candidates while holding cold memories about
+    /// that entity, the newest of those that pass the query's filters are
+    /// paged back in from `store` (see
+    /// [`PersistenceEngine::page_in_matching`]) and stay in the bank.
+    /// Paging is skipped once the query has used up the retrieval budget,
+    /// so a slow disk degrades recall rather than the frame; a page-in that
+    /// overruns the budget is logged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MemzError::Database`] or [`MemzError::Serialization`] if
+    /// paging in fails.
+    pub fn retrieve_query_paged(
+        &self,
+        query: &RetrievalQuery,
+        bank: &mut MemoryBank,
+        owner: &EntityId,
+        store: &PersistenceEngine,
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
     ) -> Result<Vec<RetrievalResult>, MemzError> {
-        let weights = &self.config.weights;
-        let top_k = self.config.top_k;
+        let start = Instant::now();
+        let mut candidates = query.candidates(bank);
+
+        let wants_episodic =
+            query.memory_types.is_empty() || query.memory_types.contains(&MemoryType::Episodic);
+        if let Some(entity) = query.entity
+            && wants_episodic
+            && candidates.len() < self.config.top_k
+            && bank.cold_count(entity) > 0
+        {
+            if start.elapsed() < self.budget {
+                let missing = self.config.top_k - candidates.len();
+                let before = bank.episodic.len();
+                store.page_in_matching(owner, bank, entity, missing, query.time_window, |m| {
+                    query.admits_episodic(m)
+                })?;
+                candidates.extend(bank.episodic[before..].iter().cloned().map(MemoryEntry::Episodic));
+                let elapsed = start.elapsed();
+                if elapsed > self.budget {
+                    warn!(
+                        entity = %entity,
+                        elapsed_us = elapsed.as_micros(),
+                        budget_us = self.budget.as_micros(),
+                        "Paging in cold memories overran the retrieval budget"
+                    );
+                }
+            } else {
+                debug!(entity = %entity, "Retrieval budget spent — cold memories not paged in");
+            }
+        }
+
+        Ok(self.rank_query(query, &candidates, current_time, personality_weights))
+    }
 
-        let mut results: Vec<RetrievalResult> = memories
+    /// What an NPC standing at `position` is reminded of: the top-K episodic
+    /// memories formed within `place_cue_radius`, found through `spatial`
+    /// and ranked with the place-cue factor (which only tells them apart by
+    /// distance once `weights.place` is raised above its default of 0).
+    ///
+    /// `spatial` must have been [synced](SpatialIndex::sync) with `bank`.
+    pub fn recall_at_place(
+        &self,
+        position: &Location,
+        bank: &MemoryBank,
+        spatial: &SpatialIndex,
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Result<Vec<RetrievalResult>, MemzError> {
+        let nearby: HashSet<MemoryId> = spatial
+            .near(position, self.config.place_cue_radius)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let candidates: Vec<MemoryEntry> = bank
+            .episodic
             .iter()
-            .map(|memory| {
-                let breakdown = scoring::compute_breakdown(
+            .filter(|m| nearby.contains(&m.id))
+            .map(|m| MemoryEntry::Episodic(m.clone()))
+            .collect();
+        let query = RetrievalQuery::new().with_place_cue(*position);
+        Ok(self.rank_query(&query, &candidates, current_time, personality_weights))
+    }
+
+    fn rank_query(
+        &self,
+        query: &RetrievalQuery,
+        candidates: &[MemoryEntry],
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Vec<RetrievalResult> {
+        let radius = self.config.place_cue_radius;
+        self.rank(candidates, personality_weights, |_, memory, w| {
+            let vector = query
+                .embedding
+                .as_ref()
+                .and_then(|embedding| scoring::embedding_similarity(memory, embedding));
+            let place = query
+                .place_cue
+                .map_or(0.0, |at| scoring::place_score(memory, &at, radius));
+            ScoreBreakdown {
+                vector_relevance: vector,
+                place: f64::from(w.place) * place,
+                ..scoring::compute_breakdown_with_relevance(
                     memory,
-                    context_embedding,
+                    vector.unwrap_or(0.5),
                     current_time,
-                    weights.recency,
-                    weights.relevance,
-                    weights.importance,
-                    weights.emotional,
-                    weights.social,
-                );
+                    &self.time,
+                    w.recency,
+                    w.relevance,
+                    w.importance,
+                    w.emotional,
+                    w.social,
+                )
+            }
+        })
+    }
+
+    /// Score every memory with `breakdown(position, memory, weights)` and
+    /// return the top-K.
+    fn rank(
+        &self,
+        memories: &[MemoryEntry],
+        personality_weights: Option<&PersonalityWeightOverrides>,
+        breakdown: impl Fn(usize, &MemoryEntry, &RetrievalWeights) -> ScoreBreakdown,
+    ) -> Vec<RetrievalResult> {
+        let weights = match personality_weights {
+            Some(overrides) => overrides.apply(&self.config.weights),
+            None => self.config.weights.clone(),
+        };
 
+        let mut scored: Vec<(f64, ScoreBreakdown, &MemoryEntry)> = memories
+            .iter()
+            .enumerate()
+            .map(|(i, memory)| {
+                let breakdown = breakdown(i, memory, &weights);
                 let score = breakdown.recency
                     + breakdown.relevance
                     + breakdown.importance
                     + breakdown.emotional
-                    + breakdown.social;
-
-                RetrievalResult {
-                    memory: memory.clone(),
-                    score,
-                    breakdown,
-                }
+                    + breakdown.social
+                    + breakdown.place;
+                (score, breakdown, memory)
             })
             .collect();
 
         // Sort descending by score.
-        results.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
 
-        results.truncate(top_k);
-        Ok(results)
+        // Only the winners are cloned.
+        scored
+            .into_iter()
+            .take(self.config.top_k)
+            .map(|(score, breakdown, memory)| RetrievalResult {
+                memory: memory.clone(),
+                score,
+                breakdown,
+            })
+            .collect()
+    }
+
+    /// Retrieve the top-K memories of `bank`, using `index` to pre-filter
+    /// candidates by semantic similarity.
+    ///
+    /// Only the `hnsw_ef_search` memories nearest to `context_embedding`,
+    /// plus the memories without an embedding, are scored.  Falls back to
+    /// scoring the whole bank when `algorithm` is not `"hnsw"` or the bank
+    /// holds fewer than `hnsw_min_memories` memories.
+    ///
+    /// `index` must have been [synced](MemoryIndex::sync) with `bank`;
+    /// memories added since are not considered.
+    ///
+    /// Performance target: < 0.5ms P50 for 20,000 memories.
+    pub fn retrieve_indexed(
+        &self,
+        context_embedding: &Embedding,
+        bank: &MemoryBank,
+        index: &MemoryIndex,
+        current_time: &GameTimestamp,
+        personality_weights: Option<&PersonalityWeightOverrides>,
+    ) -> Result<Vec<RetrievalResult>, MemzError> {
+        let candidates = if self.config.algorithm == "hnsw"
+            && bank.total_count() >= self.config.hnsw_min_memories
+        {
+            let k = self.config.hnsw_ef_search.max(self.config.top_k);
+            index.candidates(bank, context_embedding, k)
+        } else {
+            bank.all_entries()
+        };
+        self.retrieve(context_embedding, &candidates, current_time, personality_weights)
     }
 }
 
@@ -132,3 +491,305 @@ impl Default for PersonalityWeightOverrides {
         }
     }
 }
+
+impl PersonalityWeightOverrides {
+    /// Derive multipliers from personality traits.
+    ///
+    /// Every multiplier is 1.0 for the default (all-0.5) personality and
+    /// ranges over 0.5–1.5 (relevance, recency, importance: 0.75–1.25):
+    ///
+    /// | Factor     | Raised by                    | Lowered by                |
+    /// |------------|------------------------------|---------------------------|
+    /// | Emotional  | high `emotional_volatility`  | stoicism                  |
+    /// | Relevance  | stoicism (a "logical" NPC)   | high `emotional_volatility` |
+    /// | Social     | high `credulity`, `gossip_tendency` | skepticism, secrecy |
+    /// | Recency    | high `openness`              | rigidity                  |
+    /// | Importance | rigidity (dwells on the big events) | high `openness`    |
+    #[must_use]
+    pub fn from_traits(traits: &PersonalityTraits) -> Self {
+        let t = |v: f32| v.clamp(0.0, 1.0);
+        Self {
+            recency_mult: 0.75 + 0.5 * t(traits.openness),
+            relevance_mult: 1.25 - 0.5 * t(traits.emotional_volatility),
+            importance_mult: 1.25 - 0.5 * t(traits.openness),
+            emotional_mult: 0.5 + t(traits.emotional_volatility),
+            social_mult: 0.5 + 0.5 * t(traits.credulity) + 0.5 * t(traits.gossip_tendency),
+        }
+    }
+
+    /// Apply the multipliers to `weights`, then rescale so the weights keep
+    /// their original sum — personality shifts the balance between factors
+    /// without inflating or deflating overall scores.
+    #[must_use]
+    pub fn apply(&self, weights: &RetrievalWeights) -> RetrievalWeights {
+        let scaled = RetrievalWeights {
+            recency: weights.recency * self.recency_mult.max(0.0),
+            relevance: weights.relevance * self.relevance_mult.max(0.0),
+            importance: weights.importance * self.importance_mult.max(0.0),
+            emotional: weights.emotional * self.emotional_mult.max(0.0),
+            social: weights.social * self.social_mult.max(0.0),
+            place: weights.place,
+        };
+        let before = weight_sum(weights);
+        let after = weight_sum(&scaled);
+        if after <= f32::EPSILON {
+            return scaled;
+        }
+        let k = before / after;
+        RetrievalWeights {
+            recency: scaled.recency * k,
+            relevance: scaled.relevance * k,
+            importance: scaled.importance * k,
+            emotional: scaled.emotional * k,
+            social: scaled.social * k,
+            place: scaled.place * k,
+        }
+    }
+}
+
+impl From<&PersonalityTraits> for PersonalityWeightOverrides {
+    fn from(traits: &PersonalityTraits) -> Self {
+        Self::from_traits(traits)
+    }
+}
+
+fn weight_sum(w: &RetrievalWeights) -> f32 {
+    w.recency + w.relevance + w.importance + w.emotional + w.social + w.place
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::episodic::EpisodicMemory;
+    use crate::types::Location;
+
+    fn memory(text: &str, embedding: [f32; 2], valence: f32) -> MemoryEntry {
+        let mut m = EpisodicMemory::new(
+            text,
+            vec![],
+            Location::default(),
+            GameTimestamp::now(0),
+            valence,
+            0.5,
+        );
+        m.embedding = Some(Embedding(embedding.to_vec()));
+        MemoryEntry::Episodic(m)
+    }
+
+    fn top_event(results: &[RetrievalResult]) -> &str {
+        match &results[0].memory {
+            MemoryEntry::Episodic(m) => &m.event,
+            other => panic!("unexpected memory {other:?}"),
+        }
+    }
+
+    #[test]
+    fn query_filters_before_scoring() {
+        let mut bank = MemoryBank::new();
+        for (text, embedding, valence) in [
+            ("praised my bread", [1.0, 0.0], 0.8),
+            ("insulted my bread", [1.0, 0.0], -0.7),
+            ("stole a loaf", [0.0, 1.0], -0.9),
+        ] {
+            if let MemoryEntry::Episodic(m) = memory(text, embedding, valence) {
+                bank.episodic.push(m);
+            }
+        }
+        let engine = RetrievalEngine::new(RetrievalConfig::default());
+        let query = RetrievalQuery::new()
+            .with_embedding(Embedding(vec![1.0, 0.0]))
+            .with_valence(-1.0, 0.0);
+
+        let results = engine
+            .retrieve_query(&query, &bank, &GameTimestamp::now(0), None)
+            .expect("retrieval succeeds");
+        assert_eq!(results.len(), 2);
+        assert_eq!(top_event(&results), "insulted my bread");
+    }
+
+    #[test]
+    fn walking_past_a_place_recalls_what_happened_there() {
+        let mut bank = MemoryBank::new();
+        for (text, x) in [
+            ("my friend died by the old bridge", 100.0),
+            ("bought apples at the market", 0.0),
+            ("saw a deer at the bridge", 120.0),
+        ] {
+            bank.episodic.push(EpisodicMemory::new(
+                text,
+                vec![],
+                Location { x, y: 0.0, z: 0.0 },
+                GameTimestamp::now(0),
+                -0.2,
+                0.5,
+            ));
+        }
+        let mut config = RetrievalConfig::default();
+        config.weights.place = 0.1;
+        config.weights.relevance = 0.2;
+        let engine = RetrievalEngine::new(config.clone());
+        let spatial = SpatialIndex::build(&config, &bank);
+        let bridge = Location { x: 101.0, y: 2.0, z: 0.0 };
+
+        let results = engine
+            .recall_at_place(&bridge, &bank, &spatial, &GameTimestamp::now(0), None)
+            .expect("retrieval succeeds");
+        assert_eq!(results.len(), 2);
+        assert_eq!(top_event(&results), "my friend died by the old bridge");
+        assert!(results[0].breakdown.place > results[1].breakdown.place);
+
+        // Without a place cue the factor contributes nothing.
+        let unplaced = engine
+            .retrieve_query(&RetrievalQuery::new(), &bank, &GameTimestamp::now(0), None)
+            .expect("retrieval succeeds");
+        assert!(unplaced.iter().all(|r| r.breakdown.place == 0.0));
+    }
+
+    #[test]
+    fn place_weight_leaves_placeless_ranking_alone() {
+        let mut bank = MemoryBank::new();
+        for (i, (text, valence, importance)) in [
+            ("traded some wool", 0.1, 0.2),
+            ("was robbed on the road", -0.8, 0.9),
+            ("heard a bard sing", 0.4, 0.3),
+            ("fixed the mill wheel", 0.2, 0.6),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            bank.episodic.push(EpisodicMemory::new(
+                text,
+                vec![],
+                Location { x: 10.0 * i as f32, y: 0.0, z: 0.0 },
+                GameTimestamp::now(100 * i as u64),
+                valence,
+                importance,
+            ));
+        }
+        let ranked = |weights: RetrievalWeights| {
+            let engine = RetrievalEngine::new(RetrievalConfig { weights, ..RetrievalConfig::default() });
+            engine
+                .retrieve_query(&RetrievalQuery::new(), &bank, &GameTimestamp::now(1_000), None)
+                .expect("retrieval succeeds")
+                .into_iter()
+                .map(|r| (r.memory.id(), r.score))
+                .collect::<Vec<_>>()
+        };
+
+        // The five core defaults are the original ones, and the place cue
+        // is opt-in, so callers without one rank exactly as before.
+        let baseline = RetrievalWeights {
+            recency: 0.20,
+            relevance: 0.30,
+            importance: 0.20,
+            emotional: 0.20,
+            social: 0.10,
+            place: 0.0,
+        };
+        assert_eq!(ranked(RetrievalWeights::default()), ranked(baseline.clone()));
+        assert_eq!(ranked(RetrievalWeights { place: 0.25, ..baseline.clone() }), ranked(baseline));
+    }
+
+    #[test]
+    fn default_personality_is_neutral() {
+        let overrides = PersonalityWeightOverrides::from_traits(&PersonalityTraits::default());
+        for mult in [
+            overrides.recency_mult,
+            overrides.relevance_mult,
+            overrides.importance_mult,
+            overrides.emotional_mult,
+            overrides.social_mult,
+        ] {
+            assert!((mult - 1.0).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn apply_preserves_total_weight() {
+        let weights = RetrievalWeights::default();
+        let traits = PersonalityTraits {
+            emotional_volatility: 0.95,
+            credulity: 0.1,
+            ..PersonalityTraits::default()
+        };
+        let applied = PersonalityWeightOverrides::from_traits(&traits).apply(&weights);
+        assert!((weight_sum(&applied) - weight_sum(&weights)).abs() < 1e-5);
+        assert!(applied.emotional > weights.emotional);
+        assert!(applied.social < weights.social);
+        assert!(applied.relevance < weights.relevance);
+    }
+
+    #[test]
+    fn lexical_retrieval_works_without_embeddings() {
+        let engine = RetrievalEngine::new(RetrievalConfig {
+            algorithm: "tfidf".to_string(),
+            top_k: 1,
+            ..RetrievalConfig::default()
+        });
+        let mut memories = vec![
+            memory("Sold three sheep at the market", [0.0, 0.0], 0.0),
+            memory("Wolves killed two sheep by the river", [0.0, 0.0], 0.0),
+            memory("The river flooded the lower fields", [0.0, 0.0], 0.0),
+        ];
+        for m in &mut memories {
+            if let MemoryEntry::Episodic(m) = m {
+                m.embedding = None;
+            }
+        }
+        let mut bank = MemoryBank::new();
+        for m in &memories {
+            if let MemoryEntry::Episodic(m) = m {
+                bank.episodic.push(m.clone());
+            }
+        }
+        let index = LexicalIndex::build(&bank);
+
+        let results = engine
+            .retrieve_lexical(
+                "were there wolves near the sheep?",
+                &memories,
+                &index,
+                &GameTimestamp::now(0),
+                None,
+            )
+            .expect("retrieve");
+        assert_eq!(top_event(&results), "Wolves killed two sheep by the river");
+        assert!(results[0].breakdown.relevance > 0.0);
+    }
+
+    #[test]
+    fn sentimental_and_logical_npcs_recall_differently() {
+        let engine = RetrievalEngine::new(RetrievalConfig {
+            top_k: 1,
+            ..RetrievalConfig::default()
+        });
+        let memories = vec![
+            memory("the exact topic, calmly", [1.0, 0.0], 0.0),
+            memory("a tangent, but heartbreaking", [0.6, 0.8], -1.0),
+        ];
+        let context = Embedding(vec![1.0, 0.0]);
+        let now = GameTimestamp::now(0);
+
+        let sentimental = PersonalityWeightOverrides::from_traits(&PersonalityTraits {
+            emotional_volatility: 1.0,
+            ..PersonalityTraits::default()
+        });
+        let logical = PersonalityWeightOverrides::from_traits(&PersonalityTraits {
+            emotional_volatility: 0.0,
+            ..PersonalityTraits::default()
+        });
+
+        let a = engine
+            .retrieve(&context, &memories, &now, Some(&sentimental))
+            .expect("retrieve");
+        let b = engine
+            .retrieve(&context, &memories, &now, Some(&logical))
+            .expect("retrieve");
+        assert_eq!(top_event(&a), "a tangent, but heartbreaking");
+        assert_eq!(top_event(&b), "the exact topic, calmly");
+    }
+}
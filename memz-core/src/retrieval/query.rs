@@ -0,0 +1,474 @@
+//! Structured retrieval queries — metadata filters applied before scoring (§12.4)
+//!
+//! A [`RetrievalQuery`] narrows a bank down to the memories that can answer
+//! a precise question — "negative memories about this player from the last
+//! week near this site" — before the five-factor score ranks them:
+//!
+//! ```ignore
+//! let query = RetrievalQuery::new()
+//!     .with_entity(player)
+//!     .with_time_window(week_ago, now)
+//!     .with_location(site, 50.0)
+//!     .with_valence(-1.0, -0.2);
+//! let results = engine.retrieve_query(&query, &bank, &now, None)?;
+//! ```
+//!
+//! Every filter is optional and all set filters must hold.  A filter on a
+//! property a memory type does not have (a location on a semantic fact, a
+//! valence on a skill) excludes that memory.
+
+use crate::consolidation::MemoryType;
+use crate::memory::{
+    EmotionalMemory, EpisodicMemory, InjectedMemory, MemoryBank, MemoryEntry, ProceduralMemory,
+    ReflectiveMemory, SemanticMemory, SocialMemory,
+};
+use crate::types::{Embedding, EntityId, GameTimestamp, Location};
+
+use super::spatial::distance;
+
+/// A retrieval request: an optional context embedding plus metadata filters.
+#[derive(Debug, Clone, Default)]
+pub struct RetrievalQuery {
+    /// Context embedding for the relevance factor; without one every
+    /// candidate gets the neutral relevance 0.5.
+    pub embedding: Option<Embedding>,
+    /// The NPC's current position, scored by the place-cue factor.
+    pub place_cue: Option<Location>,
+    /// Allowed memory types (empty = all).
+    pub memory_types: Vec<MemoryType>,
+    /// Entity the memory must involve.
+    pub entity: Option<EntityId>,
+    /// Inclusive game-tick window the memory's timestamp must fall in.
+    pub time_window: Option<(u64, u64)>,
+    /// Centre and radius (world units) the memory must have happened within.
+    pub location: Option<(Location, f32)>,
+    /// Minimum strength (see [`strength`]).
+    pub min_strength: Option<f32>,
+    /// Inclusive emotional-valence range.
+    pub valence: Option<(f32, f32)>,
+    /// Tags the memory must all carry (case-insensitive).
+    pub tags: Vec<String>,
+}
+
+impl RetrievalQuery {
+    /// A query with no embedding and no filters — matches every memory.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Score relevance against `embedding`.
+    #[must_use]
+    pub fn with_embedding(mut self, embedding: Embedding) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// Boost memories formed near `position` (the place-cue factor).
+    #[must_use]
+    pub fn with_place_cue(mut self, position: Location) -> Self {
+        self.place_cue = Some(position);
+        self
+    }
+
+    /// Only consider memories of the given types.
+    #[must_use]
+    pub fn with_memory_types(mut self, types: &[MemoryType]) -> Self {
+        self.memory_types = types.to_vec();
+        self
+    }
+
+    /// Only consider memories involving `entity`: episodic participants,
+    /// the subject of gossip, or the target of a feeling.
+    #[must_use]
+    pub fn with_entity(mut self, entity: EntityId) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+    /// Only consider memories formed between `start` and `end` (inclusive).
+    #[must_use]
+    pub fn with_time_window(mut self, start: GameTimestamp, end: GameTimestamp) -> Self {
+        self.time_window = Some((start.tick, end.tick));
+        self
+    }
+
+    /// Only consider memories of events within `radius` of `center`.
+    #[must_use]
+    pub fn with_location(mut self, center: Location, radius: f32) -> Self {
+        self.location = Some((center, radius));
+        self
+    }
+
+    /// Only consider memories at least this strong.
+    #[must_use]
+    pub fn with_min_strength(mut self, min_strength: f32) -> Self {
+        self.min_strength = Some(min_strength);
+        self
+    }
+
+    /// Only consider memories whose valence lies in `min..=max`
+    /// (e.g. `-1.0, -0.2` for negative memories).
+    #[must_use]
+    pub fn with_valence(mut self, min: f32, max: f32) -> Self {
+        self.valence = Some((min, max));
+        self
+    }
+
+    /// Only consider memories carrying every one of `tags`.
+    #[must_use]
+    pub fn with_tags<S: Into<String>>(mut self, tags: impl IntoIterator<Item = S>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether `memory` passes every filter of this query.
+    #[must_use]
+    pub fn matches(&self, memory: &MemoryEntry) -> bool {
+        match memory {
+            MemoryEntry::Episodic(m) => self.admits(m),
+            MemoryEntry::Semantic(m) => self.admits(m),
+            MemoryEntry::Emotional(m) => self.admits(m),
+            MemoryEntry::Social(m) => self.admits(m),
+            MemoryEntry::Reflective(m) => self.admits(m),
+            MemoryEntry::Procedural(m) => self.admits(m),
+            MemoryEntry::Injected(m) => self.admits(m),
+        }
+    }
+
+    /// The memories of `bank` that pass every filter.  Memories are
+    /// filtered in place; only the survivors are cloned.
+    #[must_use]
+    pub fn candidates(&self, bank: &MemoryBank) -> Vec<MemoryEntry> {
+        let mut entries = Vec::new();
+        self.collect(&bank.episodic, &mut entries);
+        self.collect(&bank.semantic, &mut entries);
+        self.collect(&bank.emotional, &mut entries);
+        self.collect(&bank.social, &mut entries);
+        self.collect(&bank.reflective, &mut entries);
+        self.collect(&bank.procedural, &mut entries);
+        self.collect(&bank.injected, &mut entries);
+        entries
+    }
+
+    /// Push a clone of every memory in `memories` that passes the filters.
+    fn collect<M: Filterable + Clone>(&self, memories: &[M], entries: &mut Vec<MemoryEntry>) {
+        if !self.memory_types.is_empty() && !self.memory_types.contains(&M::TYPE) {
+            return;
+        }
+        entries.extend(
+            memories
+                .iter()
+                .filter(|m| self.admits(*m))
+                .cloned()
+                .map(M::into_entry),
+        );
+    }
+
+    /// Whether the episodic `memory` passes every filter, e.g. a cold memory
+    /// before it is paged in.
+    pub(crate) fn admits_episodic(&self, memory: &EpisodicMemory) -> bool {
+        self.admits(memory)
+    }
+
+    /// Whether `memory` passes every filter.
+    fn admits<M: Filterable>(&self, memory: &M) -> bool {
+        if !self.memory_types.is_empty() && !self.memory_types.contains(&M::TYPE) {
+            return false;
+        }
+        if let Some(entity) = self.entity
+            && !memory.involves(entity)
+        {
+            return false;
+        }
+        if let Some((start, end)) = self.time_window
+            && !(start..=end).contains(&memory.timestamp().tick)
+        {
+            return false;
+        }
+        if let Some((center, radius)) = self.location
+            && !memory
+                .location()
+                .is_some_and(|at| distance(at, &center) <= radius)
+        {
+            return false;
+        }
+        if let Some(min) = self.min_strength
+            && memory.strength() < min
+        {
+            return false;
+        }
+        if let Some((min, max)) = self.valence
+            && !memory.valence().is_some_and(|v| (min..=max).contains(&v))
+        {
+            return false;
+        }
+        self.tags.iter().all(|wanted| {
+            memory
+                .tags()
+                .iter()
+                .any(|tag| tag.eq_ignore_ascii_case(wanted))
+        })
+    }
+}
+
+/// How firmly the memory is held: episodic strength, confidence in a fact
+/// or reflection, intensity of a feeling, trust in a rumour's source, skill
+/// proficiency.  Injected backstory is always 1.0.
+#[must_use]
+pub fn strength(memory: &MemoryEntry) -> f32 {
+    match memory {
+        MemoryEntry::Episodic(m) => m.strength(),
+        MemoryEntry::Semantic(m) => m.strength(),
+        MemoryEntry::Emotional(m) => m.strength(),
+        MemoryEntry::Social(m) => m.strength(),
+        MemoryEntry::Reflective(m) => m.strength(),
+        MemoryEntry::Procedural(m) => m.strength(),
+        MemoryEntry::Injected(m) => m.strength(),
+    }
+}
+
+/// The properties the filters read, taken straight from a memory so
+/// candidates can be filtered by reference.
+trait Filterable {
+    /// Type of the memory.
+    const TYPE: MemoryType;
+
+    /// The memory as a [`MemoryEntry`].
+    fn into_entry(self) -> MemoryEntry;
+
+    /// Whether the memory is about `entity`.
+    fn involves(&self, _entity: EntityId) -> bool {
+        false
+    }
+
+    /// When the memory was formed (or last updated, for feelings and
+    /// skills).
+    fn timestamp(&self) -> GameTimestamp;
+
+    /// Where the remembered event happened — only episodic memories have
+    /// a place.
+    fn location(&self) -> Option<&Location> {
+        None
+    }
+
+    /// See [`strength`].
+    fn strength(&self) -> f32;
+
+    /// Signed valence: the event's emotional valence, the rumour's
+    /// sentiment, or the feeling's pleasure.
+    fn valence(&self) -> Option<f32> {
+        None
+    }
+
+    /// Tags the memory carries (injected backstory only).
+    fn tags(&self) -> &[String] {
+        &[]
+    }
+}
+
+impl Filterable for EpisodicMemory {
+    const TYPE: MemoryType = MemoryType::Episodic;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Episodic(self)
+    }
+    fn involves(&self, entity: EntityId) -> bool {
+        self.participants.contains(&entity)
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.timestamp
+    }
+    fn location(&self) -> Option<&Location> {
+        Some(&self.location)
+    }
+    fn strength(&self) -> f32 {
+        self.strength
+    }
+    fn valence(&self) -> Option<f32> {
+        Some(self.emotional_valence)
+    }
+}
+
+impl Filterable for SemanticMemory {
+    const TYPE: MemoryType = MemoryType::Semantic;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Semantic(self)
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.created_at
+    }
+    fn strength(&self) -> f32 {
+        self.confidence
+    }
+}
+
+impl Filterable for EmotionalMemory {
+    const TYPE: MemoryType = MemoryType::Emotional;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Emotional(self)
+    }
+    fn involves(&self, entity: EntityId) -> bool {
+        self.target == entity
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.last_updated
+    }
+    fn strength(&self) -> f32 {
+        self.intensity
+    }
+    fn valence(&self) -> Option<f32> {
+        Some(self.pad_state.pleasure)
+    }
+}
+
+impl Filterable for SocialMemory {
+    const TYPE: MemoryType = MemoryType::Social;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Social(self)
+    }
+    fn involves(&self, entity: EntityId) -> bool {
+        self.about == entity
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.received_at
+    }
+    fn strength(&self) -> f32 {
+        self.trust_in_source
+    }
+    fn valence(&self) -> Option<f32> {
+        Some(self.sentiment)
+    }
+}
+
+impl Filterable for ReflectiveMemory {
+    const TYPE: MemoryType = MemoryType::Reflective;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Reflective(self)
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.generated_at
+    }
+    fn strength(&self) -> f32 {
+        self.confidence
+    }
+}
+
+impl Filterable for ProceduralMemory {
+    const TYPE: MemoryType = MemoryType::Procedural;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Procedural(self)
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.last_practiced
+    }
+    fn strength(&self) -> f32 {
+        self.proficiency
+    }
+}
+
+impl Filterable for InjectedMemory {
+    const TYPE: MemoryType = MemoryType::Injected;
+    fn into_entry(self) -> MemoryEntry {
+        MemoryEntry::Injected(self)
+    }
+    fn timestamp(&self) -> GameTimestamp {
+        self.memory_timestamp
+    }
+    fn strength(&self) -> f32 {
+        1.0
+    }
+    fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::injected::InjectedPriority;
+
+    fn event(who: EntityId, tick: u64, x: f32, valence: f32) -> EpisodicMemory {
+        EpisodicMemory::new(
+            "event",
+            vec![who],
+            Location { x, y: 0.0, z: 0.0 },
+            GameTimestamp::now(tick),
+            valence,
+            0.5,
+        )
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(event(EntityId::new(), 0, 0.0, 0.0));
+        bank.injected.push(InjectedMemory::new(
+            "backstory",
+            0.5,
+            GameTimestamp::now(0),
+            InjectedPriority::Normal,
+        ));
+        assert_eq!(RetrievalQuery::new().candidates(&bank).len(), 2);
+    }
+
+    #[test]
+    fn negative_memories_about_player_last_week_near_site() {
+        let player = EntityId::new();
+        let day = crate::time::TimeModel::default().ticks_per_day;
+        let week = 7 * day;
+        let now = 10 * day;
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(event(player, now - 1_000, 5.0, -0.8)); // match
+        bank.episodic.push(event(player, now - 1_000, 5.0, 0.6)); // positive
+        bank.episodic.push(event(player, now - week - 1, 5.0, -0.8)); // too old
+        bank.episodic.push(event(player, now - 1_000, 500.0, -0.8)); // too far
+        bank.episodic.push(event(EntityId::new(), now - 1_000, 5.0, -0.8)); // someone else
+        bank.social.push(SocialMemory::new(
+            player,
+            EntityId::new(),
+            "cheats at dice",
+            0.7,
+            1,
+            GameTimestamp::now(now - 1_000),
+        )); // no location
+
+        let query = RetrievalQuery::new()
+            .with_entity(player)
+            .with_time_window(GameTimestamp::now(now - week), GameTimestamp::now(now))
+            .with_location(Location::default(), 50.0)
+            .with_valence(-1.0, -0.2);
+        let hits = query.candidates(&bank);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id(), bank.episodic[0].id);
+    }
+
+    #[test]
+    fn type_strength_and_tag_filters() {
+        let player = EntityId::new();
+        let mut bank = MemoryBank::new();
+        let mut faded = event(player, 0, 0.0, 0.0);
+        faded.strength = 0.1;
+        bank.episodic.push(faded);
+        bank.episodic.push(event(player, 0, 0.0, 0.0));
+        bank.injected.push(
+            InjectedMemory::new("war", 0.9, GameTimestamp::now(0), InjectedPriority::High)
+                .with_tags(vec!["War".to_string(), "trauma".to_string()]),
+        );
+
+        let strong = RetrievalQuery::new()
+            .with_memory_types(&[MemoryType::Episodic])
+            .with_min_strength(0.5)
+            .candidates(&bank);
+        assert_eq!(strong.len(), 1);
+        assert_eq!(strong[0].id(), bank.episodic[1].id);
+
+        let tagged = RetrievalQuery::new().with_tags(["war"]).candidates(&bank);
+        assert_eq!(tagged.len(), 1);
+        assert_eq!(tagged[0].memory_type(), MemoryType::Injected);
+        assert!(RetrievalQuery::new().with_tags(["war", "joy"]).candidates(&bank).is_empty());
+    }
+}
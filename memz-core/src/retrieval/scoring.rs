@@ -8,10 +8,16 @@
 //!   Importance(m)  = pre-computed importance score (0–1)
 //!   Emotional(m)  = |`emotional_valence`| × `emotional_volatility`
 //!   Social(m)     = `trust_in_source` × `recency_of_social_transmission`
+//!
+//! plus an optional sixth factor, w₆·Place(m) = 1 − distance / radius, for
+//! memories formed near the NPC's current position.
 
 use crate::memory::MemoryEntry;
 use crate::retrieval::ScoreBreakdown;
-use crate::types::{Embedding, GameTimestamp};
+use crate::time::TimeModel;
+use crate::types::{Embedding, GameTimestamp, Location};
+
+use super::spatial::distance;
 
 /// Default Ebbinghaus decay constant λ (per game-day).
 const DEFAULT_DECAY_LAMBDA: f64 = 0.05;
@@ -22,14 +28,42 @@ pub fn compute_breakdown(
     memory: &MemoryEntry,
     context_embedding: &Embedding,
     current_time: &GameTimestamp,
+    time: &TimeModel,
+    w_recency: f32,
+    w_relevance: f32,
+    w_importance: f32,
+    w_emotional: f32,
+    w_social: f32,
+) -> ScoreBreakdown {
+    compute_breakdown_with_relevance(
+        memory,
+        relevance_score(memory, context_embedding),
+        current_time,
+        time,
+        w_recency,
+        w_relevance,
+        w_importance,
+        w_emotional,
+        w_social,
+    )
+}
+
+/// Compute the score breakdown using an externally computed relevance in
+/// `[0, 1]` (e.g. a lexical match score) instead of embedding similarity.
+#[must_use]
+pub fn compute_breakdown_with_relevance(
+    memory: &MemoryEntry,
+    relevance: f64,
+    current_time: &GameTimestamp,
+    time: &TimeModel,
     w_recency: f32,
     w_relevance: f32,
     w_importance: f32,
     w_emotional: f32,
     w_social: f32,
 ) -> ScoreBreakdown {
-    let recency = f64::from(w_recency) * recency_score(memory, current_time);
-    let relevance = f64::from(w_relevance) * relevance_score(memory, context_embedding);
+    let recency = f64::from(w_recency) * recency_score(memory, current_time, time);
+    let relevance = f64::from(w_relevance) * relevance.clamp(0.0, 1.0);
     let importance = f64::from(w_importance) * importance_score(memory);
     let emotional = f64::from(w_emotional) * emotional_score(memory);
     let social = f64::from(w_social) * social_score(memory);
@@ -40,13 +74,14 @@ pub fn compute_breakdown(
         importance,
         emotional,
         social,
+        ..ScoreBreakdown::default()
     }
 }
 
 /// Recency score: Ebbinghaus forgetting curve R = e^(-λ · ΔT).
 ///
-/// ΔT is measured in game-days (computed from tick difference).
-fn recency_score(memory: &MemoryEntry, current_time: &GameTimestamp) -> f64 {
+/// ΔT is measured in game-days under `time`.
+fn recency_score(memory: &MemoryEntry, current_time: &GameTimestamp, time: &TimeModel) -> f64 {
     let memory_timestamp = match memory {
         MemoryEntry::Episodic(m) => &m.timestamp,
         MemoryEntry::Social(m) => &m.received_at,
@@ -60,34 +95,45 @@ fn recency_score(memory: &MemoryEntry, current_time: &GameTimestamp) -> f64 {
         }
     };
 
-    let delta_ticks = current_time.tick.saturating_sub(memory_timestamp.tick);
-    // Approximate: 1 game-day ≈ 72000 ticks (assuming 20 ticks/sec, 1 hour of real time ≈ 1 day)
-    // This is configurable but we use a sensible default.
-    let delta_days = delta_ticks as f64 / 72_000.0;
+    let delta_days = time.days_between(memory_timestamp, current_time);
 
     (-DEFAULT_DECAY_LAMBDA * delta_days).exp()
 }
 
 /// Relevance score: cosine similarity between context embedding and memory embedding.
 fn relevance_score(memory: &MemoryEntry, context_embedding: &Embedding) -> f64 {
+    // Memories without embeddings get a neutral score.
+    embedding_similarity(memory, context_embedding).unwrap_or(0.5)
+}
+
+/// Cosine similarity between the context and the memory's embedding,
+/// clamped to `[0, 1]`; `None` if the memory has no embedding.
+#[must_use]
+pub fn embedding_similarity(memory: &MemoryEntry, context_embedding: &Embedding) -> Option<f64> {
     let memory_embedding = match memory {
         MemoryEntry::Episodic(m) => m.embedding.as_ref(),
         MemoryEntry::Semantic(m) => m.embedding.as_ref(),
         MemoryEntry::Injected(m) => m.embedding.as_ref(),
-        // These types don't have embeddings — return a neutral score.
+        // These types don't have embeddings.
         MemoryEntry::Social(_)
         | MemoryEntry::Emotional(_)
         | MemoryEntry::Reflective(_)
         | MemoryEntry::Procedural(_) => None,
-    };
+    }?;
+    let sim = context_embedding.cosine_similarity(memory_embedding);
+    // Clamp to [0, 1] — negative similarity is treated as 0.
+    Some(f64::from(sim.max(0.0)))
+}
 
-    match memory_embedding {
-        Some(emb) => {
-            let sim = context_embedding.cosine_similarity(emb);
-            // Clamp to [0, 1] — negative similarity is treated as 0.
-            f64::from(sim.max(0.0))
+/// Place-cue score: 1.0 at `position`, falling linearly to 0.0 at `radius`.
+/// Only episodic memories have a location; everything else scores 0.
+#[must_use]
+pub fn place_score(memory: &MemoryEntry, position: &Location, radius: f32) -> f64 {
+    match memory {
+        MemoryEntry::Episodic(m) if radius > 0.0 => {
+            f64::from((1.0 - distance(&m.location, position) / radius).max(0.0))
         }
-        None => 0.5, // neutral relevance for memories without embeddings
+        _ => 0.0,
     }
 }
 
@@ -150,8 +196,8 @@ mod tests {
             0.5,
         ));
 
-        let score_at_1 = recency_score(&episodic, &t1);
-        let score_at_10 = recency_score(&episodic, &t10);
+        let score_at_1 = recency_score(&episodic, &t1, &TimeModel::default());
+        let score_at_10 = recency_score(&episodic, &t10, &TimeModel::default());
 
         assert!(score_at_1 > score_at_10, "Recency should decay over time");
         assert!(score_at_1 > 0.9, "1 day should still be quite recent");
@@ -0,0 +1,215 @@
+//! Spatial index — place cues for location-aware recall (§12.4)
+//!
+//! Places are strong retrieval cues: an NPC walking past the spot where
+//! their friend died should be reminded of it.  A [`SpatialIndex`] buckets a
+//! bank's episodic memories into a uniform grid on the ground plane (x, y)
+//! with `place_cue_radius`-sized cells, so "what happened near here?" only
+//! looks at the cells around a position instead of every memory.
+//!
+//! Like the other per-bank indexes it is kept current with
+//! [`SpatialIndex::sync`].  The place-cue factor itself is
+//! [`scoring::place_score`](super::scoring::place_score).
+
+use std::collections::{HashMap, HashSet};
+
+use crate::config::RetrievalConfig;
+use crate::memory::MemoryBank;
+use crate::types::{Location, MemoryId};
+
+use super::index::IndexSync;
+
+type Cell = (i32, i32);
+
+/// Uniform-grid index over the locations of one bank's episodic memories.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<(MemoryId, Location)>>,
+    /// Cell of every indexed memory, for removal.
+    memories: HashMap<MemoryId, Cell>,
+}
+
+impl SpatialIndex {
+    /// Create an empty index with cells of `place_cue_radius`.
+    #[must_use]
+    pub fn new(config: &RetrievalConfig) -> Self {
+        Self {
+            cell_size: config.place_cue_radius.max(1.0),
+            cells: HashMap::new(),
+            memories: HashMap::new(),
+        }
+    }
+
+    /// Create an index and sync it with `bank`.
+    #[must_use]
+    pub fn build(config: &RetrievalConfig, bank: &MemoryBank) -> Self {
+        let mut index = Self::new(config);
+        index.sync(bank);
+        index
+    }
+
+    /// Number of indexed memories.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.memories.len()
+    }
+
+    /// Whether no memory is indexed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.memories.is_empty()
+    }
+
+    /// Index a memory formed at `location` (replacing any previous entry).
+    pub fn insert(&mut self, id: MemoryId, location: Location) {
+        self.remove(id);
+        let cell = self.cell(&location);
+        self.cells.entry(cell).or_default().push((id, location));
+        self.memories.insert(id, cell);
+    }
+
+    /// Drop a memory from the index.
+    pub fn remove(&mut self, id: MemoryId) {
+        let Some(cell) = self.memories.remove(&id) else {
+            return;
+        };
+        if let Some(entries) = self.cells.get_mut(&cell) {
+            entries.retain(|(other, _)| *other != id);
+            if entries.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    /// Bring the index in line with `bank`'s episodic memories.
+    pub fn sync(&mut self, bank: &MemoryBank) -> IndexSync {
+        let mut report = IndexSync::default();
+        let live: HashSet<MemoryId> = bank.episodic.iter().map(|m| m.id).collect();
+
+        let stale: Vec<MemoryId> = self
+            .memories
+            .keys()
+            .filter(|id| !live.contains(id))
+            .copied()
+            .collect();
+        for id in stale {
+            self.remove(id);
+            report.removed += 1;
+        }
+        for memory in &bank.episodic {
+            if !self.memories.contains_key(&memory.id) {
+                self.insert(memory.id, memory.location);
+                report.inserted += 1;
+            }
+        }
+        report
+    }
+
+    /// Memories formed within `radius` of `position`, nearest first, with
+    /// their distance.
+    ///
+    /// Visits the cells the radius covers, or every occupied cell once
+    /// that is fewer — so a huge (or infinite) radius is a linear scan.
+    #[must_use]
+    pub fn near(&self, position: &Location, radius: f32) -> Vec<(MemoryId, f32)> {
+        let reach = (radius / self.cell_size).ceil();
+        let span = 2.0 * reach + 1.0;
+        let cells: Vec<&Vec<(MemoryId, Location)>> =
+            if span.is_nan() || span * span >= self.cells.len() as f32 {
+                self.cells.values().collect()
+            } else {
+                let (cx, cy) = self.cell(position);
+                let reach = reach as i32;
+                (cx - reach..=cx + reach)
+                    .flat_map(|x| (cy - reach..=cy + reach).map(move |y| (x, y)))
+                    .filter_map(|cell| self.cells.get(&cell))
+                    .collect()
+            };
+
+        let mut hits = Vec::new();
+        for (id, location) in cells.into_iter().flatten() {
+            let d = distance(location, position);
+            if d <= radius {
+                hits.push((*id, d));
+            }
+        }
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        hits
+    }
+
+    fn cell(&self, location: &Location) -> Cell {
+        (
+            (location.x / self.cell_size).floor() as i32,
+            (location.y / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+/// Euclidean distance between two locations.
+#[must_use]
+pub fn distance(a: &Location, b: &Location) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::episodic::EpisodicMemory;
+    use crate::types::GameTimestamp;
+
+    fn at(x: f32, y: f32) -> EpisodicMemory {
+        EpisodicMemory::new(
+            "event",
+            vec![],
+            Location { x, y, z: 0.0 },
+            GameTimestamp::now(0),
+            0.0,
+            0.5,
+        )
+    }
+
+    #[test]
+    fn near_finds_memories_across_cells() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(at(10.0, 10.0));
+        bank.episodic.push(at(-20.0, 5.0)); // neighbouring cell
+        bank.episodic.push(at(400.0, 400.0));
+        let index = SpatialIndex::build(&RetrievalConfig::default(), &bank);
+
+        let hits = index.near(&Location { x: 0.0, y: 0.0, z: 0.0 }, 30.0);
+        let ids: Vec<MemoryId> = hits.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![bank.episodic[0].id, bank.episodic[1].id]);
+    }
+
+    #[test]
+    fn huge_radius_scans_occupied_cells() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(at(10.0, 10.0));
+        bank.episodic.push(at(-3.0e6, 4.0e6));
+        let index = SpatialIndex::build(&RetrievalConfig::default(), &bank);
+
+        let position = Location::default();
+        assert_eq!(index.near(&position, f32::INFINITY).len(), 2);
+        assert_eq!(index.near(&position, 1.0e7).len(), 2);
+        assert!(index.near(&position, f32::NAN).is_empty());
+    }
+
+    #[test]
+    fn sync_tracks_inserts_and_evictions() {
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(at(0.0, 0.0));
+        bank.episodic.push(at(1.0, 1.0));
+        let mut index = SpatialIndex::build(&RetrievalConfig::default(), &bank);
+        assert_eq!(index.len(), 2);
+
+        bank.episodic.remove(0);
+        bank.episodic.push(at(2.0, 2.0));
+        assert_eq!(index.sync(&bank), IndexSync { inserted: 1, removed: 1 });
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.near(&Location::default(), 1.0).len(), 0);
+    }
+}
@@ -234,7 +234,7 @@ mod tests {
             "I grew up in a fishing village on the northern coast.",
             &config,
         )
-        .unwrap();
+        .expect("validation should not error");
         assert!(matches!(result, SafetyVerdict::Approved));
     }
 
@@ -242,7 +242,7 @@ mod tests {
     fn rejects_too_long() {
         let config = default_config();
         let long_content = "a".repeat(600);
-        let result = validate_injection(&long_content, &config).unwrap();
+        let result = validate_injection(&long_content, &config).expect("validation should not error");
         assert!(matches!(result, SafetyVerdict::Rejected { .. }));
     }
 
@@ -253,7 +253,7 @@ mod tests {
             "Check out https://example.com for my backstory",
             &config,
         )
-        .unwrap();
+        .expect("validation should not error");
         assert!(matches!(result, SafetyVerdict::Rejected { .. }));
     }
 
@@ -264,21 +264,21 @@ mod tests {
             "```python\nprint('hello')\n```",
             &config,
         )
-        .unwrap();
+        .expect("validation should not error");
         assert!(matches!(result, SafetyVerdict::Rejected { .. }));
     }
 
     #[test]
     fn rejects_game_breaking() {
         let config = default_config();
-        let result = validate_injection("I am a god and I am invincible", &config).unwrap();
+        let result = validate_injection("I am a god and I am invincible", &config).expect("validation should not error");
         assert!(matches!(result, SafetyVerdict::Rejected { .. }));
     }
 
     #[test]
     fn rejects_empty() {
         let config = default_config();
-        let result = validate_injection("", &config).unwrap();
+        let result = validate_injection("", &config).expect("validation should not error");
         assert!(matches!(result, SafetyVerdict::Rejected { .. }));
     }
 
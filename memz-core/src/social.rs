@@ -14,6 +14,7 @@
 //!   - Tenenbaum et al. (2011). "How to Grow a Mind."
 
 use crate::memory::social::SocialMemory;
+use crate::time::TimeModel;
 use crate::types::{EntityId, GameTimestamp, PersonalityTraits};
 
 /// The result of attempting to propagate a social memory to an NPC.
@@ -56,6 +57,7 @@ const HYSTERESIS: f32 = 0.05;
 /// * `receiver_emotional_state_toward_subject` — Receiver's current emotional state toward the claim's subject (-1.0 to 1.0).
 /// * `source_reliability` — Track record of the source's past claims (0.0–1.0).
 /// * `current_time` — Current game timestamp.
+/// * `time` — Tick/day conversion for information freshness.
 #[must_use] 
 pub fn propagate_memory(
     claim: &SocialMemory,
@@ -68,6 +70,7 @@ pub fn propagate_memory(
     receiver_emotional_state_toward_subject: f32,
     source_reliability: f32,
     current_time: GameTimestamp,
+    time: &TimeModel,
 ) -> PropagationResult {
     // --- 1. Prior: Direct experience vs hearsay ---
     let (prior_weight, hearsay_weight) = if has_direct_experience {
@@ -98,10 +101,7 @@ pub fn propagate_memory(
     };
 
     // --- 6. Information freshness (temporal discounting) ---
-    let days_since_claim = {
-        let delta_ticks = current_time.tick.saturating_sub(claim.received_at.tick);
-        delta_ticks as f32 / 72_000.0
-    };
+    let days_since_claim = current_time.days_since(&claim.received_at, time);
     let freshness = (-0.1 * days_since_claim).exp();
 
     // --- 7. Information chain depth (first-hand > second-hand > rumor) ---
@@ -214,6 +214,7 @@ mod tests {
             0.0,   // neutral emotional state
             0.8,   // source is reliable
             GameTimestamp::now(40_000),
+            &TimeModel::default(),
         );
 
         assert!(matches!(result, PropagationResult::Accepted { .. }));
@@ -237,6 +238,7 @@ mod tests {
             0.0,
             0.2,   // source unreliable
             GameTimestamp::now(200_000), // stale info
+            &TimeModel::default(),
         );
 
         assert!(matches!(
@@ -262,6 +264,7 @@ mod tests {
             0.5,
             0.5,
             GameTimestamp::now(40_000),
+            &TimeModel::default(),
         );
 
         assert!(matches!(result, PropagationResult::Accepted { .. }));
@@ -0,0 +1,137 @@
+//! Game-time model — one source of truth for tick conversions.
+//!
+//! MEMZ counts time in game ticks ([`GameTimestamp::tick`]).  Everything that
+//! reasons in game-hours or game-days (decay, recency scoring, eviction
+//! rings, gossip freshness, reputation decay, replay novelty) and everything
+//! scheduled in real seconds (per-tick maintenance cadences) converts through
+//! a [`TimeModel`], configured once as `[time]` in `memz.toml`.
+//!
+//! The default — 60 ticks per real second, 72,000 ticks per game-day — is
+//! what MEMZ has always assumed.  Veloren integrations should derive theirs
+//! from the server tick rate and `TimeOfDay` speed with
+//! [`TimeModel::from_day_cycle`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::GameTimestamp;
+
+/// Seconds in one game-day of `TimeOfDay`.
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Tick rate and game-day length.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeModel {
+    /// Server ticks per real-time second.
+    #[serde(default = "default_ticks_per_second")]
+    pub ticks_per_second: f64,
+    /// Ticks per game-day (24 game-hours).
+    #[serde(default = "default_ticks_per_day")]
+    pub ticks_per_day: u64,
+}
+
+impl Default for TimeModel {
+    fn default() -> Self {
+        Self {
+            ticks_per_second: default_ticks_per_second(),
+            ticks_per_day: default_ticks_per_day(),
+        }
+    }
+}
+
+impl TimeModel {
+    /// Model for a server running at `ticks_per_second` whose time of day
+    /// advances `day_cycle_factor` game-seconds per real second (Veloren's
+    /// `DAY_CYCLE_FACTOR`).
+    #[must_use]
+    pub fn from_day_cycle(ticks_per_second: f64, day_cycle_factor: f64) -> Self {
+        let real_seconds_per_day = SECONDS_PER_DAY / day_cycle_factor.max(f64::EPSILON);
+        Self {
+            ticks_per_second,
+            ticks_per_day: (real_seconds_per_day * ticks_per_second).round().max(1.0) as u64,
+        }
+    }
+
+    /// Ticks per game-hour.
+    #[must_use]
+    pub fn ticks_per_hour(&self) -> f64 {
+        self.ticks_per_day.max(1) as f64 / 24.0
+    }
+
+    /// Game-days spanned by `ticks`.
+    #[must_use]
+    pub fn ticks_to_days(&self, ticks: u64) -> f64 {
+        ticks as f64 / self.ticks_per_day.max(1) as f64
+    }
+
+    /// Game-hours spanned by `ticks`.
+    #[must_use]
+    pub fn ticks_to_hours(&self, ticks: u64) -> f64 {
+        ticks as f64 / self.ticks_per_hour()
+    }
+
+    /// Game-days from `earlier` to `later` (0 if `later` is not later).
+    #[must_use]
+    pub fn days_between(&self, earlier: &GameTimestamp, later: &GameTimestamp) -> f64 {
+        self.ticks_to_days(later.tick.saturating_sub(earlier.tick))
+    }
+
+    /// Game-hours from `earlier` to `later` (0 if `later` is not later).
+    #[must_use]
+    pub fn hours_between(&self, earlier: &GameTimestamp, later: &GameTimestamp) -> f64 {
+        self.ticks_to_hours(later.tick.saturating_sub(earlier.tick))
+    }
+
+    /// Ticks in `seconds` of real time (at least 1) — for scheduling.
+    #[must_use]
+    pub fn ticks_per_real_seconds(&self, seconds: f64) -> u64 {
+        (seconds * self.ticks_per_second).round().max(1.0) as u64
+    }
+
+    /// Ticks in `hours` of game time (at least 1) — for scheduling.
+    #[must_use]
+    pub fn ticks_per_game_hours(&self, hours: f64) -> u64 {
+        (hours * self.ticks_per_hour()).round().max(1.0) as u64
+    }
+}
+
+fn default_ticks_per_second() -> f64 {
+    60.0
+}
+
+fn default_ticks_per_day() -> u64 {
+    72_000
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matches_historical_constants() {
+        let time = TimeModel::default();
+        assert!((time.ticks_to_days(72_000) - 1.0).abs() < 1e-9);
+        assert!((time.ticks_per_hour() - 3_000.0).abs() < 1e-9);
+        assert_eq!(time.ticks_per_real_seconds(1.0), 60);
+    }
+
+    #[test]
+    fn veloren_day_cycle() {
+        // 30 TPS, a game-day every 30 real minutes.
+        let time = TimeModel::from_day_cycle(30.0, 48.0);
+        assert_eq!(time.ticks_per_day, 54_000);
+        assert_eq!(time.ticks_per_game_hours(1.0), 2_250);
+    }
+
+    #[test]
+    fn conversions_are_saturating() {
+        let time = TimeModel::default();
+        let later = GameTimestamp::now(10);
+        let earlier = GameTimestamp::now(5);
+        assert!(time.days_between(&later, &earlier).abs() < f64::EPSILON);
+        assert!(time.hours_between(&earlier, &later) > 0.0);
+    }
+}
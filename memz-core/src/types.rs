@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
 
+use crate::time::TimeModel;
+
 // ---------------------------------------------------------------------------
 // Identity Types
 // ---------------------------------------------------------------------------
@@ -16,12 +18,23 @@ use uuid::Uuid;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct EntityId(pub Uuid);
 
+/// `UUIDv5` namespace for [`EntityId::derived`].
+const ENTITY_NAMESPACE: Uuid = Uuid::from_u128(0x6d65_6d7a_656e_5000_8000_6964_7300_0001);
+
 impl EntityId {
     /// Create a new random entity ID.
     #[must_use]
     pub fn new() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// Deterministic ID (`UUIDv5`) for a stable external key — the same key
+    /// yields the same ID in every session, so saved banks stay addressable
+    /// across restarts.
+    #[must_use]
+    pub fn derived(key: &str) -> Self {
+        Self(Uuid::new_v5(&ENTITY_NAMESPACE, key.as_bytes()))
+    }
 }
 
 impl Default for EntityId {
@@ -64,12 +77,23 @@ impl fmt::Display for EntityId {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SettlementId(pub Uuid);
 
+/// `UUIDv5` namespace for [`SettlementId::derived`].
+const SETTLEMENT_NAMESPACE: Uuid = Uuid::from_u128(0x6d65_6d7a_656e_5000_8000_7369_7465_0001);
+
 impl SettlementId {
     /// Generate a new random settlement ID.
     #[must_use]
     pub fn new() -> Self {
         Self(Uuid::new_v4())
     }
+
+    /// Deterministic ID (`UUIDv5`) for a stable external key, like
+    /// [`EntityId::derived`] — reputation boards and memories keep pointing
+    /// at the same settlement across restarts.
+    #[must_use]
+    pub fn derived(key: &str) -> Self {
+        Self(Uuid::new_v5(&SETTLEMENT_NAMESPACE, key.as_bytes()))
+    }
 }
 
 impl Default for SettlementId {
@@ -137,6 +161,9 @@ pub struct GameTimestamp {
 
 impl GameTimestamp {
     /// Create a new game timestamp at the current wall-clock time.
+    ///
+    /// Reproducible runs take timestamps from a
+    /// [`Clock`](crate::determinism::Clock) instead.
     #[must_use]
     pub fn now(tick: u64) -> Self {
         Self {
@@ -145,18 +172,16 @@ impl GameTimestamp {
         }
     }
 
-    /// Game-days elapsed since `other`, assuming 24 000 ticks per game-day.
+    /// Game-days elapsed since `other` under `time`.
     #[must_use]
-    pub fn days_since(&self, other: &Self) -> f32 {
-        const TICKS_PER_DAY: f32 = 24_000.0;
-        (self.tick.saturating_sub(other.tick)) as f32 / TICKS_PER_DAY
+    pub fn days_since(&self, other: &Self, time: &TimeModel) -> f32 {
+        time.days_between(other, self) as f32
     }
 
-    /// Game-hours elapsed since `other`, assuming 1 000 ticks per game-hour.
+    /// Game-hours elapsed since `other` under `time`.
     #[must_use]
-    pub fn hours_since(&self, other: &Self) -> f32 {
-        const TICKS_PER_HOUR: f32 = 1_000.0;
-        (self.tick.saturating_sub(other.tick)) as f32 / TICKS_PER_HOUR
+    pub fn hours_since(&self, other: &Self, time: &TimeModel) -> f32 {
+        time.hours_between(other, self) as f32
     }
 }
 
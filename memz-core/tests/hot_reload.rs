@@ -0,0 +1,57 @@
+//! Config Hot-Reload — edits to `memz.toml` on disk reach the live config (§16)
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use memz_core::hot_reload::{ConfigWatcher, LiveConfig, RELOAD_DEBOUNCE};
+
+const BASE: &str = "[retrieval]\ntop_k = 5\n";
+
+fn write(path: &Path, content: &str) {
+    std::fs::write(path, content).expect("write config");
+}
+
+/// Poll `condition` for up to five seconds.
+fn eventually(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    condition()
+}
+
+#[test]
+fn watcher_swaps_valid_edits_and_rejects_invalid_ones() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("memz.toml");
+    write(&path, BASE);
+
+    let live = LiveConfig::load(&path).expect("initial config");
+    let _watcher = ConfigWatcher::spawn(&path, live.clone()).expect("watcher");
+
+    write(&path, "[retrieval]\ntop_k = 8\n");
+    assert!(eventually(|| live.current().retrieval.top_k == 8), "edit was not picked up");
+    let generation = live.generation();
+
+    // Unparseable, then semantically invalid: both are rejected.
+    write(&path, "[retrieval\ntop_k = 3\n");
+    write(&path, "[retrieval]\ntop_k = 3\nhybrid_fusion = \"median\"\n");
+    std::thread::sleep(RELOAD_DEBOUNCE * 5);
+    assert_eq!(live.current().retrieval.top_k, 8);
+    assert_eq!(live.generation(), generation);
+
+    // The hardware profile stays as loaded; the rest of the edit applies.
+    write(&path, "[general]\nprofile = \"server\"\n[retrieval]\ntop_k = 4\n");
+    assert!(eventually(|| live.current().retrieval.top_k == 4));
+    assert_eq!(live.current().general.profile, "auto");
+}
+
+#[test]
+fn shipped_config_is_valid() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../memz.toml");
+    let live = LiveConfig::load(&path).expect("memz.toml loads and validates");
+    assert!(live.reload_from(&path).expect("reload").applied.is_empty());
+}
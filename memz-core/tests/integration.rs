@@ -3,10 +3,14 @@
 //! These tests verify complete memory lifecycle scenarios:
 //! save/load round-trips, multi-NPC interactions, event→memory→behavior chains.
 
+use std::time::Duration;
+
 use memz_core::behavior;
-use memz_core::config::{MemoryConfig, PersistenceConfig};
+use memz_core::config::{EvictionConfig, MemoryConfig, PersistenceConfig, RetrievalConfig};
 use memz_core::consolidation;
 use memz_core::decay;
+use memz_core::determinism::IdGenerator;
+use memz_core::eviction;
 use memz_core::memory::episodic::EpisodicMemory;
 use memz_core::memory::emotional::EmotionalMemory;
 use memz_core::memory::social::SocialMemory;
@@ -15,7 +19,9 @@ use memz_core::observation::{self, EventKind, ObservedEvent};
 use memz_core::persistence::PersistenceEngine;
 use memz_core::replay;
 use memz_core::reputation::{NotableDeed, ReputationBoard};
+use memz_core::retrieval::{RetrievalEngine, RetrievalQuery};
 use memz_core::social;
+use memz_core::time::TimeModel;
 use memz_core::types::{EntityId, GameTimestamp, Location, PADState, PersonalityTraits, SettlementId};
 
 fn ts(tick: u64) -> GameTimestamp {
@@ -81,7 +87,7 @@ fn full_memory_lifecycle() {
     let dir = tempfile::tempdir().expect("tempdir");
     let db_path = dir.path().join("integration_test.db");
     let engine = PersistenceEngine::open(db_path.to_str().expect("path"), &persist_config).expect("open");
-    engine.save_bank(&npc, &bank).expect("save");
+    engine.save_bank(&npc, &mut bank).expect("save");
 
     // 5. Restore
     let restored = engine.load_bank(&npc).expect("load").expect("found");
@@ -99,7 +105,12 @@ fn full_memory_lifecycle() {
     let mut restored_bank = restored;
     let far_future = GameTimestamp::now(36_000_000);
     let initial_count = restored_bank.episodic.len();
-    decay::decay_episodic_memories(&mut restored_bank.episodic, &far_future, &config);
+    decay::decay_episodic_memories(
+        &mut restored_bank.episodic,
+        &far_future,
+        &TimeModel::default(),
+        &config,
+    );
     // Low-importance memories may have decayed
     assert!(restored_bank.episodic.len() <= initial_count);
 }
@@ -143,6 +154,7 @@ fn multi_npc_gossip_chain() {
         0.0,
         0.8,    // reliable source
         ts(2000),
+        &TimeModel::default(),
     );
 
     let claim_b = match result_ab {
@@ -167,6 +179,7 @@ fn multi_npc_gossip_chain() {
         0.0,
         0.6,
         ts(3000),
+        &TimeModel::default(),
     );
 
     match result_bc {
@@ -208,7 +221,7 @@ fn observation_to_price_modifier() {
         importance: 0.8,
         pad_shift: None,
     };
-    observation::observe(&event1, player, &mut bank, &[]);
+    observation::observe(&event1, player, &mut bank, &[], &IdGenerator::random());
 
     let event2 = ObservedEvent {
         kind: EventKind::Help,
@@ -221,7 +234,7 @@ fn observation_to_price_modifier() {
         importance: 0.7,
         pad_shift: None,
     };
-    observation::observe(&event2, player, &mut bank, &[]);
+    observation::observe(&event2, player, &mut bank, &[], &IdGenerator::random());
 
     // Add emotional memory to strengthen disposition
     bank.emotional.push(EmotionalMemory::new(
@@ -294,7 +307,7 @@ fn reputation_lifecycle() {
 
     // Decay over time
     let far_future = ts(100_000_000);
-    board.decay_reputations(0.05, far_future);
+    board.decay_reputations(0.05, far_future, &TimeModel::default());
 
     // After significant decay, scores should be closer to neutral
     if let Some(hero_after) = board.get_reputation(hero) {
@@ -334,7 +347,7 @@ fn replay_selection_prefers_strong_memories() {
         0.95,
     ));
 
-    let result = replay::select_replay(&bank, player, &ts(3000), 0.3);
+    let result = replay::select_replay(&bank, player, &ts(3000), &TimeModel::default(), 0.3);
 
     // Should select the strong memory
     assert!(result.is_some(), "Should find a replay");
@@ -418,8 +431,8 @@ fn multi_npc_persistence() {
     let engine = PersistenceEngine::open(db_path.to_str().expect("path"), &persist_config).expect("open");
 
     // Save both
-    engine.save_bank(&npc_a, &bank_a).expect("save A");
-    engine.save_bank(&npc_b, &bank_b).expect("save B");
+    engine.save_bank(&npc_a, &mut bank_a).expect("save A");
+    engine.save_bank(&npc_b, &mut bank_b).expect("save B");
 
     // Verify count via list_entities
     let entities = engine.list_entities().expect("list");
@@ -483,3 +496,119 @@ fn memory_storage_size_within_budget() {
         "Bincode should be < 80KB, got {bincode_kb:.1}KB"
     );
 }
+
+// ---------------------------------------------------------------------------
+// Cold ring: old history spills to SQLite and pages back in on demand
+// ---------------------------------------------------------------------------
+
+#[test]
+fn cold_history_pages_back_in_for_retrieval_and_disposition() {
+    let player = EntityId::new();
+    let npc = EntityId::new();
+    let time = TimeModel::default();
+    let day = time.ticks_per_day;
+    let store = PersistenceEngine::open_in_memory(&PersistenceConfig::default()).expect("open db");
+
+    let mut bank = MemoryBank::new();
+    for i in 0..4 {
+        bank.episodic.push(EpisodicMemory::new(
+            "The traveller robbed my stall",
+            vec![player],
+            loc(),
+            ts(i * day),
+            -0.6,
+            0.6,
+        ));
+    }
+
+    // A month later all of it is in the cold ring.
+    let now = 30 * day;
+    let report = eviction::evict_to_cold_storage(
+        &mut bank,
+        &npc,
+        &store,
+        &ts(now),
+        &time,
+        200,
+        &EvictionConfig::default(),
+    )
+    .expect("eviction pass");
+    assert_eq!(report.spilled, 4);
+    assert!(bank.episodic.is_empty());
+    assert_eq!(bank.cold_count(player), 4);
+
+    // Without the store the NPC has forgotten; with it, retrieval pages in.
+    assert_eq!(behavior::compute_disposition(&bank, player).interaction_count, 0);
+
+    let query = RetrievalQuery::new().with_entity(player);
+    let starved = RetrievalEngine::new(RetrievalConfig::default()).with_budget(Duration::ZERO);
+    let results = starved
+        .retrieve_query_paged(&query, &mut bank, &npc, &store, &ts(now), None)
+        .expect("retrieve");
+    assert!(results.is_empty(), "no budget, no page-in");
+
+    let engine = RetrievalEngine::new(RetrievalConfig { top_k: 2, ..RetrievalConfig::default() });
+    let results = engine
+        .retrieve_query_paged(&query, &mut bank, &npc, &store, &ts(now), None)
+        .expect("retrieve");
+    assert_eq!(results.len(), 2);
+    assert_eq!(bank.episodic.len(), 2, "paged-in memories stay in the bank");
+    assert_eq!(bank.cold_count(player), 2);
+
+    let disposition = behavior::compute_disposition_paged(&mut bank, &npc, &store, player)
+        .expect("disposition");
+    assert_eq!(disposition.interaction_count, 4);
+    assert!(disposition.sentiment < 0.0);
+    // Paged-in rows leave cold storage with the next save of the bank.
+    assert_eq!(store.cold_memory_count(&npc).expect("count"), 4);
+    store.save_bank(&npc, &mut bank).expect("save");
+    assert_eq!(store.cold_memory_count(&npc).expect("count"), 0);
+}
+
+#[test]
+fn cold_page_in_honours_the_query_filters() {
+    let player = EntityId::new();
+    let npc = EntityId::new();
+    let time = TimeModel::default();
+    let day = time.ticks_per_day;
+    let store = PersistenceEngine::open_in_memory(&PersistenceConfig::default()).expect("open db");
+
+    let mut bank = MemoryBank::new();
+    for i in 0..6 {
+        let valence = if i % 2 == 0 { -0.6 } else { 0.6 };
+        bank.episodic.push(EpisodicMemory::new(
+            format!("Traded with the traveller on day {i}"),
+            vec![player],
+            loc(),
+            ts(i * day),
+            valence,
+            0.6,
+        ));
+    }
+    let report = eviction::evict_to_cold_storage(
+        &mut bank,
+        &npc,
+        &store,
+        &ts(30 * day),
+        &time,
+        200,
+        &EvictionConfig::default(),
+    )
+    .expect("eviction pass");
+    assert_eq!(report.spilled, 6);
+
+    // Only the good days between day 2 and day 5 come back.
+    let query = RetrievalQuery::new()
+        .with_entity(player)
+        .with_valence(0.0, 1.0)
+        .with_time_window(ts(2 * day), ts(5 * day));
+    let engine = RetrievalEngine::new(RetrievalConfig::default());
+    let results = engine
+        .retrieve_query_paged(&query, &mut bank, &npc, &store, &ts(30 * day), None)
+        .expect("retrieve");
+    assert_eq!(results.len(), 2);
+    let mut days: Vec<u64> = bank.episodic.iter().map(|m| m.timestamp.tick / day).collect();
+    days.sort_unstable();
+    assert_eq!(days, [3, 5], "non-matching memories stay cold");
+    assert_eq!(bank.cold_count(player), 4);
+}
@@ -0,0 +1,293 @@
+//! Golden-file tests for every historical persistence format.
+//!
+//! Each fixture under `tests/golden/` is a save written by an older MEMZ
+//! build. They must keep loading, unchanged, through the migration registry
+//! in `memz_core::persistence::migrations`. Never edit an existing fixture —
+//! add a new one when the format changes.
+
+use chrono::Utc;
+use memz_core::config::PersistenceConfig;
+use memz_core::error::MemzError;
+use memz_core::memory::MemoryBank;
+use memz_core::memory::emotional::EmotionTrajectory;
+use memz_core::memory::injected::InjectedPriority;
+use memz_core::persistence::{CURRENT_SCHEMA_VERSION, PersistenceEngine};
+use memz_core::types::{Embedding, EntityId};
+use rusqlite::{Connection, params};
+use serde_json::Value;
+
+const BANK_V1: &str = include_str!("golden/bank_v1.json");
+const ROWS_V2: &str = include_str!("golden/rows_v2.json");
+const ROWS_V3: &str = include_str!("golden/rows_v3.json");
+const ROWS_V4: &str = include_str!("golden/rows_v4.json");
+
+const KINDS: [(&str, &str); 7] = [
+    ("episodic", "episodic_memories"),
+    ("semantic", "semantic_memories"),
+    ("emotional", "emotional_memories"),
+    ("social", "social_memories"),
+    ("reflective", "reflective_memories"),
+    ("procedural", "procedural_memories"),
+    ("injected", "injected_memories"),
+];
+
+fn owner() -> EntityId {
+    EntityId(uuid::Uuid::from_u128(
+        0x6d65_6d7a_0000_4000_8000_0000_0000_0101,
+    ))
+}
+
+/// Every fixture encodes the same bank; check a distinctive field of each type.
+fn assert_golden_bank(bank: &MemoryBank) {
+    assert_eq!(bank.total_count(), 7);
+    assert_eq!(
+        bank.episodic[0].event,
+        "The player drove wolves away from the east gate"
+    );
+    assert_eq!(bank.episodic[0].timestamp.tick, 48_000);
+    assert!(bank.episodic[0].is_first_meeting);
+    assert_eq!(bank.semantic[0].derived_from, vec![bank.episodic[0].id]);
+    assert_eq!(bank.emotional[0].trajectory, EmotionTrajectory::Increasing);
+    assert_eq!(
+        bank.social[0].disbelief_reason.as_deref(),
+        Some("contradicts what I saw at the forge")
+    );
+    assert_eq!(bank.social[0].propagation_depth, 2);
+    assert!(bank.reflective[0].mood_shift.is_some());
+    assert_eq!(bank.procedural[0].repetitions, 40);
+    assert_eq!(bank.injected[0].priority, InjectedPriority::High);
+    assert_eq!(bank.injected[0].tags, vec!["backstory".to_string()]);
+}
+
+fn config() -> PersistenceConfig {
+    PersistenceConfig {
+        checksum_enabled: true,
+        ..PersistenceConfig::default()
+    }
+}
+
+/// Insert fixture rows (`kind` → payload list) straight into the per-type tables.
+fn insert_rows(conn: &Connection, fixture: &str) {
+    let rows: Value = serde_json::from_str(fixture).expect("fixture");
+    let owner = owner().0.to_string();
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT OR REPLACE INTO memory_owners (entity_id, updated_at) VALUES (?1, ?2)",
+        params![owner, now],
+    )
+    .expect("owner row");
+    for (kind, table) in KINDS {
+        for (position, payload) in rows[kind].as_array().expect("kind list").iter().enumerate() {
+            let id = payload
+                .get("memory")
+                .unwrap_or(payload)
+                .get("id")
+                .and_then(Value::as_str)
+                .expect("memory id")
+                .to_string();
+            conn.execute(
+                &format!(
+                    "INSERT INTO {table} (owner, memory_id, seq, position, data, updated_at, checksum)
+                     VALUES (?1, ?2, 0, ?3, ?4, ?5, NULL)"
+                ),
+                params![owner, id, position, serde_json::to_vec(payload).expect("json"), now],
+            )
+            .expect("memory row");
+        }
+    }
+}
+
+#[test]
+fn v1_whole_bank_blob_loads() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("v1.db");
+    {
+        let conn = Connection::open(&path).expect("open raw");
+        conn.execute_batch(
+            "CREATE TABLE memory_banks (
+                entity_id  TEXT PRIMARY KEY,
+                data       BLOB NOT NULL,
+                updated_at TEXT NOT NULL,
+                checksum   TEXT
+            );",
+        )
+        .expect("v1 schema");
+        conn.execute(
+            "INSERT INTO memory_banks VALUES (?1, ?2, ?3, NULL)",
+            params![
+                owner().0.to_string(),
+                BANK_V1.as_bytes(),
+                Utc::now().to_rfc3339()
+            ],
+        )
+        .expect("v1 row");
+    }
+
+    let engine = PersistenceEngine::open(&path, &config()).expect("open");
+    assert_eq!(
+        engine.schema_version().expect("version"),
+        CURRENT_SCHEMA_VERSION
+    );
+    let bank = engine.load_bank(&owner()).expect("load").expect("migrated");
+    assert_golden_bank(&bank);
+}
+
+/// Create the per-type row tables as they looked at `version` (2–4):
+/// version 3 added the `schema_version` table, version 4 the `codec` column.
+fn create_row_tables(conn: &Connection, version: u32) {
+    let codec_column = if version >= 4 {
+        "codec TEXT NOT NULL DEFAULT 'json',"
+    } else {
+        ""
+    };
+    conn.execute_batch(
+        "CREATE TABLE memory_owners (entity_id TEXT PRIMARY KEY, updated_at TEXT NOT NULL);",
+    )
+    .expect("owners");
+    for (_, table) in KINDS {
+        conn.execute_batch(&format!(
+            "CREATE TABLE {table} (
+                owner      TEXT NOT NULL,
+                memory_id  TEXT NOT NULL,
+                seq        INTEGER NOT NULL,
+                position   INTEGER NOT NULL,
+                data       BLOB NOT NULL,
+                updated_at TEXT NOT NULL,
+                checksum   TEXT,
+                {codec_column}
+                PRIMARY KEY (owner, memory_id, seq)
+            );"
+        ))
+        .expect("row table");
+    }
+    if version >= 3 {
+        conn.execute_batch(&format!(
+            "CREATE TABLE schema_version (
+                id         INTEGER PRIMARY KEY CHECK (id = 0),
+                version    INTEGER NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            INSERT INTO schema_version VALUES (0, {version}, '2025-06-01T12:00:00Z');"
+        ))
+        .expect("schema_version");
+    }
+}
+
+#[test]
+fn v2_bare_rows_load_and_are_rewritten_on_save() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("v2.db");
+    {
+        let conn = Connection::open(&path).expect("open raw");
+        create_row_tables(&conn, 2);
+        insert_rows(&conn, ROWS_V2);
+    }
+
+    let engine = PersistenceEngine::open(&path, &config()).expect("open");
+    assert_eq!(
+        engine.schema_version().expect("version"),
+        CURRENT_SCHEMA_VERSION
+    );
+    let mut bank = engine.load_bank(&owner()).expect("load").expect("found");
+    assert_golden_bank(&bank);
+
+    // Old rows are dirty: saving rewrites all of them in the current format.
+    let report = engine.save_bank(&owner(), &mut bank).expect("save");
+    assert_eq!(report.upserted, 7);
+    drop(engine);
+
+    let conn = Connection::open(&path).expect("reopen raw");
+    let data: Vec<u8> = conn
+        .query_row("SELECT data FROM episodic_memories", [], |row| row.get(0))
+        .expect("row");
+    let payload: Value = serde_json::from_slice(&data).expect("json");
+    assert_eq!(payload["memz_schema"], CURRENT_SCHEMA_VERSION);
+}
+
+#[test]
+fn v3_envelopes_load() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("v3.db");
+    {
+        let conn = Connection::open(&path).expect("open raw");
+        create_row_tables(&conn, 3);
+        insert_rows(&conn, ROWS_V3);
+    }
+
+    let engine = PersistenceEngine::open(&path, &config()).expect("open");
+    assert_eq!(
+        engine.schema_version().expect("version"),
+        CURRENT_SCHEMA_VERSION
+    );
+    let bank = engine.load_bank(&owner()).expect("load").expect("found");
+    assert_golden_bank(&bank);
+}
+
+#[test]
+fn v4_codec_rows_load() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("v4.db");
+    {
+        let conn = Connection::open(&path).expect("open raw");
+        create_row_tables(&conn, 4);
+        insert_rows(&conn, ROWS_V4);
+    }
+
+    let engine = PersistenceEngine::open(&path, &config())
+        .expect("open")
+        .with_embedding_model("all-MiniLM-L6-v2", 384);
+    assert_eq!(
+        engine.schema_version().expect("version"),
+        CURRENT_SCHEMA_VERSION
+    );
+    let mut bank = engine.load_bank(&owner()).expect("load").expect("found");
+    assert_golden_bank(&bank);
+    assert!(bank.episodic[0].embedding.is_none());
+
+    // The upgraded rows accept embeddings.
+    bank.episodic[0].embedding = Some(Embedding(vec![0.25; 384]));
+    engine.save_bank(&owner(), &mut bank).expect("save");
+    let reloaded = engine.load_bank(&owner()).expect("load").expect("found");
+    assert!(reloaded.episodic[0].embedding.is_some());
+}
+
+#[test]
+fn newer_database_is_refused() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("future.db");
+    drop(PersistenceEngine::open(&path, &config()).expect("create"));
+    Connection::open(&path)
+        .expect("open raw")
+        .execute("UPDATE schema_version SET version = 99", [])
+        .expect("bump version");
+
+    let err = PersistenceEngine::open(&path, &config()).expect_err("should refuse");
+    assert!(matches!(
+        err,
+        MemzError::UnsupportedSchemaVersion { found: 99, .. }
+    ));
+}
+
+#[test]
+fn golden_bank_round_trips_through_every_codec() {
+    let bank: MemoryBank = serde_json::from_str(BANK_V1).expect("fixture");
+    let mut codecs = vec!["json", "msgpack", "bincode"];
+    if cfg!(feature = "zstd") {
+        codecs.extend(["json+zstd", "msgpack+zstd", "bincode+zstd"]);
+    }
+
+    for tag in codecs {
+        let (codec, compression) = tag.split_once('+').unwrap_or((tag, "none"));
+        let config = PersistenceConfig {
+            codec: codec.to_string(),
+            compression: compression.to_string(),
+            ..config()
+        };
+        let engine = PersistenceEngine::open_in_memory(&config).expect("open");
+        engine
+            .save_bank(&owner(), &mut bank.clone())
+            .expect("save");
+        let loaded = engine.load_bank(&owner()).expect("load").expect("found");
+        assert_golden_bank(&loaded);
+    }
+}
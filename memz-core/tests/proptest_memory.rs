@@ -19,6 +19,7 @@ use memz_core::memory::social::SocialMemory;
 use memz_core::memory::MemoryBank;
 use memz_core::reputation::{NotableDeed, ReputationBoard};
 use memz_core::social;
+use memz_core::time::TimeModel;
 use memz_core::types::{EntityId, GameTimestamp, Location, PADState, PersonalityTraits, SettlementId};
 
 // ---------------------------------------------------------------------------
@@ -154,7 +155,7 @@ proptest! {
 
         let before = bank.episodic.len();
         let far_future = GameTimestamp::now(72_000_000);
-        decay::decay_episodic_memories(&mut bank.episodic, &far_future, &config);
+        decay::decay_episodic_memories(&mut bank.episodic, &far_future, &TimeModel::default(), &config);
 
         prop_assert!(bank.episodic.len() <= before);
     }
@@ -178,7 +179,7 @@ proptest! {
         )];
 
         let future = GameTimestamp::now(tick_offset);
-        decay::decay_episodic_memories(&mut memories, &future, &config);
+        decay::decay_episodic_memories(&mut memories, &future, &TimeModel::default(), &config);
 
         // Flashbulb memories (|valence| > 0.8) are protected
         prop_assert!(!memories.is_empty(), "Flashbulb memory should survive decay");
@@ -205,7 +206,7 @@ proptest! {
         let mut memories = vec![mem];
 
         let future = GameTimestamp::now(tick_offset);
-        decay::decay_episodic_memories(&mut memories, &future, &config);
+        decay::decay_episodic_memories(&mut memories, &future, &TimeModel::default(), &config);
 
         prop_assert!(!memories.is_empty(), "First-meeting memory must survive");
     }
@@ -222,15 +223,15 @@ proptest! {
         current_tick in 100_000..200_000u64,
     ) {
         let config = EvictionConfig::default();
-        let ticks_per_hour = 1000u64;
-        let ring = eviction::classify_ring(memory_tick, current_tick, ticks_per_hour, &config);
+        let time = TimeModel { ticks_per_day: 24_000, ..TimeModel::default() };
+        let ring = eviction::classify_ring(memory_tick, current_tick, &time, &config);
         // Just verify it returns a valid ring (type system guarantees, but let's be thorough)
         let _valid = match ring {
             eviction::Ring::Hot | eviction::Ring::Warm | eviction::Ring::Cold | eviction::Ring::Archive => true,
         };
 
         // If current_tick == memory_tick, ring should be Hot (age = 0)
-        let ring_fresh = eviction::classify_ring(current_tick, current_tick, ticks_per_hour, &config);
+        let ring_fresh = eviction::classify_ring(current_tick, current_tick, &time, &config);
         prop_assert_eq!(ring_fresh, eviction::Ring::Hot, "Zero-age memory should be Hot");
     }
 }
@@ -404,11 +405,11 @@ proptest! {
         }
 
         // First decay pass
-        decay::decay_episodic_memories(&mut bank.episodic, &ts, &config);
+        decay::decay_episodic_memories(&mut bank.episodic, &ts, &TimeModel::default(), &config);
         let count_after_first = bank.episodic.len();
 
         // Second decay pass with same timestamp — should be idempotent
-        decay::decay_episodic_memories(&mut bank.episodic, &ts, &config);
+        decay::decay_episodic_memories(&mut bank.episodic, &ts, &TimeModel::default(), &config);
         let count_after_second = bank.episodic.len();
 
         prop_assert_eq!(count_after_first, count_after_second);
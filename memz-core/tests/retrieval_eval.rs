@@ -0,0 +1,239 @@
+//! Retrieval Evaluation — recall@5 of vector, lexical and hybrid relevance (§12.4)
+//!
+//! `tests/eval/retrieval_banks.json` holds NPC memory banks with labelled
+//! queries.  Embeddings come from a toy model that maps words to concept
+//! vectors through the fixture's lexicon: paraphrases ("beasts" / "wolves")
+//! land close together, but proper names — absent from the lexicon, as they
+//! are from small sentence models — contribute nothing.  BM25 has the
+//! opposite blind spot, so hybrid retrieval should beat both.
+//!
+//! Only the relevance factor is weighted, so the numbers measure the
+//! relevance sources rather than recency or importance.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+use memz_core::config::{RetrievalConfig, RetrievalWeights};
+use memz_core::memory::episodic::EpisodicMemory;
+use memz_core::memory::{MemoryBank, MemoryEntry};
+use memz_core::retrieval::{LexicalIndex, RetrievalEngine, RetrievalResult};
+use memz_core::types::{Embedding, GameTimestamp, Location, MemoryId};
+
+const FIXTURE: &str = include_str!("eval/retrieval_banks.json");
+const DIMENSIONS: usize = 64;
+const K: usize = 5;
+
+#[derive(Deserialize)]
+struct Fixture {
+    lexicon: HashMap<String, Vec<String>>,
+    banks: Vec<Bank>,
+}
+
+#[derive(Deserialize)]
+struct Bank {
+    npc: String,
+    memories: Vec<String>,
+    queries: Vec<Query>,
+}
+
+#[derive(Deserialize)]
+struct Query {
+    text: String,
+    relevant: Vec<usize>,
+}
+
+/// Bag-of-concepts embedder: each lexicon word is its concept's vector plus
+/// a little word-specific noise; unknown words are ignored.
+struct ToyEmbedder {
+    words: HashMap<String, Vec<f32>>,
+}
+
+impl ToyEmbedder {
+    fn new(lexicon: &HashMap<String, Vec<String>>) -> Self {
+        let mut rng = StdRng::seed_from_u64(0x6d65_6d7a);
+        let mut concepts: Vec<_> = lexicon.iter().collect();
+        concepts.sort_by_key(|(concept, _)| concept.as_str());
+
+        let mut words = HashMap::new();
+        for (_, members) in concepts {
+            let center: Vec<f32> = (0..DIMENSIONS).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            for word in members {
+                let vector = center.iter().map(|c| c + rng.gen_range(-0.2..0.2)).collect();
+                words.insert(word.clone(), vector);
+            }
+        }
+        Self { words }
+    }
+
+    fn embed(&self, text: &str) -> Embedding {
+        let mut sum = vec![0.0_f32; DIMENSIONS];
+        for word in text.split(|c: char| !c.is_alphanumeric()) {
+            if let Some(vector) = self.words.get(&word.to_lowercase()) {
+                for (s, v) in sum.iter_mut().zip(vector) {
+                    *s += v;
+                }
+            }
+        }
+        Embedding(sum)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    Vector,
+    Lexical,
+    Hybrid(&'static str),
+}
+
+impl Mode {
+    const ALL: [Self; 4] = [
+        Self::Vector,
+        Self::Lexical,
+        Self::Hybrid("rrf"),
+        Self::Hybrid("weighted"),
+    ];
+
+    fn engine(self) -> RetrievalEngine {
+        let (algorithm, fusion) = match self {
+            Self::Vector => ("brute_force", "rrf"),
+            Self::Lexical => ("tfidf", "rrf"),
+            Self::Hybrid(fusion) => ("hybrid", fusion),
+        };
+        RetrievalEngine::new(RetrievalConfig {
+            algorithm: algorithm.to_string(),
+            top_k: K,
+            hybrid_fusion: fusion.to_string(),
+            weights: RetrievalWeights {
+                recency: 0.0,
+                relevance: 1.0,
+                importance: 0.0,
+                emotional: 0.0,
+                social: 0.0,
+                place: 0.0,
+            },
+            ..RetrievalConfig::default()
+        })
+    }
+}
+
+fn build_bank(bank: &Bank, embedder: &ToyEmbedder) -> MemoryBank {
+    let mut memories = MemoryBank::new();
+    for text in &bank.memories {
+        let mut memory = EpisodicMemory::new(
+            text.as_str(),
+            vec![],
+            Location::default(),
+            GameTimestamp::now(0),
+            0.0,
+            0.5,
+        );
+        memory.embedding = Some(embedder.embed(text));
+        memories.episodic.push(memory);
+    }
+    memories
+}
+
+fn retrieve(
+    mode: Mode,
+    query: &str,
+    entries: &[MemoryEntry],
+    index: &LexicalIndex,
+    embedder: &ToyEmbedder,
+) -> Vec<RetrievalResult> {
+    let engine = mode.engine();
+    let now = GameTimestamp::now(0);
+    let results = match mode {
+        Mode::Vector => engine.retrieve(&embedder.embed(query), entries, &now, None),
+        Mode::Lexical => engine.retrieve_lexical(query, entries, index, &now, None),
+        Mode::Hybrid(_) => {
+            engine.retrieve_hybrid(&embedder.embed(query), query, entries, index, &now, None)
+        }
+    };
+    results.expect("retrieval succeeds")
+}
+
+/// Mean recall@5 of `mode` over every labelled query in the fixture.
+fn recall_at_k(fixture: &Fixture, embedder: &ToyEmbedder, mode: Mode) -> f64 {
+    let mut total = 0.0;
+    let mut queries = 0;
+    for bank in &fixture.banks {
+        let memories = build_bank(bank, embedder);
+        let entries = memories.all_entries();
+        let index = LexicalIndex::build(&memories);
+        let ids: Vec<MemoryId> = memories.episodic.iter().map(|m| m.id).collect();
+
+        for query in &bank.queries {
+            let retrieved: Vec<MemoryId> = retrieve(mode, &query.text, &entries, &index, embedder)
+                .iter()
+                .map(|r| r.memory.id())
+                .collect();
+            let hits = query
+                .relevant
+                .iter()
+                .filter(|&&i| retrieved.contains(&ids[i]))
+                .count();
+            total += hits as f64 / query.relevant.len().min(K) as f64;
+            queries += 1;
+        }
+    }
+    total / f64::from(queries)
+}
+
+#[test]
+fn fixture_is_well_formed() {
+    let fixture: Fixture = serde_json::from_str(FIXTURE).expect("fixture parses");
+    for bank in &fixture.banks {
+        assert!(!bank.queries.is_empty(), "{} has no queries", bank.npc);
+        for query in &bank.queries {
+            assert!(!query.relevant.is_empty(), "{:?} is unlabelled", query.text);
+            assert!(
+                query.relevant.iter().all(|&i| i < bank.memories.len()),
+                "{:?} labels a memory {} does not have",
+                query.text,
+                bank.npc
+            );
+        }
+    }
+}
+
+#[test]
+fn hybrid_recall_beats_single_sources() {
+    let fixture: Fixture = serde_json::from_str(FIXTURE).expect("fixture parses");
+    let embedder = ToyEmbedder::new(&fixture.lexicon);
+
+    let recall: Vec<(Mode, f64)> = Mode::ALL
+        .iter()
+        .map(|&mode| (mode, recall_at_k(&fixture, &embedder, mode)))
+        .collect();
+    let best_single = recall[0].1.max(recall[1].1);
+    for (mode, r) in &recall[2..] {
+        assert!(
+            *r > best_single,
+            "{mode:?} recall@{K} {r:.3} should beat the best single source {best_single:.3} \
+             (all modes: {recall:.3?})"
+        );
+    }
+}
+
+#[test]
+fn hybrid_breakdown_reports_both_sources() {
+    let fixture: Fixture = serde_json::from_str(FIXTURE).expect("fixture parses");
+    let embedder = ToyEmbedder::new(&fixture.lexicon);
+    let memories = build_bank(&fixture.banks[0], &embedder);
+    let entries = memories.all_entries();
+    let index = LexicalIndex::build(&memories);
+
+    let results = retrieve(
+        Mode::Hybrid("rrf"),
+        "What did Hilda trade with me?",
+        &entries,
+        &index,
+        &embedder,
+    );
+    let top = &results[0].breakdown;
+    assert!(top.vector_relevance.is_some_and(|v| v > 0.0));
+    assert!(top.lexical_relevance.is_some_and(|l| l > 0.0));
+}
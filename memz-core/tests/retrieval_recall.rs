@@ -0,0 +1,140 @@
+//! Retrieval Recall — HNSW pre-filter vs brute force (§12.4)
+//!
+//! The indexed retrieval path only scores the `hnsw_ef_search` nearest
+//! memories; these tests check it returns (nearly) the same top-K as scoring
+//! every memory in the bank.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use memz_core::config::RetrievalConfig;
+use memz_core::memory::episodic::EpisodicMemory;
+use memz_core::memory::social::SocialMemory;
+use memz_core::memory::{MemoryBank, MemoryEntry};
+use memz_core::retrieval::{MemoryIndex, RetrievalEngine, RetrievalResult};
+use memz_core::types::{Embedding, EntityId, GameTimestamp, Location, MemoryId};
+
+const DIMENSIONS: usize = 32;
+const TOPICS: usize = 40;
+
+fn normalize(values: Vec<f32>) -> Embedding {
+    let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt().max(f32::EPSILON);
+    Embedding(values.into_iter().map(|v| v / norm).collect())
+}
+
+fn random_unit(rng: &mut StdRng) -> Vec<f32> {
+    normalize((0..DIMENSIONS).map(|_| rng.gen_range(-1.0..1.0)).collect()).0
+}
+
+/// Point near `center` — memories about the same topic cluster together,
+/// as sentence embeddings do.
+fn near(center: &[f32], spread: f32, rng: &mut StdRng) -> Embedding {
+    normalize(
+        center
+            .iter()
+            .map(|c| c + rng.gen_range(-spread..spread))
+            .collect(),
+    )
+}
+
+/// A bank of `size` embedded episodic memories spread over [`TOPICS`]
+/// topics, plus a handful of social memories without embeddings.
+fn make_bank(size: usize, rng: &mut StdRng) -> (MemoryBank, Vec<Vec<f32>>) {
+    let topics: Vec<Vec<f32>> = (0..TOPICS).map(|_| random_unit(rng)).collect();
+    let mut bank = MemoryBank::new();
+    for i in 0..size {
+        let mut memory = EpisodicMemory::new(
+            format!("Event {i}"),
+            vec![],
+            Location::default(),
+            GameTimestamp::now(rng.gen_range(0..1_000_000)),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(0.1..0.9),
+        );
+        memory.embedding = Some(near(&topics[i % TOPICS], 0.15, rng));
+        bank.episodic.push(memory);
+    }
+    for i in 0..10 {
+        bank.social.push(SocialMemory::new(
+            EntityId::new(),
+            EntityId::new(),
+            format!("Rumour {i}"),
+            0.5,
+            1,
+            GameTimestamp::now(500_000),
+        ));
+    }
+    (bank, topics)
+}
+
+fn ids(results: &[RetrievalResult]) -> Vec<MemoryId> {
+    results
+        .iter()
+        .map(|r| match &r.memory {
+            MemoryEntry::Episodic(m) => m.id,
+            MemoryEntry::Social(m) => m.id,
+            other => panic!("unexpected memory {other:?}"),
+        })
+        .collect()
+}
+
+fn mean_recall(size: usize, queries: usize) -> f64 {
+    let mut rng = StdRng::seed_from_u64(7);
+    let (bank, topics) = make_bank(size, &mut rng);
+    let config = RetrievalConfig::default();
+    let engine = RetrievalEngine::new(config.clone());
+    let index = MemoryIndex::build(&config, &bank);
+    let all = bank.all_entries();
+    let now = GameTimestamp::now(1_000_000);
+
+    let mut hits = 0;
+    let mut total = 0;
+    for q in 0..queries {
+        let query = near(&topics[q % TOPICS], 0.1, &mut rng);
+        let exact = ids(&engine.retrieve(&query, &all, &now, None).expect("brute force"));
+        let indexed = ids(
+            &engine
+                .retrieve_indexed(&query, &bank, &index, &now, None)
+                .expect("indexed"),
+        );
+        hits += indexed.iter().filter(|id| exact.contains(id)).count();
+        total += exact.len();
+    }
+    hits as f64 / total as f64
+}
+
+#[test]
+fn indexed_retrieval_matches_brute_force() {
+    let recall = mean_recall(1_000, 50);
+    assert!(recall >= 0.95, "recall@5 = {recall:.3}");
+}
+
+#[test]
+fn small_banks_are_scored_exhaustively() {
+    // Below `hnsw_min_memories` the index is bypassed entirely.
+    let recall = mean_recall(80, 40);
+    assert!((recall - 1.0).abs() < f64::EPSILON, "recall@5 = {recall:.3}");
+}
+
+#[test]
+fn brute_force_algorithm_ignores_the_index() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let (bank, topics) = make_bank(500, &mut rng);
+    let config = RetrievalConfig {
+        algorithm: "brute_force".to_string(),
+        ..RetrievalConfig::default()
+    };
+    let engine = RetrievalEngine::new(config.clone());
+    // Deliberately empty: the brute-force path must not consult it.
+    let index = MemoryIndex::new(&config);
+    let now = GameTimestamp::now(1_000_000);
+
+    let query = Embedding(topics[0].clone());
+    let exact = engine
+        .retrieve(&query, &bank.all_entries(), &now, None)
+        .expect("brute force");
+    let indexed = engine
+        .retrieve_indexed(&query, &bank, &index, &now, None)
+        .expect("indexed");
+    assert_eq!(ids(&indexed), ids(&exact));
+}
@@ -0,0 +1,170 @@
+//! Time Model — game-time behaviour is independent of the tick rate (§12.1)
+//!
+//! Every scenario is laid out in game-hours and converted to ticks through
+//! the [`TimeModel`] under test.  A server running at 30 TPS with a short
+//! Veloren day, the historical 60 TPS default and a fast 120 TPS server must
+//! all decay, score, evict, gossip and replay identically.
+
+use memz_core::config::EvictionConfig;
+use memz_core::decay;
+use memz_core::eviction::{self, Ring};
+use memz_core::memory::MemoryEntry;
+use memz_core::memory::episodic::EpisodicMemory;
+use memz_core::memory::social::SocialMemory;
+use memz_core::reflection::{self, ReflectionConfig};
+use memz_core::replay;
+use memz_core::reputation::ReputationBoard;
+use memz_core::retrieval::scoring;
+use memz_core::social::{self, PropagationResult};
+use memz_core::time::TimeModel;
+use memz_core::types::{Embedding, EntityId, GameTimestamp, Location, PersonalityTraits, SettlementId};
+
+fn models() -> [TimeModel; 3] {
+    [
+        TimeModel::from_day_cycle(30.0, 48.0),
+        TimeModel::default(),
+        TimeModel {
+            ticks_per_second: 120.0,
+            ticks_per_day: 144_000,
+        },
+    ]
+}
+
+/// Timestamp `hours` game-hours after tick 0.
+fn at(time: &TimeModel, hours: f64) -> GameTimestamp {
+    GameTimestamp::now(time.ticks_per_game_hours(hours))
+}
+
+/// Evaluate `f` under every model and check the results agree.
+fn assert_consistent(what: &str, f: impl Fn(&TimeModel) -> f64) {
+    let results: Vec<f64> = models().iter().map(f).collect();
+    for r in &results[1..] {
+        assert!(
+            (r - results[0]).abs() < 1e-4,
+            "{what} depends on the tick rate: {results:?}"
+        );
+    }
+}
+
+fn memory(time: &TimeModel, hours: f64, participant: EntityId) -> EpisodicMemory {
+    EpisodicMemory::new(
+        "Traded furs at the market",
+        vec![participant],
+        Location::default(),
+        at(time, hours),
+        0.4,
+        0.5,
+    )
+}
+
+#[test]
+fn decay_and_recency_are_measured_in_game_time() {
+    let someone = EntityId::new();
+    assert_consistent("episodic retention", |time| {
+        decay::episodic_retention(&memory(time, 0.0, someone), &at(time, 60.0), time)
+    });
+    assert_consistent("social retention", |time| {
+        let gossip = SocialMemory::new(someone, someone, "heard a rumour", 0.7, 1, at(time, 2.0));
+        decay::social_retention(&gossip, &at(time, 50.0), time)
+    });
+    assert_consistent("recency score", |time| {
+        let entry = MemoryEntry::Episodic(memory(time, 0.0, someone));
+        let breakdown = scoring::compute_breakdown(
+            &entry,
+            &Embedding(vec![]),
+            &at(time, 30.0),
+            time,
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+        );
+        breakdown.recency
+    });
+}
+
+#[test]
+fn eviction_rings_follow_game_hours() {
+    let config = EvictionConfig::default();
+    for (hours, expected) in [
+        (1.0, Ring::Hot),
+        (48.0, Ring::Warm),
+        (24.0 * 30.0, Ring::Cold),
+        (24.0 * 120.0, Ring::Archive),
+    ] {
+        for time in models() {
+            let ring = eviction::classify_ring(0, time.ticks_per_game_hours(hours), &time, &config);
+            assert_eq!(ring, expected, "{hours}h old under {time:?}");
+        }
+    }
+}
+
+#[test]
+fn reputation_and_gossip_freshness_are_consistent() {
+    let hero = EntityId::new();
+    assert_consistent("reputation decay", |time| {
+        let mut board = ReputationBoard::new(SettlementId::new(), at(time, 0.0));
+        board.report_sentiment(hero, 0.9, at(time, 0.0));
+        board.decay_reputations(0.1, at(time, 24.0 * 3.0), time);
+        f64::from(board.get_reputation(hero).map_or(0.0, |r| r.score))
+    });
+
+    let personality = PersonalityTraits {
+        credulity: 0.9,
+        openness: 0.9,
+        ..PersonalityTraits::default()
+    };
+    assert_consistent("gossip belief", |time| {
+        let claim = SocialMemory::new(hero, EntityId::new(), "slew the dragon", 0.9, 0, at(time, 0.0));
+        let result = social::propagate_memory(
+            &claim,
+            EntityId::new(),
+            &personality,
+            0.8,
+            false,
+            None,
+            0.7,
+            0.0,
+            0.8,
+            at(time, 36.0),
+            time,
+        );
+        match result {
+            PropagationResult::Accepted { belief_strength, .. } => f64::from(belief_strength),
+            other => panic!("expected the claim to be accepted, got {other:?}"),
+        }
+    });
+}
+
+#[test]
+fn replay_and_reflection_scheduling_are_consistent() {
+    let player = EntityId::new();
+    let picks: Vec<String> = models()
+        .iter()
+        .map(|time| {
+            let mut bank = memz_core::memory::MemoryBank::new();
+            let mut old = memory(time, 0.0, player);
+            old.event = "Player saved my child".to_string();
+            old.emotional_valence = 0.9;
+            bank.episodic.push(old);
+            bank.episodic.push(memory(time, 24.0 * 9.0, player));
+            replay::select_replay(&bank, player, &at(time, 24.0 * 10.0), time, 0.1)
+                .expect("a replay is selected")
+                .memory
+                .event
+        })
+        .collect();
+    assert!(picks.iter().all(|p| p == &picks[0]), "replay choice varies: {picks:?}");
+
+    let config = ReflectionConfig::default();
+    let minutes = f64::from(config.interval_minutes);
+    for time in models() {
+        assert!(!replay::should_replay(0, time.ticks_per_game_hours(0.25), 1.0, &time));
+        assert!(replay::should_replay(0, time.ticks_per_game_hours(1.0), 1.0, &time));
+        let before = time.ticks_per_game_hours(minutes / 60.0 * 0.9);
+        let after = time.ticks_per_game_hours(minutes / 60.0 * 1.1);
+        assert!(!reflection::should_reflect(0, before, 5, 0.1, &time, &config));
+        assert!(reflection::should_reflect(0, after, 5, 0.1, &time, &config));
+    }
+}
@@ -0,0 +1,187 @@
+//! Cassettes — recorded LLM interactions for deterministic replay.
+//!
+//! A [`Cassette`] attached to a client with
+//! [`LlmClient::with_recorder`](crate::LlmClient::with_recorder) records
+//! every successful request/response pair; [`Cassette::save`] writes them
+//! to a JSON file.  A [`MockLlm`](crate::mock::MockLlm) built with
+//! [`replaying`](crate::mock::MockLlm::replaying) answers from the loaded
+//! file instead of a live model, so tests of LLM-driven flows run offline
+//! and give the same answers every run.
+//!
+//! Requests match on tier, prompts, grammar, response schema, temperature
+//! and token limit.  Identical requests replay their recorded responses in
+//! order; the last one repeats.  Cassettes recorded before the sampling
+//! settings were stored match any temperature and token limit.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::LlmError;
+use crate::types::{LlmRequest, LlmResponse, LlmTier};
+
+/// Cassette file format version.
+pub const CASSETTE_VERSION: u32 = 1;
+
+/// The parts of a request a recorded response is matched on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// Model tier.
+    pub tier: LlmTier,
+    /// System prompt.
+    pub system: String,
+    /// User prompt.
+    pub user: String,
+    /// GBNF grammar constraining the answer.
+    #[serde(default)]
+    pub grammar: Option<String>,
+    /// Name of the response schema constraining the answer.
+    #[serde(default)]
+    pub response_schema: Option<String>,
+    /// Sampling temperature (`None`: recorded without one, matches any).
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// Token limit (`None`: recorded without one, matches any).
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+}
+
+impl RecordedRequest {
+    /// The recorded form of `request`.
+    #[must_use]
+    pub fn of(request: &LlmRequest) -> Self {
+        Self {
+            tier: request.tier,
+            system: request.system.clone(),
+            user: request.user.clone(),
+            grammar: request.grammar.clone(),
+            response_schema: request.response_schema.as_ref().map(|s| s.name.clone()),
+            temperature: Some(request.temperature),
+            max_tokens: Some(request.max_tokens),
+        }
+    }
+
+    /// Whether `request` is this recorded request.
+    #[must_use]
+    pub fn matches(&self, request: &LlmRequest) -> bool {
+        self.tier == request.tier
+            && self.system == request.system
+            && self.user == request.user
+            && self.grammar == request.grammar
+            && self.response_schema.as_deref()
+                == request.response_schema.as_ref().map(|s| s.name.as_str())
+            && self
+                .temperature
+                .is_none_or(|t| (t - request.temperature).abs() < f32::EPSILON)
+            && self.max_tokens.is_none_or(|n| n == request.max_tokens)
+    }
+}
+
+/// One recorded request and the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// What was asked.
+    pub request: RecordedRequest,
+    /// What the model answered.
+    pub response: LlmResponse,
+}
+
+/// On-disk cassette layout.
+#[derive(Serialize, Deserialize)]
+struct CassetteFile {
+    version: u32,
+    interactions: Vec<Interaction>,
+}
+
+/// Recorded interactions, shared between clones (record with one handle,
+/// save with another).
+#[derive(Debug, Clone, Default)]
+pub struct Cassette {
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+}
+
+impl Cassette {
+    /// An empty cassette.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a cassette saved with [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LlmError::ConfigError`] if the file cannot be read, is not
+    /// a cassette, or has an unsupported version.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LlmError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            LlmError::ConfigError(format!("failed to read cassette {}: {e}", path.display()))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&text).map_err(|e| {
+            LlmError::ConfigError(format!("failed to parse cassette {}: {e}", path.display()))
+        })?;
+        if file.version != CASSETTE_VERSION {
+            return Err(LlmError::ConfigError(format!(
+                "cassette {} has version {}, expected {CASSETTE_VERSION}",
+                path.display(),
+                file.version
+            )));
+        }
+        Ok(Self::from_interactions(file.interactions))
+    }
+
+    /// A cassette holding `interactions`.
+    #[must_use]
+    pub fn from_interactions(interactions: Vec<Interaction>) -> Self {
+        Self {
+            interactions: Arc::new(Mutex::new(interactions)),
+        }
+    }
+
+    /// Write the recorded interactions to `path` as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LlmError::ConfigError`] if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LlmError> {
+        let path = path.as_ref();
+        let file = CassetteFile {
+            version: CASSETTE_VERSION,
+            interactions: self.interactions(),
+        };
+        let text = serde_json::to_string_pretty(&file)
+            .map_err(|e| LlmError::ConfigError(format!("failed to encode cassette: {e}")))?;
+        std::fs::write(path, text).map_err(|e| {
+            LlmError::ConfigError(format!("failed to write cassette {}: {e}", path.display()))
+        })
+    }
+
+    /// Record that `request` got `response`.
+    pub fn record(&self, request: &LlmRequest, response: &LlmResponse) {
+        self.interactions.lock().push(Interaction {
+            request: RecordedRequest::of(request),
+            response: response.clone(),
+        });
+    }
+
+    /// A copy of the recorded interactions, in recording order.
+    #[must_use]
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().clone()
+    }
+
+    /// Number of recorded interactions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.interactions.lock().len()
+    }
+
+    /// Whether nothing has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.interactions.lock().is_empty()
+    }
+}
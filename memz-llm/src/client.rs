@@ -1,14 +1,22 @@
 //! LLM Client — unified interface for Ollama, `OpenAI`, and llama.cpp backends.
 
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use reqwest::Client;
 use serde_json::json;
 use tracing::{debug, warn};
 
+use crate::cassette::Cassette;
 use crate::error::LlmError;
+use crate::mock::{MOCK_MODEL, MockLlm};
+use crate::parse::{self, StructuredResponse};
 use crate::types::{LlmRequest, LlmResponse, LlmTier};
 
+/// Called with the error each time a response fails to parse (e.g. to
+/// count `llm_parse_failures`).
+pub type ParseFailureHook = Arc<dyn Fn(&LlmError) + Send + Sync>;
+
 /// Provider backend for LLM inference.
 #[derive(Debug, Clone)]
 pub enum LlmProvider {
@@ -16,6 +24,10 @@ pub enum LlmProvider {
     Ollama { base_url: String },
     /// OpenAI-compatible API (also works with Anthropic, Together, etc.).
     OpenAiCompatible { base_url: String, api_key: String },
+    /// llama.cpp HTTP server (`llama-server`), with GBNF grammar enforcement.
+    LlamaCpp { base_url: String },
+    /// In-process scripted responses, for tests (see [`crate::mock`]).
+    Mock(MockLlm),
     /// No LLM available — all calls return error, triggering rule-based fallback.
     None,
 }
@@ -27,6 +39,10 @@ pub struct LlmClient {
     tier1_model: String,
     tier2_model: String,
     max_retries: u32,
+    structured_output: bool,
+    retry_on_parse_failure: bool,
+    on_parse_failure: Option<ParseFailureHook>,
+    recorder: Option<Cassette>,
 }
 
 impl LlmClient {
@@ -44,6 +60,10 @@ impl LlmClient {
             tier1_model: tier1_model.into(),
             tier2_model: tier2_model.into(),
             max_retries,
+            structured_output: true,
+            retry_on_parse_failure: true,
+            on_parse_failure: None,
+            recorder: None,
         }
     }
 
@@ -56,15 +76,56 @@ impl LlmClient {
             tier1_model: String::new(),
             tier2_model: String::new(),
             max_retries: 0,
+            structured_output: false,
+            retry_on_parse_failure: false,
+            on_parse_failure: None,
+            recorder: None,
         }
     }
 
+    /// Create a client answered by `mock` (see [`crate::mock`]).
+    #[must_use]
+    pub fn mock(mock: MockLlm) -> Self {
+        Self::new(LlmProvider::Mock(mock), MOCK_MODEL, MOCK_MODEL, 0)
+    }
+
+    /// Record every successful request/response pair on `cassette`, for
+    /// replay with [`MockLlm::replaying`].
+    #[must_use]
+    pub fn with_recorder(mut self, cassette: Cassette) -> Self {
+        self.recorder = Some(cassette);
+        self
+    }
+
+    /// Whether requests' grammars and response schemas are sent to the
+    /// backend to constrain its output (default: on).
+    #[must_use]
+    pub fn with_structured_output(mut self, enforce: bool) -> Self {
+        self.structured_output = enforce;
+        self
+    }
+
+    /// Whether [`generate_structured`](Self::generate_structured) re-prompts
+    /// once when a response cannot be parsed (default: on).
+    #[must_use]
+    pub fn with_parse_retry(mut self, retry: bool) -> Self {
+        self.retry_on_parse_failure = retry;
+        self
+    }
+
+    /// Call `hook` on every response that fails to parse.
+    #[must_use]
+    pub fn with_parse_failure_hook(mut self, hook: ParseFailureHook) -> Self {
+        self.on_parse_failure = Some(hook);
+        self
+    }
+
     /// Generate a response from the LLM.
     ///
     /// Returns `Err` if the LLM is unavailable or all retries fail.
     /// The caller should fall back to rule-based generation on error.
     pub async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
-        match &self.provider {
+        let result = match &self.provider {
             LlmProvider::None => {
                 Err(LlmError::Unavailable("No LLM provider configured".into()))
             }
@@ -74,55 +135,50 @@ impl LlmClient {
             LlmProvider::OpenAiCompatible { base_url, api_key } => {
                 self.generate_openai(base_url, api_key, request).await
             }
-        }
-    }
-
-    /// Generate using Ollama's API.
-    async fn generate_ollama(
-        &self,
-        base_url: &str,
-        request: &LlmRequest,
-    ) -> Result<LlmResponse, LlmError> {
-        let model = match request.tier {
-            LlmTier::SmallLocal => &self.tier1_model,
-            LlmTier::LargeModel => &self.tier2_model,
-            LlmTier::RuleBased => {
-                return Err(LlmError::ConfigError(
-                    "Rule-based tier does not use LLM".into(),
-                ));
+            LlmProvider::LlamaCpp { base_url } => {
+                self.generate_llama_cpp(base_url, request).await
             }
+            LlmProvider::Mock(mock) => mock.generate(request).await,
         };
+        if let (Ok(response), Some(cassette)) = (&result, &self.recorder) {
+            cassette.record(request, response);
+        }
+        result
+    }
 
-        let url = format!("{base_url}/api/generate");
-        let mut body = json!({
-            "model": model,
-            "prompt": format!("{}\n\n{}", request.system, request.user),
-            "stream": false,
-            "options": {
-                "temperature": request.temperature,
-                "num_predict": request.max_tokens,
-            }
-        });
-
-        // Add GBNF grammar if specified (Ollama supports this as "grammar").
-        if let Some(grammar) = &request.grammar {
-            body["options"]["grammar"] = json!(grammar);
+    /// Model name for `tier`.
+    fn model(&self, tier: LlmTier) -> Result<&String, LlmError> {
+        match tier {
+            LlmTier::SmallLocal => Ok(&self.tier1_model),
+            LlmTier::LargeModel => Ok(&self.tier2_model),
+            LlmTier::RuleBased => Err(LlmError::ConfigError(
+                "Rule-based tier does not use LLM".into(),
+            )),
         }
+    }
 
+    /// POST `body` to `url`, retrying up to `max_retries` times.  Returns
+    /// the JSON reply and the latency of the successful attempt.
+    async fn post_json(
+        &self,
+        backend: &str,
+        url: &str,
+        body: &serde_json::Value,
+        api_key: Option<&str>,
+        timeout_ms: u64,
+    ) -> Result<(serde_json::Value, u64), LlmError> {
         let mut last_error = String::new();
         for attempt in 0..=self.max_retries {
             if attempt > 0 {
-                debug!("Retrying LLM call (attempt {}/{})", attempt + 1, self.max_retries + 1);
+                debug!("Retrying {backend} call (attempt {}/{})", attempt + 1, self.max_retries + 1);
             }
 
             let start = Instant::now();
-            let result = self
-                .http
-                .post(&url)
-                .json(&body)
-                .timeout(Duration::from_millis(request.timeout_ms))
-                .send()
-                .await;
+            let mut post = self.http.post(url).json(body).timeout(Duration::from_millis(timeout_ms));
+            if let Some(api_key) = api_key {
+                post = post.header("Authorization", format!("Bearer {api_key}"));
+            }
+            let result = post.send().await;
 
             let latency_ms = start.elapsed().as_millis() as u64;
 
@@ -133,28 +189,17 @@ impl LlmClient {
                             .json()
                             .await
                             .map_err(|e| LlmError::ParseError(e.to_string()))?;
-
-                        let text = json["response"]
-                            .as_str()
-                            .unwrap_or("")
-                            .to_string();
-
-                        return Ok(LlmResponse {
-                            text,
-                            tokens_generated: json["eval_count"].as_u64().unwrap_or(0) as u32,
-                            latency_ms,
-                            model: model.clone(),
-                        });
+                        return Ok((json, latency_ms));
                     }
                     last_error = format!("HTTP {}: {}", resp.status(), resp.text().await.unwrap_or_default());
-                    warn!("Ollama returned error: {}", last_error);
+                    warn!("{backend} returned error: {}", last_error);
                 }
                 Err(e) => {
                     last_error = e.to_string();
                     if e.is_timeout() {
-                        warn!("Ollama request timed out after {}ms", request.timeout_ms);
+                        warn!("{backend} request timed out after {timeout_ms}ms");
                     } else {
-                        warn!("Ollama request failed: {}", last_error);
+                        warn!("{backend} request failed: {}", last_error);
                     }
                 }
             }
@@ -166,25 +211,51 @@ impl LlmClient {
         })
     }
 
-    /// Generate using OpenAI-compatible API.
+    /// Generate using Ollama's API.
+    async fn generate_ollama(
+        &self,
+        base_url: &str,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let model = self.model(request.tier)?;
+
+        let url = format!("{base_url}/api/generate");
+        let mut body = json!({
+            "model": model,
+            "prompt": format!("{}\n\n{}", request.system, request.user),
+            "stream": false,
+            "options": {
+                "temperature": request.temperature,
+                "num_predict": request.max_tokens,
+            }
+        });
+
+        // Add GBNF grammar if specified (Ollama supports this as "grammar").
+        if let Some(grammar) = request.grammar.as_ref().filter(|_| self.structured_output) {
+            body["options"]["grammar"] = json!(grammar);
+        }
+
+        let (json, latency_ms) = self.post_json("Ollama", &url, &body, None, request.timeout_ms).await?;
+        Ok(LlmResponse {
+            text: json["response"].as_str().unwrap_or("").to_string(),
+            tokens_generated: json["eval_count"].as_u64().unwrap_or(0) as u32,
+            latency_ms,
+            model: model.clone(),
+        })
+    }
+
+    /// Generate using OpenAI-compatible API.  A response schema is sent as
+    /// `response_format: json_schema` (strict).
     async fn generate_openai(
         &self,
         base_url: &str,
         api_key: &str,
         request: &LlmRequest,
     ) -> Result<LlmResponse, LlmError> {
-        let model = match request.tier {
-            LlmTier::SmallLocal => &self.tier1_model,
-            LlmTier::LargeModel => &self.tier2_model,
-            LlmTier::RuleBased => {
-                return Err(LlmError::ConfigError(
-                    "Rule-based tier does not use LLM".into(),
-                ));
-            }
-        };
+        let model = self.model(request.tier)?;
 
         let url = format!("{base_url}/v1/chat/completions");
-        let body = json!({
+        let mut body = json!({
             "model": model,
             "messages": [
                 { "role": "system", "content": request.system },
@@ -194,73 +265,96 @@ impl LlmClient {
             "temperature": request.temperature,
         });
 
-        let mut last_error = String::new();
-        for attempt in 0..=self.max_retries {
-            if attempt > 0 {
-                debug!("Retrying OpenAI call (attempt {}/{})", attempt + 1, self.max_retries + 1);
-            }
+        if let Some(schema) = request.response_schema.as_ref().filter(|_| self.structured_output) {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": schema.name,
+                    "schema": schema.schema,
+                    "strict": true,
+                },
+            });
+        }
 
-            let start = Instant::now();
-            let result = self
-                .http
-                .post(&url)
-                .header("Authorization", format!("Bearer {api_key}"))
-                .json(&body)
-                .timeout(Duration::from_millis(request.timeout_ms))
-                .send()
-                .await;
+        let (json, latency_ms) = self.post_json("OpenAI API", &url, &body, Some(api_key), request.timeout_ms).await?;
+        Ok(LlmResponse {
+            text: json["choices"][0]["message"]["content"].as_str().unwrap_or("").to_string(),
+            tokens_generated: json["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
+            latency_ms,
+            model: model.clone(),
+        })
+    }
 
-            let latency_ms = start.elapsed().as_millis() as u64;
+    /// Generate using the llama.cpp server's `/completion` endpoint.  The
+    /// GBNF grammar is enforced during sampling; without one, a response
+    /// schema is sent as `json_schema` (which the server turns into a
+    /// grammar).  The server runs a single model, so the tier only picks
+    /// the name reported when the server does not send one.
+    async fn generate_llama_cpp(
+        &self,
+        base_url: &str,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let model = self.model(request.tier)?;
 
-            match result {
-                Ok(resp) => {
-                    if resp.status().is_success() {
-                        let json: serde_json::Value = resp
-                            .json()
-                            .await
-                            .map_err(|e| LlmError::ParseError(e.to_string()))?;
+        let url = format!("{base_url}/completion");
+        let mut body = json!({
+            "prompt": format!("{}\n\n{}", request.system, request.user),
+            "n_predict": request.max_tokens,
+            "temperature": request.temperature,
+            "stream": false,
+            "cache_prompt": true,
+        });
 
-                        let text = json["choices"][0]["message"]["content"]
-                            .as_str()
-                            .unwrap_or("")
-                            .to_string();
-
-                        let tokens = json["usage"]["completion_tokens"]
-                            .as_u64()
-                            .unwrap_or(0) as u32;
-
-                        return Ok(LlmResponse {
-                            text,
-                            tokens_generated: tokens,
-                            latency_ms,
-                            model: model.clone(),
-                        });
-                    }
-                    last_error = format!("HTTP {}", resp.status());
-                    warn!("OpenAI API returned error: {}", last_error);
-                }
-                Err(e) => {
-                    last_error = e.to_string();
-                    warn!("OpenAI API request failed: {}", last_error);
-                }
+        if self.structured_output {
+            if let Some(grammar) = &request.grammar {
+                body["grammar"] = json!(grammar);
+            } else if let Some(schema) = &request.response_schema {
+                body["json_schema"] = schema.schema.clone();
             }
         }
 
-        Err(LlmError::RetriesExhausted {
-            attempts: self.max_retries + 1,
-            last_error,
+        let (json, latency_ms) = self.post_json("llama.cpp", &url, &body, None, request.timeout_ms).await?;
+        Ok(LlmResponse {
+            text: json["content"].as_str().unwrap_or("").to_string(),
+            tokens_generated: json["tokens_predicted"].as_u64().unwrap_or(0) as u32,
+            latency_ms,
+            model: json["model"].as_str().unwrap_or(model).to_string(),
         })
     }
 
-    /// Parse a raw LLM response text as structured JSON.
+    /// Parse a raw LLM response text as structured JSON, extracting it
+    /// from surrounding prose and repairing it where cheap (see
+    /// [`parse`](crate::parse)).
     ///
     /// Returns `Err` if the text is not valid JSON or doesn't match the expected type.
-    pub fn parse_structured<T: serde::de::DeserializeOwned>(
+    pub fn parse_structured<T: StructuredResponse>(
         &self,
         response: &LlmResponse,
     ) -> Result<T, LlmError> {
-        serde_json::from_str(&response.text)
-            .map_err(|e| LlmError::ParseError(format!("JSON parse error: {} — raw text: '{}'", e, response.text)))
+        parse::parse_structured(&response.text).inspect_err(|e| {
+            if let Some(hook) = &self.on_parse_failure {
+                hook(e);
+            }
+        })
+    }
+
+    /// Generate and parse a structured response, constrained by `T`'s
+    /// response schema unless the request brings its own.  If the answer
+    /// cannot be parsed and parse retries are on, re-prompt once with a
+    /// stricter, cooler request (see [`simplified_retry`]).
+    pub async fn generate_structured<T: StructuredResponse>(&self, request: &LlmRequest) -> Result<T, LlmError> {
+        let mut request = request.clone();
+        request.response_schema.get_or_insert_with(T::response_schema);
+        let response = self.generate(&request).await?;
+        match self.parse_structured(&response) {
+            Err(e @ (LlmError::ParseError(_) | LlmError::SchemaValidation(_))) if self.retry_on_parse_failure => {
+                debug!("LLM response unusable ({e}); re-prompting");
+                let retry = self.generate(&simplified_retry(&request, &e)).await?;
+                self.parse_structured(&retry)
+            }
+            result => result,
+        }
     }
 
     /// Check if the LLM client has a backend configured.
@@ -269,3 +363,28 @@ impl LlmClient {
         !matches!(self.provider, LlmProvider::None)
     }
 }
+
+/// Appended to the user prompt when re-prompting after an unparseable answer.
+pub const RETRY_TEMPLATE: &str =
+    "Your previous answer was not valid JSON. Answer again with ONLY the JSON object described above — no prose, no code fences.";
+
+/// Appended to the user prompt when re-prompting after an answer that
+/// parsed but broke a field rule; `{problem}` names the field.
+pub const RETRY_SCHEMA_TEMPLATE: &str =
+    "Your previous answer broke a rule: {problem}. Answer again with ONLY the JSON object described above — no prose, no code fences.";
+
+/// The re-prompt after an unusable answer: the same request followed by
+/// [`RETRY_TEMPLATE`] (or [`RETRY_SCHEMA_TEMPLATE`]), at a low
+/// temperature.  The model's own output is never echoed back.
+#[must_use]
+pub fn simplified_retry(request: &LlmRequest, error: &LlmError) -> LlmRequest {
+    let instruction = match error {
+        LlmError::SchemaValidation(problem) => RETRY_SCHEMA_TEMPLATE.replace("{problem}", problem),
+        _ => RETRY_TEMPLATE.to_string(),
+    };
+    LlmRequest {
+        user: format!("{}\n\n{instruction}", request.user),
+        temperature: request.temperature.min(0.2),
+        ..request.clone()
+    }
+}
@@ -0,0 +1,581 @@
+//! LLM Dispatcher — worker pool that drains the [`LlmQueue`] (§12.3)
+//!
+//! The game thread never awaits an LLM call.  It submits a request and
+//! gets back an [`LlmHandle`] (or registers a callback); `N` async workers
+//! — `max_concurrent_llm_requests` — take requests from the queue in
+//! priority order, run them through [`LlmClient::generate`], and deliver
+//! each result to its own handle.  The game thread then polls handles with
+//! [`LlmHandle::try_take`] once per frame, which never blocks.
+//!
+//! Every request gets exactly one result.  Requests that expire in the
+//! queue, are dropped because it is full, or are still pending when the
+//! dispatcher shuts down resolve to [`LlmError::Cancelled`], so callers
+//! can fall back to rule-based behaviour instead of waiting forever.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tokio::runtime::Handle;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::client::LlmClient;
+use crate::error::LlmError;
+use crate::parse::StructuredResponse;
+use crate::queue::{LlmPriority, LlmQueue};
+use crate::types::{LlmRequest, LlmResponse};
+
+/// Boxed future a worker awaits to run one request.
+type JobFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The work behind one request and where its result goes.
+trait Job: Send {
+    /// Run the request against `client` and deliver the result.
+    fn run(self: Box<Self>, client: Arc<LlmClient>, request: LlmRequest) -> JobFuture;
+    /// Deliver `error` instead; the request will never run.
+    fn cancel(self: Box<Self>, error: LlmError);
+}
+
+/// Raw response to a callback.
+struct CallbackJob<F>(F);
+
+impl<F: FnOnce(Result<LlmResponse, LlmError>) + Send + 'static> Job for CallbackJob<F> {
+    fn run(self: Box<Self>, client: Arc<LlmClient>, request: LlmRequest) -> JobFuture {
+        Box::pin(async move { (self.0)(client.generate(&request).await) })
+    }
+
+    fn cancel(self: Box<Self>, error: LlmError) {
+        (self.0)(Err(error));
+    }
+}
+
+/// Parsed (and if need be re-prompted) response to an [`LlmHandle`].
+struct StructuredJob<T>(oneshot::Sender<Result<T, LlmError>>);
+
+impl<T: StructuredResponse + Send + 'static> Job for StructuredJob<T> {
+    fn run(self: Box<Self>, client: Arc<LlmClient>, request: LlmRequest) -> JobFuture {
+        Box::pin(async move {
+            let _ = self.0.send(client.generate_structured(&request).await);
+        })
+    }
+
+    fn cancel(self: Box<Self>, error: LlmError) {
+        let _ = self.0.send(Err(error));
+    }
+}
+
+/// Delivers one request's result.  Dropped unrun, it reports
+/// cancellation, so no requester is left without an answer.
+pub(crate) struct Reply(Option<Box<dyn Job>>);
+
+impl Reply {
+    fn new(job: impl Job + 'static) -> Self {
+        Self(Some(Box::new(job)))
+    }
+
+    /// A reply that discards the result, for requests enqueued directly on
+    /// the [`LlmQueue`] without one.
+    fn discard() -> Self {
+        Self::new(CallbackJob(|_| {}))
+    }
+
+    fn run(mut self, client: Arc<LlmClient>, request: LlmRequest) -> Option<JobFuture> {
+        self.0.take().map(|job| job.run(client, request))
+    }
+
+    fn cancel(mut self, error: LlmError) {
+        if let Some(job) = self.0.take() {
+            job.cancel(error);
+        }
+    }
+}
+
+impl Drop for Reply {
+    fn drop(&mut self) {
+        if let Some(job) = self.0.take() {
+            job.cancel(LlmError::Cancelled(
+                "dropped before it ran (expired or dispatcher stopped)".into(),
+            ));
+        }
+    }
+}
+
+/// Completion handle for one submitted request, parsed as `T`.
+#[derive(Debug)]
+pub struct LlmHandle<T> {
+    id: u64,
+    receiver: oneshot::Receiver<Result<T, LlmError>>,
+    done: bool,
+}
+
+impl<T> LlmHandle<T> {
+    /// Queue ID of the request.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The result if it has arrived, without blocking.  Returns `Some`
+    /// exactly once; later calls return `None`.
+    pub fn try_take(&mut self) -> Option<Result<T, LlmError>> {
+        if self.done {
+            return None;
+        }
+        let result = match self.receiver.try_recv() {
+            Ok(result) => result,
+            Err(oneshot::error::TryRecvError::Empty) => return None,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                Err(LlmError::Cancelled("dispatcher dropped the request".into()))
+            }
+        };
+        self.done = true;
+        Some(result)
+    }
+
+    /// Whether [`try_take`](Self::try_take) has returned the result.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Wait for the result (off the game thread).
+    pub async fn wait(self) -> Result<T, LlmError> {
+        self.receiver
+            .await
+            .unwrap_or_else(|_| Err(LlmError::Cancelled("dispatcher dropped the request".into())))
+    }
+}
+
+/// Async worker pool executing queued LLM requests.
+///
+/// Dropping the dispatcher stops its workers; requests still queued are
+/// cancelled once the queue itself is dropped.
+pub struct LlmDispatcher {
+    queue: LlmQueue,
+    client: Arc<RwLock<Arc<LlmClient>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl LlmDispatcher {
+    /// Start `workers` workers on `runtime`, draining `queue` into `client`.
+    ///
+    /// With zero workers (e.g. `max_concurrent_llm_requests = 0`) nothing
+    /// is accepted and every `submit` returns `None`.
+    #[must_use]
+    pub fn start(
+        client: Arc<LlmClient>,
+        queue: LlmQueue,
+        workers: usize,
+        runtime: &Handle,
+    ) -> Self {
+        let client = Arc::new(RwLock::new(client));
+        let workers = (0..workers)
+            .map(|_| {
+                let queue = queue.clone();
+                let client = Arc::clone(&client);
+                runtime.spawn(async move {
+                    loop {
+                        let mut request = queue.next().await;
+                        let reply = request.reply.take().unwrap_or_else(Reply::discard);
+                        // Each call keeps the client it started with, even
+                        // if the client is replaced meanwhile.
+                        let llm = Arc::clone(&*client.read());
+                        if let Some(job) = reply.run(llm, request.to_llm_request()) {
+                            job.await;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            queue,
+            client,
+            workers,
+        }
+    }
+
+    /// The queue the workers drain.
+    #[must_use]
+    pub fn queue(&self) -> &LlmQueue {
+        &self.queue
+    }
+
+    /// Number of workers, i.e. the most requests in flight at once.
+    #[must_use]
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Use `client` for requests started from now on (e.g. after a config
+    /// reload); calls in flight finish against the old client.
+    pub fn set_client(&self, client: Arc<LlmClient>) {
+        *self.client.write() = client;
+    }
+
+    /// Submit `request` and have `callback` called with its raw response
+    /// on a worker.  The callback runs exactly once — with
+    /// [`LlmError::Cancelled`] if the request never runs.
+    ///
+    /// Returns the request ID, or `None` if it was not accepted.
+    pub fn submit_with(
+        &self,
+        priority: LlmPriority,
+        request: LlmRequest,
+        deadline: Duration,
+        callback: impl FnOnce(Result<LlmResponse, LlmError>) + Send + 'static,
+    ) -> Option<u64> {
+        self.push(
+            priority,
+            request,
+            deadline,
+            Reply::new(CallbackJob(callback)),
+        )
+    }
+
+    /// Submit `request` and get a handle to its raw response.
+    #[must_use]
+    pub fn submit_raw(
+        &self,
+        priority: LlmPriority,
+        request: LlmRequest,
+        deadline: Duration,
+    ) -> Option<LlmHandle<LlmResponse>> {
+        let (sender, receiver) = oneshot::channel();
+        let id = self.submit_with(priority, request, deadline, move |result| {
+            let _ = sender.send(result);
+        })?;
+        Some(LlmHandle {
+            id,
+            receiver,
+            done: false,
+        })
+    }
+
+    /// Submit `request` and get a handle to its response parsed into `T`
+    /// by [`LlmClient::generate_structured`], which re-prompts once on an
+    /// unusable answer.
+    #[must_use]
+    pub fn submit<T: StructuredResponse + Send + 'static>(
+        &self,
+        priority: LlmPriority,
+        request: LlmRequest,
+        deadline: Duration,
+    ) -> Option<LlmHandle<T>> {
+        let (sender, receiver) = oneshot::channel();
+        let id = self.push(
+            priority,
+            request,
+            deadline,
+            Reply::new(StructuredJob(sender)),
+        )?;
+        Some(LlmHandle {
+            id,
+            receiver,
+            done: false,
+        })
+    }
+
+    fn push(
+        &self,
+        priority: LlmPriority,
+        request: LlmRequest,
+        deadline: Duration,
+        reply: Reply,
+    ) -> Option<u64> {
+        if self.workers.is_empty() {
+            reply.cancel(LlmError::Cancelled("no LLM workers".into()));
+            return None;
+        }
+        self.queue.push(priority, request, deadline, Some(reply))
+    }
+}
+
+impl Drop for LlmDispatcher {
+    fn drop(&mut self) {
+        for worker in &self.workers {
+            worker.abort();
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{LlmProvider, simplified_retry};
+    use crate::types::DialogueResponse;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A stand-in Ollama server answering its n-th request with
+    /// `answers[n]` (the last one once they run out), recording the most
+    /// connections it saw at once.
+    async fn fake_ollama(answers: &'static [&'static str], peak: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let open = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(async move {
+            for n in 0.. {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let (open, peak) = (Arc::clone(&open), Arc::clone(&peak));
+                let text = answers[n.min(answers.len() - 1)];
+                tokio::spawn(async move {
+                    let now = open.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    let mut buf = vec![0u8; 16 * 1024];
+                    let _ = socket.read(&mut buf).await;
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    let body = serde_json::json!({ "response": text, "eval_count": 3 }).to_string();
+                    let reply = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    // Close the call before answering: the client may send
+                    // its next request as soon as it has the response.
+                    open.fetch_sub(1, Ordering::SeqCst);
+                    let _ = socket.write_all(reply.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .expect("runtime")
+    }
+
+    #[test]
+    fn results_route_back_to_their_handles() {
+        let rt = runtime();
+        let peak = Arc::new(AtomicUsize::new(0));
+        let answers =
+            &[r#"{"dialogue":"Welcome back!","emotion_shift":0.1,"new_memory":"They returned"}"#];
+        let base_url = rt.block_on(fake_ollama(answers, Arc::clone(&peak)));
+        let client = Arc::new(LlmClient::new(
+            LlmProvider::Ollama { base_url },
+            "tiny",
+            "big",
+            0,
+        ));
+        let dispatcher = LlmDispatcher::start(client, LlmQueue::new(16), 2, rt.handle());
+        assert_eq!(dispatcher.worker_count(), 2);
+
+        let mut handles: Vec<LlmHandle<DialogueResponse>> = (0..4)
+            .map(|i| {
+                let request = LlmRequest::tier1("system", format!("greeting {i}"));
+                dispatcher
+                    .submit(LlmPriority::Critical, request, Duration::from_secs(5))
+                    .expect("accepted")
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        let start = std::time::Instant::now();
+        while results.len() < handles.len() && start.elapsed() < Duration::from_secs(5) {
+            results.extend(handles.iter_mut().filter_map(LlmHandle::try_take));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(results.len(), 4);
+        for result in results {
+            assert_eq!(result.expect("parsed").dialogue, "Welcome back!");
+        }
+        assert!(
+            handles
+                .iter_mut()
+                .all(|h| h.is_done() && h.try_take().is_none())
+        );
+        assert!(
+            peak.load(Ordering::SeqCst) <= 2,
+            "at most one call per worker"
+        );
+    }
+
+    #[test]
+    fn failures_and_expiry_resolve_the_handle() {
+        let rt = runtime();
+        let dispatcher = LlmDispatcher::start(
+            Arc::new(LlmClient::none()),
+            LlmQueue::new(16),
+            1,
+            rt.handle(),
+        );
+
+        let handle = dispatcher
+            .submit::<DialogueResponse>(
+                LlmPriority::High,
+                LlmRequest::tier1("s", "u"),
+                Duration::from_secs(5),
+            )
+            .expect("accepted");
+        let result = rt.block_on(handle.wait());
+        assert!(matches!(result, Err(LlmError::Unavailable(_))));
+
+        // A request that outlives its deadline in the queue is cancelled.
+        let idle = LlmDispatcher::start(
+            Arc::new(LlmClient::none()),
+            LlmQueue::new(16),
+            0,
+            rt.handle(),
+        );
+        assert!(
+            idle.submit_raw(
+                LlmPriority::Low,
+                LlmRequest::tier1("s", "u"),
+                Duration::ZERO
+            )
+            .is_none()
+        );
+
+        let queue = LlmQueue::new(16);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let reply = Reply::new(CallbackJob(move |result: Result<LlmResponse, LlmError>| {
+            let _ = sender.send(result);
+        }));
+        queue.push(
+            LlmPriority::Low,
+            LlmRequest::tier1("s", "u"),
+            Duration::ZERO,
+            Some(reply),
+        );
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(queue.dequeue().is_none());
+        assert!(matches!(
+            receiver.recv().expect("callback ran"),
+            Err(LlmError::Cancelled(_))
+        ));
+
+        // Cancellation runs outside the queue lock, so a callback may use
+        // the queue.
+        for purge in [false, true] {
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let inner = queue.clone();
+            let reply = Reply::new(CallbackJob(move |_: Result<LlmResponse, LlmError>| {
+                let _ = sender.send(inner.stats().total_expired);
+            }));
+            queue.push(
+                LlmPriority::Low,
+                LlmRequest::tier1("s", "u"),
+                Duration::ZERO,
+                Some(reply),
+            );
+            std::thread::sleep(Duration::from_millis(1));
+            if purge {
+                assert_eq!(queue.purge_expired(), 1);
+            } else {
+                assert!(queue.dequeue().is_none());
+            }
+            assert!(receiver.recv().expect("callback ran") >= 2);
+        }
+    }
+
+    #[test]
+    fn requests_enqueued_without_a_reply_still_run() {
+        use crate::mock::MockLlm;
+
+        let rt = runtime();
+        let mock = MockLlm::new();
+        let dispatcher = LlmDispatcher::start(
+            Arc::new(LlmClient::mock(mock.clone())),
+            LlmQueue::new(16),
+            1,
+            rt.handle(),
+        );
+        let queue = dispatcher.queue();
+        assert!(
+            queue
+                .enqueue(
+                    LlmPriority::Low,
+                    "s".into(),
+                    "u".into(),
+                    None,
+                    16,
+                    0.5,
+                    Duration::from_secs(5)
+                )
+                .is_some()
+        );
+        assert!(
+            queue
+                .enqueue_request(
+                    LlmPriority::High,
+                    LlmRequest::tier2("s", "v"),
+                    Duration::from_secs(5)
+                )
+                .is_some()
+        );
+
+        let start = std::time::Instant::now();
+        while mock.call_count() < 2 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(mock.call_count(), 2);
+    }
+
+    #[test]
+    fn unusable_answers_are_re_prompted_once() {
+        let rt = runtime();
+        let answers: &'static [&'static str] = &[
+            "Hmm, let me think about that.",
+            r#"Here you go: {"dialogue": "Back again?", "emotion_shift": 3, "new_memory": ""}"#,
+        ];
+        let failures = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&failures);
+        let base_url = rt.block_on(fake_ollama(answers, Arc::new(AtomicUsize::new(0))));
+        let client = LlmClient::new(LlmProvider::Ollama { base_url }, "tiny", "big", 0)
+            .with_parse_failure_hook(Arc::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+        let dispatcher = LlmDispatcher::start(Arc::new(client), LlmQueue::new(16), 1, rt.handle());
+
+        let handle = dispatcher
+            .submit::<DialogueResponse>(
+                LlmPriority::High,
+                LlmRequest::tier1("s", "u"),
+                Duration::from_secs(5),
+            )
+            .expect("accepted");
+        let response = rt.block_on(handle.wait()).expect("second answer parses");
+        assert_eq!(response.dialogue, "Back again?");
+        assert!(
+            (response.emotion_shift - 1.0).abs() < f32::EPSILON,
+            "clamped"
+        );
+        assert_eq!(failures.load(Ordering::SeqCst), 1);
+
+        // Without parse retries the first unusable answer is final.
+        let base_url = rt.block_on(fake_ollama(answers, Arc::new(AtomicUsize::new(0))));
+        let client = LlmClient::new(LlmProvider::Ollama { base_url }, "tiny", "big", 0)
+            .with_parse_retry(false);
+        let result = rt
+            .block_on(client.generate_structured::<DialogueResponse>(&LlmRequest::tier1("s", "u")));
+        assert!(matches!(result, Err(LlmError::ParseError(_))));
+
+        let mut hot = LlmRequest::tier1("s", "u");
+        hot.temperature = 0.9;
+        let retry = simplified_retry(&hot, &LlmError::ParseError("echoed model output".into()));
+        assert!(retry.user.starts_with("u\n\n") && retry.user.contains("ONLY the JSON object"));
+        assert!(!retry.user.contains("echoed model output"));
+        assert!(retry.temperature <= 0.2);
+        let retry = simplified_retry(
+            &hot,
+            &LlmError::SchemaValidation("`dialogue` is empty".into()),
+        );
+        assert!(retry.user.contains("broke a rule: `dialogue` is empty."));
+    }
+}
@@ -32,6 +32,11 @@ pub enum LlmError {
         last_error: String,
     },
 
+    /// Request was dropped before it ran (deadline passed, queue full or
+    /// dispatcher shut down).
+    #[error("LLM request cancelled: {0}")]
+    Cancelled(String),
+
     /// Configuration error.
     #[error("LLM configuration error: {0}")]
     ConfigError(String),
@@ -0,0 +1,418 @@
+//! Grammar Registry — GBNF text behind prompt templates' `grammar` names.
+//!
+//! Prompt templates name their grammar by filename (`grammar =
+//! "dialogue_response.gbnf"`).  The registry maps those names to GBNF text
+//! so requests carry the grammar itself, which llama.cpp's `/completion`
+//! endpoint (and Ollama's `grammar` option) use to constrain sampling.
+//!
+//! [`GrammarRegistry::builtin`] holds the compiled-in grammars from
+//! [`prompt`](crate::prompt); [`GrammarRegistry::load_directory`] adds or
+//! overrides grammars from `*.gbnf` files, e.g. `memz-llm/prompts/grammars`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::prompt::{DIALOGUE_GRAMMAR, GOSSIP_GRAMMAR, MEMORY_SUMMARY_GRAMMAR, REFLECTION_GRAMMAR};
+
+/// Extension of grammar files.
+pub const GRAMMAR_EXTENSION: &str = "gbnf";
+
+/// GBNF grammars by filename.
+#[derive(Debug, Clone, Default)]
+pub struct GrammarRegistry {
+    grammars: HashMap<String, String>,
+}
+
+impl GrammarRegistry {
+    /// An empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The compiled-in grammars, under the filenames the built-in prompt
+    /// templates use.
+    #[must_use]
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.insert("dialogue_response.gbnf", DIALOGUE_GRAMMAR);
+        registry.insert("reflection_output.gbnf", REFLECTION_GRAMMAR);
+        registry.insert("gossip_output.gbnf", GOSSIP_GRAMMAR);
+        registry.insert("memory_summary.gbnf", MEMORY_SUMMARY_GRAMMAR);
+        registry
+    }
+
+    /// Load every `*.gbnf` file in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or a grammar file cannot be read.
+    pub fn from_directory(dir: impl AsRef<Path>) -> Result<Self, String> {
+        let mut registry = Self::new();
+        registry.load_directory(dir)?;
+        Ok(registry)
+    }
+
+    /// Add every `*.gbnf` file in `dir`, replacing grammars of the same
+    /// name.  Returns how many were loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or a grammar file cannot be read.
+    pub fn load_directory(&mut self, dir: impl AsRef<Path>) -> Result<usize, String> {
+        let dir = dir.as_ref();
+        let entries =
+            std::fs::read_dir(dir).map_err(|e| format!("failed to read {}: {e}", dir.display()))?;
+        let mut loaded = 0;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| format!("failed to read {}: {e}", dir.display()))?
+                .path();
+            if path.extension().is_none_or(|ext| ext != GRAMMAR_EXTENSION) {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+            if text.trim().is_empty() {
+                return Err(format!("grammar {} is empty", path.display()));
+            }
+            self.insert(name, text);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Register `text` as grammar `name`.
+    pub fn insert(&mut self, name: impl Into<String>, text: impl Into<String>) {
+        self.grammars.insert(name.into(), text.into());
+    }
+
+    /// The GBNF text of grammar `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.grammars.get(name).map(String::as_str)
+    }
+
+    /// Number of loaded grammars.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.grammars.len()
+    }
+
+    /// Whether no grammars are loaded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.grammars.is_empty()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{StructuredResponse, parse_structured};
+    use crate::prompt::{PromptEngine, PromptId};
+    use crate::types::{
+        DialogueResponse, GossipResponse, MemorySummaryResponse, ReflectionResponse,
+    };
+
+    /// Just enough GBNF to check samples against the grammars here:
+    /// literals, character classes, `.`, groups, alternation, rule
+    /// references and the `? * + {m,n}` repetitions.
+    #[derive(Debug)]
+    enum Node {
+        Literal(Vec<char>),
+        Class {
+            negated: bool,
+            ranges: Vec<(char, char)>,
+        },
+        Any,
+        Rule(String),
+        Seq(Vec<Node>),
+        Alt(Vec<Node>),
+        Repeat(Box<Node>, usize, Option<usize>),
+    }
+
+    struct Gbnf(HashMap<String, Node>);
+
+    impl Gbnf {
+        fn parse(text: &str) -> Self {
+            let rules = text
+                .lines()
+                .filter_map(|line| line.split_once("::="))
+                .map(|(name, body)| {
+                    let mut chars = body
+                        .trim()
+                        .chars()
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                        .peekable();
+                    (name.trim().to_string(), alternation(&mut chars))
+                })
+                .collect();
+            Self(rules)
+        }
+
+        fn accepts(&self, sample: &str) -> bool {
+            let input: Vec<char> = sample.chars().collect();
+            self.walk(&self.0["root"], &input, 0, &mut |end| end == input.len())
+        }
+
+        /// Match `node` at `at`, calling `rest` with every end position
+        /// until it accepts one.
+        fn walk(
+            &self,
+            node: &Node,
+            input: &[char],
+            at: usize,
+            rest: &mut dyn FnMut(usize) -> bool,
+        ) -> bool {
+            match node {
+                Node::Literal(lit) => input[at..].starts_with(lit) && rest(at + lit.len()),
+                Node::Class { negated, ranges } => {
+                    input.get(at).is_some_and(|c| {
+                        ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(c)) != *negated
+                    }) && rest(at + 1)
+                }
+                Node::Any => at < input.len() && rest(at + 1),
+                Node::Rule(name) => self.walk(&self.0[name], input, at, rest),
+                Node::Seq(nodes) => self.walk_seq(nodes, input, at, rest),
+                Node::Alt(options) => options
+                    .iter()
+                    .any(|option| self.walk(option, input, at, rest)),
+                Node::Repeat(inner, min, max) => {
+                    self.walk_repeat(inner, *min, *max, input, at, rest)
+                }
+            }
+        }
+
+        fn walk_seq(
+            &self,
+            nodes: &[Node],
+            input: &[char],
+            at: usize,
+            rest: &mut dyn FnMut(usize) -> bool,
+        ) -> bool {
+            match nodes.split_first() {
+                None => rest(at),
+                Some((first, tail)) => self.walk(first, input, at, &mut |next| {
+                    self.walk_seq(tail, input, next, rest)
+                }),
+            }
+        }
+
+        fn walk_repeat(
+            &self,
+            inner: &Node,
+            min: usize,
+            max: Option<usize>,
+            input: &[char],
+            at: usize,
+            rest: &mut dyn FnMut(usize) -> bool,
+        ) -> bool {
+            let more = max.is_none_or(|max| max > 0)
+                && self.walk(inner, input, at, &mut |next| {
+                    next > at
+                        && self.walk_repeat(
+                            inner,
+                            min.saturating_sub(1),
+                            max.map(|m| m - 1),
+                            input,
+                            next,
+                            rest,
+                        )
+                });
+            more || (min == 0 && rest(at))
+        }
+    }
+
+    type Chars = std::iter::Peekable<std::vec::IntoIter<char>>;
+
+    fn alternation(chars: &mut Chars) -> Node {
+        let mut options = vec![sequence(chars)];
+        while chars.next_if_eq(&'|').is_some() {
+            options.push(sequence(chars));
+        }
+        if options.len() == 1 {
+            options.remove(0)
+        } else {
+            Node::Alt(options)
+        }
+    }
+
+    fn sequence(chars: &mut Chars) -> Node {
+        let mut nodes = Vec::new();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let atom = match chars.peek() {
+                None | Some('|' | ')') => return Node::Seq(nodes),
+                Some('"') => {
+                    chars.next();
+                    let mut lit = Vec::new();
+                    while let Some(c) = chars.next().filter(|&c| c != '"') {
+                        lit.push(if c == '\\' { unescape(chars) } else { c });
+                    }
+                    Node::Literal(lit)
+                }
+                Some('[') => {
+                    chars.next();
+                    let negated = chars.next_if_eq(&'^').is_some();
+                    let mut ranges = Vec::new();
+                    while let Some(c) = chars.next().filter(|&c| c != ']') {
+                        let lo = if c == '\\' { unescape(chars) } else { c };
+                        let hi = if chars.next_if_eq(&'-').is_some() {
+                            chars.next().unwrap_or(lo)
+                        } else {
+                            lo
+                        };
+                        ranges.push((lo, hi));
+                    }
+                    Node::Class { negated, ranges }
+                }
+                Some('.') => {
+                    chars.next();
+                    Node::Any
+                }
+                Some('(') => {
+                    chars.next();
+                    let group = alternation(chars);
+                    chars.next();
+                    group
+                }
+                Some(_) => {
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '-') {
+                        name.push(c);
+                    }
+                    Node::Rule(name)
+                }
+            };
+            nodes.push(repetition(chars, atom));
+        }
+    }
+
+    fn repetition(chars: &mut Chars, atom: Node) -> Node {
+        let (min, max) = match chars.peek() {
+            Some('?') => (0, Some(1)),
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('{') => {
+                chars.next();
+                let bounds: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let (lo, hi) = bounds.split_once(',').unwrap_or((&bounds, &bounds));
+                return Node::Repeat(Box::new(atom), lo.parse().expect("min"), hi.parse().ok());
+            }
+            _ => return atom,
+        };
+        chars.next();
+        Node::Repeat(Box::new(atom), min, max)
+    }
+
+    fn unescape(chars: &mut Chars) -> char {
+        match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            other => other.unwrap_or('\\'),
+        }
+    }
+
+    /// `sample` is accepted by `grammar` and parses into a `T`.
+    fn check<T: StructuredResponse>(grammar: &str, sample: &str) -> T {
+        assert!(
+            Gbnf::parse(grammar).accepts(sample),
+            "grammar rejects {sample}"
+        );
+        parse_structured(sample).expect("grammar output parses")
+    }
+
+    fn prompts_dir() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("prompts")
+    }
+
+    #[test]
+    fn builtin_grammars_cover_builtin_templates() {
+        let engine = PromptEngine::builtin();
+        for id in engine.loaded_ids() {
+            let name = &engine.get(id).expect("loaded").grammar;
+            if !name.is_empty() && id != PromptId::BardComposition {
+                assert!(
+                    engine.grammars().get(name).is_some(),
+                    "{id} names unknown grammar {name}"
+                );
+            }
+        }
+        assert_eq!(
+            engine.grammar(PromptId::Reflection),
+            Some(REFLECTION_GRAMMAR)
+        );
+        assert_eq!(engine.grammar(PromptId::InjectionValidation), None);
+    }
+
+    #[test]
+    fn grammar_output_parses_into_the_response_types() {
+        let dialogue: DialogueResponse = check(
+            DIALOGUE_GRAMMAR,
+            r#"{"dialogue": "Back for \"more\" iron?", "emotion_shift": -0.25, "new_memory": "The traveller returned"}"#,
+        );
+        assert_eq!(dialogue.dialogue, r#"Back for "more" iron?"#);
+
+        let reflection: ReflectionResponse = check(
+            REFLECTION_GRAMMAR,
+            r#"{"reflection": "Strangers bring trouble.", "new_beliefs": ["Lock the stall"], "questions": [], "mood_shift": {"pleasure": -0.2, "arousal": 0.1, "dominance": 0}}"#,
+        );
+        assert_eq!(reflection.new_beliefs, ["Lock the stall"]);
+
+        let gossip: GossipResponse = check(
+            GOSSIP_GRAMMAR,
+            r#"{"gossip_text": "The smith waters his ale", "confidence": 0.75, "embellished": true}"#,
+        );
+        assert!(gossip.embellished);
+        let old_keys =
+            r#"{"gossip": "The smith waters his ale", "exaggeration_level": 0.5, "withheld": ""}"#;
+        assert!(!Gbnf::parse(GOSSIP_GRAMMAR).accepts(old_keys));
+
+        let summary: MemorySummaryResponse = check(
+            MEMORY_SUMMARY_GRAMMAR,
+            r#"{"fact": "Goran is a fair trader", "confidence": 1.0, "category": "person_knowledge"}"#,
+        );
+        assert_eq!(summary.category, "person_knowledge");
+    }
+
+    #[test]
+    fn grammar_files_load_from_disk() {
+        let registry =
+            GrammarRegistry::from_directory(prompts_dir().join("grammars")).expect("grammars");
+        assert_eq!(registry.len(), 5);
+        assert!(
+            registry
+                .get("bard_poem.gbnf")
+                .is_some_and(|g| g.starts_with("root"))
+        );
+        assert!(GrammarRegistry::from_directory(prompts_dir().join("missing")).is_err());
+
+        // Templates loaded from disk resolve against the sibling grammars.
+        let engine = PromptEngine::from_directory(prompts_dir().join("v1")).expect("prompts");
+        let bard = engine
+            .grammar(PromptId::BardComposition)
+            .expect("bard grammar from disk");
+        assert_eq!(Some(bard), registry.get("bard_poem.gbnf"));
+        for (id, builtin) in [
+            (PromptId::DialogueSimple, DIALOGUE_GRAMMAR),
+            (PromptId::Reflection, REFLECTION_GRAMMAR),
+            (PromptId::GossipGeneration, GOSSIP_GRAMMAR),
+            (PromptId::MemorySummary, MEMORY_SUMMARY_GRAMMAR),
+        ] {
+            assert_eq!(
+                engine.grammar(id),
+                Some(builtin),
+                "{id} grammar on disk differs from the builtin"
+            );
+        }
+    }
+}
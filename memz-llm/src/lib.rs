@@ -3,7 +3,8 @@
 //! Provides a unified interface for LLM inference across multiple backends:
 //!   - **Ollama** (local, recommended default)
 //!   - **OpenAI-compatible API** (also works with Anthropic, Together, etc.)
-//!   - **llama.cpp** (direct integration via FFI — future)
+//!   - **llama.cpp server** (`/completion` endpoint with GBNF grammars)
+//!   - **Mock** (in-process scripted responses and cassette replay, for tests)
 //!
 //! All LLM calls in MEMZ go through this crate, ensuring:
 //!   - Structured output enforcement (JSON mode / GBNF grammars)
@@ -28,13 +29,19 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_sign_loss)]
 
+pub mod cassette;
 pub mod client;
+pub mod dispatcher;
 pub mod error;
+pub mod grammar;
+pub mod mock;
+pub mod parse;
 pub mod prompt;
 pub mod queue;
 pub mod types;
 
 pub use client::LlmClient;
+pub use dispatcher::{LlmDispatcher, LlmHandle};
 pub use error::LlmError;
 pub use queue::LlmQueue;
-pub use types::{LlmRequest, LlmResponse, LlmTier};
+pub use types::{LlmRequest, LlmResponse, LlmTier, ResponseSchema};
@@ -0,0 +1,443 @@
+//! Mock LLM — an in-process, scriptable backend for tests.
+//!
+//! [`LlmProvider::Mock`](crate::client::LlmProvider::Mock) answers requests
+//! from a [`MockLlm`] instead of a model server:
+//!
+//! - **Rules** match the prompt (`system` + blank line + `user`) by
+//!   substring or [`Regex`] and answer with a script of replies, in order
+//!   (the last one repeats).  The first matching rule wins.
+//! - **Cassettes** replay recorded interactions (see [`crate::cassette`])
+//!   ahead of the rules.
+//! - **Latency** delays every answer; one longer than the request's
+//!   timeout becomes [`LlmError::Timeout`].
+//! - **Failures** can be scripted as replies or injected for the next
+//!   calls with [`MockLlm::fail_next`].
+//!
+//! Every request is logged for assertions ([`MockLlm::calls`]).
+//!
+//! ```
+//! use memz_llm::mock::{MockFailure, MockLlm, MockReply};
+//! use memz_llm::LlmClient;
+//!
+//! let mock = MockLlm::new()
+//!     .respond("greet", r#"{"dialogue": "Hello!", "emotion_shift": 0.1, "new_memory": ""}"#)
+//!     .script("reflect", [MockReply::Fail(MockFailure::Unavailable), MockReply::text("{}")]);
+//! let client = LlmClient::mock(mock.clone());
+//! assert!(client.is_available());
+//! assert_eq!(mock.call_count(), 0);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+pub use regex::Regex;
+
+use crate::cassette::{Cassette, Interaction};
+use crate::error::LlmError;
+use crate::types::{LlmRequest, LlmResponse};
+
+/// Model name mock responses report (unless replayed from a cassette).
+pub const MOCK_MODEL: &str = "mock";
+
+/// Which requests a rule answers.
+#[derive(Debug, Clone)]
+pub enum MockMatcher {
+    /// Every request.
+    Any,
+    /// Requests whose prompt contains this text.
+    Contains(String),
+    /// Requests whose prompt matches this pattern.
+    Pattern(Regex),
+}
+
+impl MockMatcher {
+    fn matches(&self, prompt: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Contains(text) => prompt.contains(text.as_str()),
+            Self::Pattern(regex) => regex.is_match(prompt),
+        }
+    }
+}
+
+impl From<&str> for MockMatcher {
+    fn from(text: &str) -> Self {
+        Self::Contains(text.to_string())
+    }
+}
+
+impl From<Regex> for MockMatcher {
+    fn from(regex: Regex) -> Self {
+        Self::Pattern(regex)
+    }
+}
+
+/// A failure the mock can produce in place of an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockFailure {
+    /// The backend is unreachable ([`LlmError::Unavailable`]).
+    Unavailable,
+    /// The call times out ([`LlmError::Timeout`]), after waiting out the
+    /// request's timeout.
+    Timeout,
+    /// The backend answers with this HTTP status
+    /// ([`LlmError::RetriesExhausted`], as after the real retries).
+    Http(u16),
+}
+
+impl MockFailure {
+    fn error(self, timeout_ms: u64) -> LlmError {
+        match self {
+            Self::Unavailable => LlmError::Unavailable("mock backend unavailable".into()),
+            Self::Timeout => LlmError::Timeout(timeout_ms),
+            Self::Http(status) => LlmError::RetriesExhausted {
+                attempts: 1,
+                last_error: format!("HTTP {status}: mock failure"),
+            },
+        }
+    }
+}
+
+/// One scripted answer.
+#[derive(Debug, Clone)]
+pub enum MockReply {
+    /// Answer with this text.
+    Text(String),
+    /// Fail this way.
+    Fail(MockFailure),
+}
+
+impl MockReply {
+    /// A text answer.
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+}
+
+/// A matcher and the replies it gives, in order.
+#[derive(Debug)]
+struct MockRule {
+    matcher: MockMatcher,
+    replies: Vec<MockReply>,
+    used: usize,
+}
+
+impl MockRule {
+    /// The next reply; the last one repeats.
+    fn next_reply(&mut self) -> Option<MockReply> {
+        let reply = self
+            .replies
+            .get(self.used.min(self.replies.len().checked_sub(1)?))?
+            .clone();
+        self.used += 1;
+        Some(reply)
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    rules: Vec<MockRule>,
+    fallback: Option<MockReply>,
+    latency: Duration,
+    failures: VecDeque<MockFailure>,
+    replay: Vec<(Interaction, bool)>,
+    calls: Vec<LlmRequest>,
+}
+
+impl MockState {
+    /// Answer from the cassette: the first unplayed recording of
+    /// `request`, or its last recording once all have played.
+    fn replayed(&mut self, request: &LlmRequest) -> Option<LlmResponse> {
+        let recorded: Vec<usize> = (0..self.replay.len())
+            .filter(|&i| self.replay[i].0.request.matches(request))
+            .collect();
+        let index = recorded
+            .iter()
+            .copied()
+            .find(|&i| !self.replay[i].1)
+            .or_else(|| recorded.last().copied())?;
+        self.replay[index].1 = true;
+        Some(self.replay[index].0.response.clone())
+    }
+
+    fn answer(&mut self, request: &LlmRequest) -> Result<LlmResponse, MockFailure> {
+        if let Some(failure) = self.failures.pop_front() {
+            return Err(failure);
+        }
+        if let Some(response) = self.replayed(request) {
+            return Ok(response);
+        }
+        let prompt = format!("{}\n\n{}", request.system, request.user);
+        let reply = self
+            .rules
+            .iter_mut()
+            .find(|rule| rule.matcher.matches(&prompt))
+            .and_then(MockRule::next_reply)
+            .or_else(|| self.fallback.clone())
+            .unwrap_or(MockReply::Fail(MockFailure::Unavailable));
+        match reply {
+            MockReply::Text(text) => Ok(LlmResponse {
+                tokens_generated: text.split_whitespace().count() as u32,
+                text,
+                latency_ms: self.latency.as_millis() as u64,
+                model: MOCK_MODEL.to_string(),
+            }),
+            MockReply::Fail(failure) => Err(failure),
+        }
+    }
+}
+
+/// A scriptable in-process LLM.  Clones share their script and call log,
+/// so a test can keep a handle to the mock it gave a client.
+#[derive(Debug, Clone, Default)]
+pub struct MockLlm {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockLlm {
+    /// A mock with no rules: every request fails as unavailable.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A mock answering from `cassette`'s recordings first.
+    #[must_use]
+    pub fn replaying(cassette: &Cassette) -> Self {
+        let mock = Self::new();
+        mock.state.lock().replay = cassette
+            .interactions()
+            .into_iter()
+            .map(|i| (i, false))
+            .collect();
+        mock
+    }
+
+    /// Answer requests matching `matcher` with `text`.
+    #[must_use]
+    pub fn respond(self, matcher: impl Into<MockMatcher>, text: impl Into<String>) -> Self {
+        self.script(matcher, [MockReply::text(text)])
+    }
+
+    /// Answer requests matching `matcher` with `replies`, one per request;
+    /// the last one repeats.
+    #[must_use]
+    pub fn script(
+        self,
+        matcher: impl Into<MockMatcher>,
+        replies: impl IntoIterator<Item = MockReply>,
+    ) -> Self {
+        self.state.lock().rules.push(MockRule {
+            matcher: matcher.into(),
+            replies: replies.into_iter().collect(),
+            used: 0,
+        });
+        self
+    }
+
+    /// Answer requests no rule or recording matches with `reply`
+    /// (default: fail as unavailable).
+    #[must_use]
+    pub fn with_fallback(self, reply: MockReply) -> Self {
+        self.state.lock().fallback = Some(reply);
+        self
+    }
+
+    /// Delay every answer by `latency`.
+    #[must_use]
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.state.lock().latency = latency;
+        self
+    }
+
+    /// Fail the next `count` requests with `failure`, whatever they match.
+    pub fn fail_next(&self, count: usize, failure: MockFailure) {
+        self.state
+            .lock()
+            .failures
+            .extend(std::iter::repeat_n(failure, count));
+    }
+
+    /// Every request received so far, in order.
+    #[must_use]
+    pub fn calls(&self) -> Vec<LlmRequest> {
+        self.state.lock().calls.clone()
+    }
+
+    /// Number of requests received so far.
+    #[must_use]
+    pub fn call_count(&self) -> usize {
+        self.state.lock().calls.len()
+    }
+
+    /// Answer `request`.
+    pub(crate) async fn generate(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+        let (answer, latency) = {
+            let mut state = self.state.lock();
+            state.calls.push(request.clone());
+            (state.answer(request), state.latency)
+        };
+        let timeout = Duration::from_millis(request.timeout_ms);
+        let wait = match answer {
+            Err(MockFailure::Timeout) => timeout,
+            _ => latency.min(timeout),
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        if latency > timeout {
+            return Err(LlmError::Timeout(request.timeout_ms));
+        }
+        answer.map_err(|failure| failure.error(request.timeout_ms))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LlmClient;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime")
+    }
+
+    #[test]
+    fn rules_match_in_order_and_scripts_advance() {
+        let rt = runtime();
+        let mock = MockLlm::new()
+            .script(
+                "Goran",
+                [MockReply::text("first"), MockReply::text("second")],
+            )
+            .respond(Regex::new(r"gossip about \w+").expect("regex"), "psst")
+            .respond(MockMatcher::Any, "anything");
+        let client = LlmClient::mock(mock.clone());
+        let ask =
+            |user: &str| rt.block_on(client.generate(&LlmRequest::tier1("You are Goran.", user)));
+
+        assert_eq!(ask("hello").expect("scripted").text, "first");
+        assert_eq!(ask("hello").expect("scripted").text, "second");
+        assert_eq!(ask("hello").expect("last repeats").text, "second");
+
+        let gossip = rt.block_on(client.generate(&LlmRequest::tier1("s", "gossip about Vera")));
+        assert_eq!(gossip.expect("regex rule").text, "psst");
+        let other = rt.block_on(client.generate(&LlmRequest::tier1("s", "u")));
+        assert_eq!(other.expect("catch-all").model, MOCK_MODEL);
+
+        assert_eq!(mock.call_count(), 5);
+        assert_eq!(mock.calls()[3].user, "gossip about Vera");
+
+        let empty = LlmClient::mock(MockLlm::new());
+        let result = rt.block_on(empty.generate(&LlmRequest::tier1("s", "u")));
+        assert!(matches!(result, Err(LlmError::Unavailable(_))));
+    }
+
+    #[test]
+    fn failures_and_latency_can_be_injected() {
+        let rt = runtime();
+        let mock = MockLlm::new()
+            .respond(MockMatcher::Any, "ok")
+            .with_latency(Duration::from_millis(20));
+        let client = LlmClient::mock(mock.clone());
+
+        mock.fail_next(2, MockFailure::Http(503));
+        for _ in 0..2 {
+            let result = rt.block_on(client.generate(&LlmRequest::tier1("s", "u")));
+            assert!(matches!(result, Err(LlmError::RetriesExhausted { .. })));
+        }
+        let start = std::time::Instant::now();
+        let response = rt
+            .block_on(client.generate(&LlmRequest::tier1("s", "u")))
+            .expect("recovered");
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(response.latency_ms, 20);
+
+        let hurried = LlmRequest::tier1("s", "u").with_timeout(5);
+        let result = rt.block_on(client.generate(&hurried));
+        assert!(matches!(result, Err(LlmError::Timeout(5))));
+    }
+
+    #[test]
+    fn cassettes_record_and_replay() {
+        let rt = runtime();
+        let source = MockLlm::new().script(
+            MockMatcher::Any,
+            [MockReply::text("one"), MockReply::text("two")],
+        );
+        let cassette = Cassette::new();
+        let recorder = LlmClient::mock(source).with_recorder(cassette.clone());
+        let request = LlmRequest::tier1("You are Elira.", "What do you think of the stranger?");
+        for _ in 0..2 {
+            rt.block_on(recorder.generate(&request)).expect("recorded");
+        }
+        assert_eq!(cassette.len(), 2);
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("elira.json");
+        cassette.save(&path).expect("saved");
+        let loaded = Cassette::load(&path).expect("loaded");
+        assert_eq!(loaded.interactions(), cassette.interactions());
+
+        let replay = LlmClient::mock(MockLlm::replaying(&loaded));
+        let texts: Vec<String> = (0..3)
+            .map(|_| {
+                rt.block_on(replay.generate(&request))
+                    .expect("replayed")
+                    .text
+            })
+            .collect();
+        assert_eq!(texts, ["one", "two", "two"]);
+        let unknown = rt.block_on(replay.generate(&LlmRequest::tier2(
+            "You are Elira.",
+            "What do you think of the stranger?",
+        )));
+        assert!(unknown.is_err(), "tier is part of the match");
+        for changed in [
+            request.clone().with_grammar("root ::= \"{}\""),
+            LlmRequest {
+                temperature: 0.1,
+                ..request.clone()
+            },
+            LlmRequest {
+                max_tokens: request.max_tokens + 1,
+                ..request.clone()
+            },
+        ] {
+            assert!(
+                rt.block_on(replay.generate(&changed)).is_err(),
+                "{changed:?} replayed"
+            );
+        }
+
+        // Cassettes without sampling settings match any.
+        let legacy = r#"{"version": 1, "interactions": [{"request": {"tier": "small_local", "system": "You are Elira.", "user": "What do you think of the stranger?"}, "response": {"text": "old", "tokens_generated": 1, "latency_ms": 0, "model": "m"}}]}"#;
+        std::fs::write(&path, legacy).expect("write");
+        let legacy = LlmClient::mock(MockLlm::replaying(
+            &Cassette::load(&path).expect("legacy cassette"),
+        ));
+        let cool = LlmRequest {
+            temperature: 0.1,
+            ..request.clone()
+        };
+        assert_eq!(
+            rt.block_on(legacy.generate(&cool)).expect("replayed").text,
+            "old"
+        );
+
+        std::fs::write(&path, r#"{"version": 99, "interactions": []}"#).expect("write");
+        assert!(matches!(
+            Cassette::load(&path),
+            Err(LlmError::ConfigError(_))
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+//! Structured-output parsing — from noisy model text to validated types.
+//!
+//! Even with a grammar or JSON mode, small models wrap their JSON in code
+//! fences, add a sentence of prose, trail commas or stop mid-object when
+//! they hit the token limit.  [`parse_structured`] handles all of these:
+//!
+//! 1. [`extract_json`] cuts the first JSON object out of the text.
+//! 2. If it does not deserialize, [`repair_json`] applies cheap fixes
+//!    (typographic quotes, trailing commas, unclosed strings and brackets)
+//!    and tries once more.
+//! 3. [`StructuredResponse::normalize`] checks field invariants, clamping
+//!    values that are merely out of range and rejecting unusable answers.
+//!
+//! What still fails is a [`LlmError::ParseError`] or
+//! [`LlmError::SchemaValidation`];
+//! [`LlmClient::generate_structured`](crate::LlmClient::generate_structured)
+//! then re-prompts once.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::error::LlmError;
+use crate::types::{
+    DialogueResponse, GossipResponse, MemorySummaryResponse, MoodShift, ReflectionResponse,
+    ResponseSchema,
+};
+
+/// A response type the LLM fills in as JSON.
+pub trait StructuredResponse: DeserializeOwned {
+    /// Check field invariants after parsing, fixing what is cheap to fix
+    /// (trimming text, clamping ranges).
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem if the response is unusable.
+    fn normalize(&mut self) -> Result<(), String>;
+
+    /// JSON schema of the response, for backends that constrain output
+    /// with a schema rather than a GBNF grammar.
+    fn response_schema() -> ResponseSchema;
+}
+
+/// Parse `text` as a `T`: extract, repair if needed, then normalize.
+///
+/// # Errors
+///
+/// [`LlmError::ParseError`] if no valid JSON `T` can be recovered,
+/// [`LlmError::SchemaValidation`] if it fails [`StructuredResponse::normalize`].
+pub fn parse_structured<T: StructuredResponse>(text: &str) -> Result<T, LlmError> {
+    let json = extract_json(text).ok_or_else(|| {
+        LlmError::ParseError(format!("no JSON object in a {}-byte response", text.len()))
+    })?;
+    let mut value: T = match serde_json::from_str(json) {
+        Ok(value) => value,
+        Err(first) => serde_json::from_str(&repair_json(json)).map_err(|_| {
+            LlmError::ParseError(format!(
+                "JSON parse error at line {}, column {}",
+                first.line(),
+                first.column()
+            ))
+        })?,
+    };
+    value.normalize().map_err(LlmError::SchemaValidation)?;
+    Ok(value)
+}
+
+/// The first JSON object in `text`, skipping code fences and prose.  An
+/// object cut off before its closing brace runs to the end of the text.
+#[must_use]
+pub fn extract_json(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(&text[start..=start + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+    let rest = text[start..].trim_end();
+    Some(rest.strip_suffix("```").map_or(rest, str::trim_end))
+}
+
+/// Cheap fixes for almost-JSON: typographic quotes, trailing commas, and
+/// strings, arrays and objects left open by a truncated response.
+///
+/// Typographic double quotes are only delimiters outside strings (or when
+/// they opened the string); inside an ordinary string they are dialogue and
+/// are left alone.
+#[must_use]
+pub fn repair_json(json: &str) -> String {
+    let json = json.replace(['\u{2018}', '\u{2019}'], "'");
+    let mut out = String::with_capacity(json.len() + 8);
+    let mut open: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut smart_string = false;
+    let mut escaped = false;
+
+    for c in json.chars() {
+        let smart = matches!(c, '\u{201C}' | '\u{201D}');
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ if smart && smart_string => in_string = false,
+                _ => {}
+            }
+            out.push(if in_string || !smart { c } else { '"' });
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            _ if smart => {
+                in_string = true;
+                smart_string = true;
+                out.push('"');
+                continue;
+            }
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                drop_trailing_comma(&mut out);
+                open.pop();
+            }
+            _ => {}
+        }
+        smart_string = false;
+        out.push(c);
+    }
+
+    if in_string {
+        if escaped {
+            out.pop();
+        }
+        out.push('"');
+    }
+    drop_trailing_comma(&mut out);
+    while let Some(close) = open.pop() {
+        out.push(close);
+    }
+    out
+}
+
+/// Remove a `,` (and whitespace after it) at the end of `out`.
+fn drop_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end().len();
+    if out[..trimmed].ends_with(',') {
+        out.truncate(trimmed - 1);
+    }
+}
+
+/// Trim `text`, rejecting it if nothing is left.
+fn require_text(text: &mut String, field: &str) -> Result<(), String> {
+    *text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(format!("`{field}` is empty"));
+    }
+    Ok(())
+}
+
+/// Clamp `value` to `min..=max`, rejecting NaN and infinities.
+fn clamp_finite(value: &mut f32, min: f32, max: f32, field: &str) -> Result<(), String> {
+    if !value.is_finite() {
+        return Err(format!("`{field}` is not a finite number"));
+    }
+    *value = value.clamp(min, max);
+    Ok(())
+}
+
+/// Trim list items and drop the empty ones.
+fn tidy_list(items: &mut Vec<String>) {
+    for item in items.iter_mut() {
+        *item = item.trim().to_string();
+    }
+    items.retain(|item| !item.is_empty());
+}
+
+/// A closed object schema: every property required, nothing else allowed
+/// (as `OpenAI` strict mode demands).
+fn object_schema(properties: Value) -> Value {
+    let required: Vec<String> = properties
+        .as_object()
+        .map(|p| p.keys().cloned().collect())
+        .unwrap_or_default();
+    let mut schema = json!({
+        "type": "object",
+        "required": required,
+        "additionalProperties": false,
+    });
+    schema["properties"] = properties;
+    schema
+}
+
+impl StructuredResponse for DialogueResponse {
+    fn normalize(&mut self) -> Result<(), String> {
+        require_text(&mut self.dialogue, "dialogue")?;
+        self.new_memory = self.new_memory.trim().to_string();
+        clamp_finite(&mut self.emotion_shift, -1.0, 1.0, "emotion_shift")
+    }
+
+    fn response_schema() -> ResponseSchema {
+        ResponseSchema {
+            name: "dialogue_response".into(),
+            schema: object_schema(json!({
+                "dialogue": { "type": "string" },
+                "emotion_shift": { "type": "number", "minimum": -1.0, "maximum": 1.0 },
+                "new_memory": { "type": "string" },
+            })),
+        }
+    }
+}
+
+impl StructuredResponse for ReflectionResponse {
+    fn normalize(&mut self) -> Result<(), String> {
+        require_text(&mut self.reflection, "reflection")?;
+        tidy_list(&mut self.new_beliefs);
+        tidy_list(&mut self.questions);
+        let MoodShift {
+            pleasure,
+            arousal,
+            dominance,
+        } = &mut self.mood_shift;
+        clamp_finite(pleasure, -1.0, 1.0, "mood_shift.pleasure")?;
+        clamp_finite(arousal, -1.0, 1.0, "mood_shift.arousal")?;
+        clamp_finite(dominance, -1.0, 1.0, "mood_shift.dominance")
+    }
+
+    fn response_schema() -> ResponseSchema {
+        let axis = json!({ "type": "number", "minimum": -1.0, "maximum": 1.0 });
+        ResponseSchema {
+            name: "reflection_output".into(),
+            schema: object_schema(json!({
+                "reflection": { "type": "string" },
+                "new_beliefs": { "type": "array", "items": { "type": "string" } },
+                "questions": { "type": "array", "items": { "type": "string" } },
+                "mood_shift": object_schema(json!({ "pleasure": axis, "arousal": axis, "dominance": axis })),
+            })),
+        }
+    }
+}
+
+impl StructuredResponse for GossipResponse {
+    fn normalize(&mut self) -> Result<(), String> {
+        require_text(&mut self.gossip_text, "gossip_text")?;
+        clamp_finite(&mut self.confidence, 0.0, 1.0, "confidence")
+    }
+
+    fn response_schema() -> ResponseSchema {
+        ResponseSchema {
+            name: "gossip_output".into(),
+            schema: object_schema(json!({
+                "gossip_text": { "type": "string" },
+                "confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+                "embellished": { "type": "boolean" },
+            })),
+        }
+    }
+}
+
+impl StructuredResponse for MemorySummaryResponse {
+    fn normalize(&mut self) -> Result<(), String> {
+        require_text(&mut self.fact, "fact")?;
+        self.category = self.category.trim().to_string();
+        clamp_finite(&mut self.confidence, 0.0, 1.0, "confidence")
+    }
+
+    fn response_schema() -> ResponseSchema {
+        ResponseSchema {
+            name: "memory_summary".into(),
+            schema: object_schema(json!({
+                "fact": { "type": "string" },
+                "confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+                "category": { "type": "string" },
+            })),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_json_from_noisy_output() {
+        let noisy = "Sure! Here is the answer:\n```json\n{\"dialogue\": \"Hi {friend}\", \"emotion_shift\": 0.2, \"new_memory\": \"\"}\n```\nHope that helps.";
+        let parsed: DialogueResponse = parse_structured(noisy).expect("parsed");
+        assert_eq!(parsed.dialogue, "Hi {friend}");
+        assert!(extract_json("no json here").is_none());
+    }
+
+    #[test]
+    fn repairs_cheap_mistakes() {
+        assert_eq!(
+            repair_json(r#"{"a": [1, 2,], "b": 3,}"#),
+            r#"{"a": [1, 2], "b": 3}"#
+        );
+        assert_eq!(repair_json(r#"{"a": "cut off"#), r#"{"a": "cut off"}"#);
+        assert_eq!(repair_json("{\u{201C}a\u{201D}: 1"), r#"{"a": 1}"#);
+
+        let quoted = "{\"dialogue\": \"She said \u{201C}run\u{201D}\",}";
+        assert_eq!(
+            repair_json(quoted),
+            "{\"dialogue\": \"She said \u{201C}run\u{201D}\"}"
+        );
+        let line = "{\"dialogue\": \"She said \u{201C}run\u{201D}\", \"emotion_shift\": 0.1, \"new_memory\": \"\",}";
+        let parsed: DialogueResponse = parse_structured(line).expect("repaired");
+        assert_eq!(parsed.dialogue, "She said \u{201C}run\u{201D}");
+
+        let truncated =
+            r#"{"reflection": "The stranger worries me", "new_beliefs": ["Strangers steal", "#;
+        let extracted = extract_json(truncated).expect("object start");
+        assert_eq!(extracted, truncated.trim_end());
+        let err =
+            parse_structured::<ReflectionResponse>(truncated).expect_err("mood_shift missing");
+        assert!(
+            matches!(&err, LlmError::ParseError(m) if !m.contains("stranger")),
+            "no raw text: {err}"
+        );
+
+        let gossip =
+            r#"{"gossip_text": "The smith cheats", "confidence": 0.7, "embellished": true,"#;
+        let parsed: GossipResponse = parse_structured(gossip).expect("repaired");
+        assert!(parsed.embellished);
+    }
+
+    #[test]
+    fn validates_and_clamps_fields() {
+        let parsed: DialogueResponse = parse_structured(
+            r#"{"dialogue": " Hello ", "emotion_shift": 1.7, "new_memory": "met"}"#,
+        )
+        .expect("clamped");
+        assert_eq!(parsed.dialogue, "Hello");
+        assert!((parsed.emotion_shift - 1.0).abs() < f32::EPSILON);
+
+        let empty = parse_structured::<DialogueResponse>(
+            r#"{"dialogue": "  ", "emotion_shift": 0, "new_memory": ""}"#,
+        );
+        assert!(matches!(empty, Err(LlmError::SchemaValidation(_))));
+
+        let reflection: ReflectionResponse = parse_structured(
+            r#"{"reflection": "Hm.", "new_beliefs": ["", " a "], "questions": [], "mood_shift": {"pleasure": -3, "arousal": 0.5, "dominance": 0}}"#,
+        )
+        .expect("valid");
+        assert_eq!(reflection.new_beliefs, ["a"]);
+        assert!((reflection.mood_shift.pleasure + 1.0).abs() < f32::EPSILON);
+    }
+}
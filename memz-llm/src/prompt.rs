@@ -94,26 +94,36 @@ Is this a plausible personal memory for a fantasy RPG character?
 Return JSON:
 {{"approved": <bool>, "reason": "why approved/rejected", "suggested_edit": "optional improved version or null"}}"#;
 
-/// GBNF grammar for structured dialogue output.
-pub const DIALOGUE_GRAMMAR: &str = r#"root   ::= "{" ws "\"dialogue\"" ws ":" ws string "," ws "\"emotion_shift\"" ws ":" ws number "," ws "\"new_memory\"" ws ":" ws string "}" ws
-string ::= "\"" ([^"\\] | "\\" .)* "\""
-number ::= "-"? [0-1] ("." [0-9]{1,2})?
-ws     ::= [ \t\n]*"#;
-
-/// GBNF grammar for structured reflection output.
-pub const REFLECTION_GRAMMAR: &str = r#"root   ::= "{" ws "\"reflection\"" ws ":" ws string "," ws "\"new_beliefs\"" ws ":" ws array "," ws "\"questions\"" ws ":" ws array "," ws "\"mood_shift\"" ws ":" ws mood "}" ws
-string ::= "\"" ([^"\\] | "\\" .)* "\""
-array  ::= "[" ws (string ("," ws string)*)? ws "]"
-mood   ::= "{" ws "\"pleasure\"" ws ":" ws float "," ws "\"arousal\"" ws ":" ws float "," ws "\"dominance\"" ws ":" ws float "}" ws
-float  ::= "-"? [0-1] ("." [0-9]{1,2})?
-ws     ::= [ \t\n]*"#;
-
-/// GBNF grammar for gossip output.
-pub const GOSSIP_GRAMMAR: &str = r#"root   ::= "{" ws "\"gossip_text\"" ws ":" ws string "," ws "\"confidence\"" ws ":" ws float "," ws "\"embellished\"" ws ":" ws bool "}" ws
-string ::= "\"" ([^"\\] | "\\" .)* "\""
-float  ::= "0" ("." [0-9]{1,2})? | "1" ("." "0"{1,2})?
-bool   ::= "true" | "false"
-ws     ::= [ \t\n]*"#;
+/// Memory summary prompt (Tier 1) — condenses forgotten memories into a gist.
+pub const MEMORY_SUMMARY_SYSTEM: &str = r"You are the long-term memory of {npc_name}, a {npc_profession}.
+The details of some old experiences are fading. Distill them into one
+general impression that {npc_name} will keep.
+You do NOT add information that isn't supported by the memories.
+Be precise and factual.";
+
+pub const MEMORY_SUMMARY_USER: &str = r#"Memories about {cluster_topic} that are fading:
+{memories_formatted}
+
+Distill these {memory_count} memories into a single first-person sentence:
+what does {npc_name} still remember, in general terms?
+
+Return JSON:
+{{"fact": "the distilled impression", "confidence": <float 0.0-1.0>, "category": "person_knowledge|world_knowledge|skill_knowledge|relationship"}}"#;
+
+/// GBNF grammar for structured dialogue output
+/// (`prompts/grammars/dialogue_response.gbnf`).
+pub const DIALOGUE_GRAMMAR: &str = include_str!("../prompts/grammars/dialogue_response.gbnf");
+
+/// GBNF grammar for structured reflection output
+/// (`prompts/grammars/reflection_output.gbnf`).
+pub const REFLECTION_GRAMMAR: &str = include_str!("../prompts/grammars/reflection_output.gbnf");
+
+/// GBNF grammar for gossip output (`prompts/grammars/gossip_output.gbnf`).
+pub const GOSSIP_GRAMMAR: &str = include_str!("../prompts/grammars/gossip_output.gbnf");
+
+/// GBNF grammar for memory summary output
+/// (`prompts/grammars/memory_summary.gbnf`).
+pub const MEMORY_SUMMARY_GRAMMAR: &str = include_str!("../prompts/grammars/memory_summary.gbnf");
 
 /// Simple template interpolation for prompts.
 ///
@@ -137,6 +147,12 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use crate::grammar::GrammarRegistry;
+use crate::types::LlmRequest;
+
+/// Directory, next to a prompt version directory, holding grammar files.
+pub const GRAMMAR_DIR: &str = "grammars";
+
 /// Identifies a prompt template by purpose.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PromptId {
@@ -279,6 +295,7 @@ pub struct PromptTemplate {
 #[derive(Debug, Clone)]
 pub struct PromptEngine {
     templates: HashMap<PromptId, PromptTemplate>,
+    grammars: GrammarRegistry,
 }
 
 impl PromptEngine {
@@ -334,6 +351,17 @@ impl PromptEngine {
             user: BARD_USER.into(),
         });
 
+        // Memory Summary
+        templates.insert(PromptId::MemorySummary, PromptTemplate {
+            version: "builtin".into(),
+            tier: 1,
+            max_tokens: 150,
+            temperature: 0.5,
+            grammar: "memory_summary.gbnf".into(),
+            system: MEMORY_SUMMARY_SYSTEM.into(),
+            user: MEMORY_SUMMARY_USER.into(),
+        });
+
         // Injection Validation
         templates.insert(PromptId::InjectionValidation, PromptTemplate {
             version: "builtin".into(),
@@ -345,17 +373,19 @@ impl PromptEngine {
             user: INJECTION_VALIDATION_USER.into(),
         });
 
-        Self { templates }
+        Self { templates, grammars: GrammarRegistry::builtin() }
     }
 
     /// Load prompt templates from a directory of TOML files.
     ///
     /// Each TOML file must match a known [`PromptId`] filename.
-    /// Unknown files are ignored.
+    /// Unknown files are ignored.  Grammars come from a `grammars`
+    /// directory next to `dir` (as in `prompts/v1` + `prompts/grammars`),
+    /// over the built-in ones.
     ///
     /// # Errors
     ///
-    /// Returns an error if a TOML file exists but cannot be parsed.
+    /// Returns an error if a TOML or grammar file exists but cannot be read.
     pub fn from_directory(dir: impl AsRef<Path>) -> Result<Self, String> {
         let dir = dir.as_ref();
         let mut templates = HashMap::new();
@@ -388,7 +418,36 @@ impl PromptEngine {
             ));
         }
 
-        Ok(Self { templates })
+        let mut grammars = GrammarRegistry::builtin();
+        if let Some(grammar_dir) = dir.parent().map(|p| p.join(GRAMMAR_DIR)).filter(|p| p.is_dir()) {
+            grammars.load_directory(grammar_dir)?;
+        }
+
+        Ok(Self { templates, grammars })
+    }
+
+    /// Use `grammars` to resolve the templates' grammar names.
+    #[must_use]
+    pub fn with_grammars(mut self, grammars: GrammarRegistry) -> Self {
+        self.grammars = grammars;
+        self
+    }
+
+    /// The grammars template grammar names resolve against.
+    #[must_use]
+    pub fn grammars(&self) -> &GrammarRegistry {
+        &self.grammars
+    }
+
+    /// GBNF text of prompt `id`'s grammar; `None` if it has none or the
+    /// grammar is not loaded.
+    #[must_use]
+    pub fn grammar(&self, id: PromptId) -> Option<&str> {
+        let name = &self.get(id)?.grammar;
+        if name.is_empty() {
+            return None;
+        }
+        self.grammars.get(name)
     }
 
     /// Get a loaded prompt template by ID.
@@ -421,6 +480,26 @@ impl PromptEngine {
         Ok((system, user))
     }
 
+    /// Render prompt `id` into a request on the template's tier, with its
+    /// token limit, temperature and grammar.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prompt ID is not loaded.
+    pub fn request(&self, id: PromptId, vars: &[(&str, &str)]) -> Result<LlmRequest, String> {
+        let (system, user) = self.render(id, vars)?;
+        let tpl = self.get(id).ok_or_else(|| format!("prompt template '{id}' not loaded"))?;
+        let mut request = if tpl.tier >= 2 {
+            LlmRequest::tier2(system, user)
+        } else {
+            LlmRequest::tier1(system, user)
+        };
+        request.max_tokens = tpl.max_tokens;
+        request.temperature = tpl.temperature;
+        request.grammar = self.grammar(id).map(str::to_string);
+        Ok(request)
+    }
+
     /// Number of loaded templates.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -482,6 +561,7 @@ mod tests {
         assert!(engine.get(PromptId::GossipGeneration).is_some());
         assert!(engine.get(PromptId::BardComposition).is_some());
         assert!(engine.get(PromptId::InjectionValidation).is_some());
+        assert!(engine.get(PromptId::MemorySummary).is_some());
     }
 
     #[test]
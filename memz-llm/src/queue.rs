@@ -13,13 +13,21 @@
 //! 4. Gossip generation (NPC→NPC)
 //! 5. Bard composition (background task)
 //! 6. Memory summarisation (batch job)
+//!
+//! The queue only orders requests; an [`LlmDispatcher`](crate::dispatcher::LlmDispatcher)
+//! runs them and routes each result back to whoever asked.
 
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
+use std::fmt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+use crate::dispatcher::Reply;
+use crate::types::{LlmRequest, LlmTier, ResponseSchema};
 
 /// Priority levels for LLM requests.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -39,18 +47,21 @@ pub enum LlmPriority {
 }
 
 /// A queued LLM request with priority and deadline.
-#[derive(Debug)]
 pub struct QueuedRequest {
     /// Unique request ID.
     pub id: u64,
     /// Priority level.
     pub priority: LlmPriority,
+    /// Which model tier runs the request.
+    pub tier: LlmTier,
     /// System prompt.
     pub system_prompt: String,
     /// User prompt.
     pub user_prompt: String,
     /// Optional GBNF grammar for structured output.
     pub grammar: Option<String>,
+    /// Optional JSON schema for structured output.
+    pub response_schema: Option<ResponseSchema>,
     /// Maximum tokens to generate.
     pub max_tokens: u32,
     /// Temperature for generation.
@@ -59,6 +70,24 @@ pub struct QueuedRequest {
     pub enqueued_at: Instant,
     /// Maximum time to wait in queue before cancelling.
     pub deadline: Duration,
+    /// Timeout for the LLM call itself, in milliseconds.
+    pub timeout_ms: u64,
+    /// Where the result goes; dropping it (e.g. on expiry) reports
+    /// cancellation to the requester.
+    pub(crate) reply: Option<Reply>,
+}
+
+impl fmt::Debug for QueuedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueuedRequest")
+            .field("id", &self.id)
+            .field("priority", &self.priority)
+            .field("tier", &self.tier)
+            .field("enqueued_at", &self.enqueued_at)
+            .field("deadline", &self.deadline)
+            .field("has_reply", &self.reply.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl QueuedRequest {
@@ -73,6 +102,21 @@ impl QueuedRequest {
     pub fn time_remaining(&self) -> Duration {
         self.deadline.saturating_sub(self.enqueued_at.elapsed())
     }
+
+    /// The request to send to the LLM client.
+    #[must_use]
+    pub fn to_llm_request(&self) -> LlmRequest {
+        LlmRequest {
+            system: self.system_prompt.clone(),
+            user: self.user_prompt.clone(),
+            tier: self.tier,
+            max_tokens: self.max_tokens,
+            temperature: self.temperature,
+            grammar: self.grammar.clone(),
+            response_schema: self.response_schema.clone(),
+            timeout_ms: self.timeout_ms,
+        }
+    }
 }
 
 // BinaryHeap is a max-heap, so higher priority = dequeued first.
@@ -103,6 +147,8 @@ impl Ord for QueuedRequest {
 /// Thread-safe LLM request queue.
 pub struct LlmQueue {
     inner: Arc<Mutex<LlmQueueInner>>,
+    /// Wakes a waiting dispatcher worker on enqueue.
+    ready: Arc<Notify>,
 }
 
 struct LlmQueueInner {
@@ -140,6 +186,7 @@ impl LlmQueue {
                 total_dropped: 0,
                 total_expired: 0,
             })),
+            ready: Arc::new(Notify::new()),
         }
     }
 
@@ -156,6 +203,31 @@ impl LlmQueue {
         max_tokens: u32,
         temperature: f32,
         deadline: Duration,
+    ) -> Option<u64> {
+        let mut request = LlmRequest::tier1(system_prompt, user_prompt);
+        request.grammar = grammar;
+        request.max_tokens = max_tokens;
+        request.temperature = temperature;
+        self.enqueue_request(priority, request, deadline)
+    }
+
+    /// Enqueue a prepared [`LlmRequest`], keeping its tier and timeout.
+    ///
+    /// A dispatcher draining the queue runs the request and discards the
+    /// result; submit through the dispatcher to receive it.  Returns the
+    /// request ID, or `None` if the queue is full.
+    #[must_use]
+    pub fn enqueue_request(&self, priority: LlmPriority, request: LlmRequest, deadline: Duration) -> Option<u64> {
+        self.push(priority, request, deadline, None)
+    }
+
+    /// Enqueue with a reply that receives the result.
+    pub(crate) fn push(
+        &self,
+        priority: LlmPriority,
+        request: LlmRequest,
+        deadline: Duration,
+        reply: Option<Reply>,
     ) -> Option<u64> {
         let mut inner = self.inner.lock();
 
@@ -171,32 +243,60 @@ impl LlmQueue {
         inner.heap.push(QueuedRequest {
             id,
             priority,
-            system_prompt,
-            user_prompt,
-            grammar,
-            max_tokens,
-            temperature,
+            tier: request.tier,
+            system_prompt: request.system,
+            user_prompt: request.user,
+            grammar: request.grammar,
+            response_schema: request.response_schema,
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
             enqueued_at: Instant::now(),
             deadline,
+            timeout_ms: request.timeout_ms,
+            reply,
         });
+        drop(inner);
+        self.ready.notify_one();
 
         Some(id)
     }
 
     /// Dequeue the highest-priority non-expired request.
     ///
-    /// Automatically skips and counts expired requests.
+    /// Automatically skips and counts expired requests.  They are dropped
+    /// (cancelling their replies) after the queue lock is released.
     #[must_use] 
     pub fn dequeue(&self) -> Option<QueuedRequest> {
+        let mut expired = Vec::new();
         let mut inner = self.inner.lock();
 
-        loop {
-            let request = inner.heap.pop()?;
+        let next = loop {
+            let Some(request) = inner.heap.pop() else {
+                break None;
+            };
             if request.is_expired() {
                 inner.total_expired += 1;
+                expired.push(request);
                 continue;
             }
-            return Some(request);
+            break Some(request);
+        };
+        drop(inner);
+        drop(expired);
+        next
+    }
+
+    /// Wait for the next non-expired request.
+    pub(crate) async fn next(&self) -> QueuedRequest {
+        loop {
+            if let Some(request) = self.dequeue() {
+                // Hand any backlog to another idle worker.
+                if !self.is_empty() {
+                    self.ready.notify_one();
+                }
+                return request;
+            }
+            self.ready.notified().await;
         }
     }
 
@@ -231,16 +331,18 @@ impl LlmQueue {
         }
     }
 
-    /// Purge all expired requests from the queue.
+    /// Purge all expired requests from the queue, cancelling their replies
+    /// after the queue lock is released.
     #[must_use] 
     pub fn purge_expired(&self) -> u64 {
+        let mut expired: Vec<QueuedRequest> = Vec::new();
         let mut inner = self.inner.lock();
-        let before = inner.heap.len();
 
         let mut valid: Vec<QueuedRequest> = Vec::new();
         while let Some(req) = inner.heap.pop() {
             if req.is_expired() {
                 inner.total_expired += 1;
+                expired.push(req);
             } else {
                 valid.push(req);
             }
@@ -249,8 +351,9 @@ impl LlmQueue {
         for r in valid {
             inner.heap.push(r);
         }
+        drop(inner);
 
-        (before - inner.heap.len()) as u64
+        expired.len() as u64
     }
 }
 
@@ -258,6 +361,7 @@ impl Clone for LlmQueue {
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            ready: Arc::clone(&self.ready),
         }
     }
 }
@@ -313,7 +417,7 @@ mod tests {
         let queue = LlmQueue::new(100);
 
         // Enqueue with 0-duration deadline (instantly expired)
-        queue.enqueue(
+        let _ = queue.enqueue(
             LlmPriority::Critical,
             "system".into(),
             "user".into(),
@@ -342,7 +446,7 @@ mod tests {
         assert_eq!(stats.depth, 2);
         assert_eq!(stats.total_enqueued, 2);
 
-        queue.dequeue();
+        let _ = queue.dequeue();
         let stats = queue.stats();
         assert_eq!(stats.depth, 1);
     }
@@ -28,10 +28,23 @@ pub struct LlmRequest {
     pub temperature: f32,
     /// Optional GBNF grammar for structured output.
     pub grammar: Option<String>,
+    /// Optional JSON schema for structured output, for backends that take
+    /// a schema instead of a grammar.
+    pub response_schema: Option<ResponseSchema>,
     /// Request timeout in milliseconds.
     pub timeout_ms: u64,
 }
 
+/// A named JSON schema the response must match (`OpenAI`
+/// `response_format: json_schema`, llama.cpp `json_schema`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseSchema {
+    /// Schema name, e.g. `dialogue_response`.
+    pub name: String,
+    /// The JSON schema itself.
+    pub schema: serde_json::Value,
+}
+
 impl LlmRequest {
     /// Create a new Tier 1 request.
     #[must_use]
@@ -43,6 +56,7 @@ impl LlmRequest {
             max_tokens: 150,
             temperature: 0.7,
             grammar: None,
+            response_schema: None,
             timeout_ms: 5000,
         }
     }
@@ -57,6 +71,7 @@ impl LlmRequest {
             max_tokens: 300,
             temperature: 0.8,
             grammar: None,
+            response_schema: None,
             timeout_ms: 5000,
         }
     }
@@ -68,6 +83,13 @@ impl LlmRequest {
         self
     }
 
+    /// Set a JSON schema for structured output.
+    #[must_use]
+    pub fn with_response_schema(mut self, schema: ResponseSchema) -> Self {
+        self.response_schema = Some(schema);
+        self
+    }
+
     /// Set the timeout.
     #[must_use]
     pub fn with_timeout(mut self, timeout_ms: u64) -> Self {
@@ -77,7 +99,7 @@ impl LlmRequest {
 }
 
 /// A response from the LLM.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LlmResponse {
     /// The generated text.
     pub text: String,
@@ -132,6 +154,17 @@ pub struct GossipResponse {
     pub embellished: bool,
 }
 
+/// Structured memory summary response (matches GBNF grammar).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySummaryResponse {
+    /// The distilled impression, in the NPC's voice.
+    pub fact: String,
+    /// How firmly the NPC holds it (0.0 to 1.0).
+    pub confidence: f32,
+    /// Knowledge category the model assigned.
+    pub category: String,
+}
+
 // Custom serialization for LlmTier since we use it in LlmRequest serialization.
 impl Serialize for LlmTier {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -145,3 +178,17 @@ impl Serialize for LlmTier {
         }
     }
 }
+
+impl<'de> Deserialize<'de> for LlmTier {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match String::deserialize(deserializer)?.as_str() {
+            "rule_based" => Ok(LlmTier::RuleBased),
+            "small_local" => Ok(LlmTier::SmallLocal),
+            "large_model" => Ok(LlmTier::LargeModel),
+            other => Err(serde::de::Error::unknown_variant(other, &["rule_based", "small_local", "large_model"])),
+        }
+    }
+}
@@ -9,10 +9,24 @@
 //! - **Offline eval:** Run `cargo test -p memz-llm --test eval_golden` to
 //!   verify template rendering produces well-formed prompts.
 //! - **Online eval (requires Ollama):** Set `MEMZ_EVAL_LLM=1` env var to
-//!   actually call the LLM and check output against golden expectations.
+//!   actually call the LLM, check output against golden expectations and
+//!   re-record `tests/cassettes/eval_golden.json`.  `MEMZ_EVAL_LLM_URL`,
+//!   `MEMZ_EVAL_TIER1_MODEL` and `MEMZ_EVAL_TIER2_MODEL` override the
+//!   `memz.toml` defaults.
+//! - **Offline LLM flows:** Without `MEMZ_EVAL_LLM`, the same flows replay
+//!   from the recorded cassette; `tests/mock_flows.rs` runs dialogue,
+//!   reflection and gossip against the scripted mock backend
+//!   (`memz_llm::mock`).
 //! - **CI:** The offline checks run in CI; the online checks are opt-in.
 
-use memz_llm::prompt;
+use memz_llm::LlmClient;
+use memz_llm::cassette::Cassette;
+use memz_llm::client::LlmProvider;
+use memz_llm::mock::MockLlm;
+use memz_llm::prompt::{self, PromptEngine, PromptId};
+use memz_llm::types::{
+    DialogueResponse, GossipResponse, MemorySummaryResponse, ReflectionResponse,
+};
 
 /// A golden test case for prompt evaluation.
 struct GoldenCase {
@@ -241,6 +255,28 @@ fn golden_cases() -> Vec<GoldenCase> {
             ],
             prompt_must_not_contain: vec!["{npc_name}"],
         },
+        // ---------------------------------------------------------------
+        // Memory summary — a baker's fading memories of a thief
+        // ---------------------------------------------------------------
+        GoldenCase {
+            name: "baker_memory_summary_user",
+            template: prompt::MEMORY_SUMMARY_USER,
+            vars: vec![
+                ("cluster_topic", "dealings with the traveller"),
+                ("memories_formatted", "- Day 3: The traveller stole bread [valence: -0.6, importance: 0.5]\n- Day 9: The traveller broke my window [valence: -0.8, importance: 0.7]"),
+                ("memory_count", "2"),
+                ("npc_name", "Hilde"),
+            ],
+            prompt_must_contain: vec![
+                "stole bread",
+                "these 2 memories",
+                "Hilde",
+            ],
+            prompt_must_not_contain: vec![
+                "{memories_formatted}",
+                "{memory_count}",
+            ],
+        },
     ]
 }
 
@@ -295,6 +331,7 @@ fn grammars_are_nonempty() {
     assert!(!prompt::DIALOGUE_GRAMMAR.is_empty());
     assert!(!prompt::REFLECTION_GRAMMAR.is_empty());
     assert!(!prompt::GOSSIP_GRAMMAR.is_empty());
+    assert!(!prompt::MEMORY_SUMMARY_GRAMMAR.is_empty());
 }
 
 #[test]
@@ -305,6 +342,7 @@ fn all_prompts_have_json_output_instruction() {
         ("reflection", prompt::REFLECTION_USER),
         ("gossip", prompt::GOSSIP_USER),
         ("injection_validation", prompt::INJECTION_VALIDATION_USER),
+        ("memory_summary", prompt::MEMORY_SUMMARY_USER),
     ];
 
     for (name, template) in &user_prompts {
@@ -331,3 +369,134 @@ fn system_prompts_have_character_instruction() {
         );
     }
 }
+
+// ---------------------------------------------------------------------------
+// LLM Flows — Recorded Cassette
+// ---------------------------------------------------------------------------
+
+/// Cassette the golden flows replay from; `MEMZ_EVAL_LLM=1` re-records it.
+const GOLDEN_CASSETTE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/cassettes/eval_golden.json"
+);
+
+fn env_or(key: &str, default: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| default.to_string())
+}
+
+/// A live client recording on `cassette` under `MEMZ_EVAL_LLM=1`, a
+/// replay of the committed cassette otherwise.
+fn golden_client(cassette: &Cassette) -> (LlmClient, bool) {
+    if std::env::var("MEMZ_EVAL_LLM").is_ok_and(|v| v == "1") {
+        let provider = LlmProvider::Ollama {
+            base_url: env_or("MEMZ_EVAL_LLM_URL", "http://localhost:11434"),
+        };
+        let client = LlmClient::new(
+            provider,
+            env_or("MEMZ_EVAL_TIER1_MODEL", "qwen2.5:1.5b"),
+            env_or("MEMZ_EVAL_TIER2_MODEL", "mistral:7b-instruct"),
+            1,
+        );
+        (client.with_recorder(cassette.clone()), true)
+    } else {
+        let recorded = Cassette::load(GOLDEN_CASSETTE).expect("recorded golden cassette");
+        (LlmClient::mock(MockLlm::replaying(&recorded)), false)
+    }
+}
+
+#[test]
+fn golden_flows_answer_in_character() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("runtime");
+    let cassette = Cassette::new();
+    let (client, live) = golden_client(&cassette);
+    let engine = PromptEngine::builtin();
+    let request = |id, vars: &[(&str, &str)]| engine.request(id, vars).expect("template loaded");
+
+    let dialogue: DialogueResponse = rt
+        .block_on(client.generate_structured(&request(PromptId::DialogueSimple, &[
+            ("npc_name", "Goran"),
+            ("npc_profession", "Blacksmith"),
+            ("settlement_name", "Ironhaven"),
+            ("personality_description", "gruff but fair, brave and straightforward"),
+            ("pad_state", "P=0.40 A=0.10 D=0.30"),
+            ("context_description", "Player returns to Goran's forge at midday"),
+            ("player_action", "greeted the blacksmith warmly"),
+            ("memories_formatted", "- [episodic] Player helped defend the forge from bandits (strength: 0.90, age: 2.0 days)"),
+            ("overall_sentiment", "trusted ally"),
+            ("confidence", "0.85"),
+        ])))
+        .expect("dialogue");
+    assert!(
+        dialogue.emotion_shift >= 0.0,
+        "a trusted ally is greeted warmly: {dialogue:?}"
+    );
+
+    let reflection: ReflectionResponse = rt
+        .block_on(client.generate_structured(&request(PromptId::Reflection, &[
+            ("npc_name", "Elira"),
+            ("npc_profession", "Merchant"),
+            ("time_window", "3 game-days"),
+            ("recent_episodic_formatted", "1. A player bought 50 iron ingots at double price (2 days ago)\n2. Bandits raided a supply caravan on the north road (5 days ago)"),
+            ("semantic_formatted", "Iron prices are rising due to scarcity."),
+            ("personality_summary", "shrewd, observant, cautious with money"),
+        ])))
+        .expect("reflection");
+    let thought = format!(
+        "{} {}",
+        reflection.reflection,
+        reflection.new_beliefs.join(" ")
+    )
+    .to_lowercase();
+    assert!(
+        ["iron", "bandit", "caravan", "price"]
+            .iter()
+            .any(|w| thought.contains(w)),
+        "{reflection:?}"
+    );
+
+    let gossip: GossipResponse = rt
+        .block_on(client.generate_structured(&request(
+            PromptId::GossipGeneration,
+            &[
+                ("npc_name", "Old Bertram"),
+                ("npc_profession", "Farmer"),
+                ("listener_name", "a traveling adventurer"),
+                (
+                    "personality_description",
+                    "talkative, slightly unreliable, loves drama",
+                ),
+                (
+                    "memory_to_share",
+                    "The mayor was seen sneaking into the abandoned mine at midnight",
+                ),
+                ("confidence", "0.90 — I saw it myself"),
+                ("source_type", "direct witness"),
+            ],
+        )))
+        .expect("gossip");
+    assert!(
+        gossip.gossip_text.to_lowercase().contains("mayor"),
+        "{gossip:?}"
+    );
+
+    let summary: MemorySummaryResponse = rt
+        .block_on(client.generate_structured(&request(PromptId::MemorySummary, &[
+            ("npc_name", "Hilde"),
+            ("npc_profession", "Baker"),
+            ("cluster_topic", "dealings with the traveller"),
+            ("memories_formatted", "- Day 3: The traveller stole bread [valence: -0.6, importance: 0.5]\n- Day 9: The traveller broke my window [valence: -0.8, importance: 0.7]"),
+            ("memory_count", "2"),
+        ])))
+        .expect("memory summary");
+    assert!(
+        summary.fact.to_lowercase().contains("traveller"),
+        "{summary:?}"
+    );
+
+    if live {
+        cassette.save(GOLDEN_CASSETTE).expect("cassette saved");
+    }
+}
@@ -0,0 +1,119 @@
+//! Offline LLM flows — dialogue, reflection and gossip end to end against
+//! the scripted mock backend (no model server needed).
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use memz_llm::mock::{MockFailure, MockLlm, MockReply, Regex};
+use memz_llm::prompt::{self, PromptEngine, PromptId};
+use memz_llm::queue::LlmPriority;
+use memz_llm::types::{DialogueResponse, GossipResponse, ReflectionResponse};
+use memz_llm::{LlmClient, LlmDispatcher, LlmError, LlmQueue};
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .expect("runtime")
+}
+
+#[test]
+fn dialogue_answers_in_character() {
+    let rt = runtime();
+    let mock = MockLlm::new().respond(
+        Regex::new(r"You are Goran, a (?i:blacksmith)").expect("regex"),
+        r#"{"dialogue": "You again! The forge is warm.", "emotion_shift": 0.3, "new_memory": "The traveller came back"}"#,
+    );
+    let client = LlmClient::mock(mock.clone());
+
+    let request = PromptEngine::builtin()
+        .request(
+            PromptId::DialogueSimple,
+            &[
+                ("npc_name", "Goran"),
+                ("npc_profession", "Blacksmith"),
+                ("player_action", "waved"),
+            ],
+        )
+        .expect("template loaded");
+    let reply: DialogueResponse = rt
+        .block_on(client.generate_structured(&request))
+        .expect("dialogue");
+    assert_eq!(reply.dialogue, "You again! The forge is warm.");
+    assert_eq!(reply.new_memory, "The traveller came back");
+
+    let calls = mock.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].grammar.as_deref(), Some(prompt::DIALOGUE_GRAMMAR));
+    assert_eq!(
+        calls[0].response_schema.as_ref().map(|s| s.name.as_str()),
+        Some("dialogue_response")
+    );
+}
+
+#[test]
+fn reflection_through_the_dispatcher_recovers_from_a_bad_answer() {
+    let rt = runtime();
+    let mock = MockLlm::new().script(
+        "reflecting",
+        [
+            MockReply::text("I have been thinking a lot lately."),
+            MockReply::text(
+                r#"{"reflection": "Strangers bring trouble.", "new_beliefs": ["Lock the stall at night"], "questions": [], "mood_shift": {"pleasure": -0.2, "arousal": 0.1, "dominance": 0.0}}"#,
+            ),
+        ],
+    );
+    let failures = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&failures);
+    let client = LlmClient::mock(mock.clone()).with_parse_failure_hook(Arc::new(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    }));
+    let dispatcher = LlmDispatcher::start(Arc::new(client), LlmQueue::new(8), 1, rt.handle());
+
+    let request = PromptEngine::builtin()
+        .request(
+            PromptId::Reflection,
+            &[("npc_name", "Elira"), ("npc_profession", "Merchant")],
+        )
+        .expect("template loaded");
+    let handle = dispatcher
+        .submit::<ReflectionResponse>(LlmPriority::High, request, Duration::from_secs(5))
+        .expect("accepted");
+    let reflection = rt.block_on(handle.wait()).expect("re-prompted answer");
+    assert_eq!(reflection.new_beliefs, ["Lock the stall at night"]);
+
+    assert_eq!(failures.load(Ordering::SeqCst), 1);
+    let calls = mock.calls();
+    assert_eq!(calls.len(), 2);
+    assert!(calls[1].user.contains("ONLY the JSON object"));
+    assert!(calls[1].temperature <= 0.2);
+}
+
+#[test]
+fn gossip_survives_an_outage_and_noisy_output() {
+    let rt = runtime();
+    let mock = MockLlm::new().respond(
+        "gossip",
+        "Oh, you'll love this:\n```json\n{\"gossip_text\": \"The smith waters his ale\", \"confidence\": 1.4, \"embellished\": true}\n```",
+    );
+    let client = LlmClient::mock(mock.clone());
+    let request = PromptEngine::builtin()
+        .request(
+            PromptId::GossipGeneration,
+            &[("npc_name", "Brina"), ("subject_name", "Goran")],
+        )
+        .expect("template loaded");
+
+    mock.fail_next(1, MockFailure::Unavailable);
+    let outage = rt.block_on(client.generate_structured::<GossipResponse>(&request));
+    assert!(matches!(outage, Err(LlmError::Unavailable(_))));
+
+    let gossip: GossipResponse = rt
+        .block_on(client.generate_structured(&request))
+        .expect("gossip");
+    assert_eq!(gossip.gossip_text, "The smith waters his ale");
+    assert!((gossip.confidence - 1.0).abs() < f32::EPSILON, "clamped");
+    assert_eq!(mock.call_count(), 2);
+}
@@ -0,0 +1,196 @@
+//! Provider wire formats — requests as llama.cpp and OpenAI-compatible
+//! servers receive them, checked against a local stand-in server.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde_json::{Value, json};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use memz_llm::client::LlmProvider;
+use memz_llm::prompt::{self, PromptEngine, PromptId};
+use memz_llm::types::{DialogueResponse, ReflectionResponse};
+use memz_llm::{LlmClient, LlmRequest};
+
+/// Requests the stand-in server received: path and JSON body.
+type Received = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// A stand-in HTTP server answering every POST with `reply`, recording
+/// what it was sent.
+async fn stand_in(reply: Value) -> (String, Received) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    let received: Received = Arc::default();
+    let log = Arc::clone(&received);
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return;
+            };
+            let (log, reply) = (Arc::clone(&log), reply.to_string());
+            tokio::spawn(async move {
+                let mut raw = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Read the headers, then as much body as they announce.
+                let (head_len, body_len) = loop {
+                    let n = socket.read(&mut buf).await.expect("read");
+                    assert!(n > 0, "connection closed mid-request");
+                    raw.extend_from_slice(&buf[..n]);
+                    if let Some(end) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                        let head = String::from_utf8_lossy(&raw[..end]).to_lowercase();
+                        let len = head
+                            .lines()
+                            .find_map(|l| l.strip_prefix("content-length:"))
+                            .and_then(|v| v.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        break (end + 4, len);
+                    }
+                };
+                while raw.len() < head_len + body_len {
+                    let n = socket.read(&mut buf).await.expect("read");
+                    assert!(n > 0, "connection closed mid-body");
+                    raw.extend_from_slice(&buf[..n]);
+                }
+                let head = String::from_utf8_lossy(&raw[..head_len]).to_string();
+                let path = head
+                    .split_whitespace()
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let body = serde_json::from_slice(&raw[head_len..head_len + body_len])
+                    .unwrap_or(Value::Null);
+                log.lock().push((path, body));
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{reply}",
+                    reply.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    (format!("http://{addr}"), received)
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("runtime")
+}
+
+#[test]
+fn llama_cpp_completion_enforces_the_template_grammar() {
+    let rt = runtime();
+    let content = r#"{"dialogue": "Back for more iron?", "emotion_shift": 0.2, "new_memory": "The traveller returned"}"#;
+    let (base_url, received) = rt.block_on(stand_in(
+        json!({ "content": content, "tokens_predicted": 14, "model": "qwen2.5-1.5b.gguf" }),
+    ));
+    let client = LlmClient::new(LlmProvider::LlamaCpp { base_url }, "tiny", "big", 0);
+
+    let request = PromptEngine::builtin()
+        .request(
+            PromptId::DialogueSimple,
+            &[("npc_name", "Goran"), ("npc_profession", "blacksmith")],
+        )
+        .expect("template loaded");
+    assert_eq!(request.grammar.as_deref(), Some(prompt::DIALOGUE_GRAMMAR));
+
+    let raw = rt
+        .block_on(client.generate(&request))
+        .expect("raw response");
+    assert_eq!(raw.text, content);
+    assert_eq!(raw.tokens_generated, 14);
+    assert_eq!(raw.model, "qwen2.5-1.5b.gguf");
+    let parsed: DialogueResponse = rt
+        .block_on(client.generate_structured(&request))
+        .expect("structured");
+    assert_eq!(parsed.dialogue, "Back for more iron?");
+
+    let received = received.lock();
+    let (path, body) = &received[0];
+    assert_eq!(path, "/completion");
+    assert_eq!(body["grammar"], prompt::DIALOGUE_GRAMMAR);
+    assert_eq!(body["n_predict"], request.max_tokens);
+    assert_eq!(body["stream"], false);
+    assert!(body["prompt"].as_str().is_some_and(|p| p.contains("Goran")));
+    // A grammar wins over the schema `generate_structured` adds.
+    assert_eq!(received[1].1["grammar"], prompt::DIALOGUE_GRAMMAR);
+    assert!(received[1].1.get("json_schema").is_none());
+}
+
+#[test]
+fn llama_cpp_falls_back_to_the_response_schema() {
+    let rt = runtime();
+    let content = r#"{"dialogue": "Hm.", "emotion_shift": 0, "new_memory": ""}"#;
+    let (base_url, received) = rt.block_on(stand_in(
+        json!({ "content": content, "tokens_predicted": 5 }),
+    ));
+    let client = LlmClient::new(LlmProvider::LlamaCpp { base_url }, "tiny", "big", 0);
+
+    let response =
+        rt.block_on(client.generate_structured::<DialogueResponse>(&LlmRequest::tier1("s", "u")));
+    assert_eq!(response.expect("structured").dialogue, "Hm.");
+
+    let received = received.lock();
+    let schema = &received[0].1["json_schema"];
+    assert_eq!(schema["type"], "object");
+    assert_eq!(
+        schema["required"],
+        json!(["dialogue", "emotion_shift", "new_memory"])
+    );
+}
+
+#[test]
+fn openai_requests_a_strict_json_schema() {
+    let rt = runtime();
+    let content = r#"{"reflection": "The stranger keeps coming back.", "new_beliefs": [], "questions": ["Why?"], "mood_shift": {"pleasure": 0.1, "arousal": 0.2, "dominance": 0.0}}"#;
+    let (base_url, received) = rt.block_on(stand_in(json!({
+        "choices": [{ "message": { "role": "assistant", "content": content } }],
+        "usage": { "completion_tokens": 30 },
+    })));
+    let provider = LlmProvider::OpenAiCompatible {
+        base_url,
+        api_key: "test-key".into(),
+    };
+    let client = LlmClient::new(provider, "gpt-small", "gpt-large", 0);
+
+    let request = LlmRequest::tier2("You are Goran.", "Reflect.");
+    let parsed: ReflectionResponse = rt
+        .block_on(client.generate_structured(&request))
+        .expect("structured");
+    assert_eq!(parsed.questions, ["Why?"]);
+
+    let received = received.lock();
+    let (path, body) = &received[0];
+    assert_eq!(path, "/v1/chat/completions");
+    assert_eq!(body["model"], "gpt-large");
+    let format = &body["response_format"];
+    assert_eq!(format["type"], "json_schema");
+    assert_eq!(format["json_schema"]["name"], "reflection_output");
+    assert_eq!(format["json_schema"]["strict"], true);
+    let schema = &format["json_schema"]["schema"];
+    assert_eq!(schema["additionalProperties"], false);
+    assert_eq!(
+        schema["properties"]["mood_shift"]["additionalProperties"],
+        false
+    );
+}
+
+#[test]
+fn structured_output_can_be_switched_off() {
+    let rt = runtime();
+    let content = r#"{"dialogue": "Hello.", "emotion_shift": 0, "new_memory": ""}"#;
+    let (base_url, received) = rt.block_on(stand_in(json!({ "content": content })));
+    let client = LlmClient::new(LlmProvider::LlamaCpp { base_url }, "tiny", "big", 0)
+        .with_structured_output(false);
+
+    let request = LlmRequest::tier1("s", "u").with_grammar(prompt::DIALOGUE_GRAMMAR);
+    let _: DialogueResponse = rt
+        .block_on(client.generate_structured(&request))
+        .expect("structured");
+
+    let body = &received.lock()[0].1;
+    assert!(body.get("grammar").is_none() && body.get("json_schema").is_none());
+}
@@ -0,0 +1,40 @@
+//! # memz-rtsim — Compiled MEMZ rule for Veloren rtsim (§12.2)
+//!
+//! `memz-veloren` is deliberately engine-free: it knows how to turn game
+//! events into memories but never links Veloren itself. This crate is the
+//! other half — a real [`rtsim::Rule`] that binds MEMZ handlers onto the
+//! vendored rtsim event bus.
+//!
+//! ```text
+//! RtState::start_rule::<MemzRule>()
+//!   └─ binds OnDeath        → memory_rule::on_death()
+//!   └─ binds OnHelped       → memory_rule::on_helped()
+//!   └─ binds OnTheft        → memory_rule::on_theft()
+//!   └─ binds OnHealthChange → memory_rule::on_combat()
+//!   └─ binds OnTick         → memory_rule::on_tick() + gossip
+//! ```
+//!
+//! The shared [`MemoryRule`](memz_veloren::memory_rule::MemoryRule) lives in
+//! an `Arc<Mutex<_>>` held by the rule state, so the server (dialogue,
+//! trading, persistence) can reach the same banks via
+//! `rtstate.rule::<MemzRule>().memory()`.
+//!
+//! ## Build Note
+//!
+//! The rule itself is behind the `rtsim` feature (on by default). Building it
+//! requires Veloren's pinned nightly toolchain (see `rust-toolchain`) and
+//! network access for Veloren's `specs` git patch. This crate is its own
+//! workspace so the root MEMZ workspace never resolves Veloren.
+
+#![warn(clippy::pedantic)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_sign_loss)]
+#![allow(clippy::missing_panics_doc)]
+
+#[cfg(feature = "rtsim")]
+pub mod rule;
+
+#[cfg(feature = "rtsim")]
+pub use rule::MemzRule;
@@ -0,0 +1,389 @@
+//! `MemzRule` — the rtsim [`Rule`] that feeds Veloren events into MEMZ.
+//!
+//! Each bound handler resolves Veloren actors to MEMZ `EntityId`s through the
+//! shared `EntityRegistry`, gathers nearby NPCs as witnesses and forwards the
+//! event to the matching `memz_veloren::memory_rule` hook.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::rtsim::{Actor, NpcActivity, Personality, PersonalityTrait, SiteId};
+use memz_core::types::{EntityId, Location, PersonalityTraits, SettlementId};
+use memz_veloren::bridge::{self, EntityRegistry};
+use memz_veloren::config::VelorenMemzConfig;
+use memz_veloren::memory_rule::{self, MemoryRule};
+use parking_lot::Mutex;
+use rtsim::data::Data;
+use rtsim::event::{EventCtx, OnDeath, OnHealthChange, OnHelped, OnSetup, OnTheft, OnTick};
+use rtsim::{RtState, Rule, RuleError};
+use vek::Vec3;
+
+/// MEMZ memory state shared between the rule and the rest of the server.
+pub type SharedMemory = Arc<Mutex<MemoryRule>>;
+
+/// rtsim resource carrying the [`SharedMemory`] handle into [`MemzRule::start`].
+///
+/// Insert it with `RtState::with_resource` before starting the rule, or use
+/// [`MemzRule::install`], which does both.
+pub struct MemzState(pub SharedMemory);
+
+/// How often (ticks) NPC personalities are re-synced from Veloren.
+const PERSONALITY_SYNC_INTERVAL: u64 = 600;
+
+/// How often (ticks) talking NPCs exchange gossip.
+const GOSSIP_INTERVAL: u64 = 30;
+
+/// Minimum ticks between two combat memories for the same attacker/defender
+/// pair. `OnHealthChange` fires on every hit; one fight is one memory.
+const FIGHT_COOLDOWN_TICKS: u64 = 600;
+
+/// Action description used for `OnHelped` — rtsim only reports rescues.
+const HELPED_ACTION: &str = "defended from danger";
+
+// ---------------------------------------------------------------------------
+// Rule
+// ---------------------------------------------------------------------------
+
+/// The compiled MEMZ rule for Veloren rtsim.
+pub struct MemzRule {
+    memory: SharedMemory,
+    /// Observation radius (world units) used to gather witnesses.
+    observation_radius: f32,
+    /// Veloren site → MEMZ settlement, derived from the site's uid so it is
+    /// the same in every session (see `EntityRegistry::site_settlement`).
+    settlements: HashMap<SiteId, SettlementId>,
+    /// Last tick a fight between (attacker, defender) was remembered.
+    recent_fights: HashMap<(EntityId, EntityId), u64>,
+}
+
+impl Rule for MemzRule {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        let memory = Arc::clone(&rtstate.resource::<MemzState>().0);
+
+        rtstate.bind::<Self, OnSetup>(on_setup);
+        rtstate.bind::<Self, OnDeath>(on_death);
+        rtstate.bind::<Self, OnHelped>(on_helped);
+        rtstate.bind::<Self, OnTheft>(on_theft);
+        rtstate.bind::<Self, OnHealthChange>(on_health_change);
+        rtstate.bind::<Self, OnTick>(on_tick);
+
+        Ok(Self {
+            memory,
+            observation_radius: VelorenMemzConfig::default().observation_radius,
+            settlements: HashMap::new(),
+            recent_fights: HashMap::new(),
+        })
+    }
+}
+
+impl MemzRule {
+    /// Register `memory` as rtsim state and start the rule.
+    ///
+    /// `OnSetup` keys the registry by the world's seed, replacing whatever
+    /// seed `memory` was built with.
+    ///
+    /// Returns the rtsim state together with a handle the server can use for
+    /// dialogue, trading and persistence.
+    #[must_use]
+    pub fn install(rtstate: RtState, memory: MemoryRule) -> (RtState, SharedMemory) {
+        let shared = Arc::new(Mutex::new(memory));
+        let mut rtstate = rtstate.with_resource(MemzState(Arc::clone(&shared)));
+        rtstate.start_rule::<Self>();
+        (rtstate, shared)
+    }
+
+    /// The shared memory handle.
+    #[must_use]
+    pub fn memory(&self) -> SharedMemory {
+        Arc::clone(&self.memory)
+    }
+
+    /// Map a Veloren site to its MEMZ settlement; `None` if rtsim does not
+    /// know the site.
+    fn settlement(&mut self, data: &Data, site: SiteId) -> Option<SettlementId> {
+        if let Some(&settlement) = self.settlements.get(&site) {
+            return Some(settlement);
+        }
+        let uid = data.sites.get(site)?.uid;
+        let settlement = self.memory.lock().registry.site_settlement(uid);
+        self.settlements.insert(site, settlement);
+        Some(settlement)
+    }
+
+    /// Settlement whose site centre is nearest to `wpos`.
+    fn nearest_settlement(&mut self, data: &Data, wpos: Vec3<f32>) -> Option<SettlementId> {
+        let site = data
+            .sites
+            .iter()
+            .min_by_key(|(_, site)| {
+                let diff = site.wpos.as_::<f32>() - wpos.xy();
+                diff.magnitude_squared() as i64
+            })
+            .map(|(id, _)| id)?;
+        self.settlement(data, site)
+    }
+}
+
+/// Periodic processing, normally driven by the bound `OnTick` handler.
+///
+/// `OnTick` carries ECS system data that only a running server can provide,
+/// so hosts without one (tests, offline simulation) call this directly with
+/// the rtsim [`Data`].
+pub fn tick_memory(memory: &SharedMemory, data: &Data, tick: u64, dt: f32) {
+    let mut rule = memory.lock();
+
+    if tick % PERSONALITY_SYNC_INTERVAL == 0 {
+        for npc in data.npcs.values().filter(|npc| !npc.is_dead()) {
+            let entity = rule.registry.npc_entity(npc.uid);
+            rule.set_personality(entity, personality_to_memz(&npc.personality));
+        }
+    }
+
+    memory_rule::on_tick(&mut rule, tick, dt);
+
+    // Gossip between NPCs that rtsim has put into a conversation.
+    if tick % GOSSIP_INTERVAL == 0 {
+        for npc in data.npcs.values() {
+            let Some(NpcActivity::Talk(Actor::Npc(target_id))) = npc.controller.activity else {
+                continue;
+            };
+            let Some(target) = data.npcs.get(target_id) else {
+                continue;
+            };
+            let speaker = rule.registry.npc_entity(npc.uid);
+            let listener = rule.registry.npc_entity(target.uid);
+            let ts = rule.timestamp(tick);
+            memory_rule::propagate_gossip(&mut rule, speaker, listener, ts);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Event handlers
+// ---------------------------------------------------------------------------
+
+/// Derive entity and settlement IDs from the real world seed, so saved
+/// banks and reputation boards are found again after a restart and never
+/// leak into another world.
+fn on_setup(ctx: EventCtx<MemzRule, OnSetup>) {
+    let world_seed = u64::from(ctx.world.sim().seed);
+    let mut rule = ctx.rule.memory.lock();
+    if rule.registry.world_seed() != world_seed {
+        rule.registry = EntityRegistry::with_world_seed(world_seed);
+        drop(rule);
+        ctx.rule.settlements.clear();
+    }
+}
+
+fn on_death(ctx: EventCtx<MemzRule, OnDeath>) {
+    let data = ctx.state.data();
+    let this = ctx.rule;
+    let settlement = ctx.event.wpos.and_then(|w| this.nearest_settlement(&data, w));
+    let mut rule = this.memory.lock();
+
+    let Some(deceased) = actor_entity(&mut rule, &data, ctx.event.actor) else {
+        return;
+    };
+    let killer = ctx
+        .event
+        .killer
+        .and_then(|k| actor_entity(&mut rule, &data, k));
+    let mut witnesses = nearby_npcs(&mut rule, &data, ctx.event.wpos, this.observation_radius);
+    witnesses.retain(|&w| w != deceased);
+
+    let timestamp = rule.timestamp(data.tick);
+    memory_rule::on_death(
+        &mut rule,
+        deceased,
+        killer,
+        &witnesses,
+        ctx.event.wpos.map(to_location).unwrap_or_default(),
+        settlement,
+        timestamp,
+    );
+}
+
+fn on_helped(ctx: EventCtx<MemzRule, OnHelped>) {
+    let data = ctx.state.data();
+    let this = ctx.rule;
+    let Some(saver) = ctx.event.saver else {
+        return;
+    };
+    let wpos = actor_wpos(&data, ctx.event.actor);
+    let settlement = wpos.and_then(|w| this.nearest_settlement(&data, w));
+    let mut rule = this.memory.lock();
+
+    let (Some(helped), Some(helper)) = (
+        actor_entity(&mut rule, &data, ctx.event.actor),
+        actor_entity(&mut rule, &data, saver),
+    ) else {
+        return;
+    };
+    let witnesses = nearby_npcs(&mut rule, &data, wpos, this.observation_radius);
+
+    let timestamp = rule.timestamp(data.tick);
+    memory_rule::on_helped(
+        &mut rule,
+        helped,
+        helper,
+        HELPED_ACTION,
+        &witnesses,
+        wpos.map(to_location).unwrap_or_default(),
+        settlement,
+        timestamp,
+    );
+}
+
+fn on_theft(ctx: EventCtx<MemzRule, OnTheft>) {
+    let data = ctx.state.data();
+    let this = ctx.rule;
+    let wpos = ctx.event.wpos.as_::<f32>();
+    let settlement = ctx.event.site.and_then(|site| this.settlement(&data, site));
+    let mut rule = this.memory.lock();
+
+    let Some(thief) = actor_entity(&mut rule, &data, ctx.event.actor) else {
+        return;
+    };
+    let witnesses = nearby_npcs(&mut rule, &data, Some(wpos), this.observation_radius);
+
+    let timestamp = rule.timestamp(data.tick);
+    memory_rule::on_theft(
+        &mut rule,
+        thief,
+        &witnesses,
+        &format!("{:?}", ctx.event.sprite),
+        to_location(wpos),
+        settlement,
+        timestamp,
+    );
+}
+
+fn on_health_change(ctx: EventCtx<MemzRule, OnHealthChange>) {
+    // Only damage with a known cause is a fight; deaths arrive via `OnDeath`.
+    let Some(cause) = ctx.event.cause else {
+        return;
+    };
+    if ctx.event.change >= 0.0 || ctx.event.new_health_fraction <= 0.0 {
+        return;
+    }
+
+    let data = ctx.state.data();
+    let this = ctx.rule;
+    let wpos = actor_wpos(&data, ctx.event.actor);
+    let settlement = wpos.and_then(|w| this.nearest_settlement(&data, w));
+    let mut rule = this.memory.lock();
+
+    let (Some(defender), Some(attacker)) = (
+        actor_entity(&mut rule, &data, ctx.event.actor),
+        actor_entity(&mut rule, &data, cause),
+    ) else {
+        return;
+    };
+
+    if let Some(&last) = this.recent_fights.get(&(attacker, defender))
+        && data.tick.saturating_sub(last) < FIGHT_COOLDOWN_TICKS
+    {
+        return;
+    }
+    this.recent_fights.insert((attacker, defender), data.tick);
+
+    let witnesses = nearby_npcs(&mut rule, &data, wpos, this.observation_radius);
+    let timestamp = rule.timestamp(data.tick);
+    memory_rule::on_combat(
+        &mut rule,
+        attacker,
+        defender,
+        ctx.event.new_health_fraction < 0.5,
+        &witnesses,
+        wpos.map(to_location).unwrap_or_default(),
+        settlement,
+        timestamp,
+    );
+}
+
+fn on_tick(ctx: EventCtx<MemzRule, OnTick>) {
+    let data = ctx.state.data();
+    // NPCs consolidate memories during Veloren's night.
+    ctx.rule.memory.lock().time_of_day = Some(ctx.event.time_of_day.0);
+    tick_memory(&ctx.rule.memory, &data, ctx.event.tick, ctx.event.dt);
+
+    let horizon = ctx.event.tick.saturating_sub(FIGHT_COOLDOWN_TICKS);
+    ctx.rule.recent_fights.retain(|_, last| *last >= horizon);
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Resolve a Veloren actor to a MEMZ entity. Unknown NPCs resolve to `None`.
+fn actor_entity(rule: &mut MemoryRule, data: &Data, actor: Actor) -> Option<EntityId> {
+    match actor {
+        Actor::Npc(npc_id) => data
+            .npcs
+            .get(npc_id)
+            .map(|npc| rule.registry.npc_entity(npc.uid)),
+        Actor::Character(cid) => Some(rule.registry.character_entity(cid.0)),
+    }
+}
+
+/// World position of an actor, if rtsim tracks one.
+fn actor_wpos(data: &Data, actor: Actor) -> Option<Vec3<f32>> {
+    match actor {
+        Actor::Npc(npc_id) => data.npcs.get(npc_id).map(|npc| npc.wpos),
+        Actor::Character(_) => None,
+    }
+}
+
+/// Living NPCs within `radius` of `wpos`, as MEMZ entities.
+///
+/// A linear scan rather than `Npcs::nearby`: the NPC grid is only refreshed
+/// by the simulation rules and does not guarantee results beyond 32 blocks.
+fn nearby_npcs(
+    rule: &mut MemoryRule,
+    data: &Data,
+    wpos: Option<Vec3<f32>>,
+    radius: f32,
+) -> Vec<EntityId> {
+    let Some(center) = wpos else {
+        return Vec::new();
+    };
+    let radius_sq = radius * radius;
+
+    data.npcs
+        .values()
+        .filter(|npc| !npc.is_dead() && npc.wpos.distance_squared(center) < radius_sq)
+        .map(|npc| rule.registry.npc_entity(npc.uid))
+        .collect()
+}
+
+fn to_location(wpos: Vec3<f32>) -> Location {
+    bridge::veloren_pos_to_location(wpos.x, wpos.y, wpos.z)
+}
+
+/// Map Veloren's OCEAN personality onto MEMZ traits.
+///
+/// Veloren keeps the raw OCEAN bytes private and only exposes trait
+/// predicates, so each axis is reconstructed as high / neutral / low.
+fn personality_to_memz(personality: &Personality) -> PersonalityTraits {
+    const HIGH: u8 = 200;
+    const MID: u8 = 128;
+    const LOW: u8 = 55;
+
+    let axis = |high: PersonalityTrait, low: PersonalityTrait| {
+        if personality.is(high) {
+            HIGH
+        } else if personality.is(low) {
+            LOW
+        } else {
+            MID
+        }
+    };
+
+    bridge::veloren_personality_to_memz(
+        axis(PersonalityTrait::Open, PersonalityTrait::Closed),
+        axis(PersonalityTrait::Conscientious, PersonalityTrait::Unconscientious),
+        axis(PersonalityTrait::Extroverted, PersonalityTrait::Introverted),
+        axis(PersonalityTrait::Agreeable, PersonalityTrait::Disagreeable),
+        axis(PersonalityTrait::Neurotic, PersonalityTrait::Stable),
+    )
+}
@@ -0,0 +1,190 @@
+//! Drives `MemzRule` inside a small generated rtsim world and checks that
+//! Veloren events end up in NPC memory banks.
+
+use std::collections::HashSet;
+
+use common::comp::{Body, humanoid};
+use common::rtsim::{Actor, CharacterId, Role, WorldSettings};
+use common::terrain::SpriteKind;
+use memz_core::types::EntityId;
+use memz_rtsim::MemzRule;
+use memz_rtsim::rule::{SharedMemory, tick_memory};
+use memz_veloren::bridge::EntityRegistry;
+use memz_veloren::memory_rule::MemoryRule;
+use rtsim::RtState;
+use rtsim::data::{Data, Site};
+use rtsim::data::npc::Npc;
+use rtsim::event::{Event, OnDeath, OnHealthChange, OnHelped, OnSetup, OnTheft};
+use vek::{Vec2, Vec3};
+use world::{IndexOwned, World};
+
+struct TestWorld {
+    world: World,
+    index: IndexOwned,
+    state: RtState,
+    memory: SharedMemory,
+    npcs: Vec<Actor>,
+}
+
+/// Generate rtsim data for an empty world and place a handful of villagers
+/// close enough to witness each other.
+fn generated_world(villagers: u32) -> TestWorld {
+    let (world, index) = World::empty();
+    let mut data = Data::generate(&WorldSettings::default(), &world, index.as_index_ref());
+
+    let npcs = (0..villagers)
+        .map(|i| {
+            let npc = Npc::new(
+                i,
+                Vec3::new(100.0 + i as f32, 100.0, 0.0),
+                Body::Humanoid(humanoid::Body::random()),
+                Role::Civilised(None),
+            );
+            Actor::Npc(data.npcs.create_npc(npc))
+        })
+        .collect();
+
+    let (state, memory) = MemzRule::install(RtState::new(data), MemoryRule::new());
+    TestWorld {
+        world,
+        index,
+        state,
+        memory,
+        npcs,
+    }
+}
+
+impl TestWorld {
+    fn emit<E: for<'a> Event<SystemData<'a> = ()>>(&mut self, event: E) {
+        self.state
+            .emit(event, &mut (), &self.world, self.index.as_index_ref());
+    }
+
+    /// MEMZ entity for one of the placed villagers.
+    fn entity(&self, actor: Actor) -> EntityId {
+        let Actor::Npc(npc_id) = actor else {
+            panic!("villagers are NPCs");
+        };
+        let uid = self.state.data().npcs[npc_id].uid;
+        self.memory.lock().registry.npc_entity(uid)
+    }
+}
+
+#[test]
+fn death_fills_witness_banks() {
+    let mut w = generated_world(5);
+    let (victim, killer) = (w.npcs[0], w.npcs[1]);
+
+    w.emit(OnDeath {
+        actor: victim,
+        wpos: Some(Vec3::new(100.0, 100.0, 0.0)),
+        killer: Some(killer),
+    });
+
+    // Killer + three bystanders remember it; the victim is not a witness.
+    let victim_id = w.entity(victim);
+    let witnesses: Vec<_> = w.npcs[1..].iter().map(|&npc| w.entity(npc)).collect();
+    let rule = w.memory.lock();
+    assert!(rule.bank(victim_id).is_none());
+    for witness in witnesses {
+        let bank = rule.bank(witness).expect("witness should have a bank");
+        assert!(bank.episodic.iter().any(|m| m.participants.contains(&victim_id)));
+    }
+}
+
+#[test]
+fn player_rescue_is_remembered() {
+    let mut w = generated_world(3);
+    let player = Actor::Character(CharacterId(7));
+
+    w.emit(OnHelped {
+        actor: w.npcs[0],
+        saver: Some(player),
+    });
+
+    let helped_id = w.entity(w.npcs[0]);
+    let mut rule = w.memory.lock();
+    let player_id = rule.registry.character_entity(7);
+    let bank = rule.bank(helped_id).expect("helped NPC should have a bank");
+    assert!(
+        bank.episodic
+            .iter()
+            .any(|m| m.participants.contains(&player_id))
+    );
+}
+
+#[test]
+fn repeated_hits_record_one_fight() {
+    let mut w = generated_world(3);
+    let (attacker, defender) = (w.npcs[0], w.npcs[1]);
+
+    for hp in [0.9, 0.7, 0.4] {
+        w.emit(OnHealthChange {
+            actor: defender,
+            cause: Some(attacker),
+            new_health_fraction: hp,
+            change: -10.0,
+        });
+    }
+
+    let defender_id = w.entity(defender);
+    let rule = w.memory.lock();
+    let bank = rule.bank(defender_id).expect("defender should have a bank");
+    assert_eq!(bank.episodic.len(), 1);
+}
+
+#[test]
+fn tick_syncs_personalities() {
+    let w = generated_world(4);
+
+    tick_memory(&w.memory, &w.state.data(), 600, 1.0 / 30.0);
+
+    let villagers: Vec<_> = w.npcs.iter().map(|&npc| w.entity(npc)).collect();
+    let rule = w.memory.lock();
+    assert!(villagers.iter().all(|id| rule.personalities.contains_key(id)));
+}
+
+#[test]
+fn setup_keys_entities_by_the_world_seed() {
+    let mut w = generated_world(1);
+    // Built for some other world; setup must re-key it.
+    w.memory.lock().registry = EntityRegistry::with_world_seed(99);
+    w.emit(OnSetup);
+
+    let world_seed = u64::from(w.world.sim().seed);
+    assert_eq!(w.memory.lock().registry.world_seed(), world_seed);
+    let uid = 42;
+    let expected = EntityRegistry::with_world_seed(world_seed).npc_entity(uid);
+    assert_eq!(w.memory.lock().registry.npc_entity(uid), expected);
+}
+
+#[test]
+fn sites_keep_their_settlement_across_restarts() {
+    // Two rules over the same world, as before and after a server restart.
+    let settlement = || {
+        let mut w = generated_world(3);
+        w.emit(OnSetup);
+        let site = w.state.data_mut().sites.create(Site {
+            uid: 0,
+            seed: 0,
+            wpos: Vec2::new(100, 100),
+            faction: None,
+            known_reports: HashSet::new(),
+            count_loaded_chunks: 0,
+            world_site: None,
+            population: HashSet::new(),
+            nearby_sites_by_size: Vec::new(),
+        });
+        w.emit(OnTheft {
+            actor: w.npcs[0],
+            wpos: Vec3::new(100, 100, 0),
+            sprite: SpriteKind::Apple,
+            site: Some(site),
+        });
+        let rule = w.memory.lock();
+        let boards: Vec<_> = rule.reputation_boards.keys().copied().collect();
+        assert_eq!(boards.len(), 1, "the theft is reported to the site's board");
+        boards[0]
+    };
+    assert_eq!(settlement(), settlement());
+}
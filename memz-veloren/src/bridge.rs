@@ -13,7 +13,8 @@
 //! This module provides bidirectional mappings so MEMZ can read Veloren state
 //! and Veloren can act on MEMZ outputs without either knowing the other's internals.
 
-use memz_core::types::{EntityId, GameTimestamp, Location, PADState, PersonalityTraits};
+use memz_core::time::TimeModel;
+use memz_core::types::{EntityId, GameTimestamp, Location, PADState, PersonalityTraits, SettlementId};
 
 use std::collections::HashMap;
 
@@ -185,10 +186,14 @@ impl SentimentLevel {
 /// Bidirectional map between Veloren NPC/Character IDs and MEMZ `EntityId`s.
 ///
 /// Veloren uses `NpcId` (slotmap index) and `CharacterId(i64)` while MEMZ
-/// uses `EntityId(Uuid)`. This registry maintains stable mappings across
-/// save/load cycles.
+/// uses `EntityId(Uuid)`. IDs are derived deterministically (`UUIDv5`) from the
+/// world seed and the NPC uid / character id, so a registry rebuilt after a
+/// server restart maps every entity to the `EntityId` its saved
+/// `MemoryBank` is keyed by — nothing needs persisting.
 #[derive(Debug, Clone, Default)]
 pub struct EntityRegistry {
+    /// Seed of the world the IDs belong to (keeps worlds apart).
+    world_seed: u64,
     /// Veloren NPC numeric seed → MEMZ `EntityId`.
     npc_to_memz: HashMap<u64, EntityId>,
     /// MEMZ `EntityId` → Veloren NPC numeric seed.
@@ -200,43 +205,70 @@ pub struct EntityRegistry {
 }
 
 impl EntityRegistry {
-    /// Create a new empty registry.
+    /// Create a new empty registry for world seed 0.
     #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Get or create a MEMZ `EntityId` for a Veloren NPC (identified by UID).
+    /// Create a new empty registry for the world with `world_seed`.
+    #[must_use]
+    pub fn with_world_seed(world_seed: u64) -> Self {
+        Self {
+            world_seed,
+            ..Self::default()
+        }
+    }
+
+    /// Seed of the world this registry maps.
+    #[must_use]
+    pub fn world_seed(&self) -> u64 {
+        self.world_seed
+    }
+
+    /// Get the MEMZ `EntityId` for a Veloren NPC (identified by UID).
     pub fn npc_entity(&mut self, npc_uid: u64) -> EntityId {
+        let world_seed = self.world_seed;
         *self
             .npc_to_memz
             .entry(npc_uid)
             .or_insert_with(|| {
-                let id = EntityId::new();
+                let id = EntityId::derived(&format!("veloren/{world_seed}/npc/{npc_uid}"));
                 self.memz_to_npc.insert(id, npc_uid);
                 id
             })
     }
 
-    /// Get or create a MEMZ `EntityId` for a player character.
+    /// Get the MEMZ `EntityId` for a player character.
     pub fn character_entity(&mut self, character_id: i64) -> EntityId {
+        let world_seed = self.world_seed;
         *self
             .character_to_memz
             .entry(character_id)
             .or_insert_with(|| {
-                let id = EntityId::new();
+                let id =
+                    EntityId::derived(&format!("veloren/{world_seed}/character/{character_id}"));
                 self.memz_to_character.insert(id, character_id);
                 id
             })
     }
 
-    /// Look up a Veloren NPC UID from a MEMZ `EntityId`.
+    /// The MEMZ settlement for a Veloren site (identified by UID), derived
+    /// from the world seed like entity IDs.
+    #[must_use]
+    pub fn site_settlement(&self, site_uid: u64) -> SettlementId {
+        SettlementId::derived(&format!("veloren/{}/site/{site_uid}", self.world_seed))
+    }
+
+    /// Look up a Veloren NPC UID from a MEMZ `EntityId` (registered this
+    /// session).
     #[must_use]
     pub fn lookup_npc(&self, entity: &EntityId) -> Option<u64> {
         self.memz_to_npc.get(entity).copied()
     }
 
-    /// Look up a character ID from a MEMZ `EntityId`.
+    /// Look up a character ID from a MEMZ `EntityId` (registered this
+    /// session).
     #[must_use]
     pub fn lookup_character(&self, entity: &EntityId) -> Option<i64> {
         self.memz_to_character.get(entity).copied()
@@ -273,11 +305,30 @@ pub fn location_to_veloren_pos(loc: &Location) -> (f32, f32, f32) {
 ///
 /// Veloren's `common::resources::Time(f64)` counts seconds since server start.
 /// We convert to a monotonic tick for MEMZ, using Veloren's tick counter.
+/// Hosts driving a [`MemoryRule`](crate::memory_rule::MemoryRule) should use
+/// its `timestamp` instead, which honours deterministic mode.
 #[must_use]
 pub fn veloren_time_to_timestamp(tick: u64) -> GameTimestamp {
     GameTimestamp::now(tick)
 }
 
+/// Build the MEMZ [`TimeModel`] for a Veloren server.
+///
+/// `ticks_per_second` is the server `TPS` (30 for `server-cli`) and
+/// `day_cycle_coefficient` is `ServerConstants::day_cycle_coefficient` —
+/// how many `TimeOfDay` seconds pass per real second (`1440 / day_length`).
+#[must_use]
+pub fn veloren_time_model(ticks_per_second: f64, day_cycle_coefficient: f64) -> TimeModel {
+    TimeModel::from_day_cycle(ticks_per_second, day_cycle_coefficient)
+}
+
+/// Game-hour (0–24) of Veloren's `TimeOfDay` (game-seconds since the
+/// world began).
+#[must_use]
+pub fn veloren_hour_of_day(time_of_day: f64) -> f64 {
+    (time_of_day / 3600.0).rem_euclid(24.0)
+}
+
 // ---------------------------------------------------------------------------
 // Dialogue Context Builder
 // ---------------------------------------------------------------------------
@@ -505,6 +556,69 @@ mod tests {
         assert_eq!(reg.lookup_character(&id), Some(1001));
     }
 
+    #[test]
+    fn entity_registry_is_stable_across_sessions() {
+        let mut before = EntityRegistry::with_world_seed(7);
+        let mut after = EntityRegistry::with_world_seed(7);
+        assert_eq!(before.npc_entity(42), after.npc_entity(42));
+        assert_eq!(before.character_entity(42), after.character_entity(42));
+        // NPC and character namespaces, and worlds, never collide.
+        assert_ne!(before.npc_entity(42), before.character_entity(42));
+        assert_ne!(
+            before.npc_entity(42),
+            EntityRegistry::with_world_seed(8).npc_entity(42)
+        );
+        assert_eq!(before.site_settlement(3), after.site_settlement(3));
+        assert_ne!(
+            before.site_settlement(3),
+            EntityRegistry::with_world_seed(8).site_settlement(3)
+        );
+    }
+
+    #[test]
+    fn banks_survive_save_restart_load() {
+        use memz_core::config::PersistenceConfig;
+        use memz_core::memory::MemoryBank;
+        use memz_core::memory::episodic::EpisodicMemory;
+        use memz_core::persistence::PersistenceEngine;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("memz.db");
+        let config = PersistenceConfig::default();
+
+        // Session 1: the blacksmith remembers the player's help; save.
+        {
+            let mut registry = EntityRegistry::with_world_seed(1234);
+            let npc = registry.npc_entity(42);
+            let player = registry.character_entity(1001);
+            let mut bank = MemoryBank::new();
+            bank.episodic.push(EpisodicMemory::new(
+                "helped defend the forge",
+                vec![player],
+                Location::default(),
+                GameTimestamp::now(1000),
+                0.8,
+                0.9,
+            ));
+            let engine = PersistenceEngine::open(&db_path, &config).unwrap();
+            engine.save_bank(&npc, &mut bank).unwrap();
+        }
+
+        // Session 2: a fresh registry after the restart finds the bank, and
+        // the memory still points at the same player.
+        let mut registry = EntityRegistry::with_world_seed(1234);
+        let engine = PersistenceEngine::open(&db_path, &config).unwrap();
+        let bank = engine
+            .load_bank(&registry.npc_entity(42))
+            .unwrap()
+            .expect("bank saved last session");
+        assert_eq!(bank.episodic.len(), 1);
+        assert_eq!(
+            bank.episodic[0].participants,
+            vec![registry.character_entity(1001)]
+        );
+    }
+
     #[test]
     fn dialogue_context_template_vars() {
         let ctx = DialogueContext {
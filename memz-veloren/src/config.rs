@@ -3,7 +3,10 @@
 //! This module provides hardware-aware profiles and Veloren-specific
 //! tuning parameters on top of the base `memz_core::config::MemoryConfig`.
 
-use memz_core::config::MemoryConfig;
+use memz_core::config::{LlmConfig, MemoryConfig};
+use memz_llm::LlmClient;
+use memz_llm::client::LlmProvider;
+use tracing::warn;
 
 // ---------------------------------------------------------------------------
 // Hardware Profiles (§12.7)
@@ -43,6 +46,13 @@ impl HardwareProfile {
         !matches!(self, Self::UltraLow)
     }
 
+    /// `[retrieval] algorithm` for this profile: keyword matching (`"tfidf"`)
+    /// when no embedding model is available, HNSW otherwise.
+    #[must_use]
+    pub fn retrieval_algorithm(self) -> &'static str {
+        if self.has_embeddings() { "hnsw" } else { "tfidf" }
+    }
+
     /// Whether this profile supports LLM calls.
     #[must_use]
     pub fn has_llm(self) -> bool {
@@ -85,8 +95,7 @@ impl HardwareProfile {
     #[must_use]
     pub fn auto_detect() -> Self {
         let cpu_count = std::thread::available_parallelism()
-            .map(std::num::NonZero::get)
-            .unwrap_or(4);
+            .map_or(4, std::num::NonZero::get);
 
         if cpu_count >= 12 {
             Self::High
@@ -129,7 +138,10 @@ pub struct VelorenMemzConfig {
     pub limit_enforcement_interval_ticks: u64,
     /// How frequently (in game ticks) to decay reputation.
     pub reputation_decay_interval_ticks: u64,
-    /// Maximum simultaneous LLM requests in flight.
+    /// Maximum simultaneous LLM requests in flight: the worker count of the
+    /// dispatcher [`MemoryRule::with_llm_workers`] starts.
+    ///
+    /// [`MemoryRule::with_llm_workers`]: crate::memory_rule::MemoryRule::with_llm_workers
     pub max_concurrent_llm_requests: usize,
     /// Whether to enable the bard composition system.
     pub enable_bard_system: bool,
@@ -211,6 +223,47 @@ impl Default for VelorenMemzConfig {
     }
 }
 
+// ---------------------------------------------------------------------------
+// LLM Client
+// ---------------------------------------------------------------------------
+
+/// Environment variable holding the API key for `provider = "openai"`.
+pub const LLM_API_KEY_ENV: &str = "MEMZ_LLM_API_KEY";
+
+/// Build the LLM client described by `[llm]`.
+///
+/// `llama_cpp` becomes [`LlmProvider::LlamaCpp`], which talks to
+/// `llama-server`'s native `/completion` endpoint so template grammars
+/// apply.  An unknown provider falls back to no LLM (rule-based dialogue).
+#[must_use]
+pub fn llm_client(config: &LlmConfig) -> LlmClient {
+    let provider = match config.provider.as_str() {
+        "ollama" => LlmProvider::Ollama {
+            base_url: config.base_url.clone(),
+        },
+        "openai" => LlmProvider::OpenAiCompatible {
+            base_url: config.base_url.clone(),
+            api_key: std::env::var(LLM_API_KEY_ENV).unwrap_or_default(),
+        },
+        "llama_cpp" => LlmProvider::LlamaCpp {
+            base_url: config.base_url.clone(),
+        },
+        "none" => return LlmClient::none(),
+        other => {
+            warn!("Unknown LLM provider {other:?}; falling back to rule-based dialogue");
+            return LlmClient::none();
+        }
+    };
+    LlmClient::new(
+        provider,
+        config.tier1_model.clone(),
+        config.tier2_model.clone(),
+        config.max_retries,
+    )
+    .with_structured_output(config.structured_output)
+    .with_parse_retry(config.retry_on_parse_failure)
+}
+
 // ---------------------------------------------------------------------------
 // Performance Budget Tracker
 // ---------------------------------------------------------------------------
@@ -270,6 +323,14 @@ impl PerformanceBudget {
 mod tests {
     use super::*;
 
+    #[test]
+    fn llm_client_follows_provider() {
+        let mut llm = LlmConfig::default();
+        assert!(llm_client(&llm).is_available());
+        llm.provider = "none".to_string();
+        assert!(!llm_client(&llm).is_available());
+    }
+
     #[test]
     fn hardware_profile_detection() {
         let profile = HardwareProfile::auto_detect();
@@ -282,6 +343,7 @@ mod tests {
         let config = VelorenMemzConfig::for_profile(HardwareProfile::UltraLow);
         assert!(!config.profile.has_llm());
         assert!(!config.profile.has_embeddings());
+        assert_eq!(config.profile.retrieval_algorithm(), "tfidf");
         assert_eq!(config.max_concurrent_llm_requests, 0);
         assert!(!config.enable_bard_system);
     }
@@ -14,8 +14,11 @@
 //!   200ms–2s, async. Used for deep conversations, reflection, bard songs.
 
 use memz_core::behavior::{self, GreetingStyle};
-use memz_core::memory::MemoryBank;
+use memz_core::consolidation::MemoryType;
+use memz_core::memory::{MemoryBank, MemoryEntry};
 use memz_core::replay;
+use memz_core::retrieval::{RetrievalQuery, query};
+use memz_core::time::TimeModel;
 use memz_core::types::{EntityId, GameTimestamp, PersonalityTraits};
 
 use crate::bridge::{DialogueContext, MemorySnippet, SentimentLevel};
@@ -38,6 +41,7 @@ pub fn generate_greeting(
     player: EntityId,
     npc_name: &str,
     current_time: &GameTimestamp,
+    time: &TimeModel,
 ) -> (String, GreetingStyle) {
     let disposition = behavior::compute_disposition(bank, player);
     let style = behavior::compute_greeting_style(&disposition);
@@ -45,7 +49,7 @@ pub fn generate_greeting(
     let text = match style {
         GreetingStyle::Warm => {
             // Check for specific memories to reference
-            let replay = replay::select_replay(bank, player, current_time, 0.3);
+            let replay = replay::select_replay(bank, player, current_time, time, 0.3);
             if let Some(replay) = replay {
                 format!(
                     "Welcome back, friend! {} What brings you here today?",
@@ -63,7 +67,7 @@ pub fn generate_greeting(
         }
         GreetingStyle::Hostile => {
             // Check if we remember why we're hostile
-            let replay = replay::select_replay(bank, player, current_time, 0.3);
+            let replay = replay::select_replay(bank, player, current_time, time, 0.3);
             if let Some(replay) = replay {
                 format!("You dare show your face here? {} Leave!", replay.dialogue_hint)
             } else {
@@ -74,7 +78,7 @@ pub fn generate_greeting(
             "...".to_string()
         }
         GreetingStyle::Excited => {
-            let replay = replay::select_replay(bank, player, current_time, 0.3);
+            let replay = replay::select_replay(bank, player, current_time, time, 0.3);
             if let Some(replay) = replay {
                 format!(
                     "Oh! It's you! {} I'm so glad you're here!",
@@ -107,11 +111,12 @@ pub fn generate_response_rule_based(
     _player_action: &str,
     npc_name: &str,
     current_time: &GameTimestamp,
+    time: &TimeModel,
 ) -> String {
     let disposition = behavior::compute_disposition(bank, player);
 
     // Try to find a relevant memory to reference
-    let replay = replay::select_replay(bank, player, current_time, 0.3);
+    let replay = replay::select_replay(bank, player, current_time, time, 0.3);
 
     let base_response = match disposition.sentiment {
         s if s > 0.5 => format!(
@@ -152,8 +157,9 @@ pub fn generate_sentiment_response(
     npc_name: &str,
     sentiment: SentimentLevel,
     current_time: &GameTimestamp,
+    time: &TimeModel,
 ) -> String {
-    let replay = replay::select_replay(bank, player, current_time, 0.3);
+    let replay = replay::select_replay(bank, player, current_time, time, 0.3);
     let memory_ref = replay
         .as_ref()
         .map(|r| r.dialogue_hint.clone())
@@ -271,9 +277,10 @@ pub fn assemble_dialogue_context(
     player_action: &str,
     sentiment: SentimentLevel,
     current_time: &GameTimestamp,
+    time: &TimeModel,
 ) -> DialogueContext {
     // Retrieve top memories about this player
-    let snippets = extract_memory_snippets(bank, &player, current_time, 5);
+    let snippets = extract_memory_snippets(bank, &player, current_time, time, 5);
 
     DialogueContext {
         npc_name: npc_name.to_string(),
@@ -295,45 +302,35 @@ fn extract_memory_snippets(
     bank: &MemoryBank,
     target: &EntityId,
     current_time: &GameTimestamp,
+    time: &TimeModel,
     top_k: usize,
 ) -> Vec<MemorySnippet> {
-    let mut snippets = Vec::new();
-
-    // Episodic memories involving the target
-    for ep in &bank.episodic {
-        if ep.participants.contains(target) {
-            snippets.push(MemorySnippet {
-                memory_type: "episodic".to_string(),
-                summary: ep.event.clone(),
-                strength: ep.strength,
-                age_days: current_time.days_since(&ep.timestamp),
-            });
-        }
-    }
-
-    // Social memories about the target
-    for soc in &bank.social {
-        if soc.about == *target {
-            snippets.push(MemorySnippet {
-                memory_type: "social".to_string(),
-                summary: soc.claim.clone(),
-                strength: soc.trust_in_source,
-                age_days: current_time.days_since(&soc.received_at),
-            });
-        }
-    }
-
-    // Emotional memories about the target
-    for emo in &bank.emotional {
-        if emo.target == *target {
-            snippets.push(MemorySnippet {
-                memory_type: "emotional".to_string(),
-                summary: format!("{} toward entity (intensity: {:.1})", emo.emotion, emo.intensity),
-                strength: emo.intensity,
-                age_days: current_time.days_since(&emo.last_updated),
-            });
-        }
-    }
+    let query = RetrievalQuery::new()
+        .with_memory_types(&[MemoryType::Episodic, MemoryType::Social, MemoryType::Emotional])
+        .with_entity(*target);
+
+    let mut snippets: Vec<MemorySnippet> = query
+        .candidates(bank)
+        .into_iter()
+        .filter_map(|entry| {
+            let (memory_type, summary, at) = match &entry {
+                MemoryEntry::Episodic(ep) => ("episodic", ep.event.clone(), ep.timestamp),
+                MemoryEntry::Social(soc) => ("social", soc.claim.clone(), soc.received_at),
+                MemoryEntry::Emotional(emo) => (
+                    "emotional",
+                    format!("{} toward entity (intensity: {:.1})", emo.emotion, emo.intensity),
+                    emo.last_updated,
+                ),
+                _ => return None,
+            };
+            Some(MemorySnippet {
+                memory_type: memory_type.to_string(),
+                summary,
+                strength: query::strength(&entry),
+                age_days: current_time.days_since(&at, time),
+            })
+        })
+        .collect();
 
     // Sort by a composite score: strength * recency
     snippets.sort_by(|a, b| {
@@ -432,13 +429,13 @@ mod tests {
             player,
             "Goran",
             &ts(3000),
+            &TimeModel::default(),
         );
 
         // Should be warm or excited
         assert!(
             matches!(style, GreetingStyle::Warm | GreetingStyle::Excited),
-            "Expected warm/excited, got {:?}",
-            style
+            "Expected warm/excited, got {style:?}"
         );
         assert!(!text.is_empty());
     }
@@ -455,6 +452,7 @@ mod tests {
             player,
             "Mira",
             &ts(1000),
+            &TimeModel::default(),
         );
 
         assert_eq!(style, GreetingStyle::Neutral);
@@ -474,6 +472,7 @@ mod tests {
             "Goran",
             SentimentLevel::Ally,
             &ts(3000),
+            &TimeModel::default(),
         );
 
         assert!(response.contains("trust") || response.contains("good"));
@@ -534,6 +533,7 @@ mod tests {
             "asked about swords",
             SentimentLevel::Ally,
             &ts(3000),
+            &TimeModel::default(),
         );
 
         assert_eq!(ctx.npc_name, "Goran");
@@ -549,7 +549,7 @@ mod tests {
         let player = EntityId::new();
         let bank = make_bank_with_history(player);
 
-        let snippets = extract_memory_snippets(&bank, &player, &ts(3000), 5);
+        let snippets = extract_memory_snippets(&bank, &player, &ts(3000), &TimeModel::default(), 5);
         assert!(!snippets.is_empty());
 
         // Should be sorted by composite score (strength * recency)
@@ -7,25 +7,85 @@
 //! we model the same pattern: a struct that holds state and functions that
 //! process event types, ready to be wired in by a thin Veloren-side adapter.
 
-use memz_core::config::MemoryConfig;
-use memz_core::decay;
+use memz_core::archival::{self, MemoryGist};
+use memz_core::behavior::{self, Disposition};
+use memz_core::config::{MemoryConfig, MemzConfig};
+use memz_core::consolidation::MemoryType;
+use memz_core::consolidation::{self, ConsolidationScheduler};
+use memz_core::determinism::{Clock, IdGenerator, SystemClock, TickClock};
+use memz_core::eviction;
+use memz_core::hot_reload::{ConfigDiff, LiveConfig};
+use memz_core::metrics::MemzCounters;
 use memz_core::memory::episodic::EpisodicMemory;
 use memz_core::memory::social::SocialMemory;
 use memz_core::memory::MemoryBank;
-use memz_core::reflection::{self, ReflectionConfig};
+use memz_core::persistence::PersistenceEngine;
+use memz_core::reflection::{self, ReflectionConfig, ReflectionInput};
 use memz_core::reputation::{ReputationBoard, NotableDeed};
+use memz_core::error::MemzError;
+use memz_core::retrieval::{MemoryIndex, PersonalityWeightOverrides, RetrievalEngine, RetrievalResult};
 use memz_core::social;
-use memz_core::types::{EntityId, GameTimestamp, Location, PersonalityTraits, SettlementId};
+use memz_core::time::TimeModel;
+use memz_core::types::{Embedding, EntityId, GameTimestamp, Location, MemoryId, PersonalityTraits, SettlementId};
 
-use crate::bridge::EntityRegistry;
+use memz_llm::prompt::PromptEngine;
+use memz_llm::queue::LlmPriority;
+use memz_llm::types::{MemorySummaryResponse, ReflectionResponse};
+use memz_llm::{LlmClient, LlmDispatcher, LlmHandle, LlmQueue};
+use tokio::runtime::Handle;
+
+use crate::bridge::{DialogueContext, EntityRegistry};
+use crate::config::VelorenMemzConfig;
 use crate::events::{CombatOutcome, GameEvent};
+use crate::systems;
 
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Requests each worker of a [`MemoryRule::with_llm_workers`] dispatcher
+/// may have waiting in its queue before new ones are turned away.
+const QUEUED_LLM_REQUESTS_PER_WORKER: usize = 16;
+
+/// How long a gist summary may wait in the LLM queue.
+const GIST_SUMMARY_DEADLINE: Duration = Duration::from_mins(1);
+
+/// How long a reflection may wait in the LLM queue before the NPC falls
+/// back to a rule-based reflection.
+const REFLECTION_DEADLINE: Duration = Duration::from_secs(30);
+
+/// How an NPC is named and described in LLM prompts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpcProfile {
+    /// The NPC's name.
+    pub name: String,
+    /// The NPC's profession or role.
+    pub profession: String,
+}
+
+impl Default for NpcProfile {
+    fn default() -> Self {
+        Self {
+            name: "the villager".to_string(),
+            profession: "villager".to_string(),
+        }
+    }
+}
 
 // ---------------------------------------------------------------------------
 // Memory Rule State
 // ---------------------------------------------------------------------------
 
+/// An NPC's retrieval index and whether its bank changed behind its back.
+struct BankIndex {
+    index: MemoryIndex,
+    /// The bank was handed out through [`MemoryRule::bank_mut`] or changed
+    /// by decay or consolidation; synced before the next retrieval.
+    stale: bool,
+}
+
 /// Central state for the MEMZ memory rule.
 ///
 /// Holds per-NPC memory banks, the entity registry, configuration,
@@ -36,14 +96,61 @@ pub struct MemoryRule {
     pub banks: HashMap<EntityId, MemoryBank>,
     /// Per-entity personality traits (cached from Veloren Personality).
     pub personalities: HashMap<EntityId, PersonalityTraits>,
+    /// Per-entity name and profession for LLM prompts.
+    pub profiles: HashMap<EntityId, NpcProfile>,
     /// Entity ID registry (Veloren ↔ MEMZ).
     pub registry: EntityRegistry,
     /// Per-settlement reputation boards.
     pub reputation_boards: HashMap<SettlementId, ReputationBoard>,
     /// Memory system configuration.
     pub config: MemoryConfig,
+    /// Tick rate and game-day length.
+    pub time: TimeModel,
+    /// Retrieval engine for dialogue and place cues.
+    pub retrieval: RetrievalEngine,
+    /// Per-entity HNSW indexes, built by the first
+    /// [`retrieve`](Self::retrieve) for an NPC and updated as the rule adds,
+    /// evicts and pages in that NPC's memories.
+    indexes: HashMap<EntityId, BankIndex>,
+    /// LLM client; replaced (not mutated) on reload so in-flight calls
+    /// finish against the client they started with.
+    pub llm: Arc<LlmClient>,
+    /// Runtime counters; the rule's LLM client counts its parse failures
+    /// here.
+    pub counters: Arc<MemzCounters>,
+    /// Wall clock behind every timestamp the rule creates itself.
+    pub clock: Arc<dyn Clock>,
+    /// Deterministic mode's [`TickClock`]; it runs at the tick rate of
+    /// [`time`](Self::time) as of each timestamp, not as of
+    /// [`with_seed`](Self::with_seed).
+    tick_clock: Option<TickClock>,
+    /// Source of the ID of every memory the rule creates.
+    pub ids: IdGenerator,
     /// Current game tick (updated each frame).
     pub current_tick: u64,
+    /// Veloren `TimeOfDay` (game-seconds), set by the host each frame.
+    /// Decides when NPCs sleep; without it the night follows MEMZ game time.
+    pub time_of_day: Option<f64>,
+    /// Nightly consolidation ("sleep cycle") schedule.
+    pub consolidation: ConsolidationScheduler,
+    /// Gists archived by decay that await an LLM summary; only filled
+    /// while an LLM is configured.  With a [`dispatcher`](Self::dispatcher)
+    /// the rule submits them itself, otherwise hosts drain them off-tick
+    /// (see [`crate::systems::summarize_gist`]).
+    pub pending_gists: Vec<(EntityId, MemoryGist)>,
+    /// Worker pool running the rule's LLM requests off the game thread.
+    pub dispatcher: Option<LlmDispatcher>,
+    /// Prompt templates for the rule's LLM requests.
+    pub prompts: PromptEngine,
+    /// Cold tier for episodic memories evicted from the banks; without one
+    /// evicted memories are forgotten (see [`eviction::evict_in_memory`]).
+    pub cold_store: Option<PersistenceEngine>,
+    /// Gist summaries in flight: owner, gist ID, completion handle.
+    summaries: Vec<(EntityId, MemoryId, LlmHandle<MemorySummaryResponse>)>,
+    /// LLM reflections in flight, with the input to fall back on.
+    reflections: Vec<(EntityId, ReflectionInput, LlmHandle<ReflectionResponse>)>,
+    /// Hot-reloaded config and the snapshot last applied from it.
+    live: Option<(LiveConfig, u64, Arc<MemzConfig>)>,
 }
 
 impl MemoryRule {
@@ -53,10 +160,28 @@ impl MemoryRule {
         Self {
             banks: HashMap::new(),
             personalities: HashMap::new(),
+            profiles: HashMap::new(),
             registry: EntityRegistry::new(),
             reputation_boards: HashMap::new(),
             config: MemoryConfig::default(),
+            time: TimeModel::default(),
+            retrieval: RetrievalEngine::new(MemzConfig::default().retrieval),
+            indexes: HashMap::new(),
+            llm: Arc::new(LlmClient::none()),
+            counters: Arc::new(MemzCounters::new()),
+            clock: Arc::new(SystemClock),
+            tick_clock: None,
+            ids: IdGenerator::random(),
             current_tick: 0,
+            time_of_day: None,
+            consolidation: ConsolidationScheduler::new(&MemoryConfig::default()),
+            pending_gists: Vec::new(),
+            dispatcher: None,
+            prompts: PromptEngine::builtin(),
+            cold_store: None,
+            summaries: Vec::new(),
+            reflections: Vec::new(),
+            live: None,
         }
     }
 
@@ -64,13 +189,196 @@ impl MemoryRule {
     #[must_use]
     pub fn with_config(config: MemoryConfig) -> Self {
         Self {
+            consolidation: ConsolidationScheduler::new(&config),
             config,
             ..Self::new()
         }
     }
 
-    /// Get or create a memory bank for an entity.
+    /// Use `time` for every tick ↔ game-time conversion (see
+    /// [`crate::bridge::veloren_time_model`]).
+    #[must_use]
+    pub fn with_time_model(mut self, time: TimeModel) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Derive NPC and player `EntityId`s from `world_seed` (Veloren's
+    /// `WorldSim::seed`) instead of 0, so two worlds never share IDs while
+    /// one world keeps its IDs across restarts.
+    #[must_use]
+    pub fn with_world_seed(mut self, world_seed: u64) -> Self {
+        self.registry = EntityRegistry::with_world_seed(world_seed);
+        self
+    }
+
+    /// Take timestamps from `clock` instead of the system clock.
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self.tick_clock = None;
+        self
+    }
+
+    /// Take memory IDs from `ids` instead of random `UUIDv4`s.
+    #[must_use]
+    pub fn with_id_generator(mut self, ids: IdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Deterministic mode: memory IDs from a generator seeded with `seed`
+    /// and a [`TickClock`] starting at the Unix epoch, so the same event
+    /// script produces byte-identical banks on every run.  The clock runs
+    /// at the rule's current tick rate, following
+    /// [`with_time_model`](Self::with_time_model) and config reloads.
+    #[must_use]
+    pub fn with_seed(self, seed: u64) -> Self {
+        let clock = TickClock::default();
+        let mut rule = self.with_clock(clock).with_id_generator(IdGenerator::seeded(seed));
+        rule.tick_clock = Some(clock);
+        rule
+    }
+
+    /// Run LLM requests (gist summaries, …) on `dispatcher`'s workers and
+    /// route their results back to the NPCs that asked, polled each tick.
+    /// The dispatcher follows the rule's LLM client across reloads.
+    #[must_use]
+    pub fn with_dispatcher(mut self, dispatcher: LlmDispatcher) -> Self {
+        dispatcher.set_client(Arc::clone(&self.llm));
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Start a dispatcher on `runtime` with `config.max_concurrent_llm_requests`
+    /// workers and use it as with [`with_dispatcher`](Self::with_dispatcher).
+    ///
+    /// With zero workers (the low-end profiles) the dispatcher accepts
+    /// nothing, so every NPC stays rule-based.
+    #[must_use]
+    pub fn with_llm_workers(self, config: &VelorenMemzConfig, runtime: &Handle) -> Self {
+        let workers = config.max_concurrent_llm_requests;
+        let queue = LlmQueue::new(workers * QUEUED_LLM_REQUESTS_PER_WORKER);
+        let dispatcher = LlmDispatcher::start(Arc::clone(&self.llm), queue, workers, runtime);
+        self.with_dispatcher(dispatcher)
+    }
+
+    /// Spill episodic memories evicted from the banks to `store`, from
+    /// where retrieval and [`disposition`](Self::disposition) page them
+    /// back in, instead of forgetting them.  Each eviction pass also saves
+    /// the bank to `store`, which commits memories paged back in.
+    #[must_use]
+    pub fn with_cold_store(mut self, store: PersistenceEngine) -> Self {
+        self.cold_store = Some(store);
+        self
+    }
+
+    /// Timestamp for game tick `tick` from the rule's clock.  Hosts should
+    /// use this (not [`GameTimestamp::now`]) for the events they report.
+    #[must_use]
+    pub fn timestamp(&self, tick: u64) -> GameTimestamp {
+        match self.tick_clock {
+            Some(clock) => TickClock {
+                ticks_per_second: self.time.ticks_per_second,
+                ..clock
+            }
+            .timestamp(tick),
+            None => self.clock.timestamp(tick),
+        }
+    }
+
+    /// Follow a hot-reloaded config: its `[memory]`, `[time]`,
+    /// `[retrieval]` and `[llm]` sections are applied now and again
+    /// whenever it changes (checked at the start of every [`on_tick`]).
+    #[must_use]
+    pub fn with_live_config(mut self, live: LiveConfig) -> Self {
+        let (generation, config) = (live.generation(), live.current());
+        self.apply_config(&config, None);
+        self.live = Some((live, generation, config));
+        self
+    }
+
+    /// Pick up a newer live config, if any. Returns whether one was applied.
+    pub fn sync_config(&mut self) -> bool {
+        let Some((live, seen, applied)) = &self.live else {
+            return false;
+        };
+        let Some((generation, config)) = live.changed_since(*seen) else {
+            return false;
+        };
+        let previous = Arc::clone(applied);
+        self.apply_config(&config, Some(&previous));
+        if let Some((_, seen, applied)) = &mut self.live {
+            *seen = generation;
+            *applied = config;
+        }
+        true
+    }
+
+    fn apply_config(&mut self, config: &MemzConfig, previous: Option<&MemzConfig>) {
+        self.config = config.memory.clone();
+        self.consolidation.reconfigure(&self.config);
+        self.time = config.time;
+        self.retrieval.reconfigure(config);
+        let retrieval_changed = previous.is_none_or(|previous| {
+            ConfigDiff::between(previous, config).map_or(true, |diff| diff.touches("retrieval"))
+        });
+        if retrieval_changed {
+            // New HNSW parameters: rebuild on the next retrieval.
+            self.indexes.clear();
+        }
+        let llm_changed = previous.is_none_or(|previous| {
+            ConfigDiff::between(previous, config).map_or(true, |diff| diff.touches("llm"))
+        });
+        if llm_changed {
+            let counters = Arc::clone(&self.counters);
+            let client = crate::config::llm_client(&config.llm).with_parse_failure_hook(Arc::new(move |_| {
+                counters.llm_parse_failures.fetch_add(1, Ordering::Relaxed);
+            }));
+            self.llm = Arc::new(client);
+            if let Some(dispatcher) = &self.dispatcher {
+                dispatcher.set_client(Arc::clone(&self.llm));
+            }
+        }
+    }
+
+    /// Game-hour (0–24) at `tick`: from [`time_of_day`](Self::time_of_day)
+    /// when the host sets it, otherwise from MEMZ game time.
+    #[must_use]
+    pub fn hour_of_day(&self, tick: u64) -> f64 {
+        self.time_of_day.map_or_else(
+            || consolidation::hour_of_day(tick, &self.time),
+            crate::bridge::veloren_hour_of_day,
+        )
+    }
+
+    /// Add an episodic memory to `entity`'s bank under a fresh ID.
+    fn push_episodic(&mut self, entity: EntityId, memory: EpisodicMemory) {
+        let memory = memory.with_id(self.ids.memory_id());
+        let bank = self.banks.entry(entity).or_default();
+        if let Some(index) = self.indexes.get_mut(&entity) {
+            let position = bank.episodic.len();
+            index.index.insert(MemoryType::Episodic, position, memory.id, memory.embedding.as_ref());
+        }
+        bank.episodic.push(memory);
+    }
+
+    /// Add a social memory to `entity`'s bank under a fresh ID.
+    fn push_social(&mut self, entity: EntityId, memory: SocialMemory) {
+        let memory = memory.with_id(self.ids.memory_id());
+        let bank = self.banks.entry(entity).or_default();
+        if let Some(index) = self.indexes.get_mut(&entity) {
+            index.index.insert(MemoryType::Social, bank.social.len(), memory.id, None);
+        }
+        bank.social.push(memory);
+    }
+
+    /// Get or create a memory bank for an entity.  Its retrieval index is
+    /// re-synced before the next [`retrieve`](Self::retrieve).
     pub fn bank_mut(&mut self, entity: EntityId) -> &mut MemoryBank {
+        if let Some(index) = self.indexes.get_mut(&entity) {
+            index.stale = true;
+        }
         self.banks.entry(entity).or_default()
     }
 
@@ -80,6 +388,57 @@ impl MemoryRule {
         self.banks.get(&entity)
     }
 
+    /// `npc`'s disposition toward `target`.  With a
+    /// [cold store](Self::with_cold_store), cold memories about `target`
+    /// are paged back into `npc`'s bank first.
+    pub fn disposition(&mut self, npc: EntityId, target: EntityId) -> Disposition {
+        let bank = self.banks.entry(npc).or_default();
+        if let Some(store) = &self.cold_store {
+            let before = bank.episodic.len();
+            let paged = behavior::compute_disposition_paged(bank, &npc, store, target);
+            if let Some(index) = self.indexes.get_mut(&npc) {
+                for (position, memory) in bank.episodic.iter().enumerate().skip(before) {
+                    index.index.insert(MemoryType::Episodic, position, memory.id, memory.embedding.as_ref());
+                }
+            }
+            match paged {
+                Ok(disposition) => return disposition,
+                Err(e) => tracing::warn!("paging in cold memories failed: {e}"),
+            }
+        }
+        behavior::compute_disposition(bank, target)
+    }
+
+    /// The top-K memories in `npc`'s bank for `context`, ranked with
+    /// `npc`'s personality.  With `[retrieval] algorithm = "hnsw"` only the
+    /// memories nearest to `context` in the NPC's [`MemoryIndex`] are scored
+    /// (see [`RetrievalEngine::retrieve_indexed`]); the index is built here
+    /// on first use and kept current from then on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if scoring the candidates fails.
+    pub fn retrieve(&mut self, npc: EntityId, context: &Embedding) -> Result<Vec<RetrievalResult>, MemzError> {
+        let weights = PersonalityWeightOverrides::from_traits(&self.personality(&npc));
+        let now = self.timestamp(self.current_tick);
+        let bank = self.banks.entry(npc).or_default();
+        let index = match self.indexes.entry(npc) {
+            Entry::Occupied(entry) => {
+                let index = entry.into_mut();
+                if index.stale {
+                    index.index.sync(bank);
+                    index.stale = false;
+                }
+                index
+            }
+            Entry::Vacant(entry) => entry.insert(BankIndex {
+                index: MemoryIndex::build(self.retrieval.config(), bank),
+                stale: false,
+            }),
+        };
+        self.retrieval.retrieve_indexed(context, bank, &index.index, &now, Some(&weights))
+    }
+
     /// Get or create personality for an entity.
     #[must_use] 
     pub fn personality(&self, entity: &EntityId) -> PersonalityTraits {
@@ -94,6 +453,101 @@ impl MemoryRule {
         self.personalities.insert(entity, traits);
     }
 
+    /// Name and profession `entity` is described by in LLM prompts.
+    #[must_use]
+    pub fn profile(&self, entity: &EntityId) -> NpcProfile {
+        self.profiles.get(entity).cloned().unwrap_or_default()
+    }
+
+    /// Set name and profession for an entity.
+    pub fn set_profile(&mut self, entity: EntityId, name: impl Into<String>, profession: impl Into<String>) {
+        self.profiles.insert(entity, NpcProfile { name: name.into(), profession: profession.into() });
+    }
+
+    /// Reflect on `input` for `entity`: via the LLM at high priority when a
+    /// dispatcher and an LLM are available, otherwise rule-based at once.
+    fn reflect(&mut self, entity: EntityId, input: ReflectionInput) {
+        if let Some(dispatcher) = self.dispatcher.as_ref().filter(|_| self.llm.is_available()) {
+            let handle = crate::systems::reflection_request(&self.prompts, &input, &self.time)
+                .and_then(|request| dispatcher.submit(LlmPriority::High, request, REFLECTION_DEADLINE));
+            if let Some(handle) = handle {
+                self.reflections.push((entity, input, handle));
+                return;
+            }
+        }
+        self.reflect_rule_based(entity, &input);
+    }
+
+    /// Store a rule-based reflection on `input` for `entity`.
+    fn reflect_rule_based(&mut self, entity: EntityId, input: &ReflectionInput) {
+        match reflection::reflect_rule_based(input, &self.ids) {
+            Ok(output) => self.bank_mut(entity).reflective.push(output.memory),
+            Err(e) => tracing::debug!("rule-based reflection failed: {e}"),
+        }
+    }
+
+    /// Submit pending gist summaries to the dispatcher and apply whatever
+    /// LLM work has finished.  Never blocks; a failed summary leaves the
+    /// rule-based gist in place, a failed reflection (timeout, unusable
+    /// answer) is replaced by a rule-based one.
+    fn route_llm_results(&mut self) {
+        let Some(dispatcher) = &self.dispatcher else {
+            return;
+        };
+        for (entity, gist) in std::mem::take(&mut self.pending_gists) {
+            let profile = self.profiles.get(&entity).cloned().unwrap_or_default();
+            let request = crate::systems::gist_summary_request(
+                &self.prompts,
+                &gist,
+                &profile.name,
+                &profile.profession,
+                &self.time,
+            );
+            if let Some(handle) = request
+                .and_then(|r| dispatcher.submit(LlmPriority::Low, r, GIST_SUMMARY_DEADLINE))
+            {
+                self.summaries.push((entity, gist.id, handle));
+            }
+        }
+
+        let mut reflections = std::mem::take(&mut self.reflections);
+        reflections.retain_mut(|(entity, input, handle)| {
+            let Some(result) = handle.try_take() else {
+                return true;
+            };
+            let output = result
+                .map_err(|e| e.to_string())
+                .and_then(|response| {
+                    crate::systems::reflection_from_response(input, response, &self.ids).map_err(|e| e.to_string())
+                });
+            match output {
+                Ok(output) => self.bank_mut(*entity).reflective.push(output.memory),
+                Err(e) => {
+                    tracing::debug!("LLM reflection failed, reflecting rule-based: {e}");
+                    self.reflect_rule_based(*entity, input);
+                }
+            }
+            false
+        });
+        self.reflections.append(&mut reflections);
+
+        let banks = &mut self.banks;
+        self.summaries.retain_mut(|(entity, gist, handle)| {
+            let Some(result) = handle.try_take() else {
+                return true;
+            };
+            match result {
+                Ok(summary) => {
+                    if let Some(bank) = banks.get_mut(entity) {
+                        archival::apply_summary(bank, *gist, summary.fact, summary.confidence);
+                    }
+                }
+                Err(e) => tracing::debug!("gist summary failed, keeping rule-based gist: {e}"),
+            }
+            false
+        });
+    }
+
     /// Get or create a reputation board for a settlement.
     pub fn reputation_board(&mut self, settlement: SettlementId, timestamp: GameTimestamp) -> &mut ReputationBoard {
         self.reputation_boards
@@ -158,7 +612,7 @@ pub fn on_death(
             0.9,  // Death is very important
         );
 
-        rule.bank_mut(witness).episodic.push(episodic);
+        rule.push_episodic(witness, episodic);
 
         // Gossip propagation — witnesses will remember and may tell others
         if let Some(k) = killer {
@@ -174,7 +628,7 @@ pub fn on_death(
                 0,   // first-hand
                 timestamp,
             );
-            rule.bank_mut(witness).social.push(social);
+            rule.push_social(witness, social);
 
             // Update reputation if in a settlement
             if let Some(settlement_id) = settlement {
@@ -220,7 +674,7 @@ pub fn on_theft(
             -0.5,
             0.6,
         );
-        rule.bank_mut(witness).episodic.push(episodic);
+        rule.push_episodic(witness, episodic);
 
         // Social memory — can gossip about the thief
         let social = SocialMemory::new(
@@ -231,7 +685,7 @@ pub fn on_theft(
             0,
             timestamp,
         );
-        rule.bank_mut(witness).social.push(social);
+        rule.push_social(witness, social);
     }
 
     // Reputation hit
@@ -271,7 +725,7 @@ pub fn on_helped(
         0.7,
         0.7,
     );
-    rule.bank_mut(helped).episodic.push(episodic);
+    rule.push_episodic(helped, episodic);
 
     // Witnesses also remember
     for &witness in witnesses {
@@ -287,7 +741,7 @@ pub fn on_helped(
             0.5,
             0.5,
         );
-        rule.bank_mut(witness).episodic.push(ep);
+        rule.push_episodic(witness, ep);
     }
 
     // Positive reputation
@@ -338,7 +792,7 @@ pub fn on_trade(
         fairness * 0.5,
         0.3,
     );
-    rule.bank_mut(buyer).episodic.push(buyer_ep);
+    rule.push_episodic(buyer, buyer_ep);
 
     let seller_ep = EpisodicMemory::new(
         seller_desc,
@@ -348,7 +802,7 @@ pub fn on_trade(
         -fairness * 0.3, // Seller has inverse feeling about fairness
         0.3,
     );
-    rule.bank_mut(seller).episodic.push(seller_ep);
+    rule.push_episodic(seller, seller_ep);
 }
 
 /// Process a combat event — attacker, defender, and witnesses all form memories.
@@ -369,7 +823,7 @@ pub fn on_combat(
         "Fought entity {defender} and {outcome_str}"
     );
     let atk_valence = if attacker_won { 0.3 } else { -0.4 };
-    rule.bank_mut(attacker).episodic.push(EpisodicMemory::new(
+    rule.push_episodic(attacker, EpisodicMemory::new(
         atk_desc,
         vec![defender],
         location,
@@ -383,7 +837,7 @@ pub fn on_combat(
         "Was attacked by entity {attacker} — {}", if attacker_won { "I lost" } else { "I won" }
     );
     let def_valence = if attacker_won { -0.5 } else { 0.2 };
-    rule.bank_mut(defender).episodic.push(EpisodicMemory::new(
+    rule.push_episodic(defender, EpisodicMemory::new(
         def_desc,
         vec![attacker],
         location,
@@ -400,7 +854,7 @@ pub fn on_combat(
         let w_desc = format!(
             "Witnessed a fight between entity {attacker} and entity {defender} — attacker {outcome_str}"
         );
-        rule.bank_mut(witness).episodic.push(EpisodicMemory::new(
+        rule.push_episodic(witness, EpisodicMemory::new(
             w_desc,
             vec![attacker, defender],
             location,
@@ -420,9 +874,19 @@ pub fn on_combat(
 /// Run periodic tick processing for all active NPCs.
 ///
 /// Called from Veloren's `OnTick` handler. Performs:
-/// 1. Memory decay (every 60 ticks)
-/// 2. Reflection check (every 5000 ticks ≈ 5 game-minutes)
-/// 3. Memory limit enforcement
+/// 1. Memory decay and gist archival (every real second)
+/// 2. Reflection check (every 5 game-minutes)
+/// 3. Consolidation sleep cycles (checked every 5 game-minutes, run at night)
+/// 4. Memory limit enforcement (every 5 real seconds): episodic memories
+///    are evicted to the [`MemoryRule::with_cold_store`] store (and the
+///    bank saved there), or forgotten into gists without one; retrieval
+///    indexes are synced with what is left
+/// 5. Reputation decay (every 4 game-hours)
+/// 6. Routing finished LLM results back to their NPCs (every tick, with a
+///    [`MemoryRule::with_dispatcher`] dispatcher)
+///
+/// Cadences are converted to ticks through `rule.time`.  A reloaded live
+/// config (see [`MemoryRule::with_live_config`]) is picked up first.
 ///
 /// Budget: < 0.5ms for 50 active NPCs.
 pub fn on_tick(
@@ -430,62 +894,169 @@ pub fn on_tick(
     tick: u64,
     _dt: f32,
 ) {
+    rule.sync_config();
     rule.current_tick = tick;
     let config = rule.config.clone();
-    let timestamp = GameTimestamp::now(tick);
+    let time = rule.time;
+    let timestamp = rule.timestamp(tick);
     let reflection_config = ReflectionConfig::default();
 
-    // Decay runs every 60 ticks (~1 second at 60 FPS)
-    if tick.is_multiple_of(60) {
-        for bank in rule.banks.values_mut() {
-            decay::decay_episodic_memories(&mut bank.episodic, &timestamp, &config);
-            decay::decay_social_memories(&mut bank.social, &timestamp, f64::from(config.decay_rate));
+    // Decay runs every real second; forgotten memories become gists
+    if tick.is_multiple_of(time.ticks_per_real_seconds(1.0)) {
+        let summarize = rule.llm.is_available();
+        // Sorted, so gists queue for summaries in the same order every run
+        let mut entities: Vec<EntityId> = rule.banks.keys().copied().collect();
+        entities.sort_by_key(|e| e.0);
+        for entity in entities {
+            let Some(bank) = rule.banks.get_mut(&entity) else {
+                continue;
+            };
+            let before = bank.total_count();
+            let gists = systems::run_decay(bank, &timestamp, &time, &config);
+            if let Some(index) = rule.indexes.get_mut(&entity)
+                && (!gists.is_empty() || bank.total_count() != before)
+            {
+                index.stale = true;
+            }
+            if summarize {
+                rule.pending_gists.extend(gists.into_iter().map(|g| (entity, g)));
+            }
+        }
+    }
+
+    // Reflection check runs every 5 game-minutes; due NPCs reflect through
+    // the LLM when one is dispatched, rule-based otherwise
+    if tick.is_multiple_of(time.ticks_per_game_hours(5.0 / 60.0)) {
+        let mut entities: Vec<EntityId> = rule.banks.keys().copied().collect();
+        entities.sort_by_key(|e| e.0);
+        for entity in entities {
+            if rule.reflections.iter().any(|(e, ..)| *e == entity) {
+                continue; // one reflection in flight per NPC
+            }
+            let Some(bank) = rule.banks.get(&entity) else {
+                continue;
+            };
+            let last_reflection_tick = bank
+                .reflective
+                .iter()
+                .map(|r| r.generated_at.tick)
+                .max()
+                .unwrap_or(0);
+            let unprocessed = || bank.episodic.iter().filter(|e| e.timestamp.tick > last_reflection_tick);
+            let should = reflection::should_reflect(
+                last_reflection_tick,
+                tick,
+                unprocessed().count(),
+                unprocessed().map(|e| e.emotional_valence.abs()).fold(0.0_f32, f32::max),
+                &time,
+                &reflection_config,
+            );
+            if !should {
+                continue;
+            }
+            let profile = rule.profiles.get(&entity).cloned().unwrap_or_default();
+            let personality = DialogueContext::describe_personality(&rule.personality(&entity));
+            let input = ReflectionInput::from_bank(
+                bank,
+                profile.name,
+                profile.profession,
+                personality,
+                timestamp,
+                &reflection_config,
+            );
+            rule.reflect(entity, input);
         }
     }
 
-    // Reflection check runs every 5000 ticks (~5 game-minutes)
-    if tick.is_multiple_of(5000) {
-        let entities: Vec<EntityId> = rule.banks.keys().copied().collect();
+    // Consolidation is checked every 5 game-minutes; each NPC runs one
+    // budgeted sleep cycle per `consolidation_interval_days`, at night.
+    if tick.is_multiple_of(time.ticks_per_game_hours(5.0 / 60.0)) {
+        let hour = rule.hour_of_day(tick);
+        let mut entities: Vec<EntityId> = rule.banks.keys().copied().collect();
+        entities.sort_by_key(|e| e.0);
         for entity in entities {
-            let _personality = rule.personality(&entity);
-            let bank = rule.banks.get(&entity);
-            if let Some(bank) = bank {
-                let last_reflection_tick = bank
-                    .reflective
-                    .last()
-                    .map_or(0, |r| r.generated_at.tick);
-                let _should = reflection::should_reflect(
-                    last_reflection_tick,
-                    tick,
-                    bank.episodic.len(),
-                    bank.episodic.iter().map(|e| e.emotional_valence.abs()).fold(0.0_f32, f32::max),
-                    &reflection_config,
+            if let Some(bank) = rule.banks.get_mut(&entity) {
+                let report = rule.consolidation.run(
+                    entity,
+                    bank,
+                    timestamp,
+                    hour,
+                    &time,
+                    &config.eviction,
+                    &rule.ids,
                 );
-                // If should_reflect is true, queue an async LLM reflection job.
-                // The actual LLM call happens off-thread via memz-llm.
-                // For now, we log the intent.
-                // TODO: Wire up to LlmQueue for async reflection generation.
+                if report.is_some()
+                    && let Some(index) = rule.indexes.get_mut(&entity)
+                {
+                    index.stale = true;
+                }
             }
         }
     }
 
-    // Memory limit enforcement runs every 300 ticks
-    if tick.is_multiple_of(300) {
-        for bank in rule.banks.values_mut() {
-            bank.episodic.truncate(config.max_episodic_per_npc);
-            bank.semantic.truncate(config.max_semantic_per_npc);
+    // Memory limit enforcement runs every 5 real seconds; evicted episodic
+    // memories go to the cold store, or are forgotten into gists.  A bank
+    // is only saved when the pass changed what is stored for it.
+    if tick.is_multiple_of(time.ticks_per_real_seconds(5.0)) {
+        let mut entities: Vec<EntityId> = rule.banks.keys().copied().collect();
+        entities.sort_by_key(|e| e.0);
+        for entity in entities {
+            let Some(bank) = rule.banks.get_mut(&entity) else {
+                continue;
+            };
+            let max = config.max_episodic_per_npc;
+            let before = bank.total_count();
+            let spilled = if let Some(store) = &rule.cold_store {
+                match eviction::evict_to_cold_storage(
+                    bank,
+                    &entity,
+                    store,
+                    &timestamp,
+                    &time,
+                    max,
+                    &config.eviction,
+                ) {
+                    Ok(report) => report.spilled + report.archived > 0,
+                    Err(e) => {
+                        tracing::warn!("spilling cold memories failed: {e}");
+                        false
+                    }
+                }
+            } else {
+                eviction::evict_in_memory(bank, &timestamp, &time, max, &config.eviction);
+                false
+            };
+            systems::truncate_semantic(bank, config.max_semantic_per_npc);
             bank.social.truncate(config.max_social_per_npc);
             bank.procedural.truncate(config.max_procedural_per_npc);
             bank.reflective.truncate(config.max_reflective_per_npc);
+            if let Some(index) = rule.indexes.get_mut(&entity)
+                && (index.stale || bank.total_count() != before)
+            {
+                index.index.sync(bank);
+                index.stale = false;
+            }
+
+            // Spilled rows must leave the hot tables, and paged-in ones the
+            // cold table, before a restart could load them twice.
+            if let Some(store) = &rule.cold_store
+                && (spilled || bank.has_unsaved_cold_changes())
+                && let Err(e) = store.save_bank(&entity, bank)
+            {
+                tracing::warn!("saving spilled memory bank failed: {e}");
+            }
         }
     }
 
-    // Reputation decay runs every 10000 ticks
-    if tick.is_multiple_of(10_000) {
+    // Reputation decay runs every 4 game-hours
+    if tick.is_multiple_of(time.ticks_per_game_hours(4.0)) {
         for board in rule.reputation_boards.values_mut() {
-            board.decay_reputations(0.02, timestamp);
+            board.decay_reputations(0.02, timestamp, &time);
         }
     }
+
+    // LLM work is submitted and collected every tick, without blocking
+    rule.route_llm_results();
 }
 
 /// Process a `GameEvent` through the full MEMZ pipeline.
@@ -525,7 +1096,7 @@ pub fn process_game_event(
 
             // Victim also remembers
             let desc = format!("Entity {perpetrator} harmed me: {action}");
-            rule.bank_mut(*victim).episodic.push(EpisodicMemory::new(
+            rule.push_episodic(*victim, EpisodicMemory::new(
                 desc,
                 vec![*perpetrator],
                 location,
@@ -582,7 +1153,7 @@ pub fn process_game_event(
             ..
         } => {
             let desc = format!("Entity {speaker} said: \"{content}\"");
-            rule.bank_mut(*listener).episodic.push(EpisodicMemory::new(
+            rule.push_episodic(*listener, EpisodicMemory::new(
                 desc.clone(),
                 vec![*speaker],
                 location,
@@ -592,7 +1163,7 @@ pub fn process_game_event(
             ));
             // Speaker also remembers what they said
             let speaker_desc = format!("I told entity {listener}: \"{content}\"");
-            rule.bank_mut(*speaker).episodic.push(EpisodicMemory::new(
+            rule.push_episodic(*speaker, EpisodicMemory::new(
                 speaker_desc,
                 vec![*listener],
                 location,
@@ -607,7 +1178,7 @@ pub fn process_game_event(
         } => {
             for &observer in observers {
                 let desc = format!("Entity {entity} arrived at {location}");
-                rule.bank_mut(observer).episodic.push(EpisodicMemory::new(
+                rule.push_episodic(observer, EpisodicMemory::new(
                     desc,
                     vec![*entity],
                     location,
@@ -626,7 +1197,7 @@ pub fn process_game_event(
             ..
         } => {
             let desc = format!("Quest '{quest_name}': {event_type}");
-            rule.bank_mut(*entity).episodic.push(EpisodicMemory::new(
+            rule.push_episodic(*entity, EpisodicMemory::new(
                 desc.clone(),
                 witnesses.clone(),
                 location,
@@ -638,7 +1209,7 @@ pub fn process_game_event(
                 if witness == *entity {
                     continue;
                 }
-                rule.bank_mut(witness).episodic.push(EpisodicMemory::new(
+                rule.push_episodic(witness, EpisodicMemory::new(
                     format!("Witnessed {desc} by entity {entity}"),
                     vec![*entity],
                     location,
@@ -657,7 +1228,7 @@ pub fn process_game_event(
             ..
         } => {
             for &participant in participants {
-                rule.bank_mut(participant).episodic.push(EpisodicMemory::new(
+                rule.push_episodic(participant, EpisodicMemory::new(
                     description.clone(),
                     participants.clone(),
                     location,
@@ -730,9 +1301,10 @@ pub fn propagate_gossip(
             0.0,    // neutral emotional state
             0.7,    // reasonably reliable source
             timestamp,
+            &rule.time,
         );
         if let social::PropagationResult::Accepted { new_memory, .. } = result {
-            rule.bank_mut(listener).social.push(new_memory);
+            rule.push_social(listener, new_memory);
         }
     }
 }
@@ -922,6 +1494,310 @@ mod tests {
         assert!(rule.bank(entity).unwrap().episodic.len() <= rule.config.max_episodic_per_npc);
     }
 
+    #[test]
+    fn on_tick_spills_to_the_cold_store() {
+        use memz_core::config::PersistenceConfig;
+
+        let store = PersistenceEngine::open_in_memory(&PersistenceConfig::default()).unwrap();
+        let mut rule = MemoryRule::new().with_cold_store(store);
+        let (npc, player) = (EntityId::new(), EntityId::new());
+        for i in 0..300 {
+            rule.bank_mut(npc).episodic.push(EpisodicMemory::new(
+                format!("The player wronged me, time {i}"),
+                vec![player],
+                loc(),
+                ts(i),
+                -0.4,
+                0.1,
+            ));
+        }
+        let quiet = EntityId::new();
+        let calm = EpisodicMemory::new("A calm day", vec![], loc(), ts(0), 0.1, 0.1);
+        rule.bank_mut(quiet).episodic.push(calm);
+
+        on_tick(&mut rule, 300, 1.0 / 60.0);
+        let max = rule.config.max_episodic_per_npc;
+        let bank = rule.bank(npc).unwrap();
+        assert_eq!(bank.episodic.len(), max);
+        assert_eq!(bank.cold_count(player), 300 - max);
+
+        // Asking how the NPC feels pages some of them back in.
+        assert!(rule.disposition(npc, player).sentiment < 0.0);
+        assert!(rule.bank(npc).unwrap().episodic.len() > max);
+
+        // The next pass spills them again and commits the page-in.
+        on_tick(&mut rule, 600, 1.0 / 60.0);
+        let store = rule.cold_store.as_ref().unwrap();
+        assert_eq!(store.cold_memory_count(&npc).unwrap(), 300 - max);
+        assert_eq!(store.load_bank(&npc).unwrap().unwrap().episodic.len(), max);
+        assert!(store.load_bank(&quiet).unwrap().is_none(), "nothing spilled, nothing saved");
+    }
+
+    #[test]
+    fn retrieval_index_follows_the_bank() {
+        use memz_core::config::RetrievalConfig;
+
+        let mut rule = MemoryRule::new();
+        rule.retrieval = RetrievalEngine::new(RetrievalConfig {
+            algorithm: "hnsw".to_string(),
+            hnsw_min_memories: 0,
+            top_k: 1,
+            ..RetrievalConfig::default()
+        });
+        let npc = EntityId::new();
+        let (north, east) = (Embedding(vec![0.0, 1.0]), Embedding(vec![1.0, 0.0]));
+        let embedded = |embedding: &Embedding| {
+            let mut memory = EpisodicMemory::new("Something happened", vec![], loc(), ts(0), 0.0, 0.5);
+            memory.embedding = Some(embedding.clone());
+            memory
+        };
+        let top = |rule: &mut MemoryRule, context: &Embedding| rule.retrieve(npc, context).unwrap()[0].memory.id();
+
+        rule.push_episodic(npc, embedded(&north));
+        let first = rule.bank(npc).unwrap().episodic[0].id;
+        assert_eq!(top(&mut rule, &east), first);
+
+        // New memories reach the index without a sync.
+        rule.push_episodic(npc, embedded(&east));
+        let second = rule.bank(npc).unwrap().episodic[1].id;
+        assert!(!rule.indexes[&npc].stale);
+        assert_eq!(top(&mut rule, &east), second);
+
+        // Re-embedding through `bank_mut` is picked up by the next retrieval.
+        let bank = rule.bank_mut(npc);
+        bank.episodic[0].embedding = Some(east.clone());
+        bank.episodic[1].embedding = Some(north.clone());
+        assert_eq!(top(&mut rule, &east), first);
+
+        // Evictions leave the index in step with the bank.
+        for i in 0..10 {
+            rule.push_episodic(npc, EpisodicMemory::new(format!("Chore {i}"), vec![], loc(), ts(i), 0.0, 0.1));
+        }
+        rule.config.max_episodic_per_npc = 3;
+        on_tick(&mut rule, 300, 1.0 / 60.0);
+        let bank = rule.bank(npc).unwrap();
+        assert_eq!(bank.episodic.len(), 3);
+        assert_eq!(rule.indexes[&npc].index.len(), bank.total_count());
+    }
+
+    #[test]
+    fn on_tick_cadence_follows_time_model() {
+        // Veloren server-cli: 30 TPS, so enforcement lands on tick 150.
+        let mut rule = MemoryRule::new().with_time_model(crate::bridge::veloren_time_model(30.0, 48.0));
+        let entity = EntityId::new();
+        for i in 0..300 {
+            rule.bank_mut(entity).episodic.push(EpisodicMemory::new(
+                format!("Event {i}"),
+                vec![],
+                loc(),
+                ts(i),
+                0.0,
+                0.1,
+            ));
+        }
+
+        // Tick 240 is not a multiple of 5 real seconds at 30 TPS.
+        on_tick(&mut rule, 240, 1.0 / 30.0);
+        assert_eq!(rule.bank(entity).unwrap().episodic.len(), 300);
+
+        on_tick(&mut rule, 150, 1.0 / 30.0);
+        assert!(rule.bank(entity).unwrap().episodic.len() <= rule.config.max_episodic_per_npc);
+    }
+
+    #[test]
+    fn on_tick_consolidates_at_night() {
+        let mut rule = MemoryRule::new();
+        let (npc, smith) = (EntityId::new(), EntityId::new());
+        for event in ["bought nails", "bought a hinge", "bought a lock"] {
+            rule.bank_mut(npc)
+                .episodic
+                .push(EpisodicMemory::new(event, vec![smith], loc(), ts(0), 0.2, 0.5));
+        }
+        // 02:00, when every NPC is past its bedtime.
+        let night = rule.time.ticks_per_game_hours(26.0);
+
+        // The host's TimeOfDay says it is noon.
+        rule.time_of_day = Some(12.0 * 3600.0);
+        on_tick(&mut rule, night, 1.0 / 60.0);
+        assert!(rule.bank(npc).unwrap().semantic.is_empty());
+
+        rule.time_of_day = None;
+        on_tick(&mut rule, night, 1.0 / 60.0);
+        let bank = rule.bank(npc).unwrap();
+        assert_eq!(bank.semantic.len(), 1);
+        assert!(bank.semantic[0].fact.contains("3 experiences"));
+    }
+
+    #[test]
+    fn failed_gist_summaries_keep_the_rule_based_gist() {
+        let runtime =
+            tokio::runtime::Builder::new_multi_thread().worker_threads(1).build().unwrap();
+        let dispatcher = LlmDispatcher::start(
+            Arc::new(LlmClient::none()),
+            memz_llm::LlmQueue::new(8),
+            1,
+            runtime.handle(),
+        );
+        let mut rule = MemoryRule::new().with_dispatcher(dispatcher);
+        let (npc, thief) = (EntityId::new(), EntityId::new());
+        rule.set_profile(npc, "Greta", "baker");
+        let forgotten =
+            vec![EpisodicMemory::new("stole bread", vec![thief], loc(), ts(0), -0.6, 0.4)];
+        let time = rule.time;
+        let gists = archival::archive(rule.bank_mut(npc), &forgotten, ts(1), &time);
+        let fact = rule.bank(npc).unwrap().semantic[0].fact.clone();
+        rule.pending_gists.push((npc, gists[0].clone()));
+
+        let start = std::time::Instant::now();
+        rule.route_llm_results();
+        assert!(rule.pending_gists.is_empty(), "submitted to the dispatcher");
+        while !rule.summaries.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            rule.route_llm_results();
+        }
+        assert!(rule.summaries.is_empty(), "result routed back");
+        assert_eq!(rule.bank(npc).unwrap().semantic[0].fact, fact);
+    }
+
+    #[test]
+    fn reflections_fall_back_to_rule_based() {
+        let entity = EntityId::new();
+        let robbed = |rule: &mut MemoryRule| {
+            for i in 0..3 {
+                rule.bank_mut(entity).episodic.push(EpisodicMemory::new(
+                    format!("was robbed {i}"),
+                    vec![],
+                    loc(),
+                    ts(i),
+                    -0.9,
+                    0.8,
+                ));
+            }
+        };
+        let check_tick = TimeModel::default().ticks_per_game_hours(5.0 / 60.0) * 40;
+
+        // No LLM: reflect rule-based right away.
+        let mut rule = MemoryRule::new();
+        robbed(&mut rule);
+        on_tick(&mut rule, check_tick, 1.0 / 60.0);
+        let reflective = &rule.bank(entity).unwrap().reflective;
+        assert_eq!(reflective.len(), 1);
+        assert!(reflective[0].reflection.contains("was robbed"));
+
+        // An unreachable LLM: the request fails and the rule-based path
+        // takes over once the result is routed back.
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(1).enable_all().build().unwrap();
+        let client = LlmClient::new(
+            memz_llm::client::LlmProvider::Ollama { base_url: "http://127.0.0.1:9".into() },
+            "tiny",
+            "big",
+            0,
+        );
+        let mut rule = MemoryRule::new();
+        rule.llm = Arc::new(client);
+        let dispatcher = LlmDispatcher::start(Arc::clone(&rule.llm), memz_llm::LlmQueue::new(8), 1, runtime.handle());
+        let mut rule = rule.with_dispatcher(dispatcher);
+        robbed(&mut rule);
+        on_tick(&mut rule, check_tick, 1.0 / 60.0);
+        let stats = rule.dispatcher.as_ref().unwrap().queue().stats();
+        assert_eq!(stats.total_enqueued, 1, "submitted to the LLM");
+
+        let start = std::time::Instant::now();
+        while !rule.reflections.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            rule.route_llm_results();
+        }
+        let reflective = &rule.bank(entity).unwrap().reflective;
+        assert_eq!(reflective.len(), 1, "fell back to a rule-based reflection");
+        assert!(reflective[0].confidence < 0.6);
+    }
+
+    #[test]
+    fn llm_workers_follow_the_hardware_profile() {
+        use crate::config::HardwareProfile;
+
+        let runtime =
+            tokio::runtime::Builder::new_multi_thread().worker_threads(1).build().unwrap();
+        for (profile, workers) in [
+            (HardwareProfile::UltraLow, 0),
+            (HardwareProfile::Medium, 2),
+            (HardwareProfile::CloudAssisted, 8),
+        ] {
+            let config = VelorenMemzConfig::for_profile(profile);
+            let rule = MemoryRule::new().with_llm_workers(&config, runtime.handle());
+            assert_eq!(rule.dispatcher.as_ref().map(LlmDispatcher::worker_count), Some(workers));
+        }
+    }
+
+    #[test]
+    fn llm_reflections_reach_the_bank() {
+        use memz_llm::mock::MockLlm;
+
+        let entity = EntityId::new();
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap();
+        let mock = MockLlm::new().respond(
+            "reflecting",
+            concat!(
+                r#"{"reflection": "Thieves keep finding me.", "#,
+                r#""new_beliefs": ["Guard the till"], "questions": ["Who is next?"], "#,
+                r#""mood_shift": {"pleasure": -0.4, "arousal": 0.3, "dominance": -0.1}}"#,
+            ),
+        );
+        let mut rule = MemoryRule::new();
+        rule.llm = Arc::new(LlmClient::mock(mock.clone()));
+        let dispatcher = LlmDispatcher::start(
+            Arc::clone(&rule.llm),
+            memz_llm::LlmQueue::new(8),
+            1,
+            runtime.handle(),
+        );
+        let mut rule = rule.with_dispatcher(dispatcher);
+        for i in 0..3 {
+            rule.bank_mut(entity).episodic.push(EpisodicMemory::new(
+                format!("was robbed {i}"),
+                vec![],
+                loc(),
+                ts(i),
+                -0.9,
+                0.8,
+            ));
+        }
+        on_tick(&mut rule, TimeModel::default().ticks_per_game_hours(5.0 / 60.0) * 40, 1.0 / 60.0);
+
+        let start = std::time::Instant::now();
+        while !rule.reflections.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+            rule.route_llm_results();
+        }
+        let reflective = &rule.bank(entity).unwrap().reflective;
+        assert_eq!(reflective.len(), 1);
+        assert_eq!(reflective[0].reflection, "Thieves keep finding me.");
+        assert!(mock.calls()[0].user.contains("was robbed"));
+    }
+
+    #[test]
+    fn live_config_reload_reaches_rule() {
+        let live = LiveConfig::new(MemzConfig::default());
+        let mut rule = MemoryRule::new().with_live_config(live.clone());
+        assert!(rule.llm.is_available());
+
+        let mut edit = MemzConfig::default();
+        edit.memory.max_episodic_per_npc = 10;
+        edit.time.ticks_per_day = 54_000;
+        edit.llm.provider = "none".to_string();
+        live.apply(edit).unwrap();
+
+        on_tick(&mut rule, 1, 1.0 / 60.0);
+        assert_eq!(rule.config.max_episodic_per_npc, 10);
+        assert_eq!(rule.time.ticks_per_day, 54_000);
+        assert!(!rule.llm.is_available());
+        assert!(!rule.sync_config());
+    }
+
     #[test]
     fn gossip_propagation_between_npcs() {
         let mut rule = MemoryRule::new();
@@ -966,8 +1842,90 @@ mod tests {
         // Listener should have received the gossip (credulous + high trust + very recent)
         let listener_social = rule
             .bank(listener)
-            .map(|b| b.social.len())
-            .unwrap_or(0);
+            .map_or(0, |b| b.social.len());
         assert!(listener_social > 0, "Credulous listener should accept high-trust recent gossip");
     }
+
+    /// Run a short scripted session and dump every bank, in entity order.
+    fn scripted_session(rule: MemoryRule) -> Vec<String> {
+        let mut rule = rule;
+        let npc = |name: &str| EntityId::derived(name);
+        let (smith, guard, bard, player) = (npc("smith"), npc("guard"), npc("bard"), npc("player"));
+        let settlement = IdGenerator::seeded(0).settlement_id();
+
+        for tick in [600, 1200, 1800] {
+            let at = rule.timestamp(tick);
+            on_helped(&mut rule, smith, player, "fixed the forge", &[guard], loc(), Some(settlement), at);
+            on_trade(&mut rule, smith, player, "a sword", 0.4, loc(), at);
+            on_theft(&mut rule, player, &[guard, bard], "an apple", loc(), Some(settlement), at);
+            propagate_gossip(&mut rule, guard, bard, at);
+            on_tick(&mut rule, tick, 1.0 / 60.0);
+        }
+
+        [smith, guard, bard, player]
+            .iter()
+            .map(|entity| format!("{:?}", rule.bank(*entity)))
+            .collect()
+    }
+
+    #[test]
+    fn seeded_rule_is_reproducible() {
+        let first = scripted_session(MemoryRule::new().with_seed(42));
+        let second = scripted_session(MemoryRule::new().with_seed(42));
+        assert_eq!(first, second, "same seed, same banks");
+
+        let other_seed = scripted_session(MemoryRule::new().with_seed(43));
+        assert_ne!(first, other_seed, "memory IDs follow the seed");
+
+        let rule = MemoryRule::new().with_seed(42);
+        assert_eq!(rule.timestamp(90), TickClock::default().timestamp(90));
+    }
+
+    #[test]
+    fn decay_queues_gists_in_entity_order() {
+        use memz_llm::mock::MockLlm;
+
+        let entities: Vec<EntityId> =
+            (0..16).map(|i| EntityId::derived(&format!("npc {i}"))).collect();
+        let mut rule = MemoryRule::new().with_seed(7);
+        rule.llm = Arc::new(LlmClient::mock(MockLlm::new()));
+        for entity in entities.iter().rev() {
+            let memory = EpisodicMemory::new("A dull day", vec![], loc(), ts(0), 0.0, 0.0);
+            rule.bank_mut(*entity).episodic.push(memory);
+        }
+        let tick = rule.time.ticks_per_game_hours(24.0 * 365.0);
+        let tick = tick - tick % rule.time.ticks_per_real_seconds(1.0);
+        on_tick(&mut rule, tick, 1.0 / 60.0);
+
+        let queued: Vec<EntityId> = rule.pending_gists.iter().map(|(e, _)| *e).collect();
+        let mut sorted = entities;
+        sorted.sort_by_key(|e| e.0);
+        assert_eq!(queued, sorted, "not hash order");
+    }
+
+    #[test]
+    fn seeded_clock_follows_the_tick_rate() {
+        let time = TimeModel {
+            ticks_per_second: 30.0,
+            ..TimeModel::default()
+        };
+        // The time model may come after the seed.
+        let mut rule = MemoryRule::new().with_seed(42).with_time_model(time);
+        let at_30 = TickClock::new(TickClock::default().epoch, 30.0);
+        assert_eq!(rule.timestamp(90), at_30.timestamp(90));
+
+        // So may a new rate, as from a `[time]` reload.
+        rule.time.ticks_per_second = 10.0;
+        assert_eq!(rule.timestamp(90).real_time, at_30.timestamp(270).real_time);
+    }
+
+    #[test]
+    fn world_seed_keys_entity_ids() {
+        let mut first = MemoryRule::new().with_world_seed(7);
+        let mut again = MemoryRule::new().with_world_seed(7);
+        let mut other = MemoryRule::new().with_world_seed(8);
+        assert_eq!(first.registry.world_seed(), 7);
+        assert_eq!(first.registry.npc_entity(42), again.registry.npc_entity(42));
+        assert_ne!(first.registry.npc_entity(42), other.registry.npc_entity(42));
+    }
 }
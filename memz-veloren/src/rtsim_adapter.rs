@@ -1,267 +1,40 @@
 //! Veloren rtsim adapter — thin glue wiring MEMZ into `RtState` (§12.2).
 //!
-//! This module provides the concrete [`Rule`] implementation that registers
-//! MEMZ event handlers with Veloren's rtsim event bus.  It converts native
-//! Veloren events (`OnDeath`, `OnHelped`, `OnTheft`, `OnTick`, etc.) into
-//! MEMZ `MemoryRule` calls, and feeds memory-informed data back into the
-//! NPC `Controller` for dialogue, pricing, and behavior.
+//! The compiled rtsim `Rule` lives in the sibling `memz-rtsim` crate as
+//! `memz_rtsim::MemzRule`. It converts native Veloren events (`OnDeath`,
+//! `OnHelped`, `OnTheft`, `OnHealthChange`, `OnTick`) into `MemoryRule`
+//! calls, using the `bridge` mappings from this crate.
 //!
 //! ## Integration Pattern
 //!
 //! ```text
-//! RtState::start_rule::<MemzRule>()
-//!   └─ binds OnDeath  → on_death_handler()
-//!   └─ binds OnHelped → on_helped_handler()
-//!   └─ binds OnTheft  → on_theft_handler()
-//!   └─ binds OnTick   → on_tick_handler()
+//! MemzRule::install(rtstate, MemoryRule::new())
+//!   └─ binds OnDeath        → memory_rule::on_death()
+//!   └─ binds OnHelped       → memory_rule::on_helped()
+//!   └─ binds OnTheft        → memory_rule::on_theft()
+//!   └─ binds OnHealthChange → memory_rule::on_combat()
+//!   └─ binds OnTick         → memory_rule::on_tick() + gossip
 //! ```
 //!
 //! ## Lifecycle
 //!
-//! 1. On server startup, `MemzRule::start()` loads config, creates `MemoryRule`.
+//! 1. On server startup, `MemzRule::install()` stores an
+//!    `Arc<Mutex<MemoryRule>>` as rtsim state and starts the rule.
 //! 2. Each bound handler converts Veloren types to MEMZ types via `bridge`.
 //! 3. On `OnTick`, decay + gossip + reflection are run within frame budget.
-//! 4. On dialogue initiation, the adapter provides memory context to the
-//!    dialogue tree so NPCs can reference past events.
+//! 4. On dialogue initiation, the server locks the shared handle and provides
+//!    memory context to the dialogue tree (see below).
 //!
 //! ## Build Note
 //!
-//! This file is **not compiled** as part of `memz-veloren`'s normal build
-//! because it would require a direct dependency on the `veloren-rtsim` crate,
-//! creating a circular dependency.  Instead, this serves as the ready-to-paste
-//! adapter that a Veloren fork would place inside `veloren/rtsim/src/rule/`.
+//! `memz-rtsim` is a separate workspace rather than a feature of this crate:
+//! Cargo resolves optional dependencies into the lockfile, so even a disabled
+//! `veloren-rtsim` dependency would drag Veloren's nightly toolchain and git
+//! patches into every MEMZ build. This crate stays Veloren-free.
 //!
-//! The adapter depends on:
-//! - `rtsim::{RtState, Rule, RuleError, EventCtx}`
-//! - `rtsim::event::{OnDeath, OnHelped, OnTheft, OnTick, OnHealthChange}`
-//! - `rtsim::data::npc::{Npc, NpcId}`
-//! - `common::rtsim::Actor`
-//! - `memz_core` (via `memz-veloren`)
+//! The snippets below are the remaining server-side hooks, which live outside
+//! rtsim and are applied in a Veloren fork.
 
-// ---- The code below is intended for `veloren/rtsim/src/rule/memz.rs` ----
-//
-// ```rust
-// use crate::{RtState, Rule, RuleError, event::*};
-// use memz_veloren::{bridge, memory_rule, dialogue};
-// use memz_veloren::memory_rule::MemoryRule;
-// use memz_core::types::Location;
-// use parking_lot::Mutex;
-// use std::sync::Arc;
-//
-// pub struct MemzRule {
-//     memory: Arc<Mutex<MemoryRule>>,
-// }
-//
-// impl Rule for MemzRule {
-//     fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
-//         let memory = Arc::new(Mutex::new(MemoryRule::new()));
-//
-//         // --- Bind: OnDeath ---
-//         {
-//             let mem = Arc::clone(&memory);
-//             rtstate.bind::<Self, OnDeath>(move |ctx| {
-//                 let mut rule = mem.lock();
-//                 let data = ctx.state.data();
-//
-//                 let deceased_id = match ctx.event.actor {
-//                     Actor::Npc(npc_id) => {
-//                         if let Some(npc) = data.npcs.get(npc_id) {
-//                             rule.registry.npc_entity(npc.uid)
-//                         } else { return; }
-//                     }
-//                     Actor::Character(cid) => rule.registry.character_entity(cid.0),
-//                 };
-//
-//                 let killer_id = ctx.event.killer.map(|actor| match actor {
-//                     Actor::Npc(npc_id) => {
-//                         data.npcs.get(npc_id)
-//                             .map(|npc| rule.registry.npc_entity(npc.uid))
-//                     }
-//                     Actor::Character(cid) => Some(rule.registry.character_entity(cid.0)),
-//                 }).flatten();
-//
-//                 let location = ctx.event.wpos.map(|w| Location { x: w.x, y: w.y, z: w.z })
-//                     .unwrap_or_default();
-//
-//                 // Gather nearby NPC witnesses (within observation radius)
-//                 let witnesses = gather_nearby_npcs(&data, ctx.event.wpos, &mut rule.registry);
-//
-//                 let settlement = resolve_settlement(&data, ctx.event.wpos);
-//
-//                 memory_rule::on_death(
-//                     &mut rule, deceased_id, killer_id,
-//                     &witnesses, location, settlement,
-//                     bridge::veloren_time_to_timestamp(data.tick),
-//                 );
-//             });
-//         }
-//
-//         // --- Bind: OnHelped ---
-//         {
-//             let mem = Arc::clone(&memory);
-//             rtstate.bind::<Self, OnHelped>(move |ctx| {
-//                 let mut rule = mem.lock();
-//                 let data = ctx.state.data();
-//
-//                 let helped_id = match ctx.event.actor {
-//                     Actor::Npc(npc_id) => {
-//                         data.npcs.get(npc_id).map(|n| rule.registry.npc_entity(n.uid))
-//                     }
-//                     Actor::Character(cid) => Some(rule.registry.character_entity(cid.0)),
-//                 };
-//                 let helper_id = ctx.event.saver.and_then(|actor| match actor {
-//                     Actor::Npc(npc_id) => data.npcs.get(npc_id).map(|n| rule.registry.npc_entity(n.uid)),
-//                     Actor::Character(cid) => Some(rule.registry.character_entity(cid.0)),
-//                 });
-//
-//                 if let (Some(helped), Some(helper)) = (helped_id, helper_id) {
-//                     let npc_pos = match ctx.event.actor {
-//                         Actor::Npc(npc_id) => data.npcs.get(npc_id).map(|n| n.wpos),
-//                         _ => None,
-//                     };
-//                     let location = npc_pos.map(|w| Location { x: w.x, y: w.y, z: w.z })
-//                         .unwrap_or_default();
-//
-//                     let witnesses = gather_nearby_npcs(&data, npc_pos, &mut rule.registry);
-//                     let settlement = resolve_settlement(&data, npc_pos);
-//
-//                     memory_rule::on_helped(
-//                         &mut rule, helped, helper,
-//                         "defended from danger",
-//                         &witnesses, location, settlement,
-//                         bridge::veloren_time_to_timestamp(data.tick),
-//                     );
-//                 }
-//             });
-//         }
-//
-//         // --- Bind: OnTheft ---
-//         {
-//             let mem = Arc::clone(&memory);
-//             rtstate.bind::<Self, OnTheft>(move |ctx| {
-//                 let mut rule = mem.lock();
-//                 let data = ctx.state.data();
-//
-//                 let thief_id = match ctx.event.actor {
-//                     Actor::Npc(npc_id) => data.npcs.get(npc_id).map(|n| rule.registry.npc_entity(n.uid)),
-//                     Actor::Character(cid) => Some(rule.registry.character_entity(cid.0)),
-//                 };
-//
-//                 if let Some(thief) = thief_id {
-//                     let location = Location {
-//                         x: ctx.event.wpos.x as f32,
-//                         y: ctx.event.wpos.y as f32,
-//                         z: ctx.event.wpos.z as f32,
-//                     };
-//                     let sprite_desc = format!("{:?}", ctx.event.sprite);
-//                     let witnesses = gather_nearby_npcs(
-//                         &data,
-//                         Some(vek::Vec3::new(location.x, location.y, location.z)),
-//                         &mut rule.registry,
-//                     );
-//                     let settlement = ctx.event.site.and_then(|site_id| {
-//                         // Map Veloren SiteId → MEMZ SettlementId
-//                         Some(memz_core::types::SettlementId::new()) // TODO: stable mapping
-//                     });
-//
-//                     memory_rule::on_theft(
-//                         &mut rule, thief, &witnesses,
-//                         &sprite_desc, location, settlement,
-//                         bridge::veloren_time_to_timestamp(data.tick),
-//                     );
-//                 }
-//             });
-//         }
-//
-//         // --- Bind: OnTick ---
-//         {
-//             let mem = Arc::clone(&memory);
-//             rtstate.bind::<Self, OnTick>(move |ctx| {
-//                 let mut rule = mem.lock();
-//                 let data = ctx.state.data();
-//
-//                 // Sync personality traits for loaded NPCs
-//                 if ctx.event.tick % 600 == 0 {
-//                     for (npc_id, npc) in data.npcs.iter() {
-//                         let entity = rule.registry.npc_entity(npc.uid);
-//                         let memz_personality = bridge::veloren_personality_to_memz(
-//                             npc.personality.openness(),    // Would need accessor
-//                             npc.personality.conscientiousness(),
-//                             npc.personality.extraversion(),
-//                             npc.personality.agreeableness(),
-//                             npc.personality.neuroticism(),
-//                         );
-//                         rule.set_personality(entity, memz_personality);
-//                     }
-//                 }
-//
-//                 // Run MEMZ tick (decay, reflection, eviction)
-//                 memory_rule::on_tick(&mut rule, ctx.event.tick, ctx.event.dt);
-//
-//                 // Gossip propagation for NPCs that are talking
-//                 // (Veloren marks NPCs with NpcActivity::Talk)
-//                 if ctx.event.tick % 30 == 0 {
-//                     for (npc_id, npc) in data.npcs.iter() {
-//                         if let Some(common::rtsim::NpcActivity::Talk(target)) =
-//                             npc.controller.activity
-//                         {
-//                             if let Actor::Npc(target_npc_id) = target {
-//                                 if let Some(target_npc) = data.npcs.get(target_npc_id) {
-//                                     let speaker = rule.registry.npc_entity(npc.uid);
-//                                     let listener = rule.registry.npc_entity(target_npc.uid);
-//                                     let ts = bridge::veloren_time_to_timestamp(data.tick);
-//                                     memory_rule::propagate_gossip(&mut rule, speaker, listener, ts);
-//                                 }
-//                             }
-//                         }
-//                     }
-//                 }
-//             });
-//         }
-//
-//         Ok(Self { memory })
-//     }
-// }
-//
-// // ---------------------------------------------------------------------------
-// // Helper functions
-// // ---------------------------------------------------------------------------
-//
-// /// Gather MEMZ EntityIds for NPCs within observation radius of a world position.
-// fn gather_nearby_npcs(
-//     data: &crate::data::Data,
-//     wpos: Option<vek::Vec3<f32>>,
-//     registry: &mut bridge::EntityRegistry,
-// ) -> Vec<memz_core::types::EntityId> {
-//     let Some(center) = wpos else { return Vec::new(); };
-//     let radius_sq = 50.0_f32 * 50.0; // 50 block observation radius
-//
-//     data.npcs.iter()
-//         .filter(|(_, npc)| {
-//             let diff = npc.wpos - center;
-//             diff.magnitude_squared() < radius_sq && !npc.is_dead()
-//         })
-//         .map(|(_, npc)| registry.npc_entity(npc.uid))
-//         .collect()
-// }
-//
-// /// Resolve the MEMZ SettlementId for a world position.
-// fn resolve_settlement(
-//     data: &crate::data::Data,
-//     wpos: Option<vek::Vec3<f32>>,
-// ) -> Option<memz_core::types::SettlementId> {
-//     // Find the nearest site to the position
-//     let center = wpos?;
-//     let _nearest = data.sites.iter()
-//         .min_by_key(|(_, site)| {
-//             let diff = site.wpos.as_::<f32>() - center.xy();
-//             diff.magnitude_squared() as i64
-//         });
-//     // TODO: maintain a stable SiteId → SettlementId mapping
-//     Some(memz_core::types::SettlementId::new())
-// }
-// ```
-//
 // ## Dialogue Integration
 //
 // To wire memory-aware dialogue into Veloren's dialogue tree, modify
@@ -355,14 +128,15 @@
 
 // ---------------------------------------------------------------------------
 // This file is intentionally a documentation-only module with no compiled code.
-// The actual adapter code (shown above in doc-comments) is designed to be
-// pasted into a Veloren fork's `rtsim/src/rule/memz.rs` file.
+// The rtsim rule itself is compiled in `memz-rtsim`; the snippets above are
+// applied to Veloren's dialogue, agent and trading code.
 // ---------------------------------------------------------------------------
 
 #[cfg(test)]
 mod tests {
     /// Verify the module compiles and the doc-comments are well-formed.
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn adapter_module_exists() {
         // This test simply confirms the module is reachable.
         assert!(true);
@@ -14,11 +14,19 @@
 //! | Behavior Mod        | 0.2ms    | On interaction   |
 //! | Compact/Evict       | 0.1ms    | On memory add    |
 
+use memz_core::archival::{self, MemoryGist};
 use memz_core::config::MemoryConfig;
 use memz_core::decay;
+use memz_core::determinism::IdGenerator;
 use memz_core::memory::MemoryBank;
+use memz_core::error::MemzError;
 use memz_core::memory::episodic::EpisodicMemory;
-use memz_core::types::GameTimestamp;
+use memz_core::reflection::{self, ReflectionInput, ReflectionOutput};
+use memz_core::time::TimeModel;
+use memz_core::types::{GameTimestamp, PADState};
+use memz_llm::prompt::{PromptEngine, PromptId};
+use memz_llm::types::{LlmRequest, MemorySummaryResponse, ReflectionResponse};
+use memz_llm::LlmClient;
 
 use crate::events::GameEvent;
 
@@ -28,6 +36,7 @@ use crate::events::GameEvent;
 pub fn observe_event(
     event: &GameEvent,
     observer_bank: &mut MemoryBank,
+    ids: &IdGenerator,
 ) {
     let description = event_to_description(event);
     let participants = event.all_entities();
@@ -43,21 +52,107 @@ pub fn observe_event(
         timestamp,
         valence,
         importance,
-    );
+    )
+    .with_id(ids.memory_id());
 
     observer_bank.episodic.push(episodic);
 }
 
 /// Run the memory decay pass for a single NPC.
 ///
+/// With `eviction.gist_archival` on, forgotten episodic memories are
+/// condensed into gists (see [`archival`]) instead of dropped; the new
+/// gists are returned so the caller can request an LLM summary.
+///
 /// Performance target: < 0.05ms per NPC (§12.6).
 pub fn run_decay(
     bank: &mut MemoryBank,
     current_time: &GameTimestamp,
+    time: &TimeModel,
     config: &MemoryConfig,
-) {
-    decay::decay_episodic_memories(&mut bank.episodic, current_time, config);
-    decay::decay_social_memories(&mut bank.social, current_time, f64::from(config.decay_rate));
+) -> Vec<MemoryGist> {
+    let forgotten = decay::forget_episodic_memories(&mut bank.episodic, current_time, time, config);
+    decay::decay_social_memories(&mut bank.social, current_time, time, f64::from(config.decay_rate));
+    if config.eviction.gist_archival && !forgotten.is_empty() {
+        archival::archive(bank, &forgotten, *current_time, time)
+    } else {
+        Vec::new()
+    }
+}
+
+/// The `memory_summary` request for `gist`, with the template's token
+/// limit, temperature and grammar; `None` if the prompt is not loaded.
+#[must_use]
+pub fn gist_summary_request(
+    prompts: &PromptEngine,
+    gist: &MemoryGist,
+    npc_name: &str,
+    npc_profession: &str,
+    time: &TimeModel,
+) -> Option<LlmRequest> {
+    let gist_vars = gist.prompt_vars(time);
+    let mut vars: Vec<(&str, &str)> = gist_vars.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    vars.push(("npc_name", npc_name));
+    vars.push(("npc_profession", npc_profession));
+    prompts.request(PromptId::MemorySummary, &vars).ok()
+}
+
+/// Ask the LLM to summarize `gist` via the `memory_summary` prompt.
+///
+/// Returns `None` when no LLM is configured, the prompt is not loaded, or
+/// the call or parse fails — the gist then keeps its rule-based fact.
+/// Apply a result with [`archival::apply_summary`].
+pub async fn summarize_gist(
+    llm: &LlmClient,
+    prompts: &PromptEngine,
+    gist: &MemoryGist,
+    npc_name: &str,
+    npc_profession: &str,
+    time: &TimeModel,
+) -> Option<MemorySummaryResponse> {
+    if !llm.is_available() {
+        return None;
+    }
+    let request = gist_summary_request(prompts, gist, npc_name, npc_profession, time)?;
+    match llm.generate_structured(&request).await {
+        Ok(summary) => Some(summary),
+        Err(e) => {
+            tracing::debug!("memory summary failed, keeping rule-based gist: {e}");
+            None
+        }
+    }
+}
+
+/// The `reflection` request for `input`, on the template's tier with its
+/// token limit, temperature and grammar; `None` if the prompt is not loaded.
+#[must_use]
+pub fn reflection_request(prompts: &PromptEngine, input: &ReflectionInput, time: &TimeModel) -> Option<LlmRequest> {
+    let owned = input.prompt_vars(time);
+    let vars: Vec<(&str, &str)> = owned.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    prompts.request(PromptId::Reflection, &vars).ok()
+}
+
+/// Turn a parsed `reflection` answer into a reflective memory.
+///
+/// # Errors
+///
+/// Returns an error if the answer is unusable (see
+/// [`reflection::reflect_from_llm`]); fall back to
+/// [`reflection::reflect_rule_based`].
+pub fn reflection_from_response(
+    input: &ReflectionInput,
+    response: ReflectionResponse,
+    ids: &IdGenerator,
+) -> Result<ReflectionOutput, MemzError> {
+    let mood = response.mood_shift;
+    reflection::reflect_from_llm(
+        input,
+        &response.reflection,
+        response.new_beliefs,
+        response.questions,
+        PADState::new(mood.pleasure, mood.arousal, mood.dominance),
+        ids,
+    )
 }
 
 /// Enforce memory limits by evicting low-priority memories.
@@ -67,12 +162,26 @@ pub fn enforce_limits(bank: &mut MemoryBank, config: &MemoryConfig) {
     // Simple truncation — more sophisticated eviction (hot/warm/cold rings)
     // will be implemented in memz-core MemoryBank.
     bank.episodic.truncate(config.max_episodic_per_npc);
-    bank.semantic.truncate(config.max_semantic_per_npc);
+    truncate_semantic(bank, config.max_semantic_per_npc);
     bank.social.truncate(config.max_social_per_npc);
     bank.procedural.truncate(config.max_procedural_per_npc);
     bank.reflective.truncate(config.max_reflective_per_npc);
 }
 
+/// Keep the first `max` semantic memories other than archival gists.
+///
+/// Gists are exempt: there is one per entity, and dropping one would
+/// forget what archival condensed so the NPC could remember it.
+pub fn truncate_semantic(bank: &mut MemoryBank, max: usize) {
+    let mut facts = 0;
+    bank.semantic.retain(|m| {
+        m.category == archival::GIST_CATEGORY || {
+            facts += 1;
+            facts <= max
+        }
+    });
+}
+
 /// Generate a human-readable description from a game event.
 fn event_to_description(event: &GameEvent) -> String {
     match event {
@@ -150,6 +259,7 @@ fn event_to_description(event: &GameEvent) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use memz_core::memory::semantic::SemanticMemory;
     use memz_core::types::{EntityId, GameTimestamp, Location};
 
     #[test]
@@ -164,7 +274,7 @@ mod tests {
             timestamp: GameTimestamp::now(36_000),
         };
 
-        observe_event(&event, &mut bank);
+        observe_event(&event, &mut bank, &IdGenerator::random());
         assert_eq!(bank.episodic.len(), 1);
         assert!(bank.episodic[0].event.contains("defended from wolves"));
     }
@@ -183,4 +293,57 @@ mod tests {
         let desc = event_to_description(&event);
         assert!(desc.contains("iron sword"));
     }
+
+    #[test]
+    fn decay_archives_forgotten_memories_as_gists() {
+        let time = TimeModel::default();
+        let mut config = MemoryConfig::default();
+        let mut bank = MemoryBank::new();
+        let trader = EntityId::new();
+        let old = GameTimestamp::now(0);
+        bank.episodic.push(EpisodicMemory::new("Bought bread", vec![trader], Location::default(), old, 0.1, 0.1));
+        let now = GameTimestamp::now(365 * time.ticks_per_day);
+
+        let gists = run_decay(&mut bank, &now, &time, &config);
+        assert!(bank.episodic.is_empty());
+        assert_eq!(gists.len(), 1);
+        assert_eq!(gists[0].about, Some(trader));
+        assert_eq!(bank.semantic.len(), 1);
+        assert_eq!(bank.semantic[0].category, archival::GIST_CATEGORY);
+
+        // Without an LLM the rule-based fact stays.
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let summary = runtime.block_on(summarize_gist(
+            &LlmClient::none(),
+            &PromptEngine::builtin(),
+            &gists[0],
+            "Greta",
+            "baker",
+            &time,
+        ));
+        assert!(summary.is_none());
+
+        config.eviction.gist_archival = false;
+        let mut bank = MemoryBank::new();
+        bank.episodic.push(EpisodicMemory::new("Bought bread", vec![trader], Location::default(), old, 0.1, 0.1));
+        assert!(run_decay(&mut bank, &now, &time, &config).is_empty());
+        assert!(bank.semantic.is_empty(), "forgotten outright");
+    }
+
+    #[test]
+    fn truncating_semantic_memories_keeps_gists() {
+        let time = TimeModel::default();
+        let now = GameTimestamp::now(0);
+        let mut bank = MemoryBank::new();
+        for i in 0..3 {
+            bank.semantic.push(SemanticMemory::new(format!("Fact {i}"), 0.5, vec![], "world_fact", now));
+        }
+        let forgotten = [EpisodicMemory::new("Bought bread", vec![EntityId::new()], Location::default(), now, 0.1, 0.1)];
+        archival::archive(&mut bank, &forgotten, now, &time);
+
+        truncate_semantic(&mut bank, 1);
+        let kept: Vec<&str> = bank.semantic.iter().map(|m| m.category.as_str()).collect();
+        assert_eq!(kept, ["world_fact", archival::GIST_CATEGORY]);
+        assert_eq!(bank.semantic[0].fact, "Fact 0");
+    }
 }